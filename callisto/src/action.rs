@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::debug;
 use crate::entity::Entities;
-use crate::ship::{Ship, ShipSystem};
+use crate::ship::{PowerAllocation, Ship, ShipSystem};
 
 /// Identifies a specific queued action that a captain can boost. Mirrors the
 /// shape of the underlying `ShipAction` for the kinds that are eligible to
@@ -125,7 +125,7 @@ pub fn boost_target_alive<S: BuildHasher>(
         return false;
       };
       let ship_ref = ship_lock.read().expect("(boost_target_alive) Unable to read ship lock.");
-      return ship_ref.get_dodge_thrust() > 0;
+      return ship_ref.is_dodging();
     }
     BoostTarget::AssistGunner { ship } => {
       let Some(ship_lock) = ships.get(ship) else {
@@ -195,6 +195,9 @@ pub enum ShipAction {
       //with = "::serde_with::rust::unwrap_or_skip"
   )]
     called_shot_system: Option<ShipSystem>,
+    /// Fire each weapon of a multi-weapon turret as its own attack rather than together for extra damage.
+    #[serde(default, skip_serializing_if = "is_false")]
+    multiple_attacks: bool,
   },
   PointDefenseAction {
     weapon_id: usize,
@@ -219,6 +222,23 @@ pub enum ShipAction {
   Repair {
     system: ShipSystem,
   },
+  /// Skim fuel from the atmosphere of a gas giant the ship is in a skimming orbit of.
+  SkimFuel,
+  /// Pump fuel across to `target`, a ship with matched velocity alongside.  Without an `amount` as much is sent as
+  /// the target can take.
+  TransferFuel {
+    target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    amount: Option<u32>,
+  },
+  /// Share out the power plant's output among the ship's systems from now on.
+  AllocatePower {
+    allocation: PowerAllocation,
+  },
+  /// Medical action: the ship's medic works to stabilize the dying crew member `member`.
+  Stabilize {
+    member: String,
+  },
   /// Captain-only action queued under the captain's own ship. Bundles the
   /// 2d6+leadership pre-resolution roll and the list of targets to apply +1
   /// boosts to. Resolved in `player.update()` Phase 0 before any other
@@ -240,19 +260,31 @@ pub enum ShipAction {
 
 /// Returns true if the action is an engineer action.
 ///
-/// Jump is included: it consumes the engineer's turn just like the other
-/// engineer actions, is mutually exclusive with them in `merge`, and is
-/// resolved alongside them in `engineer_actions`.
+/// Jump, fuel skimming, fuel transfers and power allocation are included: they
+/// consume the engineer's turn just like the other engineer actions, are
+/// mutually exclusive with them in `merge`, and are resolved alongside them in
+/// `engineer_actions`.
 #[must_use]
 pub fn is_engineer_action(action: &ShipAction) -> bool {
   matches!(
     action,
-    ShipAction::OverloadDrive | ShipAction::OverloadPlant | ShipAction::Repair { .. } | ShipAction::Jump
+    ShipAction::OverloadDrive
+      | ShipAction::OverloadPlant
+      | ShipAction::Repair { .. }
+      | ShipAction::Jump
+      | ShipAction::SkimFuel
+      | ShipAction::TransferFuel { .. }
+      | ShipAction::AllocatePower { .. }
   )
 }
 
 pub type ShipActionList = Vec<(String, Vec<ShipAction>)>;
 
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(value: &bool) -> bool {
+  !value
+}
+
 /// Merge the new actions into the existing actions in the entities.
 ///
 /// # Arguments
@@ -337,10 +369,21 @@ pub fn merge(entities: &mut Entities, new_actions: ShipActionList) {
           // Engineer actions are mutually exclusive - only one engineer action per turn.
           // A new engineer action replaces any existing engineer action.
           // Jump is treated as an engineer action.
-          ShipAction::OverloadDrive | ShipAction::OverloadPlant | ShipAction::Repair { .. } | ShipAction::Jump => {
+          ShipAction::OverloadDrive
+          | ShipAction::OverloadPlant
+          | ShipAction::Repair { .. }
+          | ShipAction::Jump
+          | ShipAction::SkimFuel
+          | ShipAction::TransferFuel { .. }
+          | ShipAction::AllocatePower { .. } => {
             current_actions.retain(|action| !is_engineer_action(action));
             current_actions.push(next_action.clone());
           }
+          // The medic treats one patient a turn.
+          ShipAction::Stabilize { .. } => {
+            current_actions.retain(|action| !matches!(action, ShipAction::Stabilize { .. }));
+            current_actions.push(next_action.clone());
+          }
           // Each leadership check replaces the previous one (only one per ship per turn).
           ShipAction::LeadershipCheck { .. } => {
             current_actions.retain(|action| !matches!(action, ShipAction::LeadershipCheck { .. }));
@@ -1,12 +1,15 @@
 use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
 use once_cell::sync::Lazy;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt::Debug;
+use std::num::NonZeroU32;
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -15,10 +18,9 @@ use tracing::{event, Level};
 
 use tokio_tungstenite::tungstenite::handshake::server::{Callback, ErrorResponse, Request, Response};
 
-use crate::{
-  get_file_last_modified_timestamp, read_local_or_cloud_file_with_generation,
-  write_local_or_cloud_file_if_generation_match, GenerationWriteError, LOG_AUTH_RESULT,
-};
+use crate::payloads::Role;
+use crate::storage::{Generation, GenerationWriteError, StorageBackend};
+use crate::LOG_AUTH_RESULT;
 
 #[allow(unused_imports)]
 use crate::{debug, error, info, warn, LOG_FILE_USE};
@@ -32,6 +34,9 @@ const COOKIE_ID: &str = "cookie";
 /// Maximum number of attempts for a generation-guarded write before giving up.
 const REGISTER_WRITE_MAX_ATTEMPTS: u32 = 5;
 
+/// Prefix on every minted API token so they are recognizable in config files and logs.
+const API_TOKEN_PREFIX: &str = "cst_";
+
 /// Per-user account status. Stored in the authorized-users file alongside
 /// timestamps. `Active` users may log in and play; `Blacklisted` users are
 /// kicked at every gate (Login, Register, and on watcher-driven reload).
@@ -53,6 +58,62 @@ pub struct UserRecord {
   pub registered_at: i64,
   #[serde(skip_serializing_if = "Option::is_none", default)]
   pub blacklisted_at: Option<i64>,
+  /// Admins may mint and revoke API tokens for any user.
+  #[serde(skip_serializing_if = "is_false", default)]
+  pub admin: bool,
+  /// Long-lived tokens for bots and other non-browser clients acting as this user.
+  #[serde(skip_serializing_if = "Vec::is_empty", default)]
+  pub api_tokens: Vec<ApiTokenRecord>,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(b: &bool) -> bool {
+  !*b
+}
+
+/// What an API token session is allowed to do. An empty list means "no
+/// restriction" for that dimension.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiTokenScope {
+  #[serde(skip_serializing_if = "Vec::is_empty", default)]
+  pub roles: Vec<Role>,
+  #[serde(skip_serializing_if = "Vec::is_empty", default)]
+  pub scenarios: Vec<String>,
+}
+
+impl ApiTokenScope {
+  #[must_use]
+  pub fn allows_role(&self, role: Role) -> bool {
+    self.roles.is_empty() || self.roles.contains(&role)
+  }
+
+  #[must_use]
+  pub fn allows_scenario(&self, scenario: &str) -> bool {
+    self.scenarios.is_empty() || self.scenarios.iter().any(|s| s == scenario)
+  }
+}
+
+/// On-disk record of an API token. Only a hash of the secret is stored; the
+/// token itself is shown once, when minted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTokenRecord {
+  pub id: String,
+  #[serde(default)]
+  pub label: String,
+  pub hash: String,
+  pub created_at: i64,
+  #[serde(skip_serializing_if = "Option::is_none", default)]
+  pub revoked_at: Option<i64>,
+  #[serde(default)]
+  pub scope: ApiTokenScope,
+}
+
+/// A successfully validated API token: who it acts as and what it may do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiTokenGrant {
+  pub email: String,
+  pub id: String,
+  pub scope: ApiTokenScope,
 }
 
 /// In-memory snapshot of the authorized-users file. `active`/`blacklisted`
@@ -65,7 +126,7 @@ pub struct UserDirectory {
   pub active: HashSet<String>,
   pub blacklisted: HashSet<String>,
   pub raw: Vec<UserRecord>,
-  pub generation: Option<i64>,
+  pub generation: Option<Generation>,
   pub last_modified: i64,
 }
 
@@ -79,6 +140,113 @@ impl UserDirectory {
   pub fn is_active(&self, email: &str) -> bool {
     self.active.contains(&email.to_lowercase())
   }
+
+  #[must_use]
+  pub fn is_admin(&self, email: &str) -> bool {
+    let email = email.to_lowercase();
+    self.is_active(&email) && self.raw.iter().any(|record| record.admin && record.email == email)
+  }
+
+  /// Find a token by id along with the user record that owns it.
+  #[must_use]
+  pub fn find_api_token(&self, id: &str) -> Option<(&UserRecord, &ApiTokenRecord)> {
+    self.raw.iter().find_map(|record| {
+      record
+        .api_tokens
+        .iter()
+        .find(|token| token.id == id)
+        .map(|token| (record, token))
+    })
+  }
+
+  /// True if the token exists, has not been revoked, and its owner is still active.
+  #[must_use]
+  pub fn api_token_live(&self, id: &str) -> bool {
+    self
+      .find_api_token(id)
+      .is_some_and(|(owner, token)| token.revoked_at.is_none() && self.is_active(&owner.email))
+  }
+
+  /// Validate a presented API token (`cst_<id>.<secret>`).
+  ///
+  /// # Errors
+  /// Returns an `InvalidKeyError` if the token is malformed, unknown, revoked, or
+  /// does not match, and a `BlacklistedUserError` if its owner is blacklisted.
+  pub fn verify_api_token(&self, token: &str) -> Result<ApiTokenGrant, Box<dyn Error>> {
+    let (id, secret) = token
+      .strip_prefix(API_TOKEN_PREFIX)
+      .and_then(|rest| rest.split_once('.'))
+      .ok_or(InvalidKeyError {})?;
+    let (owner, record) = self.find_api_token(id).ok_or(InvalidKeyError {})?;
+
+    if !constant_time_eq(hash_api_token_secret(secret).as_bytes(), record.hash.as_bytes()) {
+      return Err(Box::new(InvalidKeyError {}));
+    }
+    if self.is_blacklisted(&owner.email) {
+      return Err(Box::new(BlacklistedUserError {}));
+    }
+    if record.revoked_at.is_some() || !self.is_active(&owner.email) {
+      return Err(Box::new(InvalidKeyError {}));
+    }
+    Ok(ApiTokenGrant {
+      email: owner.email.clone(),
+      id: record.id.clone(),
+      scope: record.scope.clone(),
+    })
+  }
+}
+
+fn hash_api_token_secret(secret: &str) -> String {
+  general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+  a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Mint a new API token for `email` in `records`. Returns the token id and the
+/// full token; the latter is not recoverable once this call returns.
+///
+/// # Errors
+/// Returns an error if `email` is not an active user.
+pub fn mint_api_token(
+  records: &mut [UserRecord], email: &str, label: &str, scope: ApiTokenScope, now: i64,
+) -> Result<(String, String), String> {
+  let email = email.to_lowercase();
+  let owner = records
+    .iter_mut()
+    .find(|record| record.email == email && record.status == UserStatus::Active)
+    .ok_or_else(|| format!("No active user {email} to mint a token for."))?;
+
+  let mut rng = rand::thread_rng();
+  let id = general_purpose::URL_SAFE_NO_PAD.encode(rng.gen::<[u8; 9]>());
+  let secret = general_purpose::URL_SAFE_NO_PAD.encode(rng.gen::<[u8; 32]>());
+  owner.api_tokens.push(ApiTokenRecord {
+    id: id.clone(),
+    label: label.to_string(),
+    hash: hash_api_token_secret(&secret),
+    created_at: now,
+    revoked_at: None,
+    scope,
+  });
+  Ok((id.clone(), format!("{API_TOKEN_PREFIX}{id}.{secret}")))
+}
+
+/// Revoke the API token `id` in `records`. Returns the email of its owner.
+///
+/// # Errors
+/// Returns an error if no such token exists or it was already revoked.
+pub fn revoke_api_token(records: &mut [UserRecord], id: &str, now: i64) -> Result<String, String> {
+  for record in records.iter_mut() {
+    if let Some(token) = record.api_tokens.iter_mut().find(|token| token.id == id) {
+      if token.revoked_at.is_some() {
+        return Err(format!("API token {id} is already revoked."));
+      }
+      token.revoked_at = Some(now);
+      return Ok(record.email.clone());
+    }
+  }
+  Err(format!("No API token {id}."))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -114,7 +282,7 @@ fn parse_user_directory_body(body: &[u8]) -> Result<Vec<UserRecord>, Box<dyn std
 }
 
 fn build_directory_from_records(
-  records: Vec<UserRecord>, generation: Option<i64>, last_modified: i64,
+  records: Vec<UserRecord>, generation: Option<Generation>, last_modified: i64,
 ) -> UserDirectory {
   let mut active = HashSet::new();
   let mut blacklisted = HashSet::new();
@@ -143,25 +311,35 @@ fn build_directory_from_records(
   }
 }
 
-/// Load the authorized-users file from disk or GCS into a `UserDirectory`.
+/// Load the authorized-users file from `storage` into a `UserDirectory`.
 ///
 /// # Errors
 /// Returns an error if the file cannot be read or if both the v1 and legacy
 /// shapes fail to parse.
-pub async fn load_user_directory(filename: &str) -> Result<UserDirectory, Box<dyn std::error::Error>> {
-  let (body, generation) = read_local_or_cloud_file_with_generation(filename).await?;
-  let last_modified = get_file_last_modified_timestamp(filename).await?.unwrap_or(0);
+pub async fn load_user_directory(
+  storage: &dyn StorageBackend, filename: &str,
+) -> Result<UserDirectory, Box<dyn std::error::Error>> {
+  let (body, generation) = storage.read_versioned(filename).await.map_err(|e| e as Box<dyn Error>)?;
+  let last_modified = storage
+    .last_modified(filename)
+    .await
+    .map_err(|e| e as Box<dyn Error>)?
+    .unwrap_or(0);
   let records = parse_user_directory_body(&body)?;
   Ok(build_directory_from_records(records, generation, last_modified))
 }
 
+/// An edit to the authorized-users records; see [`Authenticator::update_user_records`].
+pub type UserRecordsUpdate<'a> = dyn FnMut(&mut Vec<UserRecord>) -> Result<(), String> + Send + 'a;
+
 /// Trait defining the authentication behavior for the application
 #[async_trait]
 pub trait Authenticator: Send + Sync + DynClone + Debug {
   /// Returns the web server URL
   fn get_web_server(&self) -> String;
 
-  /// Authenticates a Google user with the provided code
+  /// Authenticates a user with the provided code: an OAuth authorization code
+  /// for Google / OIDC, or encoded [`PasswordCredentials`] for local accounts.
   /// Returns a tuple of `(session_key, user_profile)` on success.
   async fn authenticate_user(
     &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
@@ -190,6 +368,55 @@ pub trait Authenticator: Send + Sync + DynClone + Debug {
   fn directory_snapshot(&self) -> Option<Arc<UserDirectory>> {
     None
   }
+
+  /// Authenticate with a long-lived API token instead of an OAuth code. The
+  /// token is checked against the current directory snapshot.
+  ///
+  /// Token sessions are deliberately not recorded in `session_keys`, so they
+  /// cannot be resumed through a cached cookie (which would drop their scope).
+  async fn authenticate_api_token(&mut self, token: &str) -> Result<ApiTokenGrant, Box<dyn Error>> {
+    let grant = authenticate_api_token_in(self.directory_snapshot().as_deref(), token)?;
+    self.set_email(Some(&grant.email));
+    Ok(grant)
+  }
+
+  /// Apply `update` to the user records and persist the result, swapping the
+  /// shared directory cell on success. `update` may be called more than once
+  /// if a concurrent writer forces a retry. Default implementation has no
+  /// directory to update.
+  async fn update_user_records(&mut self, _update: &mut UserRecordsUpdate<'_>) -> Result<Arc<UserDirectory>, String> {
+    Err("This authenticator has no user directory to update.".to_string())
+  }
+}
+
+/// Validate `token` against `directory`, emitting the `LOG_AUTH_RESULT` audit event.
+fn authenticate_api_token_in(directory: Option<&UserDirectory>, token: &str) -> Result<ApiTokenGrant, Box<dyn Error>> {
+  // The id half of a token is not secret, so it is safe (and useful) to log on failure.
+  let token_id = token
+    .strip_prefix(API_TOKEN_PREFIX)
+    .and_then(|rest| rest.split_once('.'))
+    .map_or("", |(id, _)| id);
+
+  let Some(directory) = directory else {
+    event!(target: LOG_AUTH_RESULT, Level::INFO, email = "", token_id, result = "Failure");
+    return Err(Box::new(UnauthorizedUserError {}));
+  };
+
+  match directory.verify_api_token(token) {
+    Ok(grant) => {
+      event!(target: LOG_AUTH_RESULT, Level::INFO, email = grant.email.as_str(), token_id, result = "Success");
+      Ok(grant)
+    }
+    Err(e) => {
+      let email = directory.find_api_token(token_id).map_or("", |(owner, _)| owner.email.as_str());
+      if e.is::<BlacklistedUserError>() {
+        event!(target: LOG_AUTH_RESULT, Level::INFO, email, token_id, result = "Blacklisted");
+      } else {
+        event!(target: LOG_AUTH_RESULT, Level::INFO, email, token_id, result = "Failure");
+      }
+      Err(e)
+    }
+  }
 }
 
 /// Tagged error used by `register_user` so the processor can map to the
@@ -237,8 +464,8 @@ impl Error for RegisterError {}
 ///
 /// # Panics
 /// If the file cannot be read.
-pub async fn load_authorized_users(users_file: &str) -> Vec<String> {
-  let directory = load_user_directory(users_file).await.unwrap_or_else(|_| {
+pub async fn load_authorized_users(storage: &dyn StorageBackend, users_file: &str) -> Vec<String> {
+  let directory = load_user_directory(storage, users_file).await.unwrap_or_else(|_| {
     panic!("(load_authorized_users) Unable to load authorized users file. No such file or directory '{users_file}'.");
   });
   info!(
@@ -263,60 +490,71 @@ impl Callback for HeaderCallback {
   }
 }
 
+/// Record `email` against this connection's session key so a reconnect with
+/// the same cookie resumes the login.
+fn bind_session_key(
+  session_key: Option<&String>, email: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
+) {
+  if let Some(key) = session_key {
+    session_keys.lock().unwrap().insert(key.clone(), Some(email.to_string()));
+  } else {
+    warn!("(bind_session_key) No session key on this connection.");
+  }
+}
+
+/// The login gates every authenticator applies once it knows who the user is.
+/// Blacklist takes precedence over "not in active list" — must come first.
+fn check_directory_gates(directory: &UserDirectory, email: &str) -> Result<(), Box<dyn Error>> {
+  if directory.is_blacklisted(email) {
+    event!(target: LOG_AUTH_RESULT, Level::INFO, email, result = "Blacklisted");
+    return Err(Box::new(BlacklistedUserError {}));
+  }
+  if !directory.is_active(email) {
+    event!(target: LOG_AUTH_RESULT, Level::INFO, email, result = "Failure");
+    return Err(Box::new(UnauthorizedUserError {}));
+  }
+  Ok(())
+}
+
+fn unix_now() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// The authorized-users file together with its in-memory [`UserDirectory`].
+/// Shared by every file-backed authenticator (Google, local accounts, OIDC);
+/// per-connection clones all point at the same cell and lock.
 #[derive(Debug, Clone)]
-pub struct GoogleAuthenticator {
-  /**
-   * The Google credentials for this server's domain (for oauth2).
-   */
-  credentials: Arc<GoogleCredentials>,
-  /**
-   * The email address of the user.  If None, the user hasn't logged in yet.
-   */
-  email: Option<String>,
-  /**
-   * The session key for this user.  If None, the user hasn't logged in yet.
-   */
-  session_key: Option<String>,
-  /**
-   * The URL of the web server (front end).
-   */
-  web_server: String,
-  /**
-   * The Google public keys.  These are used to validate the Google tokens.
-   */
-  google_keys: Arc<GooglePublicKeys>,
+pub struct UserDirectoryStore {
   /**
    * Shared, hot-reloadable user directory. Every per-connection clone of
-   * this authenticator points at the same backing `RwLock<Arc<...>>`, so a
-   * successful register-write swaps the inner `Arc` and is immediately
+   * the owning authenticator points at the same backing `RwLock<Arc<...>>`,
+   * so a successful register-write swaps the inner `Arc` and is immediately
    * visible to all sessions. (This is the per-clone-Arc bug fix from the
    * old `authorized_users: Arc<Vec<String>>` design.)
    */
   directory: Arc<RwLock<Arc<UserDirectory>>>,
   /**
-   * Serializes concurrent `register_user` calls within this replica. Single
-   * deployment topology + GCS generation preconditions handle inter-replica
-   * concurrency; this lock just coalesces same-process registrations so we
-   * don't burn retry budget on self-conflicts.
+   * Serializes concurrent registrations and record edits within this
+   * replica. Single deployment topology + GCS generation preconditions
+   * handle inter-replica concurrency; this lock just coalesces same-process
+   * writes so we don't burn retry budget on self-conflicts.
    */
   register_lock: Arc<tokio::sync::Mutex<()>>,
+  /**
+   * Where the authorized users file lives.
+   */
+  storage: Arc<dyn StorageBackend>,
   /**
    * The path to the authorized users file.
    */
   authorized_users_file: String,
 }
 
-impl GoogleAuthenticator {
-  /// Creates a new `GoogleAuthenticator` instance
-  ///
-  /// # Arguments
-  /// * `web_server` - The URL of the web server (front end).
-  /// * `credentials` - The Google credentials for this server's domain (for oauth2).
-  /// * `google_keys` - A copy of the previously fetched Google public keys.
-  /// * `authorized_users_file` - The path to the authorized users file.
-  /// * `directory` - Shared user directory (reload-cell). Cloned authenticators
-  ///   point at the same cell so a register-write is visible everywhere.
-  /// * `register_lock` - Shared lock used to serialize registrations.
+impl UserDirectoryStore {
+  /// Seed `directory` from `authorized_users_file`. Any later changes (CLI
+  /// edits, registrations) update the same cell.
   ///
   /// # Errors
   /// Returns `Err` if the authorized users file cannot be read or parsed.
@@ -325,60 +563,67 @@ impl GoogleAuthenticator {
   /// Panics if the directory `RwLock` is poisoned (process startup; never
   /// expected in normal operation).
   pub async fn new(
-    web_server: &str, credentials: Arc<GoogleCredentials>, google_keys: Arc<GooglePublicKeys>,
-    authorized_users_file: &str, directory: Arc<RwLock<Arc<UserDirectory>>>,
+    storage: Arc<dyn StorageBackend>, authorized_users_file: &str, directory: Arc<RwLock<Arc<UserDirectory>>>,
     register_lock: Arc<tokio::sync::Mutex<()>>,
-  ) -> Result<Self, Box<dyn std::error::Error>> {
-    // Seed the directory cell from the file. Any later changes (CLI edits,
-    // register_user) will update the same cell.
-    let initial = load_user_directory(authorized_users_file).await?;
-    *directory.write().expect("(GoogleAuthenticator.new) directory lock poisoned") = Arc::new(initial);
-
-    Ok(GoogleAuthenticator {
-      credentials,
-      email: None,
-      session_key: None,
+  ) -> Result<Self, Box<dyn Error>> {
+    let initial = load_user_directory(storage.as_ref(), authorized_users_file).await?;
+    *directory.write().expect("(UserDirectoryStore.new) directory lock poisoned") = Arc::new(initial);
+    Ok(UserDirectoryStore {
       directory,
       register_lock,
-      google_keys,
-      web_server: web_server.to_string(),
+      storage,
       authorized_users_file: authorized_users_file.to_string(),
     })
   }
 
+  /// The backend holding the users file; companion files (e.g. the local
+  /// password store) live on the same backend.
+  #[must_use]
+  pub fn storage(&self) -> &dyn StorageBackend {
+    self.storage.as_ref()
+  }
+
   /// Snapshot of the currently-loaded directory.
-  fn directory(&self) -> Arc<UserDirectory> {
+  ///
+  /// # Panics
+  /// Panics if the directory `RwLock` is poisoned.
+  #[must_use]
+  pub fn snapshot(&self) -> Arc<UserDirectory> {
     self
       .directory
       .read()
-      .expect("(GoogleAuthenticator.directory) directory lock poisoned")
+      .expect("(UserDirectoryStore.snapshot) directory lock poisoned")
       .clone()
   }
 
+  fn swap(&self, new_dir: Arc<UserDirectory>) {
+    *self
+      .directory
+      .write()
+      .expect("(UserDirectoryStore.swap) directory lock poisoned") = new_dir;
+  }
+
   /// Reload the directory if the source file has been touched since the last
   /// load. Returns the latest snapshot regardless of whether a reload happened.
-  async fn maybe_reload_user_directory(&self) -> Arc<UserDirectory> {
-    let last_modified = match get_file_last_modified_timestamp(&self.authorized_users_file).await {
+  pub async fn maybe_reload(&self) -> Arc<UserDirectory> {
+    let last_modified = match self.storage.last_modified(&self.authorized_users_file).await {
       Ok(last_modified) => last_modified,
       Err(e) => {
         warn!(
           "(maybe_reload_user_directory) Unable to check authorized users file {}: {e}",
           self.authorized_users_file
         );
-        return self.directory();
+        return self.snapshot();
       }
     };
 
-    let current = self.directory();
+    let current = self.snapshot();
     if let Some(last_modified) = last_modified {
       if last_modified > current.last_modified {
-        match load_user_directory(&self.authorized_users_file).await {
+        match load_user_directory(self.storage(), &self.authorized_users_file).await {
           Ok(new_dir) => {
             let new_arc = Arc::new(new_dir);
-            *self
-              .directory
-              .write()
-              .expect("(maybe_reload_user_directory) directory lock poisoned") = new_arc.clone();
+            self.swap(new_arc.clone());
             event!(target: LOG_FILE_USE, Level::INFO, file_name = &self.authorized_users_file, use = "Reloaded authorized users");
             return new_arc;
           }
@@ -394,6 +639,183 @@ impl GoogleAuthenticator {
     current
   }
 
+  /// Apply `update` to the user records and persist them with a
+  /// generation-guarded write. See [`Authenticator::update_user_records`].
+  ///
+  /// # Errors
+  /// Returns `Err` if `update` refuses the edit or the write cannot be completed.
+  pub async fn update_records(&self, update: &mut UserRecordsUpdate<'_>) -> Result<Arc<UserDirectory>, String> {
+    let _guard = self.register_lock.lock().await;
+    self.update_records_locked(update).await
+  }
+
+  /// [`Self::update_records`] for callers already holding `register_lock`.
+  async fn update_records_locked(&self, update: &mut UserRecordsUpdate<'_>) -> Result<Arc<UserDirectory>, String> {
+    let mut latest = self.maybe_reload().await;
+
+    // Retry loop: on 412, re-read and re-apply the edit to the fresh records.
+    for attempt in 1..=REGISTER_WRITE_MAX_ATTEMPTS {
+      let mut records = latest.raw.clone();
+      update(&mut records)?;
+      let file = UsersFileV1 {
+        version: 1,
+        users: records.clone(),
+      };
+      let bytes = serde_json::to_vec_pretty(&file).map_err(|e| format!("Unable to serialize users file: {e:?}"))?;
+
+      match self
+        .storage
+        .write_if_generation_match(&self.authorized_users_file, bytes, latest.generation.as_deref())
+        .await
+      {
+        Ok(new_generation) => {
+          // Build the new directory in-memory (avoid an extra read round-trip)
+          // and atomically swap the cell so all clones see it.
+          let mtime = self
+            .storage
+            .last_modified(&self.authorized_users_file)
+            .await
+            .ok()
+            .and_then(|t| t)
+            .unwrap_or_else(unix_now);
+          let new_dir = Arc::new(build_directory_from_records(records, Some(new_generation), mtime));
+          self.swap(new_dir.clone());
+          return Ok(new_dir);
+        }
+        Err(GenerationWriteError::PreconditionFailed) => {
+          warn!(
+            "(update_user_records) GCS generation precondition failed on attempt {attempt}/{REGISTER_WRITE_MAX_ATTEMPTS}; re-reading."
+          );
+          tokio::time::sleep(Duration::from_millis(50 * u64::from(attempt))).await;
+          let new_dir = load_user_directory(self.storage(), &self.authorized_users_file)
+            .await
+            .map_err(|e| format!("Unable to re-read users file after 412: {e:?}"))?;
+          latest = Arc::new(new_dir);
+          self.swap(latest.clone());
+        }
+        Err(GenerationWriteError::Other(e)) => {
+          return Err(format!("Write failed for {}: {e}", self.authorized_users_file));
+        }
+      }
+    }
+
+    Err(format!(
+      "Exhausted {REGISTER_WRITE_MAX_ATTEMPTS} retries trying to update {}.",
+      self.authorized_users_file
+    ))
+  }
+
+  /// Add `email` to the directory as a newly registered active user.
+  ///
+  /// # Errors
+  /// Returns `RegisterError` mapped to the wire-pinned strings.
+  pub async fn register_email(&self, email: &str) -> Result<Arc<UserDirectory>, RegisterError> {
+    let _guard = self.register_lock.lock().await;
+    self.register_email_locked(email).await
+  }
+
+  /// [`Self::register_email`] for callers already holding `register_lock`.
+  async fn register_email_locked(&self, email: &str) -> Result<Arc<UserDirectory>, RegisterError> {
+    let now = unix_now();
+    // The predicates are re-checked against the freshly read records on every
+    // retry, so a CLI edit that lands mid-registration still wins.
+    let mut refused = None;
+    let result = self
+      .update_records_locked(&mut |records: &mut Vec<UserRecord>| {
+        let directory = build_directory_from_records(records.clone(), None, 0);
+        if directory.is_blacklisted(email) {
+          refused = Some(RegisterError::NotAuthorized);
+          return Err("blacklisted".to_string());
+        }
+        if directory.is_active(email) {
+          refused = Some(RegisterError::AlreadyRegistered);
+          return Err("already registered".to_string());
+        }
+        records.push(UserRecord {
+          email: email.to_string(),
+          status: UserStatus::Active,
+          registered_at: now,
+          blacklisted_at: None,
+          admin: false,
+          api_tokens: Vec::new(),
+        });
+        Ok(())
+      })
+      .await;
+
+    match (result, refused) {
+      (Ok(directory), _) => {
+        event!(target: LOG_AUTH_RESULT, Level::INFO, email, result = "Registered");
+        Ok(directory)
+      }
+      (Err(_), Some(RegisterError::NotAuthorized)) => {
+        event!(target: LOG_AUTH_RESULT, Level::INFO, email, result = "Blacklisted");
+        Err(RegisterError::NotAuthorized)
+      }
+      (Err(_), Some(refusal)) => {
+        event!(target: LOG_AUTH_RESULT, Level::INFO, email, result = "AlreadyRegistered");
+        Err(refusal)
+      }
+      (Err(e), None) => {
+        error!("(register) Unable to register {email} in {}: {e}", self.authorized_users_file);
+        Err(RegisterError::WriteFailed)
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct GoogleAuthenticator {
+  /**
+   * The Google credentials for this server's domain (for oauth2).
+   */
+  credentials: Arc<GoogleCredentials>,
+  /**
+   * The email address of the user.  If None, the user hasn't logged in yet.
+   */
+  email: Option<String>,
+  /**
+   * The session key for this user.  If None, the user hasn't logged in yet.
+   */
+  session_key: Option<String>,
+  /**
+   * The URL of the web server (front end).
+   */
+  web_server: String,
+  /**
+   * The Google public keys.  These are used to validate the Google tokens.
+   */
+  google_keys: Arc<GooglePublicKeys>,
+  /**
+   * The authorized-users file and its shared, hot-reloadable directory.
+   */
+  users: UserDirectoryStore,
+}
+
+impl GoogleAuthenticator {
+  /// Creates a new `GoogleAuthenticator` instance
+  ///
+  /// # Arguments
+  /// * `web_server` - The URL of the web server (front end).
+  /// * `credentials` - The Google credentials for this server's domain (for oauth2).
+  /// * `google_keys` - A copy of the previously fetched Google public keys.
+  /// * `users` - The authorized users file and its shared directory. Cloned
+  ///   authenticators point at the same cell so a register-write is visible everywhere.
+  #[must_use]
+  pub fn new(
+    web_server: &str, credentials: Arc<GoogleCredentials>, google_keys: Arc<GooglePublicKeys>,
+    users: UserDirectoryStore,
+  ) -> Self {
+    GoogleAuthenticator {
+      credentials,
+      email: None,
+      session_key: None,
+      google_keys,
+      web_server: web_server.to_string(),
+      users,
+    }
+  }
+
   /// Validate a Google authorization code, returning the email claim. Shared
   /// between `authenticate_user` and `register_user` so JWT semantics stay
   /// identical at both gates.
@@ -475,268 +897,754 @@ impl GoogleAuthenticator {
   ///
   /// # Panics
   /// Panics if the directory `RwLock` or `session_keys` mutex is poisoned.
-  #[allow(clippy::too_many_lines)]
   pub async fn register(
     &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
   ) -> Result<String, RegisterError> {
-    // 1. Validate token first — outside the lock — so a bad token doesn't hold
-    //    up the next concurrent registration.
+    // Validate token first — outside the register lock — so a bad token
+    // doesn't hold up the next concurrent registration.
     let email = self.validate_google_token(code).await.map_err(|e| {
       warn!("(register) Token validation failed: {e:?}");
       RegisterError::AuthFailed
     })?;
 
-    // 2. Acquire the register lock to serialize concurrent registrations within
-    //    this replica.
-    let _guard = self.register_lock.lock().await;
+    self.users.register_email(&email).await?;
+    bind_session_key(self.session_key.as_ref(), &email, session_keys);
+    self.email = Some(email.clone());
+    Ok(email)
+  }
 
-    // 3. Reload the directory under the lock so we see CLI edits.
-    let current = self.maybe_reload_user_directory().await;
-    if current.is_blacklisted(&email) {
-      event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Blacklisted");
-      return Err(RegisterError::NotAuthorized);
-    }
-    if current.is_active(&email) {
-      event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "AlreadyRegistered");
-      return Err(RegisterError::AlreadyRegistered);
-    }
+  // Static helper methods
+  /**
+   * Fetches the Google public keys from the Google API (public internet).  These are hosted at a well known
+   * public URL.
+   *
+   * # Returns
+   * The Google public keys.
+   *
+   * # Panics
+   * If the keys cannot be fetched or parsed.
+   */
+  pub async fn fetch_google_public_keys() -> Arc<GooglePublicKeys> {
+    // Fetch Google's public keys
+    let client = reqwest::Client::new();
+    let public_keys_response = client
+      .get(GOOGLE_X509_CERT_URL)
+      .send()
+      .await
+      .expect("(validate_google_token) Unable to fetch Google public keys");
 
-    // 4. Retry loop: on 412, re-read, re-check predicates, re-serialize.
-    let now = SystemTime::now()
-      .duration_since(UNIX_EPOCH)
-      .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX));
+    debug!("(validate_google_token) Fetched Google public keys.",);
 
-    let mut latest = current;
+    let text = public_keys_response.text().await.unwrap();
+
+    debug!("(validate_google_token) Fetched Google public keys okay.");
+
+    Arc::new(
+      serde_json::from_str::<GooglePublicKeys>(&text)
+        .unwrap_or_else(|e| panic!("(validate_google_token) Error: Unable to parse Google public keys: {e:?}")),
+    )
+  }
+
+  /// Load the oauth credentials for this server's domain from a file.
+  ///
+  /// # Arguments
+  /// * `file_name` - The name of the file to load.
+  ///
+  /// # Returns
+  /// The Google issued oauth credentials.
+  ///
+  /// # Panics
+  /// If the file cannot be read or the credentials are malformed (cannot be parsed).
+  #[must_use]
+  pub fn load_google_credentials(file_name: &str) -> GoogleCredentials {
+    let file = std::fs::File::open(file_name)
+      .unwrap_or_else(|e| panic!("Error {e:?} opening Google credentials file {file_name}"));
+    let reader = std::io::BufReader::new(file);
+    let credentials: GoogleCredsJson = serde_json::from_reader(reader)
+      .unwrap_or_else(|e| panic!("Error {e:?} parsing Google credentials file {file_name}"));
+    debug!("Load Google credentials file \"{}\".", file_name);
+    credentials.web
+  }
+}
+
+fn generic_on_request(
+  session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>, request: &Request, response: Response,
+) -> (Response, String, Option<String>) {
+  let cookies = request
+    .headers()
+    .iter()
+    .filter_map(|(key, value)| {
+      if key.as_str() == COOKIE_ID {
+        Some(value.to_str().unwrap().to_string())
+      } else {
+        None
+      }
+      .and_then(|cookie_header| {
+        // Split the cookie header by "; " to get individual cookies
+        cookie_header.split("; ").find_map(|cookie| {
+          cookie
+            .strip_prefix(&format!("{SESSION_COOKIE_NAME}="))
+            .map(std::string::ToString::to_string)
+        })
+      })
+    })
+    .collect::<Vec<String>>();
+
+  // If we found a cookie, find if there's a valid email address to go with it.
+  // This happens in the case where we get disconnected and the client reconnects.
+  let valid_pair = {
+    let unlocked_session_keys = session_keys.lock().expect("Unable to get lock session keys.");
+
+    let mut emails = cookies
+      .iter()
+      .filter_map(|cookie| {
+        unlocked_session_keys
+          .get(cookie)
+          .and_then(|email| email.as_ref().map(|email| (cookie.clone(), email.clone())))
+      })
+      .collect::<Vec<(String, String)>>();
+
+    if emails.len() > 1 {
+      warn!("(on_request) Found multiple valid emails for session key.");
+    }
+    emails.pop()
+  };
+
+  if let Some((key, email)) = valid_pair {
+    // We have a logged in user with a valid email, so record that.
+    debug!("(on_request) Found valid email {}", email,);
+    (response, key.clone(), Some(email.clone()))
+  } else if !cookies.is_empty() {
+    // We have cookies but they don't have a valid email yet (e.g., reconnection after server restart or logout).
+    // Reuse the first cookie's session key instead of creating a new one.
+    // This allows the client to maintain the same session key across reconnections.
+    debug!(
+      "(on_request) Found {} cookie(s) but none have valid email. Reusing first cookie.",
+      cookies.len()
+    );
+    let session_key = cookies[0].clone();
+
+    // Ensure the session key is in the map (it might not be if this is a fresh reconnection)
+    let mut session_keys_lock = session_keys.lock().expect("Unable to get lock session keys.");
+    session_keys_lock.entry(session_key.clone()).or_insert(None);
+    drop(session_keys_lock);
+
+    (response, session_key, None)
+  } else {
+    // No cookies found, create a new session key.
+    debug!("(on_request) No cookies found. Creating new session key.");
+    let mut response = response.clone();
+
+    let session_key = generate_session_key();
+    session_keys
+      .lock()
+      .expect("Unable to get lock session keys.")
+      .insert(session_key.clone(), None);
+
+    let cookie_value = if cfg!(feature = "no_tls_upgrade") {
+      // For local development without TLS, don't set Secure flag
+      format!("{SESSION_COOKIE_NAME}={session_key}; HttpOnly; SameSite=Lax")
+    } else {
+      // For production with TLS, set Secure flag
+      format!("{SESSION_COOKIE_NAME}={session_key}; HttpOnly; SameSite=None; Secure")
+    };
+
+    response.headers_mut().insert("Set-Cookie", cookie_value.parse().unwrap());
+
+    (response, session_key, None)
+  }
+}
+
+#[async_trait]
+impl Authenticator for GoogleAuthenticator {
+  fn get_web_server(&self) -> String {
+    self.web_server.clone()
+  }
+
+  fn get_email(&self) -> Option<String> {
+    self.email.clone()
+  }
+
+  fn get_session_key(&self) -> Option<String> {
+    self.session_key.clone()
+  }
+
+  fn set_email(&mut self, email: Option<&String>) {
+    self.email = email.cloned();
+  }
+
+  fn set_session_key(&mut self, session_key: &str) {
+    self.session_key = Some(session_key.to_string());
+  }
+
+  async fn authenticate_user(
+    &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
+  ) -> Result<GoogleProfile, Box<dyn Error>> {
+    // Reload the directory before checking — we want CLI edits (including
+    // mid-session blacklists) to bite the next login attempt.
+    let directory = self.users.maybe_reload().await;
+
+    // Validate token, lowercase the email.
+    let email = self.validate_google_token(code).await?;
+
+    check_directory_gates(&directory, &email)?;
+    bind_session_key(self.session_key.as_ref(), &email, session_keys);
+
+    event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Success");
+    self.email = Some(email.clone());
+    Ok(email)
+  }
+
+  async fn register_user(
+    &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
+  ) -> Result<GoogleProfile, Box<dyn Error>> {
+    self
+      .register(code, session_keys)
+      .await
+      .map_err(|e| Box::new(e) as Box<dyn Error>)
+  }
+
+  fn directory_snapshot(&self) -> Option<Arc<UserDirectory>> {
+    Some(self.users.snapshot())
+  }
+
+  async fn authenticate_api_token(&mut self, token: &str) -> Result<ApiTokenGrant, Box<dyn Error>> {
+    // Reload first so a token revoked by a CLI edit stops working on the next login.
+    let directory = self.users.maybe_reload().await;
+    let grant = authenticate_api_token_in(Some(&directory), token)?;
+    self.email = Some(grant.email.clone());
+    Ok(grant)
+  }
+
+  async fn update_user_records(&mut self, update: &mut UserRecordsUpdate<'_>) -> Result<Arc<UserDirectory>, String> {
+    self.users.update_records(update).await
+  }
+
+  /**
+   * Check if a user is logged in on this session.
+   *
+   * # Returns
+   * `true` if the user is logged in, `false` otherwise.
+   */
+  fn validated_user(&self) -> bool {
+    self.email.is_some()
+  }
+}
+
+/// PBKDF2-HMAC-SHA256 work factor for newly stored passwords. Each record
+/// carries its own count, so raising this never invalidates existing hashes.
+const PASSWORD_HASH_ITERATIONS: u32 = 600_000;
+const PASSWORD_SALT_LEN: usize = 16;
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// Email and password for a local account. Local logins travel in the same
+/// `LoginMsg { code }` as OAuth codes, so clients send [`Self::to_code`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PasswordCredentials {
+  pub email: String,
+  pub password: String,
+}
+
+impl Debug for PasswordCredentials {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("PasswordCredentials")
+      .field("email", &self.email)
+      .field("password", &"<redacted>")
+      .finish()
+  }
+}
+
+impl PasswordCredentials {
+  #[must_use]
+  pub fn new(email: &str, password: &str) -> Self {
+    PasswordCredentials {
+      email: email.to_string(),
+      password: password.to_string(),
+    }
+  }
+
+  /// Encode as the `code` of a `LoginMsg`.
+  #[must_use]
+  pub fn to_code(&self) -> String {
+    serde_json::to_string(self).unwrap_or_default()
+  }
+
+  fn from_code(code: &str) -> Option<Self> {
+    serde_json::from_str::<PasswordCredentials>(code)
+      .ok()
+      .map(|creds| PasswordCredentials {
+        email: creds.email.trim().to_lowercase(),
+        password: creds.password,
+      })
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PasswordRecord {
+  email: String,
+  salt: String,
+  hash: String,
+  iterations: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PasswordsFileV1 {
+  version: u32,
+  passwords: Vec<PasswordRecord>,
+}
+
+/// Where the local account store lives for a given users file: alongside
+/// it, e.g. `authorized_users.json` → `authorized_users.passwords.json`.
+#[must_use]
+pub fn passwords_file_for(users_file: &str) -> String {
+  let stem = users_file.strip_suffix(".json").unwrap_or(users_file);
+  format!("{stem}.passwords.json")
+}
+
+fn derive_password_hash(password: &str, salt: &[u8], iterations: NonZeroU32) -> [u8; ring::digest::SHA256_OUTPUT_LEN] {
+  let mut out = [0u8; ring::digest::SHA256_OUTPUT_LEN];
+  ring::pbkdf2::derive(
+    ring::pbkdf2::PBKDF2_HMAC_SHA256,
+    iterations,
+    salt,
+    password.as_bytes(),
+    &mut out,
+  );
+  out
+}
+
+fn new_password_record(email: &str, password: &str) -> PasswordRecord {
+  let salt: [u8; PASSWORD_SALT_LEN] = rand::thread_rng().gen();
+  let iterations = NonZeroU32::new(PASSWORD_HASH_ITERATIONS).expect("iteration count is non-zero");
+  PasswordRecord {
+    email: email.to_string(),
+    salt: general_purpose::STANDARD_NO_PAD.encode(salt),
+    hash: general_purpose::STANDARD_NO_PAD.encode(derive_password_hash(password, &salt, iterations)),
+    iterations: iterations.get(),
+  }
+}
+
+fn verify_password(record: &PasswordRecord, password: &str) -> bool {
+  let (Ok(salt), Ok(hash), Some(iterations)) = (
+    general_purpose::STANDARD_NO_PAD.decode(&record.salt),
+    general_purpose::STANDARD_NO_PAD.decode(&record.hash),
+    NonZeroU32::new(record.iterations),
+  ) else {
+    warn!("(verify_password) Malformed password record for {}.", record.email);
+    return false;
+  };
+  ring::pbkdf2::verify(ring::pbkdf2::PBKDF2_HMAC_SHA256, iterations, &salt, password.as_bytes(), &hash).is_ok()
+}
+
+async fn load_password_records(
+  storage: &dyn StorageBackend, filename: &str,
+) -> Result<(Vec<PasswordRecord>, Option<Generation>), Box<dyn Error>> {
+  let (body, generation) = storage.read_versioned(filename).await.map_err(|e| e as Box<dyn Error>)?;
+  if body.is_empty() {
+    return Ok((Vec::new(), generation));
+  }
+  let file = serde_json::from_slice::<PasswordsFileV1>(&body)?;
+  Ok((file.passwords, generation))
+}
+
+/// Username/password accounts held by the server itself. Salted password
+/// hashes live in a file next to the users file (see [`passwords_file_for`]);
+/// whether an account may log in is still decided by the [`UserDirectory`],
+/// so statuses and the blacklist work exactly as they do for Google logins.
+#[derive(Debug, Clone)]
+pub struct LocalAuthenticator {
+  email: Option<String>,
+  session_key: Option<String>,
+  web_server: String,
+  users: UserDirectoryStore,
+  passwords_file: String,
+}
+
+impl LocalAuthenticator {
+  /// Creates a new `LocalAuthenticator` instance.
+  ///
+  /// # Arguments
+  /// * `web_server` - The URL of the web server (front end).
+  /// * `users` - The authorized users file and its shared directory. The
+  ///   password store is kept alongside the file, on the same backend.
+  #[must_use]
+  pub fn new(web_server: &str, users: UserDirectoryStore) -> Self {
+    LocalAuthenticator {
+      email: None,
+      session_key: None,
+      web_server: web_server.to_string(),
+      passwords_file: passwords_file_for(&users.authorized_users_file),
+      users,
+    }
+  }
+
+  async fn check_password(&self, creds: &PasswordCredentials) -> Result<bool, Box<dyn Error>> {
+    let (records, _) = load_password_records(self.users.storage(), &self.passwords_file).await?;
+    let Some(record) = records.into_iter().find(|record| record.email == creds.email) else {
+      return Ok(false);
+    };
+    // PBKDF2 is deliberately slow; keep it off the async worker threads.
+    let password = creds.password.clone();
+    Ok(tokio::task::spawn_blocking(move || verify_password(&record, &password)).await?)
+  }
+
+  /// Store (or replace) the password hash for `creds.email`, using the same
+  /// generation-guarded retry as the users file.
+  async fn store_password(&self, creds: &PasswordCredentials) -> Result<(), String> {
+    let (email, password) = (creds.email.clone(), creds.password.clone());
+    let record = tokio::task::spawn_blocking(move || new_password_record(&email, &password))
+      .await
+      .map_err(|e| format!("Unable to hash password: {e}"))?;
     for attempt in 1..=REGISTER_WRITE_MAX_ATTEMPTS {
-      // Build the next file body from the latest snapshot.
-      let mut new_records: Vec<UserRecord> = latest.raw.clone();
-      new_records.push(UserRecord {
-        email: email.clone(),
-        status: UserStatus::Active,
-        registered_at: now,
-        blacklisted_at: None,
-      });
-      let file = UsersFileV1 {
+      let (mut records, generation) = load_password_records(self.users.storage(), &self.passwords_file)
+        .await
+        .map_err(|e| format!("Unable to read {}: {e}", self.passwords_file))?;
+      records.retain(|r| r.email != record.email);
+      records.push(record.clone());
+      let file = PasswordsFileV1 {
         version: 1,
-        users: new_records.clone(),
-      };
-      let bytes = match serde_json::to_vec_pretty(&file) {
-        Ok(b) => b,
-        Err(e) => {
-          error!("(register) Unable to serialize users file: {e:?}");
-          return Err(RegisterError::WriteFailed);
-        }
+        passwords: records,
       };
-
-      match write_local_or_cloud_file_if_generation_match(&self.authorized_users_file, bytes, latest.generation).await {
-        Ok(new_generation) => {
-          // Build a fresh directory in-memory (avoid an extra read round-trip)
-          // and atomically swap the cell so all clones see it.
-          let mtime = get_file_last_modified_timestamp(&self.authorized_users_file)
-            .await
-            .ok()
-            .and_then(|t| t)
-            .unwrap_or(now);
-          let new_dir = build_directory_from_records(new_records, Some(new_generation), mtime);
-          *self.directory.write().expect("(register) directory lock poisoned") = Arc::new(new_dir);
-
-          // Record the session key for the newly registered user. Mirrors the
-          // login flow.
-          if let Some(key) = self.session_key.clone() {
-            session_keys.lock().unwrap().insert(key, Some(email.clone()));
-          } else {
-            warn!("(register) No session key on this connection.");
-          }
-
-          event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Registered");
-          self.email = Some(email.clone());
-          return Ok(email);
-        }
+      let bytes = serde_json::to_vec_pretty(&file).map_err(|e| format!("Unable to serialize passwords file: {e:?}"))?;
+
+      match self
+        .users
+        .storage()
+        .write_if_generation_match(&self.passwords_file, bytes, generation.as_deref())
+        .await
+      {
+        Ok(_) => return Ok(()),
         Err(GenerationWriteError::PreconditionFailed) => {
           warn!(
-            "(register) GCS generation precondition failed on attempt {attempt}/{REGISTER_WRITE_MAX_ATTEMPTS}; re-reading."
+            "(LocalAuthenticator.store_password) GCS generation precondition failed on attempt {attempt}/{REGISTER_WRITE_MAX_ATTEMPTS}; re-reading."
           );
-          // Backoff then re-read.
           tokio::time::sleep(Duration::from_millis(50 * u64::from(attempt))).await;
-          match load_user_directory(&self.authorized_users_file).await {
-            Ok(new_dir) => {
-              let new_arc = Arc::new(new_dir);
-              *self.directory.write().expect("(register) directory lock poisoned") = new_arc.clone();
-              latest = new_arc;
-            }
-            Err(e) => {
-              error!("(register) Unable to re-read users file after 412: {e:?}");
-              return Err(RegisterError::WriteFailed);
-            }
-          }
-          // Re-check predicates against the freshly read directory.
-          if latest.is_blacklisted(&email) {
-            event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Blacklisted");
-            return Err(RegisterError::NotAuthorized);
-          }
-          if latest.is_active(&email) {
-            event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "AlreadyRegistered");
-            return Err(RegisterError::AlreadyRegistered);
-          }
         }
         Err(GenerationWriteError::Other(e)) => {
-          error!("(register) Write failed for {}: {e}", self.authorized_users_file);
-          return Err(RegisterError::WriteFailed);
+          return Err(format!("Write failed for {}: {e}", self.passwords_file));
         }
       }
     }
+    Err(format!(
+      "Exhausted {REGISTER_WRITE_MAX_ATTEMPTS} retries trying to update {}.",
+      self.passwords_file
+    ))
+  }
 
-    error!(
-      "(register) Exhausted {REGISTER_WRITE_MAX_ATTEMPTS} retries trying to register {email} in {}.",
-      self.authorized_users_file
-    );
-    Err(RegisterError::WriteFailed)
+  /// Register a new local account: store its password and add it to the
+  /// users file as active.
+  ///
+  /// # Errors
+  /// Returns `RegisterError` mapped to the wire-pinned strings. Malformed
+  /// credentials or a password shorter than 8 characters are `AuthFailed`.
+  pub async fn register(
+    &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
+  ) -> Result<String, RegisterError> {
+    let creds = PasswordCredentials::from_code(code)
+      .filter(|creds| creds.email.contains('@') && creds.password.chars().count() >= MIN_PASSWORD_LEN)
+      .ok_or_else(|| {
+        warn!("(LocalAuthenticator.register) Malformed credentials or password too short.");
+        RegisterError::AuthFailed
+      })?;
+    let email = creds.email.clone();
+
+    // Check the directory before touching the password store so a
+    // registration attempt can never overwrite an existing account's password.
+    let _guard = self.users.register_lock.lock().await;
+    let current = self.users.maybe_reload().await;
+    if current.is_blacklisted(&email) {
+      event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Blacklisted");
+      return Err(RegisterError::NotAuthorized);
+    }
+    if current.is_active(&email) {
+      event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "AlreadyRegistered");
+      return Err(RegisterError::AlreadyRegistered);
+    }
+
+    self.store_password(&creds).await.map_err(|e| {
+      error!("(LocalAuthenticator.register) Unable to store password for {email}: {e}");
+      RegisterError::WriteFailed
+    })?;
+    self.users.register_email_locked(&email).await?;
+
+    bind_session_key(self.session_key.as_ref(), &email, session_keys);
+    self.email = Some(email.clone());
+    Ok(email)
   }
+}
 
-  // Static helper methods
-  /**
-   * Fetches the Google public keys from the Google API (public internet).  These are hosted at a well known
-   * public URL.
-   *
-   * # Returns
-   * The Google public keys.
-   *
-   * # Panics
-   * If the keys cannot be fetched or parsed.
-   */
-  pub async fn fetch_google_public_keys() -> Arc<GooglePublicKeys> {
-    // Fetch Google's public keys
-    let client = reqwest::Client::new();
-    let public_keys_response = client
-      .get(GOOGLE_X509_CERT_URL)
-      .send()
+#[async_trait]
+impl Authenticator for LocalAuthenticator {
+  fn get_web_server(&self) -> String {
+    self.web_server.clone()
+  }
+
+  fn get_email(&self) -> Option<String> {
+    self.email.clone()
+  }
+
+  fn get_session_key(&self) -> Option<String> {
+    self.session_key.clone()
+  }
+
+  fn set_email(&mut self, email: Option<&String>) {
+    self.email = email.cloned();
+  }
+
+  fn set_session_key(&mut self, session_key: &str) {
+    self.session_key = Some(session_key.to_string());
+  }
+
+  async fn authenticate_user(
+    &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
+  ) -> Result<GoogleProfile, Box<dyn Error>> {
+    let directory = self.users.maybe_reload().await;
+    let creds = PasswordCredentials::from_code(code).ok_or(InvalidKeyError {})?;
+    let email = creds.email.clone();
+
+    // Check the password first so a bad guess learns nothing about the
+    // account's blacklist or pending status.
+    if !self.check_password(&creds).await? {
+      event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Failure");
+      return Err(Box::new(UnauthorizedUserError {}));
+    }
+    check_directory_gates(&directory, &email)?;
+    bind_session_key(self.session_key.as_ref(), &email, session_keys);
+
+    event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Success");
+    self.email = Some(email.clone());
+    Ok(email)
+  }
+
+  async fn register_user(
+    &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
+  ) -> Result<GoogleProfile, Box<dyn Error>> {
+    self
+      .register(code, session_keys)
       .await
-      .expect("(validate_google_token) Unable to fetch Google public keys");
+      .map_err(|e| Box::new(e) as Box<dyn Error>)
+  }
 
-    debug!("(validate_google_token) Fetched Google public keys.",);
+  fn directory_snapshot(&self) -> Option<Arc<UserDirectory>> {
+    Some(self.users.snapshot())
+  }
 
-    let text = public_keys_response.text().await.unwrap();
+  async fn authenticate_api_token(&mut self, token: &str) -> Result<ApiTokenGrant, Box<dyn Error>> {
+    let directory = self.users.maybe_reload().await;
+    let grant = authenticate_api_token_in(Some(&directory), token)?;
+    self.email = Some(grant.email.clone());
+    Ok(grant)
+  }
 
-    debug!("(validate_google_token) Fetched Google public keys okay.");
+  async fn update_user_records(&mut self, update: &mut UserRecordsUpdate<'_>) -> Result<Arc<UserDirectory>, String> {
+    self.users.update_records(update).await
+  }
 
-    Arc::new(
-      serde_json::from_str::<GooglePublicKeys>(&text)
-        .unwrap_or_else(|e| panic!("(validate_google_token) Error: Unable to parse Google public keys: {e:?}")),
-    )
+  fn validated_user(&self) -> bool {
+    self.email.is_some()
+  }
+}
+
+/// Settings for a generic `OpenID` Connect provider.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+  /// Expected `iss` claim, and the base of the provider's discovery document.
+  pub issuer: String,
+  /// Where the provider publishes its signing keys.
+  pub jwks_url: String,
+  pub client_id: String,
+  pub client_secret: String,
+  /// Where authorization codes are exchanged for ID tokens.
+  pub token_endpoint: String,
+}
+
+impl OidcConfig {
+  /// Build the configuration, reading the token endpoint from the issuer's
+  /// `/.well-known/openid-configuration` document.
+  ///
+  /// # Errors
+  /// Returns `Err` if the discovery document cannot be fetched or parsed.
+  pub async fn discover(
+    issuer: &str, jwks_url: &str, client_id: &str, client_secret: &str,
+  ) -> Result<Self, Box<dyn Error>> {
+    let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+    let discovery: OidcDiscovery = reqwest::Client::new()
+      .get(&url)
+      .send()
+      .await?
+      .error_for_status()?
+      .json()
+      .await?;
+    debug!(
+      "(OidcConfig.discover) Token endpoint for {issuer} is {}.",
+      discovery.token_endpoint
+    );
+    Ok(OidcConfig {
+      issuer: issuer.to_string(),
+      jwks_url: jwks_url.to_string(),
+      client_id: client_id.to_string(),
+      client_secret: client_secret.to_string(),
+      token_endpoint: discovery.token_endpoint,
+    })
   }
+}
 
-  /// Load the oauth credentials for this server's domain from a file.
+/// Login through any standards-compliant `OpenID` Connect provider. The
+/// flow mirrors [`GoogleAuthenticator`]: the client's authorization code is
+/// exchanged for an ID token, whose signature is checked against the
+/// provider's JWKS and whose `email` claim is then gated by the directory.
+#[derive(Debug, Clone)]
+pub struct OidcAuthenticator {
+  config: Arc<OidcConfig>,
+  /**
+   * The provider's signing keys. Shared across clones and refetched when a
+   * token names an unknown key id, so provider key rotation needs no restart.
+   */
+  keys: Arc<RwLock<Arc<JwkSet>>>,
+  email: Option<String>,
+  session_key: Option<String>,
+  web_server: String,
+  users: UserDirectoryStore,
+}
+
+impl OidcAuthenticator {
+  /// Creates a new `OidcAuthenticator` instance, fetching the provider's keys.
   ///
   /// # Arguments
-  /// * `file_name` - The name of the file to load.
-  ///
-  /// # Returns
-  /// The Google issued oauth credentials.
+  /// * `web_server` - The URL of the web server (front end); used as the redirect URI.
+  /// * `config` - The provider settings.
+  /// * `users` - The authorized users file and its shared directory.
   ///
-  /// # Panics
-  /// If the file cannot be read or the credentials are malformed (cannot be parsed).
-  #[must_use]
-  pub fn load_google_credentials(file_name: &str) -> GoogleCredentials {
-    let file = std::fs::File::open(file_name)
-      .unwrap_or_else(|e| panic!("Error {e:?} opening Google credentials file {file_name}"));
-    let reader = std::io::BufReader::new(file);
-    let credentials: GoogleCredsJson = serde_json::from_reader(reader)
-      .unwrap_or_else(|e| panic!("Error {e:?} parsing Google credentials file {file_name}"));
-    debug!("Load Google credentials file \"{}\".", file_name);
-    credentials.web
-  }
-}
-
-fn generic_on_request(
-  session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>, request: &Request, response: Response,
-) -> (Response, String, Option<String>) {
-  let cookies = request
-    .headers()
-    .iter()
-    .filter_map(|(key, value)| {
-      if key.as_str() == COOKIE_ID {
-        Some(value.to_str().unwrap().to_string())
-      } else {
-        None
-      }
-      .and_then(|cookie_header| {
-        // Split the cookie header by "; " to get individual cookies
-        cookie_header.split("; ").find_map(|cookie| {
-          cookie
-            .strip_prefix(&format!("{SESSION_COOKIE_NAME}="))
-            .map(std::string::ToString::to_string)
-        })
-      })
+  /// # Errors
+  /// Returns `Err` if the JWKS cannot be fetched.
+  pub async fn new(
+    web_server: &str, config: Arc<OidcConfig>, users: UserDirectoryStore,
+  ) -> Result<Self, Box<dyn Error>> {
+    let keys = Self::fetch_jwks(&config.jwks_url).await?;
+    Ok(OidcAuthenticator {
+      config,
+      keys: Arc::new(RwLock::new(Arc::new(keys))),
+      email: None,
+      session_key: None,
+      web_server: web_server.to_string(),
+      users,
     })
-    .collect::<Vec<String>>();
-
-  // If we found a cookie, find if there's a valid email address to go with it.
-  // This happens in the case where we get disconnected and the client reconnects.
-  let valid_pair = {
-    let unlocked_session_keys = session_keys.lock().expect("Unable to get lock session keys.");
+  }
 
-    let mut emails = cookies
-      .iter()
-      .filter_map(|cookie| {
-        unlocked_session_keys
-          .get(cookie)
-          .and_then(|email| email.as_ref().map(|email| (cookie.clone(), email.clone())))
-      })
-      .collect::<Vec<(String, String)>>();
+  /// Fetch the provider's JSON Web Key Set.
+  ///
+  /// # Errors
+  /// Returns `Err` if the keys cannot be fetched or parsed.
+  pub async fn fetch_jwks(jwks_url: &str) -> Result<JwkSet, Box<dyn Error>> {
+    let keys = reqwest::Client::new()
+      .get(jwks_url)
+      .send()
+      .await?
+      .error_for_status()?
+      .json::<JwkSet>()
+      .await?;
+    debug!(
+      "(OidcAuthenticator.fetch_jwks) Fetched {} keys from {jwks_url}.",
+      keys.keys.len()
+    );
+    Ok(keys)
+  }
 
-    if emails.len() > 1 {
-      warn!("(on_request) Found multiple valid emails for session key.");
+  /// Find the signing key `kid`, refetching the JWKS once if it is unknown.
+  async fn find_key(&self, kid: &str) -> Result<Jwk, Box<dyn Error>> {
+    let cached = self.keys.read().expect("(OidcAuthenticator.find_key) lock poisoned").clone();
+    if let Some(jwk) = cached.find(kid) {
+      return Ok(jwk.clone());
     }
-    emails.pop()
-  };
+    let fresh = Arc::new(Self::fetch_jwks(&self.config.jwks_url).await?);
+    *self.keys.write().expect("(OidcAuthenticator.find_key) lock poisoned") = fresh.clone();
+    fresh.find(kid).cloned().ok_or_else(|| "No matching public key found".into())
+  }
 
-  if let Some((key, email)) = valid_pair {
-    // We have a logged in user with a valid email, so record that.
-    debug!("(on_request) Found valid email {}", email,);
-    (response, key.clone(), Some(email.clone()))
-  } else if !cookies.is_empty() {
-    // We have cookies but they don't have a valid email yet (e.g., reconnection after server restart or logout).
-    // Reuse the first cookie's session key instead of creating a new one.
-    // This allows the client to maintain the same session key across reconnections.
-    debug!(
-      "(on_request) Found {} cookie(s) but none have valid email. Reusing first cookie.",
-      cookies.len()
-    );
-    let session_key = cookies[0].clone();
+  /// Exchange an authorization code for the provider's ID token.
+  async fn exchange_code(&self, code: &str) -> Result<String, Box<dyn Error>> {
+    let redirect_uri = self.get_web_server();
+    let token_request = [
+      ("grant_type", "authorization_code"),
+      ("code", code),
+      ("client_id", self.config.client_id.as_str()),
+      ("client_secret", self.config.client_secret.as_str()),
+      ("redirect_uri", redirect_uri.as_str()),
+    ];
+    let response = reqwest::Client::new()
+      .post(&self.config.token_endpoint)
+      .form(&token_request)
+      .send()
+      .await?;
+    if !response.status().is_success() {
+      let status = response.status();
+      let body = response.text().await.unwrap_or_default();
+      error!("(OidcAuthenticator.exchange_code) Token endpoint returned {status}: {body}");
+      return Err(Box::new(InvalidKeyError {}));
+    }
+    Ok(response.json::<OidcTokenResponse>().await?.id_token)
+  }
 
-    // Ensure the session key is in the map (it might not be if this is a fresh reconnection)
-    let mut session_keys_lock = session_keys.lock().expect("Unable to get lock session keys.");
-    session_keys_lock.entry(session_key.clone()).or_insert(None);
-    drop(session_keys_lock);
+  /// Validate an ID token, returning its (lowercased) email claim.
+  async fn validate_id_token(&self, token: &str) -> Result<String, Box<dyn Error>> {
+    let header = decode_header(token)?;
+    // Provider keys are public; a symmetric signature could only have been
+    // made by someone holding a shared secret, which we never configure.
+    if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+      return Err("Symmetrically signed ID tokens are not accepted".into());
+    }
+    let kid = header.kid.ok_or("ID token has no key id")?;
+    let jwk = self.find_key(&kid).await?;
+    if matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)) {
+      return Err("Symmetric keys in the provider JWKS are not accepted".into());
+    }
+    let decoding_key = DecodingKey::from_jwk(&jwk)?;
 
-    (response, session_key, None)
-  } else {
-    // No cookies found, create a new session key.
-    debug!("(on_request) No cookies found. Creating new session key.");
-    let mut response = response.clone();
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(std::slice::from_ref(&self.config.client_id));
+    validation.set_issuer(std::slice::from_ref(&self.config.issuer));
 
-    let session_key = generate_session_key();
-    session_keys
-      .lock()
-      .expect("Unable to get lock session keys.")
-      .insert(session_key.clone(), None);
+    let token_data = decode::<OidcClaims>(token, &decoding_key, &validation).map_err(|e| -> Box<dyn Error> {
+      if matches!(e.kind(), jsonwebtoken::errors::ErrorKind::ExpiredSignature) {
+        Box::new(TokenTimeoutError {})
+      } else {
+        Box::new(e)
+      }
+    })?;
+    if token_data.claims.email_verified == Some(false) {
+      debug!("(OidcAuthenticator.validate_id_token) Provider reports the email as unverified.");
+      return Err(Box::new(UnauthorizedUserError {}));
+    }
+    let email = token_data.claims.email.ok_or("ID token has no email claim")?;
+    Ok(email.to_lowercase())
+  }
 
-    let cookie_value = if cfg!(feature = "no_tls_upgrade") {
-      // For local development without TLS, don't set Secure flag
-      format!("{SESSION_COOKIE_NAME}={session_key}; HttpOnly; SameSite=Lax")
-    } else {
-      // For production with TLS, set Secure flag
-      format!("{SESSION_COOKIE_NAME}={session_key}; HttpOnly; SameSite=None; Secure")
-    };
+  async fn validate_code(&self, code: &str) -> Result<String, Box<dyn Error>> {
+    let token = self.exchange_code(code).await?;
+    self.validate_id_token(&token).await
+  }
 
-    response.headers_mut().insert("Set-Cookie", cookie_value.parse().unwrap());
+  /// Register a new user. See trait docs.
+  ///
+  /// # Errors
+  /// Returns `RegisterError` mapped to the wire-pinned strings.
+  pub async fn register(
+    &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
+  ) -> Result<String, RegisterError> {
+    let email = self.validate_code(code).await.map_err(|e| {
+      warn!("(OidcAuthenticator.register) Token validation failed: {e:?}");
+      RegisterError::AuthFailed
+    })?;
 
-    (response, session_key, None)
+    self.users.register_email(&email).await?;
+    bind_session_key(self.session_key.as_ref(), &email, session_keys);
+    self.email = Some(email.clone());
+    Ok(email)
   }
 }
 
 #[async_trait]
-impl Authenticator for GoogleAuthenticator {
+impl Authenticator for OidcAuthenticator {
   fn get_web_server(&self) -> String {
     self.web_server.clone()
   }
@@ -760,33 +1668,11 @@ impl Authenticator for GoogleAuthenticator {
   async fn authenticate_user(
     &mut self, code: &str, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>,
   ) -> Result<GoogleProfile, Box<dyn Error>> {
-    // Reload the directory before checking — we want CLI edits (including
-    // mid-session blacklists) to bite the next login attempt.
-    let directory = self.maybe_reload_user_directory().await;
-
-    // Validate token, lowercase the email.
-    let email = self.validate_google_token(code).await?;
-
-    // Blacklist takes precedence over "not in active list" — must come first.
-    if directory.is_blacklisted(&email) {
-      event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Blacklisted");
-      return Err(Box::new(BlacklistedUserError {}));
-    }
-
-    if !directory.is_active(&email) {
-      event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Failure");
-      return Err(Box::new(UnauthorizedUserError {}));
-    }
+    let directory = self.users.maybe_reload().await;
+    let email = self.validate_code(code).await?;
 
-    // Associate with session key.
-    if self.session_key.is_none() {
-      warn!("(authenticate_google_user) No session key found.");
-    } else {
-      session_keys
-        .lock()
-        .unwrap()
-        .insert(self.session_key.clone().unwrap(), Some(email.clone()));
-    }
+    check_directory_gates(&directory, &email)?;
+    bind_session_key(self.session_key.as_ref(), &email, session_keys);
 
     event!(target: LOG_AUTH_RESULT, Level::INFO, email = email.as_str(), result = "Success");
     self.email = Some(email.clone());
@@ -803,15 +1689,20 @@ impl Authenticator for GoogleAuthenticator {
   }
 
   fn directory_snapshot(&self) -> Option<Arc<UserDirectory>> {
-    Some(self.directory())
+    Some(self.users.snapshot())
+  }
+
+  async fn authenticate_api_token(&mut self, token: &str) -> Result<ApiTokenGrant, Box<dyn Error>> {
+    let directory = self.users.maybe_reload().await;
+    let grant = authenticate_api_token_in(Some(&directory), token)?;
+    self.email = Some(grant.email.clone());
+    Ok(grant)
+  }
+
+  async fn update_user_records(&mut self, update: &mut UserRecordsUpdate<'_>) -> Result<Arc<UserDirectory>, String> {
+    self.users.update_records(update).await
   }
 
-  /**
-   * Check if a user is logged in on this session.
-   *
-   * # Returns
-   * `true` if the user is logged in, `false` otherwise.
-   */
   fn validated_user(&self) -> bool {
     self.email.is_some()
   }
@@ -979,8 +1870,10 @@ impl Authenticator for MockAuthenticator {
       status: UserStatus::Active,
       registered_at: now,
       blacklisted_at: None,
+      admin: false,
+      api_tokens: Vec::new(),
     });
-    let new_dir = build_directory_from_records(new_records, snapshot.generation, snapshot.last_modified);
+    let new_dir = build_directory_from_records(new_records, snapshot.generation.clone(), snapshot.last_modified);
     *directory.write().expect("(MockAuthenticator.register) lock poisoned for write") = Arc::new(new_dir);
 
     if let Some(key) = self.session_key.clone() {
@@ -996,6 +1889,25 @@ impl Authenticator for MockAuthenticator {
     self.directory_snapshot_inner()
   }
 
+  async fn update_user_records(&mut self, update: &mut UserRecordsUpdate<'_>) -> Result<Arc<UserDirectory>, String> {
+    // Like `register_user`, the mock only updates the in-memory cell.
+    let Some(directory) = self.directory.clone() else {
+      return Err("Mock authenticator has no user directory to update.".to_string());
+    };
+    let mut guard = directory
+      .write()
+      .expect("(MockAuthenticator.update_user_records) lock poisoned");
+    let mut records = guard.raw.clone();
+    update(&mut records)?;
+    let new_dir = Arc::new(build_directory_from_records(
+      records,
+      guard.generation.clone(),
+      guard.last_modified,
+    ));
+    *guard = new_dir.clone();
+    Ok(new_dir)
+  }
+
   fn validated_user(&self) -> bool {
     self.email.is_some()
   }
@@ -1108,9 +2020,27 @@ struct GoogleCredsJson {
   web: GoogleCredentials,
 }
 
+// Message structures to/from a generic OIDC provider.
+#[derive(Debug, Deserialize)]
+struct OidcDiscovery {
+  token_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OidcTokenResponse {
+  id_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OidcClaims {
+  email: Option<String>,
+  email_verified: Option<bool>,
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
   use super::*;
+  use crate::storage::{GcsStorage, LocalStorage};
 
   const GOOGLE_CREDENTIALS_FILE: &str = "google_credentials.json";
 
@@ -1142,7 +2072,7 @@ pub(crate) mod tests {
   #[should_panic = "No such file or directory"]
   async fn test_bad_credentials_file() {
     const BAD_FILE: &str = "./not_there_file.json";
-    let authorized_users = load_authorized_users(BAD_FILE).await;
+    let authorized_users = load_authorized_users(&LocalStorage, BAD_FILE).await;
     assert!(
       authorized_users.is_empty(),
       "Should not get any contents on a bad authorized user file."
@@ -1153,7 +2083,7 @@ pub(crate) mod tests {
   #[test_log::test(tokio::test)]
   #[cfg_attr(feature = "ci", ignore = "Not testable in CI environment.")]
   async fn test_load_authorized_users_from_gcs() {
-    let directory = load_user_directory(GCS_TEST_FILE).await.unwrap();
+    let directory = load_user_directory(&GcsStorage, GCS_TEST_FILE).await.unwrap();
     assert!(!directory.active.is_empty(), "Authorized users file is empty");
   }
 
@@ -1161,7 +2091,7 @@ pub(crate) mod tests {
   #[test_log::test(tokio::test)]
   #[cfg_attr(feature = "ci", ignore = "Not testable in CI environment.")]
   async fn test_load_authorized_users_from_file() {
-    let directory = load_user_directory(LOCAL_TEST_FILE).await.unwrap();
+    let directory = load_user_directory(&LocalStorage, LOCAL_TEST_FILE).await.unwrap();
     assert!(!directory.active.is_empty(), "Authorized users file is empty");
   }
 
@@ -1191,11 +2121,11 @@ pub(crate) mod tests {
     }"#;
     let records = parse_user_directory_body(body).unwrap();
     assert_eq!(records.len(), 2);
-    let dir = build_directory_from_records(records, Some(7), 12345);
+    let dir = build_directory_from_records(records, Some("7".to_string()), 12345);
     assert!(dir.is_active("alice@example.com"));
     assert!(!dir.is_active("troll@example.com"));
     assert!(dir.is_blacklisted("troll@example.com"));
-    assert_eq!(dir.generation, Some(7));
+    assert_eq!(dir.generation.as_deref(), Some("7"));
     assert_eq!(dir.last_modified, 12345);
   }
 
@@ -1237,4 +2167,301 @@ pub(crate) mod tests {
     assert!(snapshot.is_active("alice@example.com"), "alice should be active");
     assert!(snapshot.is_active("bob@example.com"), "bob should be active");
   }
+
+  fn record(email: &str, admin: bool) -> UserRecord {
+    UserRecord {
+      email: email.to_string(),
+      status: UserStatus::Active,
+      registered_at: 1,
+      blacklisted_at: None,
+      admin,
+      api_tokens: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_api_token_mint_verify_revoke() {
+    let mut records = vec![record("admin@example.com", true), record("bot@example.com", false)];
+    let scope = ApiTokenScope {
+      roles: vec![Role::Pilot],
+      scenarios: vec!["arena".to_string()],
+    };
+    let (id, token) = mint_api_token(&mut records, "Bot@Example.com", "discord", scope.clone(), 100).unwrap();
+    assert!(token.starts_with(API_TOKEN_PREFIX));
+    assert!(
+      !serde_json::to_string(&records)
+        .unwrap()
+        .contains(token.split_once('.').unwrap().1),
+      "The token secret must never be stored"
+    );
+
+    let dir = build_directory_from_records(records.clone(), None, 0);
+    assert!(dir.is_admin("admin@example.com"));
+    assert!(!dir.is_admin("bot@example.com"));
+    let grant = dir.verify_api_token(&token).unwrap();
+    assert_eq!(grant.email, "bot@example.com");
+    assert_eq!(grant.id, id);
+    assert_eq!(grant.scope, scope);
+    assert!(grant.scope.allows_role(Role::Pilot) && !grant.scope.allows_role(Role::General));
+    assert!(grant.scope.allows_scenario("arena") && !grant.scope.allows_scenario("other"));
+
+    // Tampered secrets and garbage are rejected.
+    assert!(dir.verify_api_token(&format!("{token}x")).is_err());
+    assert!(dir.verify_api_token("not-a-token").is_err());
+
+    assert_eq!(revoke_api_token(&mut records, &id, 200).unwrap(), "bot@example.com");
+    assert!(revoke_api_token(&mut records, &id, 300).is_err(), "Double revoke should fail");
+    let dir = build_directory_from_records(records, None, 0);
+    assert!(!dir.api_token_live(&id));
+    assert!(dir.verify_api_token(&token).is_err());
+  }
+
+  #[test]
+  fn test_api_token_blacklisted_owner() {
+    let mut records = vec![record("bot@example.com", false)];
+    let (id, token) = mint_api_token(&mut records, "bot@example.com", "", ApiTokenScope::default(), 1).unwrap();
+    records[0].status = UserStatus::Blacklisted;
+    let dir = build_directory_from_records(records.clone(), None, 0);
+    let err = dir.verify_api_token(&token).unwrap_err();
+    assert!(err.is::<BlacklistedUserError>());
+    assert!(!dir.api_token_live(&id));
+    assert!(
+      mint_api_token(&mut records, "bot@example.com", "", ApiTokenScope::default(), 1).is_err(),
+      "Cannot mint for a blacklisted user"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_mock_api_token_login() {
+    let directory: Arc<RwLock<Arc<UserDirectory>>> = Arc::new(RwLock::new(Arc::new(build_directory_from_records(
+      vec![record("bot@example.com", false)],
+      None,
+      0,
+    ))));
+    let mut auth = MockAuthenticator::new("http://test").with_directory(directory.clone());
+
+    let mut minted = None;
+    auth
+      .update_user_records(&mut |records| {
+        minted = Some(mint_api_token(records, "bot@example.com", "", ApiTokenScope::default(), 1)?);
+        Ok(())
+      })
+      .await
+      .unwrap();
+    let (_, token) = minted.unwrap();
+
+    let grant = auth.authenticate_api_token(&token).await.unwrap();
+    assert_eq!(grant.email, "bot@example.com");
+    assert!(auth.validated_user());
+    assert!(MockAuthenticator::new("http://test")
+      .authenticate_api_token(&token)
+      .await
+      .is_err());
+  }
+
+  fn scratch_users_file(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("callisto_{name}_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let users_file = dir.join("authorized_users.json");
+    let body = UsersFileV1 {
+      version: 1,
+      users: vec![UserRecord {
+        status: UserStatus::Blacklisted,
+        blacklisted_at: Some(2),
+        ..record("mallory@example.com", false)
+      }],
+    };
+    std::fs::write(&users_file, serde_json::to_vec(&body).unwrap()).unwrap();
+    let _ = std::fs::remove_file(passwords_file_for(users_file.to_str().unwrap()));
+    users_file.to_str().unwrap().to_string()
+  }
+
+  #[test]
+  fn test_passwords_file_for() {
+    assert_eq!(
+      passwords_file_for("./config/authorized_users.json"),
+      "./config/authorized_users.passwords.json"
+    );
+    assert_eq!(passwords_file_for("gs://bucket/users"), "gs://bucket/users.passwords.json");
+  }
+
+  #[tokio::test]
+  async fn test_local_authenticator_register_and_login() {
+    let users_file = scratch_users_file("local_auth");
+    let directory: Arc<RwLock<Arc<UserDirectory>>> = Arc::new(RwLock::new(Arc::new(UserDirectory::default())));
+    let session_keys: Arc<Mutex<HashMap<String, Option<String>>>> = Arc::new(Mutex::new(HashMap::new()));
+    let users = UserDirectoryStore::new(
+      Arc::new(LocalStorage),
+      &users_file,
+      directory.clone(),
+      Arc::new(tokio::sync::Mutex::new(())),
+    )
+    .await
+    .unwrap();
+    let template = LocalAuthenticator::new("http://test", users);
+
+    let mut auth = template.clone();
+    auth.set_session_key("session_alice");
+    let alice = PasswordCredentials::new("Alice@Example.com", "correct horse");
+    let email = auth.register_user(&alice.to_code(), &session_keys).await.unwrap();
+    assert_eq!(email, "alice@example.com");
+    assert!(directory.read().unwrap().is_active("alice@example.com"));
+    assert_eq!(
+      session_keys.lock().unwrap().get("session_alice"),
+      Some(&Some("alice@example.com".to_string()))
+    );
+
+    // Only the salted hash is stored.
+    let stored = std::fs::read_to_string(passwords_file_for(&users_file)).unwrap();
+    assert!(stored.contains("alice@example.com"));
+    assert!(!stored.contains("correct horse"));
+
+    let mut login = template.clone();
+    login.set_session_key("session_alice_2");
+    assert_eq!(
+      login.authenticate_user(&alice.to_code(), &session_keys).await.unwrap(),
+      "alice@example.com"
+    );
+
+    let wrong = PasswordCredentials::new("alice@example.com", "wrong horse");
+    let err = template
+      .clone()
+      .authenticate_user(&wrong.to_code(), &session_keys)
+      .await
+      .unwrap_err();
+    assert!(err.is::<UnauthorizedUserError>());
+
+    // Re-registering must not replace the existing password.
+    let err = template.clone().register(&wrong.to_code(), &session_keys).await.unwrap_err();
+    assert!(matches!(err, RegisterError::AlreadyRegistered));
+    assert!(template
+      .clone()
+      .authenticate_user(&alice.to_code(), &session_keys)
+      .await
+      .is_ok());
+
+    let mallory = PasswordCredentials::new("mallory@example.com", "long enough");
+    let err = template.clone().register(&mallory.to_code(), &session_keys).await.unwrap_err();
+    assert!(matches!(err, RegisterError::NotAuthorized));
+
+    let short = PasswordCredentials::new("bob@example.com", "short");
+    let err = template.clone().register(&short.to_code(), &session_keys).await.unwrap_err();
+    assert!(matches!(err, RegisterError::AuthFailed));
+
+    // Blacklisting an account with a valid password locks it out.
+    let mut admin = template.clone();
+    admin
+      .update_user_records(&mut |records: &mut Vec<UserRecord>| {
+        for record in records.iter_mut().filter(|r| r.email == "alice@example.com") {
+          record.status = UserStatus::Blacklisted;
+        }
+        Ok(())
+      })
+      .await
+      .unwrap();
+    let err = template
+      .clone()
+      .authenticate_user(&alice.to_code(), &session_keys)
+      .await
+      .unwrap_err();
+    assert!(err.is::<BlacklistedUserError>());
+    // ...but a wrong password doesn't reveal that the account is blacklisted.
+    let err = template
+      .clone()
+      .authenticate_user(&wrong.to_code(), &session_keys)
+      .await
+      .unwrap_err();
+    assert!(err.is::<UnauthorizedUserError>());
+  }
+
+  #[tokio::test]
+  async fn test_oidc_validate_id_token() {
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+    let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let jwks: JwkSet = serde_json::from_value(serde_json::json!({
+      "keys": [{
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "kid": "k1",
+        "x": general_purpose::URL_SAFE_NO_PAD.encode(pair.public_key().as_ref()),
+      }]
+    }))
+    .unwrap();
+
+    let users_file = scratch_users_file("oidc_auth");
+    let config = OidcConfig {
+      issuer: "https://id.example.com".to_string(),
+      // Never fetched: the only lookups below hit the cached key.
+      jwks_url: "http://127.0.0.1:9/jwks".to_string(),
+      client_id: "callisto".to_string(),
+      client_secret: "secret".to_string(),
+      token_endpoint: "http://127.0.0.1:9/token".to_string(),
+    };
+    let auth = OidcAuthenticator {
+      config: Arc::new(config),
+      keys: Arc::new(RwLock::new(Arc::new(jwks))),
+      email: None,
+      session_key: None,
+      web_server: "http://test".to_string(),
+      users: UserDirectoryStore::new(
+        Arc::new(LocalStorage),
+        &users_file,
+        Arc::new(RwLock::new(Arc::new(UserDirectory::default()))),
+        Arc::new(tokio::sync::Mutex::new(())),
+      )
+      .await
+      .unwrap(),
+    };
+
+    let sign = |claims: serde_json::Value| {
+      let mut header = Header::new(Algorithm::EdDSA);
+      header.kid = Some("k1".to_string());
+      encode(&header, &claims, &EncodingKey::from_ed_der(pkcs8.as_ref())).unwrap()
+    };
+    let exp = unix_now() + 300;
+
+    let good = sign(serde_json::json!({
+      "iss": "https://id.example.com", "aud": "callisto", "exp": exp,
+      "email": "Carol@Example.com", "email_verified": true,
+    }));
+    assert_eq!(auth.validate_id_token(&good).await.unwrap(), "carol@example.com");
+
+    let wrong_audience = sign(serde_json::json!({
+      "iss": "https://id.example.com", "aud": "someone-else", "exp": exp, "email": "carol@example.com",
+    }));
+    assert!(auth.validate_id_token(&wrong_audience).await.is_err());
+
+    let wrong_issuer = sign(serde_json::json!({
+      "iss": "https://evil.example.com", "aud": "callisto", "exp": exp, "email": "carol@example.com",
+    }));
+    assert!(auth.validate_id_token(&wrong_issuer).await.is_err());
+
+    let unverified = sign(serde_json::json!({
+      "iss": "https://id.example.com", "aud": "callisto", "exp": exp,
+      "email": "carol@example.com", "email_verified": false,
+    }));
+    assert!(auth
+      .validate_id_token(&unverified)
+      .await
+      .unwrap_err()
+      .is::<UnauthorizedUserError>());
+
+    let expired = sign(serde_json::json!({
+      "iss": "https://id.example.com", "aud": "callisto", "exp": exp - 3600, "email": "carol@example.com",
+    }));
+    assert!(auth.validate_id_token(&expired).await.unwrap_err().is::<TokenTimeoutError>());
+
+    let mut hs_header = Header::new(Algorithm::HS256);
+    hs_header.kid = Some("k1".to_string());
+    let symmetric = encode(
+      &hs_header,
+      &serde_json::json!({"iss": "https://id.example.com", "aud": "callisto", "exp": exp, "email": "carol@example.com"}),
+      &EncodingKey::from_secret(b"guess"),
+    )
+    .unwrap();
+    assert!(auth.validate_id_token(&symmetric).await.is_err());
+  }
 }
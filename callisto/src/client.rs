@@ -0,0 +1,341 @@
+//! A typed Rust client for the Callisto WebSocket protocol.
+//!
+//! The browser front end speaks JSON-encoded [`RequestMsg`] / [`ResponseMsg`] over a WebSocket. This module wraps that
+//! conversation so bots, AI opponents, load tests and our own integration tests don't each have to hand-roll frames
+//! and JSON. A [`CallistoClient`] is both a typed sender (see [`CallistoClient::send`]) and a typed
+//! [`Stream`] of [`ResponseMsg`].
+//!
+//! Typical use:
+//! ```no_run
+//! # async fn example() -> Result<(), callisto::client::ClientError> {
+//! use callisto::client::CallistoClient;
+//! use callisto::payloads::RequestMsg;
+//!
+//! let mut client = CallistoClient::connect("ws://127.0.0.1:30000/ws").await?;
+//! let session = client.login("test_code").await?;
+//! println!("Logged in as {}", session.auth.email);
+//! client.create_scenario("my_scenario", "").await?;
+//! let entities = client.rpc(RequestMsg::EntitiesRequest).await?;
+//! # Ok(())
+//! # }
+//! ```
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::{SinkExt, Stream, StreamExt};
+use rustls::pki_types::{pem::PemObject, CertificateDer};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{connect_async, connect_async_tls_with_config, Connector, MaybeTlsStream, WebSocketStream};
+
+use crate::entity::Entities;
+use crate::payloads::{
+  ApiTokenLoginMsg, AuthResponse, CreateScenarioMsg, JoinScenarioMsg, LoginMsg, RequestMsg, ResponseMsg, ScenariosMsg,
+  ShipDesignTemplateMsg, UserData,
+};
+
+#[allow(unused_imports)]
+use crate::{debug, error, info, warn};
+
+type ClientSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Errors surfaced by [`CallistoClient`].
+#[derive(Debug)]
+pub enum ClientError {
+  /// The underlying WebSocket failed (connect, send or receive).
+  WebSocket(Box<tungstenite::Error>),
+  /// A frame could not be encoded or decoded as a protocol message.
+  Json(serde_json::Error),
+  /// TLS configuration (e.g. the root CA file) could not be loaded.
+  Tls(String),
+  /// The server closed the connection.
+  Closed,
+  /// No message arrived within the requested time.
+  Timeout,
+  /// The server answered with `ResponseMsg::Error`.
+  Server(String),
+  /// The server answered with a message we did not expect at this point in the conversation.
+  Unexpected(Box<ResponseMsg>),
+}
+
+impl std::fmt::Display for ClientError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ClientError::WebSocket(e) => write!(f, "WebSocket error: {e}"),
+      ClientError::Json(e) => write!(f, "Malformed protocol message: {e}"),
+      ClientError::Tls(e) => write!(f, "TLS configuration error: {e}"),
+      ClientError::Closed => write!(f, "Connection closed by server"),
+      ClientError::Timeout => write!(f, "Timed out waiting for server"),
+      ClientError::Server(msg) => write!(f, "Server error: {msg}"),
+      ClientError::Unexpected(msg) => write!(f, "Unexpected response: {msg:?}"),
+    }
+  }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<tungstenite::Error> for ClientError {
+  fn from(e: tungstenite::Error) -> Self {
+    ClientError::WebSocket(Box::new(e))
+  }
+}
+
+impl From<serde_json::Error> for ClientError {
+  fn from(e: serde_json::Error) -> Self {
+    ClientError::Json(e)
+  }
+}
+
+/// Everything the server pushes on a successful login or registration.
+#[derive(Debug)]
+pub struct LoginSession {
+  pub auth: AuthResponse,
+  pub scenarios: ScenariosMsg,
+  pub designs: ShipDesignTemplateMsg,
+}
+
+/// Everything the server pushes after joining (or creating) a scenario.
+#[derive(Debug)]
+pub struct JoinedScenario {
+  pub name: String,
+  pub entities: Entities,
+  pub users: Vec<UserData>,
+  /// Creating a scenario also broadcasts a refreshed scenario list; joining does not.
+  pub scenarios: Option<ScenariosMsg>,
+}
+
+/// A connection to a Callisto server.
+pub struct CallistoClient {
+  socket: ClientSocket,
+}
+
+impl CallistoClient {
+  /// Connect to a server at `url` (`ws://...` or `wss://...`).  `wss` urls are verified against the platform's
+  /// native root certificates.
+  ///
+  /// # Errors
+  /// Returns an error if the WebSocket handshake fails.
+  pub async fn connect(url: &str) -> Result<Self, ClientError> {
+    debug!("(CallistoClient.connect) Connecting to {url}");
+    let (socket, _) = connect_async(url).await?;
+    Ok(CallistoClient { socket })
+  }
+
+  /// Connect to a `wss://` server whose certificate is signed by the CA in the PEM file `root_ca`.
+  /// Useful for local servers running with self-signed keys.
+  ///
+  /// # Errors
+  /// Returns an error if the CA file cannot be read or the WebSocket handshake fails.
+  pub async fn connect_with_root_ca(url: &str, root_ca: impl AsRef<Path>) -> Result<Self, ClientError> {
+    let certs = CertificateDer::pem_file_iter(root_ca.as_ref())
+      .map_err(|e| ClientError::Tls(format!("Cannot open CA file {}: {e}", root_ca.as_ref().display())))?
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|e| ClientError::Tls(e.to_string()))?;
+    let mut root_store = rustls::RootCertStore::empty();
+    root_store.add_parsable_certificates(certs);
+
+    let config = rustls::client::ClientConfig::builder()
+      .with_root_certificates(root_store)
+      .with_no_client_auth();
+
+    debug!("(CallistoClient.connect_with_root_ca) Connecting to {url}");
+    let (socket, _) =
+      connect_async_tls_with_config(url, None, false, Some(Connector::Rustls(Arc::new(config)))).await?;
+    Ok(CallistoClient { socket })
+  }
+
+  /// Send a single request.  Responses (if any) arrive on the stream.
+  ///
+  /// # Errors
+  /// Returns an error if the request cannot be serialized or the socket write fails.
+  pub async fn send(&mut self, request: RequestMsg) -> Result<(), ClientError> {
+    debug!("(CallistoClient.send) Sending request: {request:?}");
+    let text = serde_json::to_string(&request)?;
+    self.socket.send(text.into()).await?;
+    Ok(())
+  }
+
+  /// Wait for the next response from the server.
+  ///
+  /// # Errors
+  /// Returns `ClientError::Closed` if the connection is closed, or any socket or decoding error.
+  pub async fn next_response(&mut self) -> Result<ResponseMsg, ClientError> {
+    self.next().await.unwrap_or(Err(ClientError::Closed))
+  }
+
+  /// Wait at most `duration` for the next response from the server.
+  ///
+  /// # Errors
+  /// Returns `ClientError::Timeout` if nothing arrives in time, otherwise as [`CallistoClient::next_response`].
+  pub async fn next_response_with_timeout(&mut self, duration: Duration) -> Result<ResponseMsg, ClientError> {
+    tokio::time::timeout(duration, self.next_response())
+      .await
+      .map_err(|_| ClientError::Timeout)?
+  }
+
+  /// Send a request and return the first response that follows it.  Note that broadcasts from other players can
+  /// interleave with replies, so this is only exact when nobody else is active in the scenario.
+  ///
+  /// # Errors
+  /// Returns an error if the send fails or the connection closes before a reply.
+  pub async fn rpc(&mut self, request: RequestMsg) -> Result<ResponseMsg, ClientError> {
+    self.send(request).await?;
+    let reply = self.next_response().await?;
+    debug!("(CallistoClient.rpc) Received response: {reply:?}");
+    Ok(reply)
+  }
+
+  /// Log in with an authorization code.  Against a server in test mode these are the `MockAuthenticator` codes
+  /// (e.g. `test_code`).  Consumes the scenario list and design templates the server pushes after a login.
+  ///
+  /// # Errors
+  /// Returns `ClientError::Server` if the login is rejected, or any transport error.
+  pub async fn login(&mut self, code: &str) -> Result<LoginSession, ClientError> {
+    let reply = self.rpc(RequestMsg::Login(LoginMsg { code: code.to_string() })).await?;
+    self.finish_login(reply).await
+  }
+
+  /// Log in with a long-lived API token minted by an admin.  Like [`CallistoClient::login`] on success.
+  ///
+  /// # Errors
+  /// Returns `ClientError::Server` if the token is rejected, or any transport error.
+  pub async fn login_with_api_token(&mut self, token: &str) -> Result<LoginSession, ClientError> {
+    let reply = self
+      .rpc(RequestMsg::ApiTokenLogin(ApiTokenLoginMsg {
+        token: token.to_string(),
+      }))
+      .await?;
+    self.finish_login(reply).await
+  }
+
+  /// Register a new user with an authorization code.  Like [`CallistoClient::login`] on success.
+  ///
+  /// # Errors
+  /// Returns `ClientError::Server` if the registration is rejected, or any transport error.
+  pub async fn register(&mut self, code: &str) -> Result<LoginSession, ClientError> {
+    let reply = self.rpc(RequestMsg::Register(LoginMsg { code: code.to_string() })).await?;
+    self.finish_login(reply).await
+  }
+
+  async fn finish_login(&mut self, reply: ResponseMsg) -> Result<LoginSession, ClientError> {
+    let auth = match reply {
+      ResponseMsg::AuthResponse(auth) => auth,
+      other => return Err(unexpected(other)),
+    };
+    let scenarios = match self.next_response().await? {
+      ResponseMsg::Scenarios(scenarios) => scenarios,
+      other => return Err(unexpected(other)),
+    };
+    let designs = match self.next_response().await? {
+      ResponseMsg::DesignTemplateResponse(designs) => designs,
+      other => return Err(unexpected(other)),
+    };
+    Ok(LoginSession {
+      auth,
+      scenarios,
+      designs,
+    })
+  }
+
+  /// Join a running scenario by name, consuming the entities and users pushed after the join.
+  ///
+  /// # Errors
+  /// Returns `ClientError::Server` if the scenario does not exist, or any transport error.
+  pub async fn join_scenario(&mut self, scenario_name: &str) -> Result<JoinedScenario, ClientError> {
+    self
+      .send(RequestMsg::JoinScenario(JoinScenarioMsg {
+        scenario_name: scenario_name.to_string(),
+      }))
+      .await?;
+    self.finish_join().await
+  }
+
+  /// Create (and join) a new scenario named `name`, optionally seeded from the scenario file `scenario`
+  /// (empty for a blank scenario).
+  ///
+  /// # Errors
+  /// Returns `ClientError::Server` if the name is taken, or any transport error.
+  pub async fn create_scenario(&mut self, name: &str, scenario: &str) -> Result<JoinedScenario, ClientError> {
+    self
+      .send(RequestMsg::CreateScenario(CreateScenarioMsg {
+        name: name.to_string(),
+        scenario: scenario.to_string(),
+      }))
+      .await?;
+    self.finish_join().await
+  }
+
+  // The server answers a join with `JoinedScenario`, `EntityResponse` and `Users`. Creating a scenario also
+  // broadcasts a `Scenarios` refresh, which can land anywhere in that sequence.
+  async fn finish_join(&mut self) -> Result<JoinedScenario, ClientError> {
+    let mut name = None;
+    let mut entities = None;
+    let mut scenarios = None;
+    loop {
+      match self.next_response().await? {
+        ResponseMsg::JoinedScenario(joined) => name = Some(joined),
+        ResponseMsg::EntityResponse(update) if name.is_some() => entities = Some(update),
+        ResponseMsg::Scenarios(update) => scenarios = Some(update),
+        ResponseMsg::Users(users) if name.is_some() && entities.is_some() => {
+          return Ok(JoinedScenario {
+            name: name.unwrap_or_default(),
+            entities: entities.unwrap_or_default(),
+            users,
+            scenarios,
+          });
+        }
+        other => return Err(unexpected(other)),
+      }
+    }
+  }
+
+  /// Ask a test-mode server to shut down and close the connection.
+  ///
+  /// # Errors
+  /// Returns an error if the request or close frame cannot be sent.
+  pub async fn quit(mut self) -> Result<(), ClientError> {
+    self.send(RequestMsg::Quit).await?;
+    self.close().await
+  }
+
+  /// Close the connection.
+  ///
+  /// # Errors
+  /// Returns an error if the close frame cannot be sent.
+  pub async fn close(&mut self) -> Result<(), ClientError> {
+    self.socket.close(None).await?;
+    Ok(())
+  }
+}
+
+fn unexpected(msg: ResponseMsg) -> ClientError {
+  match msg {
+    ResponseMsg::Error(e) => ClientError::Server(e),
+    other => ClientError::Unexpected(Box::new(other)),
+  }
+}
+
+/// The stream ends when the server closes the connection. Ping/pong and other control frames are skipped.
+impl Stream for CallistoClient {
+  type Item = Result<ResponseMsg, ClientError>;
+
+  fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    loop {
+      return match self.socket.poll_next_unpin(cx) {
+        Poll::Ready(Some(Ok(Message::Text(text)))) => {
+          Poll::Ready(Some(serde_json::from_str::<ResponseMsg>(&text).map_err(ClientError::from)))
+        }
+        Poll::Ready(Some(Ok(Message::Binary(bytes)))) => {
+          Poll::Ready(Some(serde_json::from_slice::<ResponseMsg>(&bytes).map_err(ClientError::from)))
+        }
+        Poll::Ready(Some(Ok(Message::Close(_))) | None) => Poll::Ready(None),
+        Poll::Ready(Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_)))) => continue,
+        Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+        Poll::Pending => Poll::Pending,
+      };
+    }
+  }
+}
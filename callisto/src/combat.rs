@@ -1,16 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::BuildHasher;
 use std::sync::{Arc, RwLock};
 
 use cgmath::InnerSpace;
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 
 use crate::action::{
   boost_for_assist_gunner, boost_for_evade, boost_for_fire, boost_for_point_defense, BoostMap, ShipAction,
 };
+use crate::crew::{CrewMember, Health};
 use crate::entity::Entity;
-use crate::payloads::{EffectMsg, LaunchMissileMsg};
-use crate::rules_tables::{DAMAGE_WEAPON_DICE, HIT_WEAPON_MOD, RANGE_BANDS, RANGE_MOD};
+use crate::payloads::{CritEffect, EffectMsg, LaunchMissileMsg, RollPurpose, RollRecord};
+use crate::rules_tables::RulesTables;
 use crate::ship::{BaySize, Range, Sensors, Ship, ShipSystem, Weapon, WeaponMount, WeaponType};
 use crate::{debug, error, info, warn};
 use tracing::event;
@@ -18,7 +20,9 @@ use tracing::Level;
 
 const DIE_SIZE: u32 = 6;
 pub const STANDARD_ROLL_THRESHOLD: i32 = 8;
-const CRITICAL_THRESHOLD: i32 = 5 + STANDARD_ROLL_THRESHOLD;
+
+// Bonus to hit a ship skimming fuel: it is committed to its orbit through the atmosphere and can't maneuver.
+const SKIMMING_HIT_MOD: i32 = 2;
 
 pub fn roll(rng: &mut dyn RngCore) -> u8 {
   u8::try_from(rng.next_u32() % DIE_SIZE + 1).unwrap_or(0)
@@ -33,6 +37,13 @@ pub fn roll_dice(dice: u8, rng: &mut dyn RngCore) -> u8 {
   (0..dice).map(|_| roll(rng)).sum()
 }
 
+/// Roll `dice` for `purpose` on behalf of `actor`, noting it in `rolls` for the audit trail.
+fn logged_roll(dice: u8, purpose: RollPurpose, actor: &str, rng: &mut dyn RngCore, rolls: &mut Vec<EffectMsg>) -> u8 {
+  let roll = roll_dice(dice, rng);
+  rolls.push(RollRecord::new(purpose, actor, dice, roll).into());
+  roll
+}
+
 #[must_use]
 pub fn task_chain_impact(effect: i32) -> i32 {
   match effect {
@@ -45,6 +56,304 @@ pub fn task_chain_impact(effect: i32) -> i32 {
   }
 }
 
+/// Task chain impact of the pilot's roll when assisting the gunners.
+fn assist_impact(pilot: i32, roll: i32) -> i32 {
+  task_chain_impact(roll - STANDARD_ROLL_THRESHOLD + pilot)
+}
+
+/// Smart missiles gain the attacker's tech level advantage over the target, between +1 and +6.
+#[must_use]
+pub fn smart_missile_bonus(source: &Ship, target: &Ship) -> i32 {
+  i32::from(source.design.tl.saturating_sub(target.design.tl)).clamp(1, 6)
+}
+
+/// The captain's evade boost applies to the first attack against a dodging ship each turn.
+fn evade_boost(defender: &Ship, boost_map: &BoostMap) -> i32 {
+  i32::from(
+    defender.is_dodging() && boost_for_evade(boost_map, defender.get_name()) > 0 && !defender.has_evade_boost_used(),
+  )
+}
+
+/// Every modifier that goes into a single attack roll and its damage.  Both [`attack`] and [`attack_odds`] build
+/// their numbers from this so the live roll and the odds shown to players cannot drift apart.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AttackModifiers {
+  pub range: Range,
+  /// False when the weapon cannot reach the target at `range`; such an attack never rolls.
+  pub in_range: bool,
+  /// Modifiers supplied by the caller: gunnery skill, assists, leadership boosts or smart missile bonus.
+  pub hit_mod: i32,
+  pub weapon_mod: i32,
+  pub range_mod: i32,
+  pub lock_mod: i32,
+  pub called_mod: i32,
+  /// Penalty from the defender dodging (pilot skill plus any evade boost).
+  pub dodge_mod: i32,
+  /// Bonus against a defender held in a skimming orbit.
+  #[serde(default)]
+  pub skimming_mod: i32,
+  pub damage_mod: i32,
+  pub armor: u32,
+}
+
+impl AttackModifiers {
+  /// Work out the modifiers for `attacker` firing `weapon` at `defender`.  This does not touch the defender, so
+  /// consuming dodge thrust or the evade boost is left to the caller.
+  #[must_use]
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    hit_mod: i32, damage_mod: i32, attacker: &Ship, defender: &Ship, weapon: &Weapon,
+    called_shot_system: Option<&ShipSystem>, evade_boost: i32, rules: &RulesTables,
+  ) -> Self {
+    // This in theory could be lossy but that would require there to be more than 4.29x10^9m which is VERY far.  If we
+    // wanted to be safer we check if the magnitude was greater than u32::MAX and then just use that.
+    // Note we will lose precision here but this is just for range so okay.
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    let range = rules.range_band((defender.get_position() - attacker.get_position()).magnitude() as u32);
+    let in_range = weapon.kind == WeaponType::Missile || weapon.kind.in_range(range);
+
+    let range_mod = if weapon.kind == WeaponType::Missile || !in_range {
+      0
+    } else {
+      rules.range_mod(range)
+    };
+
+    let lock_mod = if attacker.sensor_locks.contains(&defender.get_name().to_string()) {
+      2
+    } else {
+      0
+    };
+
+    let dodge_mod = if defender.is_dodging() {
+      -i32::from(defender.get_crew().get_pilot().max(0)) - evade_boost
+    } else {
+      0
+    };
+
+    AttackModifiers {
+      range,
+      in_range,
+      hit_mod,
+      weapon_mod: rules.hit_weapon_mod(weapon.kind),
+      range_mod,
+      lock_mod,
+      called_mod: if called_shot_system.is_some() { -2 } else { 0 },
+      dodge_mod,
+      skimming_mod: if defender.is_skimming() { SKIMMING_HIT_MOD } else { 0 },
+      damage_mod,
+      armor: defender.get_current_armor(),
+    }
+  }
+
+  /// Audit record of a 2d6 attack roll made by `actor` with these modifiers.
+  #[must_use]
+  pub fn attack_roll(&self, actor: &str, roll: u8) -> RollRecord {
+    RollRecord::new(RollPurpose::Attack, actor, 2, roll)
+      .with_modifier("skill", self.hit_mod)
+      .with_modifier("weapon", self.weapon_mod)
+      .with_modifier("range", self.range_mod)
+      .with_modifier("called shot", self.called_mod)
+      .with_modifier("sensor lock", self.lock_mod)
+      .with_modifier("dodge", self.dodge_mod)
+      .with_modifier("skimming", self.skimming_mod)
+      .with_threshold(STANDARD_ROLL_THRESHOLD)
+  }
+
+  /// Sum of all modifiers added to the 2d6 attack roll.
+  #[must_use]
+  pub fn total_hit_mod(&self) -> i32 {
+    self.hit_mod
+      + self.weapon_mod
+      + self.range_mod
+      + self.called_mod
+      + self.lock_mod
+      + self.dodge_mod
+      + self.skimming_mod
+  }
+
+  /// Damage left after the damage modifier and armor are applied to a damage `roll` with hit `effect`.  Zero means the
+  /// armor absorbed the hit.
+  #[must_use]
+  pub fn damage_through_armor(&self, roll: u32, effect: u32) -> u32 {
+    let damage = (i64::from(roll) + i64::from(effect) + i64::from(self.damage_mod)).max(0);
+    u32::try_from(damage).unwrap_or(u32::MAX).saturating_sub(self.armor)
+  }
+}
+
+/// Apply the mount multiplier to damage that got through armor.  Larger missile mounts just launch more missiles so
+/// missiles are never multiplied.
+fn mount_damage(weapon: &Weapon, damage: u32, rules: &RulesTables) -> u32 {
+  if weapon.kind == WeaponType::Missile {
+    return damage;
+  }
+  match weapon.mount {
+    WeaponMount::Turret(num) => damage + (u32::from(num) - 1) * u32::from(rules.damage_weapon_dice(weapon.kind)),
+    WeaponMount::Barbette => damage * 3,
+    WeaponMount::Bay(BaySize::Small) => damage * 10,
+    WeaponMount::Bay(BaySize::Medium) => damage * 20,
+    WeaponMount::Bay(BaySize::Large) => damage * 100,
+    WeaponMount::Spinal => damage * 250,
+  }
+}
+
+/// Probability of each total when rolling `dice` d6, indexed by the total.
+fn dice_distribution(dice: u8) -> Vec<f64> {
+  let mut dist = vec![1.0];
+  for _ in 0..dice {
+    let mut next = vec![0.0; dist.len() + DIE_SIZE as usize];
+    for (total, p) in dist.iter().enumerate() {
+      for face in 1..=DIE_SIZE as usize {
+        next[total + face] += p / f64::from(DIE_SIZE);
+      }
+    }
+    dist = next;
+  }
+  dist
+}
+
+/// The odds of one weapon hitting a target this turn, with the breakdown of how they were reached.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AttackOdds {
+  pub attacker: String,
+  pub weapon_id: usize,
+  pub target: String,
+  /// The named crew member manning the weapon.  `None` for a crew without named members or a weapon with no gunner.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub gunner: Option<String>,
+  pub gunnery: i32,
+  pub leadership: i32,
+  /// Captain's assist gunner boost.
+  pub assist_boost: i32,
+  /// True when the pilot is assisting the gunners; that roll is folded into `hit_probability`.
+  pub pilot_assist: bool,
+  /// Smart missile bonus (missiles only; they roll on impact rather than when fired).
+  pub smart_missile: i32,
+  pub modifiers: AttackModifiers,
+  /// False when the target is outside the weapon's firing arc; such a weapon can't fire at it.
+  pub in_arc: bool,
+  /// The planet hiding the target from the attacker, if any.  Nothing can be fired at a hidden target.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub occluded_by: Option<String>,
+  /// Chance of hitting on 2d6 against the standard threshold.
+  pub hit_probability: f64,
+  /// Expected damage to hull after armor and mount multipliers, counting misses as zero.  Sand, point defense
+  /// and critical hits are not included.
+  pub expected_damage: f64,
+}
+
+impl AttackOdds {
+  /// These odds with `planet` (if any) hiding the target: then the weapon can't fire at all.
+  #[must_use]
+  pub fn with_occlusion(mut self, planet: Option<String>) -> Self {
+    if planet.is_some() {
+      self.hit_probability = 0.0;
+      self.expected_damage = 0.0;
+    }
+    self.occluded_by = planet;
+    self
+  }
+}
+
+/// Compute the odds of `attacker` hitting `defender` with weapon `weapon_id` on its first shot this turn, and the
+/// damage to expect.  The modifiers are exactly those [`do_fire_actions`] and [`attack`] would use.
+///
+/// # Panics
+/// Panics if `weapon_id` is not a weapon on `attacker`.
+#[must_use]
+pub fn attack_odds(
+  attacker: &Ship, weapon_id: usize, defender: &Ship, called_shot_system: Option<&ShipSystem>, boost_map: &BoostMap,
+  rules: &RulesTables,
+) -> AttackOdds {
+  let weapon = attacker.get_weapon(weapon_id);
+  let missile = weapon.kind == WeaponType::Missile;
+
+  let (gunnery, leadership, assist_boost, smart_missile) = if missile {
+    (0, 0, 0, smart_missile_bonus(attacker, defender))
+  } else {
+    (
+      i32::from(attacker.get_crew().get_gunnery(weapon_id)),
+      i32::from(boost_for_fire(boost_map, attacker.get_name(), weapon_id)),
+      i32::from(attacker.get_assist_gunners() && boost_for_assist_gunner(boost_map, attacker.get_name()) > 0),
+      0,
+    )
+  };
+  let pilot_assist = !missile && attacker.get_assist_gunners();
+
+  let modifiers = AttackModifiers::new(
+    gunnery + leadership + assist_boost + smart_missile,
+    0,
+    attacker,
+    defender,
+    weapon,
+    // Missiles cannot do called shots
+    if missile { None } else { called_shot_system },
+    evade_boost(defender, boost_map),
+    rules,
+  );
+
+  let in_arc = weapon
+    .firing_arc()
+    .bears(attacker.axis(), defender.get_position() - attacker.get_position());
+
+  let mut hit_probability = 0.0;
+  let mut expected_damage = 0.0;
+  if modifiers.in_range && in_arc {
+    let two_dice = dice_distribution(2);
+    let damage_dice = dice_distribution(rules.damage_weapon_dice(weapon.kind));
+    let pilot = i32::from(attacker.get_crew().get_pilot());
+    let assists: Vec<(i32, f64)> = if pilot_assist {
+      (0..two_dice.len())
+        .filter(|roll| two_dice[*roll] > 0.0)
+        .map(|roll| (assist_impact(pilot, i32::try_from(roll).unwrap_or(0)), two_dice[roll]))
+        .collect()
+    } else {
+      vec![(0, 1.0)]
+    };
+
+    for (assist, assist_p) in assists {
+      for (roll, p) in two_dice.iter().enumerate().filter(|(_, p)| **p > 0.0) {
+        let hit_roll = i32::try_from(roll).unwrap_or(0) + modifiers.total_hit_mod() + assist;
+        if hit_roll < STANDARD_ROLL_THRESHOLD {
+          continue;
+        }
+        let effect = u32::try_from(hit_roll - STANDARD_ROLL_THRESHOLD).unwrap_or(0);
+        let damage: f64 = damage_dice
+          .iter()
+          .enumerate()
+          .map(|(damage_roll, damage_p)| {
+            let through = modifiers.damage_through_armor(u32::try_from(damage_roll).unwrap_or(0), effect);
+            if through == 0 {
+              0.0
+            } else {
+              damage_p * f64::from(mount_damage(weapon, through, rules))
+            }
+          })
+          .sum();
+        hit_probability += assist_p * p;
+        expected_damage += assist_p * p * damage;
+      }
+    }
+  }
+
+  AttackOdds {
+    attacker: attacker.get_name().to_string(),
+    weapon_id,
+    target: defender.get_name().to_string(),
+    gunner: attacker.get_crew().gunner_for(weapon_id).map(|gunner| gunner.name.clone()),
+    gunnery,
+    leadership,
+    assist_boost,
+    pilot_assist,
+    smart_missile,
+    modifiers,
+    in_arc,
+    occluded_by: None,
+    hit_probability,
+    expected_damage,
+  }
+}
+
 /// Do the attack of one ship's weapon system against a ship.  This includes resolving previously launched missiles that
 /// now impact the target.
 ///
@@ -63,22 +372,36 @@ pub fn task_chain_impact(effect: i32) -> i32 {
 /// # Panics
 /// Panics if the lock cannot be obtained to read a ship or if we have a case where a check was made and then untrue
 /// (e.g. finding the index number of a ship in a list after ensuring its in the list).
-// Eight params (one over the clippy default) is the natural seam: hit/damage
-// mods, attacker, defender, weapon, called-shot, boost map, and rng.
+// Nine params (two over the clippy default) is the natural seam: hit/damage
+// mods, attacker, defender, weapon, called-shot, boost map, rules, and rng.
 // Splitting them into a struct would not improve clarity here.
 #[allow(clippy::too_many_lines, clippy::too_many_arguments)]
 pub fn attack(
   hit_mod: i32, damage_mod: i32, attacker: &Ship, defender: &mut Ship, weapon: &Weapon,
-  called_shot_system: Option<&ShipSystem>, boost_map: &BoostMap, rng: &mut dyn RngCore,
+  called_shot_system: Option<&ShipSystem>, boost_map: &BoostMap, rules: &RulesTables, rng: &mut dyn RngCore,
 ) -> Vec<EffectMsg> {
   let attacker_name = attacker.get_name();
 
-  // This in theory could be lossy but that would require there to be more than 4.29x10^9m which is VERY far.  If we
-  // wanted to be safer we check if the magnitude was greater than u32::MAX and then just use that.
-  // Note we will lose precision here but this is just for range so okay.
-  #[allow(clippy::cast_sign_loss)]
-  #[allow(clippy::cast_possible_truncation)]
-  let range_band = find_range_band((defender.get_position() - attacker.get_position()).magnitude() as u32);
+  // Captain Evade boost: +1 to the defender's evasion roll on the FIRST
+  // attack against this ship this turn (only applies if the ship is actually
+  // dodging, i.e. has dodge_thrust remaining). Decrement happens via the
+  // standard `decrement_dodge_thrust` path below; this just sets the
+  // already-consumed flag so subsequent attacks this turn don't get the +1.
+  let evade_boost = evade_boost(defender, boost_map);
+  if evade_boost > 0 {
+    defender.set_evade_boost_used(true);
+  }
+
+  let modifiers = AttackModifiers::new(
+    hit_mod,
+    damage_mod,
+    attacker,
+    defender,
+    weapon,
+    called_shot_system,
+    evade_boost,
+    rules,
+  );
 
   debug!(
         "(Combat.attack) Calculating range with attacker {} at {:?}, defender {} at {:?}.  Distance is {}.  Range is {}. Range_mod is {}",
@@ -87,44 +410,22 @@ pub fn attack(
         defender.get_name(),
         defender.get_position(),
         (defender.get_position() - attacker.get_position()).magnitude(),
-        range_band,
-        RANGE_MOD[range_band as usize]
+        modifiers.range,
+        modifiers.range_mod
     );
 
-  // Captain Evade boost: +1 to the defender's evasion roll on the FIRST
-  // attack against this ship this turn (only applies if the ship is actually
-  // dodging, i.e. has dodge_thrust remaining). Decrement happens via the
-  // standard `decrement_dodge_thrust` path below; this just sets the
-  // already-consumed flag so subsequent attacks this turn don't get the +1.
-  let evade_boost = if defender.get_dodge_thrust() > 0
-    && boost_for_evade(boost_map, defender.get_name()) > 0
-    && !defender.has_evade_boost_used()
-  {
-    defender.set_evade_boost_used(true);
-    1_i32
-  } else {
-    0
-  };
-
-  let defensive_modifier = if defender.get_dodge_thrust() > 0 {
+  if defender.is_dodging() {
     debug!(
-      "(Combat.attack) {} has dodge thrust {}, so defensive modifier is -{} (with evade boost {}).",
+      "(Combat.attack) {} has dodge thrust {}, so defensive modifier is {} (with evade boost {}).",
       defender.get_name(),
       defender.get_dodge_thrust(),
-      defender.get_crew().get_pilot(),
+      modifiers.dodge_mod,
       evade_boost
     );
     defender.decrement_dodge_thrust();
-    -i32::from(defender.get_crew().get_pilot()) - evade_boost
-  } else {
-    0
-  };
+  }
 
-  let range_mod = if weapon.kind == WeaponType::Missile {
-    0
-  } else if weapon.kind.in_range(range_band) {
-    RANGE_MOD[range_band as usize]
-  } else {
+  if !modifiers.in_range {
     // We are out of range so cannot attack
     // Should never get here!
     error!(
@@ -139,41 +440,40 @@ pub fn attack(
       attacker.get_name(),
       String::from(&weapon.kind)
     ))];
-  };
-
-  let lock_mod = if attacker.sensor_locks.contains(&defender.get_name().to_string()) {
-    2
-  } else {
-    0
-  };
-
-  let called_mod = if called_shot_system.is_some() { -2 } else { 0 };
+  }
 
   info!(
-        "(Combat.attack) Ship {attacker_name} attacking with {weapon:?} against {} with hit mod {hit_mod}, weapon hit mod {}, range mod {range_mod}, called mod {called_mod},lock mod {lock_mod}, defense mod {defensive_modifier}",
+        "(Combat.attack) Ship {attacker_name} attacking with {weapon:?} against {} with hit mod {hit_mod}, weapon hit mod {}, range mod {}, called mod {},lock mod {}, defense mod {}",
         defender.get_name(),
-        HIT_WEAPON_MOD[weapon.kind as usize]
+        modifiers.weapon_mod,
+        modifiers.range_mod,
+        modifiers.called_mod,
+        modifiers.lock_mod,
+        modifiers.dodge_mod
     );
 
   if let Some(cs) = called_shot_system {
     info!("(Combat.attack) Called shot system is {:?}.", cs);
   }
 
-  let roll = i32::from(roll_dice(2, rng));
-  let hit_roll =
-    roll + hit_mod + HIT_WEAPON_MOD[weapon.kind as usize] + range_mod + called_mod + lock_mod + defensive_modifier;
+  let attack_roll = roll_dice(2, rng);
+  let roll = i32::from(attack_roll);
+  let hit_roll = roll + modifiers.total_hit_mod();
+  let mut rolls = vec![modifiers.attack_roll(attacker_name, attack_roll).into()];
 
   if hit_roll < STANDARD_ROLL_THRESHOLD {
     debug!(
       "(Combat.attack) {}'s attack roll is {}, adjusted to {}, and misses.",
       attacker_name, roll, hit_roll
     );
-    return vec![EffectMsg::message(format!(
-      "{}'s {} attack misses {}.",
-      attacker_name,
-      String::from(&weapon.kind),
-      defender.get_name()
-    ))];
+    rolls.push(EffectMsg::AttackMiss {
+      attacker: attacker_name.to_string(),
+      target: defender.get_name().to_string(),
+      weapon: weapon.kind,
+      roll: attack_roll,
+      modifiers,
+    });
+    return rolls;
   }
 
   let effect: u32 = u32::try_from(hit_roll - STANDARD_ROLL_THRESHOLD).unwrap_or(0);
@@ -185,18 +485,19 @@ pub fn attack(
 
   // Damage is compute as the weapon dice for the given weapon
   // + the effect of the hit roll
-  let roll = u32::from(roll_dice(DAMAGE_WEAPON_DICE[weapon.kind as usize], rng));
-  let mut damage = roll + effect;
-
-  damage = if i64::from(damage) + i64::from(damage_mod) < 0 {
-    0
-  } else {
-    u32::try_from(i32::try_from(damage).unwrap_or(i32::MAX) + damage_mod).unwrap_or(0)
-  };
+  let damage_dice = rules.damage_weapon_dice(weapon.kind);
+  let damage_roll = roll_dice(damage_dice, rng);
+  let roll = u32::from(damage_roll);
+  let mut damage = modifiers.damage_through_armor(roll, effect);
+  rolls.push(
+    RollRecord::new(RollPurpose::Damage, attacker_name, damage_dice, damage_roll)
+      .with_modifier("effect", i32::try_from(effect).unwrap_or(i32::MAX))
+      .with_modifier("damage", damage_mod)
+      .with_modifier("armor", -i32::try_from(modifiers.armor).unwrap_or(i32::MAX))
+      .into(),
+  );
 
-  damage = if damage > defender.get_current_armor() {
-    damage - defender.get_current_armor()
-  } else {
+  if damage == 0 {
     debug!(
             "(Combat.attack) Due too armor, {} does no damage to {} after rolling {}, adjustment with damage modifier {}, hit effect {}, and defender armor -{}.",
             attacker_name,
@@ -207,71 +508,52 @@ pub fn attack(
             defender.get_current_armor()
         );
 
-    return vec![EffectMsg::message(format!(
-      "{} hit by {}'s {} but damage absorbed by armor.",
-      defender.get_name(),
-      attacker.get_name(),
-      String::from(weapon.kind)
-    ))];
-  };
+    rolls.push(EffectMsg::ArmorAbsorbed {
+      attacker: attacker_name.to_string(),
+      target: defender.get_name().to_string(),
+      weapon: weapon.kind,
+      roll: attack_roll,
+      modifiers,
+      damage_roll: roll,
+    });
+    return rolls;
+  }
 
   debug!(
         "(Combat.attack) {attacker_name} does {damage} damage to {} after rolling {roll} ({}D), adjustment with damage modifier {}, hit effect {}, and defender armor -{}.",
         defender.get_name(),
-        DAMAGE_WEAPON_DICE[weapon.kind as usize],
+        damage_dice,
         damage_mod,
         (hit_roll - STANDARD_ROLL_THRESHOLD),
         defender.get_current_armor()
     );
 
   // Calculate additional damage multipliers (for non missiles) and effects for non-crits now.
-  let mut effects = if weapon.kind == WeaponType::Missile {
-    // Create two effects: a message stating the damage and a ship impact on the defender.
-    vec![
-      EffectMsg::Message {
-        content: format!("{} hit by a missile for {} damage.", defender.get_name(), damage),
-      },
+  // Weapon multiples are only for non-missiles.  Larger missile mounts just launch more missiles.
+  damage = mount_damage(weapon, damage, rules);
+  let mut effects = rolls;
+  effects.extend([
+    EffectMsg::AttackHit {
+      attacker: attacker_name.to_string(),
+      target: defender.get_name().to_string(),
+      weapon: weapon.kind,
+      roll: attack_roll,
+      modifiers,
+      damage_roll: roll,
+      damage,
+    },
+    if weapon.kind == WeaponType::Missile {
       EffectMsg::ShipImpact {
         target: defender.get_name().to_string(),
         position: defender.get_position(),
-      },
-    ]
-  } else {
-    // Weapon multiples are only for non-missiles.  Larger missile mounts just launch more missiles.
-    match weapon.mount {
-      WeaponMount::Turret(num) => {
-        damage += (u32::from(num) - 1) * u32::from(DAMAGE_WEAPON_DICE[weapon.kind as usize]);
-      }
-      WeaponMount::Barbette => {
-        damage *= 3;
       }
-      WeaponMount::Bay(size) => match size {
-        BaySize::Small => {
-          damage *= 10;
-        }
-        BaySize::Medium => {
-          damage *= 20;
-        }
-        BaySize::Large => {
-          damage *= 100;
-        }
-      },
-    }
-    vec![
-      EffectMsg::Message {
-        content: format!(
-          "{} hit by {} for {} damage.",
-          defender.get_name(),
-          String::from(&weapon.kind),
-          damage
-        ),
-      },
+    } else {
       EffectMsg::BeamHit {
         origin: attacker.get_position(),
         position: defender.get_position(),
-      },
-    ]
-  };
+      }
+    },
+  ]);
 
   debug!(
     "(Combat.attack) After modifiers {} does {} damage to {}.",
@@ -280,33 +562,15 @@ pub fn attack(
     defender.get_name()
   );
 
-  // The primary crit (if any) is a single crit at a level determined by the success of the hit.
-  let primary_crit = hit_roll - CRITICAL_THRESHOLD > 0;
-
-  if primary_crit {
-    debug!(
-      "(Combat.attack) Primary crit level {} to {}.",
-      hit_roll - CRITICAL_THRESHOLD,
-      defender.get_name()
-    );
-    // Add a single crit at the effect level
-    effects.append(&mut do_critical(
-      u8::try_from(hit_roll - CRITICAL_THRESHOLD).expect("(combat.attack) hit_role primary crit calc is out of range"),
-      defender,
-      called_shot_system,
-      rng,
-    ));
+  // The primary crit (if any) is a single crit at a level set by the edition of the rules.
+  if let Some(level) = rules.ruleset().primary_crit(hit_roll - STANDARD_ROLL_THRESHOLD, damage) {
+    debug!("(Combat.attack) Primary crit level {} to {}.", level, defender.get_name());
+    effects.append(&mut do_critical(level, defender, called_shot_system, rng));
   }
-  // Given get_max_hull_points() is u32, we divide it by 10 then the conversion to u64 is safe.
-  #[allow(clippy::cast_possible_truncation)]
-  #[allow(clippy::cast_sign_loss)]
-  let crit_threshold = (f64::from(defender.get_max_hull_points()) / 10.0).ceil() as u64;
 
   // The secondary crit occurs for each new 10% of the ship's hull points that this hit passes.
   let current_hull = defender.get_current_hull_points();
-  let prev_crits = u64::from(defender.get_max_hull_points() - current_hull) / crit_threshold;
-  let secondary_crit = u64::from(defender.get_max_hull_points() - current_hull + damage) / crit_threshold - prev_crits;
-
+  let secondary_crit = rules.ruleset().sustained_damage_crits(damage, defender);
   debug!("(Combat.attack) Secondary crits {} to {}.", secondary_crit, defender.get_name());
 
   // Add a level 1 crit for each secondary crit.
@@ -322,12 +586,16 @@ pub fn attack(
 fn do_critical(
   crit_level: u8, defender: &mut Ship, called_shot_system: Option<&ShipSystem>, rng: &mut dyn RngCore,
 ) -> Vec<EffectMsg> {
+  let mut rolls = vec![];
   let location = if let Some(system) = called_shot_system {
     debug!("(Combat.do_critical) Critical on called shot system '{system:?}'.");
     *system
   } else {
-    let loc = ShipSystem::from_repr(usize::from(roll_dice(2, rng) - 2))
-      .expect("(combat.apply_crit) Unable to convert a roll to ship system.");
+    let name = defender.get_name().to_string();
+    let loc = ShipSystem::from_repr(usize::from(
+      logged_roll(2, RollPurpose::CritLocation, &name, rng, &mut rolls) - 2,
+    ))
+    .expect("(combat.apply_crit) Unable to convert a roll to ship system.");
     debug!("(Combat.do_critical) Critical on random system '{loc:?}'.");
     loc
   };
@@ -336,26 +604,69 @@ fn do_critical(
 
   info!("(Combat.do_critical) {} suffers crits: {:?}.", defender.get_name(), effects);
 
-  effects
+  rolls.extend(effects);
+  rolls
+}
+
+pub(crate) fn apply_crit(
+  crit_level: u8, location: ShipSystem, defender: &mut Ship, rng: &mut dyn RngCore,
+) -> Vec<EffectMsg> {
+  let mut rolls = vec![];
+  let mut effects = crit_effects(crit_level, location, defender, rng, &mut rolls);
+  rolls.append(&mut effects);
+  rolls
+}
+
+/// Injure one of the defender's crew for whom `at_risk` is true, reporting what became of them.  Ships without named
+/// crew members make no rolls here.
+fn injure_crew(
+  defender: &mut Ship, damage: u8, at_risk: impl Fn(&CrewMember) -> bool, rng: &mut dyn RngCore,
+) -> Vec<EffectMsg> {
+  defender
+    .get_crew_mut()
+    .injure_random(damage, at_risk, rng)
+    .map(|(member, health)| crew_injury_msg(defender, &member, health))
+    .into_iter()
+    .collect()
+}
+
+/// The effect for a crew member of `ship` left in `health` by an injury.  A death also reduces the crew count.
+fn crew_injury_msg(ship: &mut Ship, member: &str, health: Health) -> EffectMsg {
+  if health == Health::Dead {
+    ship.current_crew = ship.current_crew.saturating_sub(1);
+  }
+  EffectMsg::CrewInjury {
+    ship: ship.get_name().to_string(),
+    member: member.to_string(),
+    health,
+  }
 }
 
+/// The effects of a crit, with every roll made noted in `rolls`.
 #[allow(clippy::too_many_lines)]
-fn apply_crit(crit_level: u8, location: ShipSystem, defender: &mut Ship, rng: &mut dyn RngCore) -> Vec<EffectMsg> {
+fn crit_effects(
+  crit_level: u8, location: ShipSystem, defender: &mut Ship, rng: &mut dyn RngCore, rolls: &mut Vec<EffectMsg>,
+) -> Vec<EffectMsg> {
+  let name = defender.get_name().to_string();
   let current_level = defender.crit_level[location as usize];
   let level = u8::max(current_level + 1, crit_level);
+  let crit = |ship: &Ship, effect: CritEffect| EffectMsg::CriticalHit {
+    ship: ship.get_name().to_string(),
+    system: location,
+    level,
+    effect,
+  };
 
   debug!(
     "(Combat.apply_crit) {} suffers crit level {level} to {location:?}.",
     defender.get_name(),
   );
 
-  // Reset repair bonus if this component was being repaired
-  if defender.get_last_repair_component() == Some(location) {
-    defender.reset_repair_bonus();
-  }
+  // Fresh damage undoes the progress of any team repairing this system.
+  defender.repair_setback(location);
 
   if level > 6 {
-    let damage = u32::from(roll_dice(6, rng));
+    let damage = u32::from(logged_roll(6, RollPurpose::CritEffect, &name, rng, rolls));
     debug!(
       "(Combat.apply_crit) {} suffers > level 6 crit to {:?} for {}.",
       defender.get_name(),
@@ -363,11 +674,7 @@ fn apply_crit(crit_level: u8, location: ShipSystem, defender: &mut Ship, rng: &m
       damage
     );
     defender.set_hull_points(u32::saturating_sub(defender.get_current_hull_points(), damage));
-    vec![EffectMsg::message(format!(
-      "{}'s critical hit at level {level} caused {} damage.",
-      defender.get_name(),
-      damage
-    ))]
+    vec![crit(defender, CritEffect::HullDamage { damage })]
   } else {
     event!(
       Level::INFO,
@@ -380,65 +687,49 @@ fn apply_crit(crit_level: u8, location: ShipSystem, defender: &mut Ship, rng: &m
 
     match (location, level) {
       // I take some liberties with interpreting Sensors impact to make it a bit structured
-      (ShipSystem::Sensors, 1) => {
+      (ShipSystem::Sensors | ShipSystem::Weapon, 1) => {
         defender.attack_dm -= 1;
-        vec![EffectMsg::message(format!(
-          "{}'s sensors critical hit (level {level}) and attack DM reduced by 1.",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::AttackDmReduced)]
       }
       (ShipSystem::Sensors, 6) => {
         defender.active_weapons = vec![false; defender.active_weapons.len()];
-        vec![EffectMsg::message(format!(
-          "{}'s sensors critical hit (level 6) and completely disabled.",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::Offline)]
       }
       (ShipSystem::Sensors, _) => {
         if defender.current_sensors == Sensors::Basic {
           defender.active_weapons = vec![false; defender.active_weapons.len()];
-          vec![EffectMsg::message(format!(
-            "{}'s sensors critical hit (level {level}) and completely disabled.",
-            defender.get_name()
-          ))]
+          vec![crit(defender, CritEffect::Offline)]
         } else {
           defender.current_sensors = defender.current_sensors - 1;
-          vec![EffectMsg::message(format!(
-            "{}'s sensors critical hit (level {level}) and reduced to {}.",
-            defender.get_name(),
-            String::from(defender.current_sensors)
-          ))]
+          vec![crit(
+            defender,
+            CritEffect::SensorsReduced {
+              sensors: defender.current_sensors,
+            },
+          )]
         }
       }
       (ShipSystem::Powerplant, 3) => {
         defender.current_power = u32::saturating_sub(defender.current_power, defender.design.power / 2);
-        vec![EffectMsg::message(format!(
-          "{}'s powerplant critical hit (level 3) and reduced by 50%.",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::ReducedByPercent { percent: 50 })]
       }
       (ShipSystem::Powerplant, 4) => {
         defender.current_power = 0;
-        vec![EffectMsg::message(format!(
-          "{}'s powerplant critical hit (level 4) and offline.",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::Offline)]
       }
       (ShipSystem::Powerplant, level) if level < 3 => {
         defender.current_power = u32::saturating_sub(defender.current_power, defender.design.power / 10);
-        vec![EffectMsg::message(format!(
-          "{}'s powerplant critical hit (level {level}) and reduced by 10%.",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::ReducedByPercent { percent: 10 })]
       }
       (ShipSystem::Powerplant, level) => {
         defender.current_power = 0;
-        let mut effects = vec![EffectMsg::message(format!(
-          "{}'s powerplant critical hit (level {level}) and offline.",
-          defender.get_name()
-        ))];
+        let mut effects = vec![crit(defender, CritEffect::Offline)];
         effects.append(&mut apply_crit(
-          if level == 5 { 1 } else { roll(rng) },
+          if level == 5 {
+            1
+          } else {
+            logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls)
+          },
           ShipSystem::Hull,
           defender,
           rng,
@@ -447,39 +738,29 @@ fn apply_crit(crit_level: u8, location: ShipSystem, defender: &mut Ship, rng: &m
       }
       (ShipSystem::Fuel, level) if level < 4 => {
         let fuel_loss = match level {
-          1 => u32::from(roll(rng)),
-          2 => u32::from(roll_dice(2, rng)),
-          3 => u32::from(roll(rng)) * defender.design.fuel / 10,
+          1 => u32::from(logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls)),
+          2 => u32::from(logged_roll(2, RollPurpose::CritEffect, &name, rng, rolls)),
+          3 => u32::from(logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls)) * defender.design.fuel / 10,
           _ => 0,
         };
         defender.current_fuel = u32::saturating_sub(defender.current_fuel, fuel_loss);
-        vec![EffectMsg::message(format!(
-          "{}'s fuel critical hit (level {level}) and reduced by {}.",
-          defender.get_name(),
-          fuel_loss
-        ))]
+        vec![crit(defender, CritEffect::ReducedBy { amount: fuel_loss })]
       }
       (ShipSystem::Fuel, level) => {
         defender.current_fuel = 0;
-        let mut effects = vec![EffectMsg::message(format!(
-          "{}'s fuel critical hit (level {level}) and fuel take destroyed.",
-          defender.get_name()
-        ))];
+        let mut effects = vec![crit(defender, CritEffect::Offline)];
         effects.append(&mut apply_crit(
-          if level == 5 { 1 } else { roll(rng) },
+          if level == 5 {
+            1
+          } else {
+            logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls)
+          },
           ShipSystem::Hull,
           defender,
           rng,
         ));
         effects
       }
-      (ShipSystem::Weapon, 1) => {
-        defender.attack_dm -= 1;
-        vec![EffectMsg::message(format!(
-          "{}'s weapon critical hit (level 1) and attack DM reduced by 1.",
-          defender.get_name()
-        ))]
-      }
       (ShipSystem::Weapon, level) => {
         let possible = defender.active_weapons.iter().filter(|x| **x).count();
         let mut effects = if possible > 0 {
@@ -504,20 +785,23 @@ fn apply_crit(crit_level: u8, location: ShipSystem, defender: &mut Ship, rng: &m
             .unwrap();
 
           defender.active_weapons[selected_index] = false;
-          vec![EffectMsg::message(format!(
-            "{}'s weapon critical hit (level {level}) and {} disabled.",
-            defender.get_name(),
-            String::from(&defender.design.weapons[selected_index])
-          ))]
+          vec![crit(
+            defender,
+            CritEffect::WeaponDisabled {
+              weapon: Some(defender.design.weapons[selected_index].clone()),
+            },
+          )]
         } else {
-          vec![EffectMsg::message(format!(
-            "{}'s weapon critical hit (level {level}) but all weapons already disabled.",
-            defender.get_name()
-          ))]
+          vec![crit(defender, CritEffect::WeaponDisabled { weapon: None })]
         };
         effects.append(&mut match level {
           5 => apply_crit(1, ShipSystem::Hull, defender, rng),
-          6 => apply_crit(roll(rng), ShipSystem::Hull, defender, rng),
+          6 => apply_crit(
+            logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls),
+            ShipSystem::Hull,
+            defender,
+            rng,
+          ),
           _ => vec![],
         });
         effects
@@ -525,209 +809,163 @@ fn apply_crit(crit_level: u8, location: ShipSystem, defender: &mut Ship, rng: &m
       (ShipSystem::Armor, level) => {
         let damage = match level {
           1 => 1_u32,
-          2 => u32::from(roll(rng)) / 2,
-          x if x < 5 => u32::from(roll(rng)),
-          _ => u32::from(roll_dice(2, rng)),
+          2 => u32::from(logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls)) / 2,
+          x if x < 5 => u32::from(logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls)),
+          _ => u32::from(logged_roll(2, RollPurpose::CritEffect, &name, rng, rolls)),
         };
 
         defender.current_armor = u32::saturating_sub(defender.current_armor, damage);
-        let mut effects = vec![EffectMsg::message(format!(
-          "{}'s armor critical hit (level {level}) and reduced by {}.",
-          defender.get_name(),
-          damage
-        ))];
+        let mut effects = vec![crit(defender, CritEffect::ReducedBy { amount: damage })];
         if level >= 5 {
           effects.append(&mut apply_crit(1, ShipSystem::Hull, defender, rng));
         }
         effects
       }
       (ShipSystem::Hull, level) => {
-        let damage = u32::from(roll_dice(level, rng));
-        defender.current_hull = u32::saturating_sub(defender.current_hull, damage);
-        vec![EffectMsg::message(format!(
-          "{}'s hull critical hit (level {level}) and reduced by {}.",
-          defender.get_name(),
-          damage
-        ))]
+        let damage = logged_roll(level, RollPurpose::CritEffect, &name, rng, rolls);
+        defender.current_hull = u32::saturating_sub(defender.current_hull, u32::from(damage));
+        let mut effects = vec![crit(
+          defender,
+          CritEffect::ReducedBy {
+            amount: u32::from(damage),
+          },
+        )];
+        // Someone is caught in the breach.
+        effects.append(&mut injure_crew(defender, damage, |_| true, rng));
+        effects
       }
       (ShipSystem::Maneuver, 5) => {
         defender.current_maneuver = 0;
-        vec![EffectMsg::message(format!(
-          "{}'s maneuver critical hit (level 5) and offline.",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::Offline)]
       }
       (ShipSystem::Maneuver, 6) => {
         defender.current_maneuver = 0;
-        let mut effects = vec![EffectMsg::message(format!(
-          "{}'s maneuver critical hit (level 6) and offline.",
-          defender.get_name()
-        ))];
-        effects.append(&mut apply_crit(roll(rng), ShipSystem::Hull, defender, rng));
+        let mut effects = vec![crit(defender, CritEffect::Offline)];
+        effects.append(&mut apply_crit(
+          logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls),
+          ShipSystem::Hull,
+          defender,
+          rng,
+        ));
         effects
       }
       (ShipSystem::Maneuver, _) => {
         defender.current_maneuver = u8::saturating_sub(defender.current_maneuver, 1);
-        vec![EffectMsg::message(format!(
-          "{}'s maneuver critical hit (level {level}) and reduced by 1.",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::ReducedBy { amount: 1 })]
       }
-      (ShipSystem::Cargo, 1) => vec![EffectMsg::message(format!(
-        "{}'s cargo critical hit (level {level}) and 10% of cargo destroyed.",
-        defender.get_name()
-      ))],
+      (ShipSystem::Cargo, 1) => vec![crit(defender, CritEffect::CargoDestroyed { percent: 10 })],
       (ShipSystem::Cargo, 2) => {
-        let percent_destroyed = format!("{}%", 10 * roll(rng));
-        vec![EffectMsg::message(format!(
-          "{}'s cargo critical hit (level {level}) and {percent_destroyed}% of cargo destroyed.",
-          defender.get_name()
-        ))]
+        let percent = 10 * u32::from(logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls));
+        vec![crit(defender, CritEffect::CargoDestroyed { percent })]
       }
       (ShipSystem::Cargo, 3) => {
-        let percent_destroyed = format!("{}%", roll_dice(2, rng).min(10) * 10);
-        vec![EffectMsg::message(format!(
-          "{}'s cargo critical hit (level {level}) and {percent_destroyed}% of cargo destroyed.",
-          defender.get_name()
-        ))]
+        let percent = u32::from(logged_roll(2, RollPurpose::CritEffect, &name, rng, rolls).min(10)) * 10;
+        vec![crit(defender, CritEffect::CargoDestroyed { percent })]
       }
-      (ShipSystem::Cargo, 4) => vec![EffectMsg::message(format!(
-        "{}'s cargo critical hit (level {level}) and all cargo destroyed.",
-        defender.get_name()
-      ))],
+      (ShipSystem::Cargo, 4) => vec![crit(defender, CritEffect::CargoDestroyed { percent: 100 })],
       (ShipSystem::Cargo, _) => {
         let mut effects = apply_crit(1, ShipSystem::Hull, defender, rng);
-        effects.push(EffectMsg::message(format!(
-          "{}'s cargo critical hit (level {level}) and all cargo destroyed.",
-          defender.get_name()
-        )));
+        effects.push(crit(defender, CritEffect::CargoDestroyed { percent: 100 }));
         effects
       }
       (ShipSystem::Jump, 1) => {
         defender.current_jump = u8::saturating_sub(defender.current_jump, 1);
-        vec![EffectMsg::message(format!(
-          "{}'s jump critical hit (level 1) and reduced by 1.",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::ReducedBy { amount: 1 })]
       }
       (ShipSystem::Jump, level) => {
         defender.current_jump = 0;
-        let mut effects = vec![EffectMsg::message(format!(
-          "{}'s jump critical hit (level {level}) and offline.",
-          defender.get_name()
-        ))];
+        let mut effects = vec![crit(defender, CritEffect::Offline)];
         if level >= 4 {
           effects.append(&mut apply_crit(1, ShipSystem::Hull, defender, rng));
         }
         effects
       }
       (ShipSystem::Crew, 1) => {
-        let crew_damage = roll(rng);
-        vec![EffectMsg::message(format!(
-          "{}'s crew critical hit (level 1) and random occupant takes {crew_damage} damage.",
-          defender.get_name()
-        ))]
+        let crew_damage = logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls);
+        let mut effects = vec![crit(
+          defender,
+          CritEffect::OccupantsDamaged {
+            damage: vec![crew_damage],
+          },
+        )];
+        effects.append(&mut injure_crew(defender, crew_damage, |_| true, rng));
+        effects
       }
       (ShipSystem::Crew, 2) => {
-        let hours = roll(rng);
-        vec![EffectMsg::message(format!(
-          "{}'s crew critical hit (level 2) and life support fails within {hours} hours.",
-          defender.get_name()
-        ))]
+        let hours = logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls);
+        vec![crit(defender, CritEffect::LifeSupportFailsInHours { hours })]
       }
       (ShipSystem::Crew, 3) => {
-        let num_occupants = roll(rng);
-        let damages = (0..num_occupants)
-          .map(|_| format!("{}", roll_dice(2, rng)))
-          .collect::<Vec<String>>()
-          .join(", ");
-        vec![EffectMsg::message(format!(
-          "{}'s crew critical hit (level 3) and {num_occupants} take {damages} points of damage.",
-          defender.get_name()
-        ))]
+        let num_occupants = logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls);
+        let damage: Vec<u8> = (0..num_occupants)
+          .map(|_| logged_roll(2, RollPurpose::CritEffect, &name, rng, rolls))
+          .collect();
+        let mut effects = vec![crit(defender, CritEffect::OccupantsDamaged { damage: damage.clone() })];
+        for crew_damage in damage {
+          effects.append(&mut injure_crew(defender, crew_damage, |_| true, rng));
+        }
+        effects
       }
       (ShipSystem::Crew, 4) => {
-        let rounds = roll(rng);
-        vec![EffectMsg::message(format!(
-          "{}'s crew critical hit (level 4) and life support fails in {rounds} rounds.",
-          defender.get_name()
-        ))]
+        let rounds = logged_roll(1, RollPurpose::CritEffect, &name, rng, rolls);
+        vec![crit(defender, CritEffect::LifeSupportFailsInRounds { rounds })]
       }
       (ShipSystem::Crew, 5) => {
-        vec![EffectMsg::message(format!(
-          "{}'s crew critical hit (level 5) and all occupants take 3D damage (roll each separately).",
-          defender.get_name()
-        ))]
+        let mut effects = vec![crit(defender, CritEffect::AllOccupantsDamaged { dice: 3 })];
+        let living = defender
+          .get_crew()
+          .get_members()
+          .iter()
+          .filter(|member| member.health != Health::Dead)
+          .map(|member| member.name.clone())
+          .collect::<Vec<_>>();
+        for member in living {
+          let crew_damage = logged_roll(3, RollPurpose::CritEffect, &name, rng, rolls);
+          if let Some(health) = defender.get_crew_mut().injure(&member, crew_damage) {
+            effects.push(crew_injury_msg(defender, &member, health));
+          }
+        }
+        effects
       }
-      (ShipSystem::Crew, 6) => vec![EffectMsg::message(format!(
-        "{}'s crew critical hit (level 6) and life support fails.",
-        defender.get_name()
-      ))],
+      (ShipSystem::Crew, 6) => vec![crit(defender, CritEffect::LifeSupportFailed)],
       (ShipSystem::Crew, _) => {
+        // This is a bug - should never hit this level.
         let mut effects = apply_crit(1, ShipSystem::Hull, defender, rng);
-        effects.push(EffectMsg::message(format!(
-          "{}'s crew critical hit (level {level}) (<- This is a bug - should never hit this level). Life support fails.",
-          defender.get_name()
-        )));
+        effects.push(crit(defender, CritEffect::LifeSupportFailed));
         effects
       }
-      (ShipSystem::Bridge, 1) => vec![EffectMsg::message(format!(
-        "{}'s bridge critical hit (level 1) and random bridge system disabled.",
-        defender.get_name()
-      ))],
-      (ShipSystem::Bridge, 2) => vec![EffectMsg::message(format!(
-        "{}'s bridge critical hit (level 2) and computer reboots, all software unavailable this round and next.",
-        defender.get_name()
-      ))],
+      (ShipSystem::Bridge, 1) => vec![crit(defender, CritEffect::BridgeSystemDisabled)],
+      (ShipSystem::Bridge, 2) => vec![crit(defender, CritEffect::ComputerReboot)],
       (ShipSystem::Bridge, 3) => {
         defender.current_computer /= 2;
-        vec![EffectMsg::message(format!(
-          "{}'s bridge critical hit (level 3) and computer damaged: reduce bandwidth -50%",
-          defender.get_name()
-        ))]
+        vec![crit(defender, CritEffect::ReducedByPercent { percent: 50 })]
       }
       (ShipSystem::Bridge, 4) => {
-        let crew_damage = roll_dice(2, rng);
-        vec![EffectMsg::message(format!(
-        "{}'s bridge critical hit (level 4) and random bridge station destroyed: occupant takes {crew_damage} damage.",
-        defender.get_name()
-      ))]
+        let damage = logged_roll(2, RollPurpose::CritEffect, &name, rng, rolls);
+        let mut effects = vec![crit(defender, CritEffect::BridgeStationDestroyed { damage })];
+        effects.append(&mut injure_crew(defender, damage, |member| member.station.on_bridge(), rng));
+        effects
       }
       (ShipSystem::Bridge, 5) => {
         defender.current_computer = 0;
-        vec![EffectMsg::message(format!(
-          "{}'s bridge critical hit (level 5) and computer destroyed.",
-          defender.get_name(),
-        ))]
+        vec![crit(defender, CritEffect::Offline)]
       }
       (ShipSystem::Bridge, 6) => {
-        let crew_damage = roll_dice(3, rng);
+        let damage = logged_roll(3, RollPurpose::CritEffect, &name, rng, rolls);
         let mut effects = apply_crit(1, ShipSystem::Hull, defender, rng);
-        effects.push(EffectMsg::message(format!(
-          "{}'s bridge critical hit (level 6) and random bridge station destroyed: occupant takes {crew_damage} damage.",
-          defender.get_name()
-        )));
+        effects.push(crit(defender, CritEffect::BridgeStationDestroyed { damage }));
+        effects.append(&mut injure_crew(defender, damage, |member| member.station.on_bridge(), rng));
         effects
       }
-      (ShipSystem::Bridge, level) => {
-        let crew_damage = roll_dice(3, rng);
-        vec![EffectMsg::message(format!(
-          "{}'s bridge critical hit (level {level}) (<- This is a bug - should never hit this level) and random bridge station destroyed: occupant takes {crew_damage} damage.",
-          defender.get_name()
-      ))]
+      (ShipSystem::Bridge, _) => {
+        // This is a bug - should never hit this level.
+        let damage = logged_roll(3, RollPurpose::CritEffect, &name, rng, rolls);
+        vec![crit(defender, CritEffect::BridgeStationDestroyed { damage })]
       }
     }
   }
 }
 
-fn find_range_band(distance: u32) -> Range {
-  RANGE_BANDS
-    .iter()
-    .position(|&x| x >= distance)
-    .and_then(Range::from_repr)
-    .unwrap_or(Range::Distant)
-}
-
 /// Process all incoming fire actions and turn them into either missile launches or attacks.
 ///
 /// # Arguments
@@ -747,20 +985,29 @@ fn find_range_band(distance: u32) -> Range {
 #[allow(clippy::too_many_lines)]
 pub fn do_fire_actions<S: BuildHasher>(
   attacker: &Ship, ships: &mut HashMap<String, Arc<RwLock<Ship>>, S>, sand_counts: &mut HashMap<String, Vec<i32>, S>,
-  actions: &[ShipAction], boost_map: &BoostMap, rng: &mut dyn RngCore,
+  actions: &[ShipAction], boost_map: &BoostMap, rules: &RulesTables, rng: &mut dyn RngCore,
 ) -> (Vec<LaunchMissileMsg>, Vec<EffectMsg>) {
   let mut new_missiles = vec![];
+  let mut rolls = vec![];
 
   let assist_bonus = if attacker.get_assist_gunners() {
-    let effect = i32::from(roll_dice(2, rng)) - STANDARD_ROLL_THRESHOLD + i32::from(attacker.get_crew().get_pilot());
+    let roll = roll_dice(2, rng);
+    rolls.push(
+      RollRecord::new(RollPurpose::PilotAssist, attacker.get_name(), 2, roll)
+        .with_modifier("pilot", attacker.get_crew().get_pilot())
+        .with_threshold(STANDARD_ROLL_THRESHOLD)
+        .into(),
+    );
+    let roll = i32::from(roll);
+    let impact = assist_impact(i32::from(attacker.get_crew().get_pilot()), roll);
     debug!(
-      "(Combat.do_fire_actions) Pilot of {} with skill {} is assisting gunners.  Effect is {} so task chain impact is {}.",
+      "(Combat.do_fire_actions) Pilot of {} with skill {} is assisting gunners.  Roll is {} so task chain impact is {}.",
       attacker.get_name(),
       attacker.get_crew().get_pilot(),
-      effect,
-      task_chain_impact(effect)
+      roll,
+      impact
     );
-    task_chain_impact(effect)
+    impact
   } else {
     0
   };
@@ -771,6 +1018,9 @@ pub fn do_fire_actions<S: BuildHasher>(
   // attacker's weapons), so a ship-level flag would be redundant.
   let mut first_assist_consumed = false;
 
+  // With named crew, each gunner makes one attack a turn whatever they are assigned.
+  let mut gunners_firing = HashSet::new();
+
   let effects = actions
     .iter()
     .flat_map(|action| {
@@ -778,6 +1028,7 @@ pub fn do_fire_actions<S: BuildHasher>(
         weapon_id,
         target,
         called_shot_system,
+        multiple_attacks,
       } = action
       else {
         error!("(Combat.do_fire_actions) Expected FireAction but got {:?}.", action);
@@ -795,6 +1046,32 @@ pub fn do_fire_actions<S: BuildHasher>(
         return vec![];
       }
 
+      if !attacker.weapon_powered(*weapon_id) {
+        return vec![EffectMsg::WeaponUnpowered {
+          ship: attacker.get_name().to_string(),
+          weapon_name: String::from(attacker.get_weapon(*weapon_id)),
+        }];
+      }
+
+      let gunner = if attacker.get_crew().has_members() {
+        let Some(gunner) = attacker.get_crew().gunner_for(*weapon_id) else {
+          return vec![EffectMsg::NoGunner {
+            ship: attacker.get_name().to_string(),
+            weapon_name: String::from(attacker.get_weapon(*weapon_id)),
+          }];
+        };
+        if !gunners_firing.insert(gunner.name.clone()) {
+          return vec![EffectMsg::GunnerBusy {
+            gunner: gunner.name.clone(),
+            ship: attacker.get_name().to_string(),
+            weapon_name: String::from(attacker.get_weapon(*weapon_id)),
+          }];
+        }
+        Some(gunner.name.clone())
+      } else {
+        None
+      };
+
       let weapon = attacker.get_weapon(*weapon_id);
       let gunnery_skill = i32::from(attacker.get_crew().get_gunnery(*weapon_id));
       // Captain leadership boost for this specific (ship, weapon) fire action.
@@ -826,7 +1103,7 @@ pub fn do_fire_actions<S: BuildHasher>(
       // Note we will lose precision here but this is just for range so okay.
       #[allow(clippy::cast_sign_loss)]
       #[allow(clippy::cast_possible_truncation)]
-      let range_band = find_range_band((target.get_position() - attacker.get_position()).magnitude() as u32);
+      let range_band = rules.range_band((target.get_position() - attacker.get_position()).magnitude() as u32);
       if weapon.kind != WeaponType::Missile && !weapon.kind.in_range(range_band) {
         // We are out of range so cannot attack
         debug!(
@@ -843,162 +1120,207 @@ pub fn do_fire_actions<S: BuildHasher>(
         ))];
       }
 
-      // At this point all these attacks should be in range.
-      match weapon.kind {
-        WeaponType::Missile => {
-          // Missiles don't actually attack when fired.  They'll come back and call the attack function on impact.
-          let num_missiles = match weapon.mount {
-            WeaponMount::Turret(num) => num,
-            WeaponMount::Barbette => 5,
-            WeaponMount::Bay(BaySize::Small) => 12,
-            WeaponMount::Bay(BaySize::Medium) => 24,
-            WeaponMount::Bay(BaySize::Large) => 120,
-          };
-          for _ in 0..num_missiles {
-            new_missiles.push(LaunchMissileMsg {
-              source: attacker.get_name().to_string(),
-              target: target.get_name().to_string(),
-            });
-          }
+      // Spinal mounts and weapons with a limited arc can only fire where the ship is pointed.
+      if !weapon
+        .firing_arc()
+        .bears(attacker.axis(), target.get_position() - attacker.get_position())
+      {
+        return vec![EffectMsg::OutOfArc {
+          ship: attacker.get_name().to_string(),
+          weapon: String::from(weapon),
+          target: target.get_name().to_string(),
+        }];
+      }
 
-          debug!(
-            "(Combat.do_fire_actions) {} launches {} missile at {}.",
-            attacker.get_name(),
-            num_missiles,
-            target.get_name()
-          );
+      // A turret of several weapons fires them together as one attack with extra damage, or as a separate attack each.
+      let (attacks, weapon) = match weapon.mount {
+        WeaponMount::Turret(num) if *multiple_attacks && weapon.kind != WeaponType::Missile => (
+          num,
+          Weapon {
+            kind: weapon.kind,
+            mount: WeaponMount::Turret(1),
+            arc: weapon.arc,
+          },
+        ),
+        _ => (1, weapon.clone()),
+      };
+      let weapon = &weapon;
+
+      let mut effects = gunner
+        .map(|gunner| EffectMsg::GunnerFires {
+          gunner,
+          ship: attacker.get_name().to_string(),
+          weapon_name: String::from(attacker.get_weapon(*weapon_id)),
+          target: target.get_name().to_string(),
+        })
+        .into_iter()
+        .collect::<Vec<_>>();
 
-          vec![EffectMsg::message(format!(
-            "{} launches {} missile(s) at {}.",
-            attacker.get_name(),
-            num_missiles,
-            target.get_name()
-          ))]
-        }
-        WeaponType::Beam | WeaponType::Pulse => {
-          // Lasers are special as sand can be used against them.
-          debug!(
-            "(Combat.do_fire_actions) {} fires {} at {} with lasers.",
-            attacker.get_name(),
-            String::from(&weapon.kind),
-            target.get_name()
-          );
+      // At this point all these attacks should be in range.
+      for _ in 0..attacks {
+        effects.append(&mut match weapon.kind {
+          WeaponType::Missile => {
+            // Missiles don't actually attack when fired.  They'll come back and call the attack function on impact.
+            let num_missiles = match weapon.mount {
+              WeaponMount::Turret(num) => num,
+              WeaponMount::Barbette => 5,
+              WeaponMount::Bay(BaySize::Small) => 12,
+              WeaponMount::Bay(BaySize::Medium) => 24,
+              WeaponMount::Bay(BaySize::Large) => 120,
+              WeaponMount::Spinal => {
+                error!("Spinal missile mount not supported.");
+                0
+              }
+            };
+            for _ in 0..num_missiles {
+              new_missiles.push(LaunchMissileMsg {
+                source: attacker.get_name().to_string(),
+                target: target.get_name().to_string(),
+              });
+            }
 
-          let (sand_mod, mut effects) = match sand_counts.get_mut(target.get_name()) {
-            Some(sand_casters) if !sand_casters.is_empty() => {
-              // There is a serious error if after checking if the sand_casters list isn't empty
-              // it then cannot pop an element. So unwrap() is safe here.
-              let modifier = sand_casters.pop().unwrap();
-              let effect = i32::from(roll_dice(2, rng)) - STANDARD_ROLL_THRESHOLD + modifier;
-              if effect >= 0 {
-                debug!(
+            debug!(
+              "(Combat.do_fire_actions) {} launches {} missile at {}.",
+              attacker.get_name(),
+              num_missiles,
+              target.get_name()
+            );
+
+            vec![EffectMsg::message(format!(
+              "{} launches {} missile(s) at {}.",
+              attacker.get_name(),
+              num_missiles,
+              target.get_name()
+            ))]
+          }
+          WeaponType::Beam | WeaponType::Pulse => {
+            // Lasers are special as sand can be used against them.
+            debug!(
+              "(Combat.do_fire_actions) {} fires {} at {} with lasers.",
+              attacker.get_name(),
+              String::from(&weapon.kind),
+              target.get_name()
+            );
+
+            let (sand_mod, mut effects) = match sand_counts.get_mut(target.get_name()) {
+              Some(sand_casters) if !sand_casters.is_empty() => {
+                // There is a serious error if after checking if the sand_casters list isn't empty
+                // it then cannot pop an element. So unwrap() is safe here.
+                let modifier = sand_casters.pop().unwrap();
+                let sand_roll = roll_dice(2, rng);
+                let mut effects = vec![RollRecord::new(RollPurpose::Sand, target.get_name(), 2, sand_roll)
+                  .with_modifier("sand", modifier)
+                  .with_threshold(STANDARD_ROLL_THRESHOLD)
+                  .into()];
+                let effect = i32::from(sand_roll) - STANDARD_ROLL_THRESHOLD + modifier;
+                let sand_mod = if effect >= 0 {
+                  debug!(
                   "(Combat.do_fire_actions) {}'s sand (modifier = {})successfully deployed against {} with effect {}.",
                   target.get_name(),
                   modifier,
                   attacker.get_name(),
                   effect
                 );
-                let sand_mod = effect + i32::from(roll(rng));
-                (
-                  sand_mod,
-                  vec![EffectMsg::message(format!(
-                    "{}'s sand successfully deployed against {} reducing damage by {}.",
+                  let reduction = roll(rng);
+                  effects.push(RollRecord::new(RollPurpose::SandReduction, target.get_name(), 1, reduction).into());
+                  effect + i32::from(reduction)
+                } else {
+                  debug!(
+                    "(Combat.do_fire_actions) {}'s sand (modifier = {}) failed to deploy against {} with effect {}.",
                     target.get_name(),
+                    modifier,
                     attacker.get_name(),
-                    sand_mod
-                  ))],
-                )
-              } else {
+                    effect
+                  );
+                  0
+                };
+                effects.push(EffectMsg::SandDeployed {
+                  ship: target.get_name().to_string(),
+                  attacker: attacker.get_name().to_string(),
+                  roll: sand_roll,
+                  modifier,
+                  success: effect >= 0,
+                  reduction: sand_mod,
+                });
+                (sand_mod, effects)
+              }
+              _ => {
                 debug!(
-                  "(Combat.do_fire_actions) {}'s sand (modifier = {}) failed to deploy against {} with effect {}.",
+                  "(Combat.do_fire_actions) {} has no sand to deploy against {}.",
                   target.get_name(),
-                  modifier,
-                  attacker.get_name(),
-                  effect
+                  attacker.get_name()
                 );
-
-                (
-                  0,
-                  vec![EffectMsg::message(format!(
-                    "{}'s sand failed to deploy against {}.",
-                    target.get_name(),
-                    attacker.get_name()
-                  ))],
-                )
+                (0, vec![])
               }
+            };
+
+            // Captain AssistGunner +1 applies to the FIRST actual fire roll
+            // this attacker makes this turn (only when assist_gunners is set
+            // on the attacker). Missiles don't roll here, so they don't
+            // consume the bonus.
+            let mut effective_assist = assist_bonus;
+            if attacker.get_assist_gunners()
+              && boost_for_assist_gunner(boost_map, attacker.get_name()) > 0
+              && !first_assist_consumed
+            {
+              effective_assist += 1;
+              first_assist_consumed = true;
             }
-            _ => {
-              debug!(
-                "(Combat.do_fire_actions) {} has no sand to deploy against {}.",
-                target.get_name(),
-                attacker.get_name()
-              );
-              (0, vec![])
-            }
-          };
-
-          // Captain AssistGunner +1 applies to the FIRST actual fire roll
-          // this attacker makes this turn (only when assist_gunners is set
-          // on the attacker). Missiles don't roll here, so they don't
-          // consume the bonus.
-          let mut effective_assist = assist_bonus;
-          if attacker.get_assist_gunners()
-            && boost_for_assist_gunner(boost_map, attacker.get_name()) > 0
-            && !first_assist_consumed
-          {
-            effective_assist += 1;
-            first_assist_consumed = true;
-          }
 
-          effects.append(&mut attack(
-            effective_assist + gunnery_skill + leadership_boost,
-            -sand_mod,
-            attacker,
-            &mut target,
-            weapon,
-            called_shot_system.as_ref(),
-            boost_map,
-            rng,
-          ));
-          effects
-        }
-        _ => {
-          debug!(
-            "(Combat.do_fire_actions) {} fires {} at {}.",
-            attacker.get_name(),
-            String::from(&weapon.kind),
-            target.get_name()
-          );
-
-          // Captain AssistGunner +1 applies to the FIRST actual fire roll
-          // this attacker makes this turn (only when assist_gunners is set
-          // on the attacker). Missiles don't roll here, so they don't
-          // consume the bonus.
-          let mut effective_assist = assist_bonus;
-          if attacker.get_assist_gunners()
-            && boost_for_assist_gunner(boost_map, attacker.get_name()) > 0
-            && !first_assist_consumed
-          {
-            effective_assist += 1;
-            first_assist_consumed = true;
+            effects.append(&mut rules.ruleset().attack(
+              effective_assist + gunnery_skill + leadership_boost,
+              -sand_mod,
+              attacker,
+              &mut target,
+              weapon,
+              called_shot_system.as_ref(),
+              boost_map,
+              rules,
+              rng,
+            ));
+            effects
           }
+          _ => {
+            debug!(
+              "(Combat.do_fire_actions) {} fires {} at {}.",
+              attacker.get_name(),
+              String::from(&weapon.kind),
+              target.get_name()
+            );
+
+            // Captain AssistGunner +1 applies to the FIRST actual fire roll
+            // this attacker makes this turn (only when assist_gunners is set
+            // on the attacker). Missiles don't roll here, so they don't
+            // consume the bonus.
+            let mut effective_assist = assist_bonus;
+            if attacker.get_assist_gunners()
+              && boost_for_assist_gunner(boost_map, attacker.get_name()) > 0
+              && !first_assist_consumed
+            {
+              effective_assist += 1;
+              first_assist_consumed = true;
+            }
 
-          attack(
-            effective_assist + gunnery_skill + leadership_boost,
-            0,
-            attacker,
-            &mut target,
-            weapon,
-            called_shot_system.as_ref(),
-            boost_map,
-            rng,
-          )
-        }
+            rules.ruleset().attack(
+              effective_assist + gunnery_skill + leadership_boost,
+              0,
+              attacker,
+              &mut target,
+              weapon,
+              called_shot_system.as_ref(),
+              boost_map,
+              rules,
+              rng,
+            )
+          }
+        });
       }
+      effects
     })
-    .collect();
+    .collect::<Vec<_>>();
 
-  (new_missiles, effects)
+  rolls.extend(effects);
+  (new_missiles, rolls)
 }
 
 #[must_use]
@@ -1025,6 +1347,10 @@ pub fn create_sand_counts<S: BuildHasher>(ship_snapshot: &HashMap<String, Ship,
                   error!("Bay sand mount not supported.");
                   None
                 }
+                WeaponMount::Spinal => {
+                  error!("Spinal sand mount not supported.");
+                  None
+                }
               }
             } else {
               None
@@ -1045,27 +1371,47 @@ fn point_defense_score(weapon: &Weapon) -> u16 {
     WeaponType::Missile | WeaponType::Sand | WeaponType::Particle => 0,
   }) * match weapon.mount {
     WeaponMount::Turret(num) => u16::from(num),
-    WeaponMount::Barbette | WeaponMount::Bay(_) => 0,
+    WeaponMount::Barbette | WeaponMount::Bay(_) | WeaponMount::Spinal => 0,
   }
 }
 
+/// The gunners of `ship` who take their one attack this turn with `fire_actions`, by the same rule as `do_fire_actions`.
+fn firing_gunners(ship: &Ship, fire_actions: &[ShipAction]) -> HashSet<String> {
+  fire_actions
+    .iter()
+    .filter_map(|action| match action {
+      ShipAction::FireAction { weapon_id, .. }
+        if ship.active_weapons[*weapon_id] && ship.weapon_powered(*weapon_id) =>
+      {
+        ship.get_crew().gunner_for(*weapon_id).map(|gunner| gunner.name.clone())
+      }
+      _ => None,
+    })
+    .collect()
+}
+
 /// For a given ship, and a list of ``PointDefenseAction`` actions, build a list of the weapons to use for point defense.
 /// and sort them by effectiveness.  Each item in the list is a pair of (id of the weapon, bonus to the check)
+///
+/// With named crew a weapon needs a gunner for point defense, and that gunner can't already be firing this turn
+/// (`fire_actions` are the ship's fire actions this turn) or running point defense on another weapon.
 #[must_use]
 pub fn build_point_defense_tallies(
-  ship: &Ship, actions: &[ShipAction], boost_map: &BoostMap, ship_name: &str,
+  ship: &Ship, actions: &[ShipAction], fire_actions: &[ShipAction], boost_map: &BoostMap, ship_name: &str,
 ) -> Vec<(usize, u16)> {
   let mut point_defense_list = Vec::new();
+  let mut gunners_busy = firing_gunners(ship, fire_actions);
 
   // A table indexed by weapon of the score for that weapon.
-  // The score is one more than the bonus to the check; 0 means it cannot be used for point defense.
+  // The score is one more than the bonus to the check; 0 means it cannot be used (e.g. it is damaged or unpowered).
+  let powered = ship.powered_systems();
   let weapon_scores = ship
     .design
     .weapons
     .iter()
     .enumerate()
     .map(|(index, weapon)| {
-      if ship.active_weapons[index] {
+      if powered.weapons[index] {
         point_defense_score(weapon) + u16::from(ship.crew.get_gunnery(index))
       } else {
         0
@@ -1091,6 +1437,20 @@ pub fn build_point_defense_tallies(
       continue;
     }
 
+    if ship.crew.has_members() {
+      let Some(gunner) = ship.crew.gunner_for(*weapon_id) else {
+        debug!("(Ship.add_point_defense) Weapon {} has no gunner.", weapon_id);
+        continue;
+      };
+      if !gunners_busy.insert(gunner.name.clone()) {
+        debug!(
+          "(Ship.add_point_defense) {} is already busy so weapon {} can't be used.",
+          gunner.name, weapon_id
+        );
+        continue;
+      }
+    }
+
     // Convert the score to an actual check modifier and apply any captain
     // leadership boost for this specific (ship, weapon) point-defense action.
     #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
@@ -1110,16 +1470,24 @@ pub fn build_point_defense_tallies(
   point_defense_list
 }
 
-/// Check if point defense hits an incoming missile.
+/// Check if point defense on `ship` hits an incoming missile, noting the roll in `rolls`.
 ///
 /// # Return
 /// The effect of the check if successful (so a minimum of 1). O if not successful.
-pub fn use_next_point_defense(point_defense_list: &mut Vec<(usize, u16)>, rng: &mut dyn RngCore) -> u32 {
+pub fn use_next_point_defense(
+  ship: &str, point_defense_list: &mut Vec<(usize, u16)>, rng: &mut dyn RngCore, rolls: &mut Vec<EffectMsg>,
+) -> u32 {
   let Some((next, bonus)) = point_defense_list.pop() else {
     return 0;
   };
 
   let roll = roll_dice(2, rng);
+  rolls.push(
+    RollRecord::new(RollPurpose::PointDefense, ship, 2, roll)
+      .with_modifier("point defense", bonus)
+      .with_threshold(STANDARD_ROLL_THRESHOLD)
+      .into(),
+  );
   debug!(
     "(Ship.use_next_point_defense) Using point defense weapon {next} with roll {roll}, point defense bonus {bonus}."
   );
@@ -1164,26 +1532,32 @@ mod tests {
         Weapon {
           kind: WeaponType::Beam,
           mount: WeaponMount::Turret(1),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Turret(2),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Barbette,
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Small),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Medium),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Large),
+          arc: None,
         },
       ],
       ..ShipDesignTemplate::default()
@@ -1223,36 +1597,50 @@ mod tests {
         weapon_id: 0,
         target: "Target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       }, // Beam Turret
       ShipAction::FireAction {
         weapon_id: 1,
         target: "Target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       }, // Missile Turret
       ShipAction::FireAction {
         weapon_id: 2,
         target: "Target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       }, // Missile Barbette
       ShipAction::FireAction {
         weapon_id: 3,
         target: "Target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       }, // Missile Bay (Small)
       ShipAction::FireAction {
         weapon_id: 4,
         target: "Target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       }, // Missile Bay (Medium)
       ShipAction::FireAction {
         weapon_id: 5,
         target: "Target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       }, // Missile Bay (Large)
     ];
 
     let boost_map = BoostMap::default();
-    let (missiles, effects) = do_fire_actions(&attacker, &mut ships, &mut sand_counts, &actions, &boost_map, &mut rng);
+    let (missiles, effects) = do_fire_actions(
+      &attacker,
+      &mut ships,
+      &mut sand_counts,
+      &actions,
+      &boost_map,
+      &RulesTables::default(),
+      &mut rng,
+    );
 
     // Check beam weapon effect
     assert!(effects.iter().any(|e| matches!(e, EffectMsg::BeamHit { .. })));
@@ -1274,6 +1662,14 @@ mod tests {
     // For example, checking for specific damage amounts or other effect details
   }
 
+  /// The effects of a crit less the dice rolls recorded on the way.
+  fn without_rolls(effects: Vec<EffectMsg>) -> Vec<EffectMsg> {
+    effects
+      .into_iter()
+      .filter(|e| !matches!(e, EffectMsg::DiceRoll { .. }))
+      .collect()
+  }
+
   #[test_log::test]
   fn test_apply_crit() {
     let mut rng = StdRng::seed_from_u64(42); // Use a seeded RNG for reproducibility
@@ -1288,8 +1684,8 @@ mod tests {
 
     // Test Hull critical hits
     for level in 1..=6 {
-      let effects = apply_crit(level, ShipSystem::Hull, &mut ship, &mut rng);
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      let effects = without_rolls(apply_crit(level, ShipSystem::Hull, &mut ship, &mut rng));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
       assert_eq!(ship.crit_level[ShipSystem::Hull as usize], level);
     }
 
@@ -1302,26 +1698,32 @@ mod tests {
         Weapon {
           kind: WeaponType::Beam,
           mount: WeaponMount::Turret(1),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Turret(2),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Barbette,
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Small),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Medium),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Large),
+          arc: None,
         },
       ],
       ..ShipDesignTemplate::default()
@@ -1332,17 +1734,17 @@ mod tests {
 
     // Test Armor critical hits
     for level in 1..=6 {
-      let effects = apply_crit(level, ShipSystem::Armor, &mut ship, &mut rng);
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      let effects = without_rolls(apply_crit(level, ShipSystem::Armor, &mut ship, &mut rng));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
       assert_eq!(ship.crit_level[ShipSystem::Armor as usize], level);
     }
 
     // Test Sensor critical hits
     for level in 1..=6 {
       let orig_sensors = ship.current_sensors;
-      let effects = apply_crit(level, ShipSystem::Sensors, &mut ship, &mut rng);
+      let effects = without_rolls(apply_crit(level, ShipSystem::Sensors, &mut ship, &mut rng));
 
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
       match level {
         1 => assert_eq!(ship.attack_dm, -1),
         6 => assert_eq!(ship.active_weapons, vec![false; 6]),
@@ -1356,9 +1758,9 @@ mod tests {
       ship.current_power = 100; // Reset power before each test
       ship.current_hull = 100; // Reset hull before each test
 
-      let effects = apply_crit(level, ShipSystem::Powerplant, &mut ship, &mut rng);
+      let effects = without_rolls(apply_crit(level, ShipSystem::Powerplant, &mut ship, &mut rng));
 
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
 
       match level {
         1 | 2 => {
@@ -1394,8 +1796,8 @@ mod tests {
     // Test Weapon critical hits
     for level in 1..=6 {
       ship.active_weapons = vec![true, true, true, true, true, true];
-      let effects = apply_crit(level, ShipSystem::Weapon, &mut ship, &mut rng);
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      let effects = without_rolls(apply_crit(level, ShipSystem::Weapon, &mut ship, &mut rng));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
       assert_eq!(ship.crit_level[ShipSystem::Weapon as usize], level);
     }
 
@@ -1404,9 +1806,9 @@ mod tests {
       ship.current_fuel = 100; // Reset fuel before each test
       ship.current_hull = 100; // Reset hull before each test
 
-      let effects = apply_crit(level, ShipSystem::Fuel, &mut ship, &mut rng);
+      let effects = without_rolls(apply_crit(level, ShipSystem::Fuel, &mut ship, &mut rng));
 
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
 
       match level {
         1..=3 => {
@@ -1431,16 +1833,16 @@ mod tests {
     // Test Drive critical hits
     for level in 1..=6 {
       ship.current_maneuver = 6;
-      let effects = apply_crit(level, ShipSystem::Maneuver, &mut ship, &mut rng);
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      let effects = without_rolls(apply_crit(level, ShipSystem::Maneuver, &mut ship, &mut rng));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
       assert_eq!(ship.crit_level[ShipSystem::Maneuver as usize], level);
     }
 
     // Test Jump critical hits
     for level in 1..=6 {
       ship.current_jump = 6;
-      let effects = apply_crit(level, ShipSystem::Jump, &mut ship, &mut rng);
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      let effects = without_rolls(apply_crit(level, ShipSystem::Jump, &mut ship, &mut rng));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
       assert_eq!(ship.crit_level[ShipSystem::Jump as usize], level);
       if level >= 2 {
         assert_eq!(ship.current_jump, 0);
@@ -1454,30 +1856,85 @@ mod tests {
 
     // Test Crew critical hits
     for level in 1..=6 {
-      let effects = apply_crit(level, ShipSystem::Crew, &mut ship, &mut rng);
+      let effects = without_rolls(apply_crit(level, ShipSystem::Crew, &mut ship, &mut rng));
       assert_eq!(effects.len(), 1);
-      assert!(matches!(effects[0], EffectMsg::Message { .. }));
+      assert!(matches!(effects[0], EffectMsg::CriticalHit { .. }));
     }
 
     // Test Bridge critical hits
     // Level 1-5 only have one effect.
     for level in 1..6 {
-      let effects = apply_crit(level, ShipSystem::Bridge, &mut ship, &mut rng);
+      let effects = without_rolls(apply_crit(level, ShipSystem::Bridge, &mut ship, &mut rng));
       assert_eq!(
         effects.len(),
         1,
         "Should have exactly one effect for level {level}. Instead found {effects:?}"
       );
-      assert!(matches!(effects[0], EffectMsg::Message { .. }));
+      assert!(matches!(effects[0], EffectMsg::CriticalHit { .. }));
     }
-    let effects = apply_crit(6, ShipSystem::Bridge, &mut ship, &mut rng);
+    let effects = without_rolls(apply_crit(6, ShipSystem::Bridge, &mut ship, &mut rng));
     assert_eq!(
       effects.len(),
       2,
       "Should have exactly two effects for level 6. Instead found {effects:?}"
     );
-    assert!(matches!(effects[0], EffectMsg::Message { .. }));
-    assert!(matches!(effects[1], EffectMsg::Message { .. }));
+    assert!(matches!(effects[0], EffectMsg::CriticalHit { .. }));
+    assert!(matches!(effects[1], EffectMsg::CriticalHit { .. }));
+  }
+
+  #[test_log::test]
+  fn test_crit_injures_crew() {
+    use crate::crew::{CrewMember, Health, Skills, Station};
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut ship = Ship::new(
+      "TestShip".to_string(),
+      Vec3::zero(),
+      Vec3::zero(),
+      &Arc::new(ShipDesignTemplate::default()),
+      None,
+    );
+    ship
+      .get_crew_mut()
+      .add_member(CrewMember::new("Ada", Station::Pilot, &[(Skills::Pilot, 2)]));
+    ship
+      .get_crew_mut()
+      .add_member(CrewMember::new("Ben", Station::Gunner, &[(Skills::Gunnery, 1)]));
+
+    // Everyone aboard takes 3D.
+    let effects = without_rolls(apply_crit(5, ShipSystem::Crew, &mut ship, &mut rng));
+    assert_eq!(
+      effects.len(),
+      3,
+      "Expected the crit and a message for each crew member: {effects:?}"
+    );
+    assert!(effects[1..].iter().all(|e| matches!(e, EffectMsg::CrewInjury { .. })));
+    let health = |ship: &Ship| {
+      ship
+        .get_crew()
+        .get_members()
+        .iter()
+        .map(|member| member.health)
+        .collect::<Vec<_>>()
+    };
+    assert!(health(&ship).iter().all(|health| !health.is_fit()));
+
+    // A destroyed bridge station only hurts those on the bridge.
+    let ben = health(&ship)[1];
+    let effects = without_rolls(apply_crit(4, ShipSystem::Bridge, &mut ship, &mut rng));
+    assert_eq!(effects.len(), 2, "Expected the crit and a message for Ada: {effects:?}");
+    let EffectMsg::CrewInjury {
+      ship: name,
+      member,
+      health: ada,
+    } = &effects[1]
+    else {
+      panic!("Expected a crew injury, got {:?}", effects[1]);
+    };
+    assert_eq!((name.as_str(), member.as_str()), ("TestShip", "Ada"));
+    assert_eq!(health(&ship)[0], *ada);
+    assert_eq!(health(&ship)[1], ben);
+    assert_ne!(health(&ship)[0], Health::Fit);
   }
 
   #[test_log::test]
@@ -1490,26 +1947,32 @@ mod tests {
         Weapon {
           kind: WeaponType::Beam,
           mount: WeaponMount::Turret(1),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Turret(2),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Pulse,
           mount: WeaponMount::Barbette,
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Small),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Medium),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Missile,
           mount: WeaponMount::Bay(BaySize::Large),
+          arc: None,
         },
       ],
       hull: 100,
@@ -1562,6 +2025,7 @@ mod tests {
       let weapon = Weapon {
         kind: weapon_type,
         mount: weapon_mount.clone(),
+        arc: None,
       };
 
       let starting_hull = defender.get_current_hull_points();
@@ -1574,6 +2038,7 @@ mod tests {
         &weapon,
         None,
         &BoostMap::default(),
+        &RulesTables::default(),
         &mut rng,
       );
       // Check that we have effects. If not it means we missed which is okay for some attacks.
@@ -1582,12 +2047,12 @@ mod tests {
         assert!(
                     effects
                         .iter()
-                        .any(|e| !matches!(e, EffectMsg::Message { .. })),
+                        .any(|e| matches!(e, EffectMsg::AttackHit { .. })),
                     "Expected hit in test case [hit_mod: {hit_mod}, damage_mod: {damage_mod}, weapon_type: {weapon_type:?}, weapon_mount: {weapon_mount:?}] and should produce effects: {effects:?}"
                 );
       } else {
         assert!(
-          !effects.iter().any(|e| !matches!(e, EffectMsg::Message { .. })),
+          !effects.iter().any(|e| matches!(e, EffectMsg::AttackHit { .. })),
           "Miss should produce no effects"
         );
         continue;
@@ -1600,7 +2065,7 @@ mod tests {
         }
         WeaponType::Missile => {
           // For missiles, we don't check for BeamHit, but we should have a damage message
-          assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+          assert!(effects.iter().any(|e| matches!(e, EffectMsg::AttackHit { .. })));
         }
         _ => panic!("Unexpected weapon type"),
       }
@@ -1638,13 +2103,17 @@ mod tests {
       &Weapon {
         kind: WeaponType::Beam,
         mount: WeaponMount::Turret(1),
+        arc: None,
       },
       None,
       &BoostMap::default(),
+      &RulesTables::default(),
       &mut rng,
     );
     assert!(
-      !miss_effects.iter().any(|e| !matches!(e, EffectMsg::Message { .. })),
+      miss_effects
+        .iter()
+        .all(|e| matches!(e, EffectMsg::AttackMiss { .. } | EffectMsg::DiceRoll { .. })),
       "Miss should produce no effects"
     );
 
@@ -1658,14 +2127,14 @@ mod tests {
       &Weapon {
         kind: WeaponType::Beam,
         mount: WeaponMount::Turret(1),
+        arc: None,
       },
       None,
       &BoostMap::default(),
+      &RulesTables::default(),
       &mut rng,
     );
-    assert!(crit_effects
-      .iter()
-      .any(|e| matches!(e, EffectMsg::Message { content } if content.contains("critical"))));
+    assert!(crit_effects.iter().any(|e| matches!(e, EffectMsg::CriticalHit { .. })));
 
     info!("(test.test_attack) Test non-missile medium and large bays.");
     // Test scenario for non-missile weapons in medium or large bays
@@ -1683,7 +2152,7 @@ mod tests {
       let mut effects = vec![];
 
       // Repeat the attack until we have a hit.
-      while !effects.iter().any(|e| !matches!(e, EffectMsg::Message { .. })) {
+      while !effects.iter().any(|e| matches!(e, EffectMsg::AttackHit { .. })) {
         defender.current_hull = 200;
         effects = attack(
           0,
@@ -1693,9 +2162,11 @@ mod tests {
           &Weapon {
             kind: WeaponType::Particle,
             mount: WeaponMount::Bay(size),
+            arc: None,
           },
           None,
           &BoostMap::default(),
+          &RulesTables::default(),
           &mut rng,
         );
       }
@@ -1712,7 +2183,7 @@ mod tests {
           200 - defender.current_hull
         ),
       }
-      assert!(effects.iter().any(|e| matches!(e, EffectMsg::Message { .. })));
+      assert!(effects.iter().any(|e| matches!(e, EffectMsg::AttackHit { .. })));
     }
   }
 
@@ -1738,6 +2209,7 @@ mod tests {
     let in_range_weapon = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Turret(1),
+      arc: None,
     };
     defender.set_position(Vec3::new(1_000_000.0, 0.0, 0.0)); // Assuming this is within range
     let result = attack(
@@ -1748,6 +2220,7 @@ mod tests {
       &in_range_weapon,
       None,
       &BoostMap::default(),
+      &RulesTables::default(),
       &mut rng,
     );
     assert!(result.iter().all(|msg| !msg.to_string().contains("out of range")));
@@ -1756,6 +2229,7 @@ mod tests {
     let out_of_range_weapon = Weapon {
       kind: WeaponType::Pulse,
       mount: WeaponMount::Turret(1),
+      arc: None,
     };
     defender.set_position(Vec3::new(30_000_000.0, 0.0, 0.0)); // Assuming this is out of range
     let result = attack(
@@ -1766,6 +2240,7 @@ mod tests {
       &out_of_range_weapon,
       None,
       &BoostMap::default(),
+      &RulesTables::default(),
       &mut rng,
     );
     assert!(result.iter().any(|msg| msg.to_string().contains("out of range")));
@@ -1774,6 +2249,7 @@ mod tests {
     let missile_weapon = Weapon {
       kind: WeaponType::Missile,
       mount: WeaponMount::Turret(1),
+      arc: None,
     };
     let result = attack(
       0,
@@ -1783,6 +2259,7 @@ mod tests {
       &missile_weapon,
       None,
       &BoostMap::default(),
+      &RulesTables::default(),
       &mut rng,
     );
     assert!(result.iter().all(|msg| !msg.to_string().contains("out of range")));
@@ -1814,15 +2291,27 @@ mod tests {
     let weapon = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Turret(1),
+      arc: None,
     };
 
     #[allow(clippy::cast_sign_loss)]
     #[allow(clippy::cast_possible_truncation)]
-    let range_band = find_range_band(attacker.get_position().distance(defender.get_position()) as u32);
+    let range_band =
+      RulesTables::default().range_band(attacker.get_position().distance(defender.get_position()) as u32);
 
     assert_eq!(range_band, Range::Long);
 
-    let result = attack(0, 0, &attacker, &mut defender, &weapon, None, &BoostMap::default(), &mut rng);
+    let result = attack(
+      0,
+      0,
+      &attacker,
+      &mut defender,
+      &weapon,
+      None,
+      &BoostMap::default(),
+      &RulesTables::default(),
+      &mut rng,
+    );
 
     assert_eq!(result.len(), 1);
     assert!(
@@ -1843,11 +2332,22 @@ mod tests {
 
     #[allow(clippy::cast_sign_loss)]
     #[allow(clippy::cast_possible_truncation)]
-    let range_band = find_range_band(attacker.get_position().distance(defender.get_position()) as u32);
+    let range_band =
+      RulesTables::default().range_band(attacker.get_position().distance(defender.get_position()) as u32);
 
     assert_eq!(range_band, Range::Medium);
 
-    let result = attack(0, 0, &attacker, &mut defender, &weapon, None, &BoostMap::default(), &mut rng);
+    let result = attack(
+      0,
+      0,
+      &attacker,
+      &mut defender,
+      &weapon,
+      None,
+      &BoostMap::default(),
+      &RulesTables::default(),
+      &mut rng,
+    );
     assert!(
       result.iter().all(|msg| !msg.to_string().contains("out of range")),
       "Expected no out of range message"
@@ -1896,10 +2396,21 @@ mod tests {
     let weapon = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Turret(1),
+      arc: None,
     };
 
     // First attack: evade boost consumed, flag flips to true.
-    let _ = attack(0, 0, &attacker, &mut defender, &weapon, None, &boost_map, &mut rng);
+    let _ = attack(
+      0,
+      0,
+      &attacker,
+      &mut defender,
+      &weapon,
+      None,
+      &boost_map,
+      &RulesTables::default(),
+      &mut rng,
+    );
     assert!(
       defender.has_evade_boost_used(),
       "First attack should have consumed the evade boost"
@@ -1911,7 +2422,17 @@ mod tests {
     );
 
     // Second attack: flag stays true (already consumed); dodge thrust decrements again.
-    let _ = attack(0, 0, &attacker, &mut defender, &weapon, None, &boost_map, &mut rng);
+    let _ = attack(
+      0,
+      0,
+      &attacker,
+      &mut defender,
+      &weapon,
+      None,
+      &boost_map,
+      &RulesTables::default(),
+      &mut rng,
+    );
     assert!(
       defender.has_evade_boost_used(),
       "Evade boost should remain consumed after second attack"
@@ -1964,6 +2485,7 @@ mod tests {
     let weapon = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Turret(1),
+      arc: None,
     };
 
     // Run a number of trials with the same seed schedule. With the same
@@ -1989,6 +2511,7 @@ mod tests {
         &weapon,
         None,
         &BoostMap::default(),
+        &RulesTables::default(),
         &mut rng_unboosted,
       );
 
@@ -1996,7 +2519,17 @@ mod tests {
       boost_map.insert(BoostTarget::Evade {
         ship: "Defender".to_string(),
       });
-      let _ = attack(0, 0, &attacker, &mut d_boosted, &weapon, None, &boost_map, &mut rng_boosted);
+      let _ = attack(
+        0,
+        0,
+        &attacker,
+        &mut d_boosted,
+        &weapon,
+        None,
+        &boost_map,
+        &RulesTables::default(),
+        &mut rng_boosted,
+      );
 
       let unboosted_damage = ShipDesignTemplate::default().hull - d_unboosted.get_current_hull_points();
       let boosted_damage = ShipDesignTemplate::default().hull - d_boosted.get_current_hull_points();
@@ -2055,9 +2588,11 @@ mod tests {
       &Weapon {
         kind: WeaponType::Beam,
         mount: WeaponMount::Turret(1),
+        arc: None,
       },
       None,
       &boost_map,
+      &RulesTables::default(),
       &mut rng,
     );
 
@@ -2098,6 +2633,7 @@ mod tests {
       weapons: vec![Weapon {
         kind: WeaponType::Beam,
         mount: WeaponMount::Turret(1),
+        arc: None,
       }],
       ..ShipDesignTemplate::default()
     });
@@ -2126,6 +2662,7 @@ mod tests {
       weapon_id: 0,
       target: "Target".to_string(),
       called_shot_system: None,
+      multiple_attacks: false,
     }];
 
     let mut total_unboosted: u64 = 0;
@@ -2156,6 +2693,7 @@ mod tests {
         &mut sand_counts_unboosted,
         &actions,
         &BoostMap::default(),
+        &RulesTables::default(),
         &mut rng_unboosted,
       );
       let unboosted_hull = ships_unboosted.get("Target").unwrap().read().unwrap().get_current_hull_points();
@@ -2187,6 +2725,7 @@ mod tests {
         &mut sand_counts_boosted,
         &actions,
         &boost_map,
+        &RulesTables::default(),
         &mut rng_boosted,
       );
       let boosted_hull = ships_boosted.get("Target").unwrap().read().unwrap().get_current_hull_points();
@@ -2229,10 +2768,12 @@ mod tests {
         Weapon {
           kind: WeaponType::Beam,
           mount: WeaponMount::Turret(1),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Beam,
           mount: WeaponMount::Turret(1),
+          arc: None,
         },
       ],
       ..ShipDesignTemplate::default()
@@ -2271,11 +2812,13 @@ mod tests {
         weapon_id: 0,
         target: "Target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       },
       ShipAction::FireAction {
         weapon_id: 1,
         target: "Target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       },
     ];
 
@@ -2287,6 +2830,329 @@ mod tests {
 
     // Should complete without panic. The first FireAction is the one that
     // consumes the +1; the second runs at the base assist_bonus.
-    let _ = do_fire_actions(&attacker, &mut ships, &mut sand_counts, &actions, &boost_map, &mut rng);
+    let _ = do_fire_actions(
+      &attacker,
+      &mut ships,
+      &mut sand_counts,
+      &actions,
+      &boost_map,
+      &RulesTables::default(),
+      &mut rng,
+    );
+  }
+
+  #[test_log::test]
+  fn test_do_fire_actions_gunners() {
+    use crate::crew::{CrewMember, Skills, Station};
+
+    let attacker_design = Arc::new(ShipDesignTemplate {
+      name: "Attacker".to_string(),
+      weapons: vec![
+        Weapon {
+          kind: WeaponType::Beam,
+          mount: WeaponMount::Turret(3),
+          arc: None,
+        },
+        Weapon {
+          kind: WeaponType::Beam,
+          mount: WeaponMount::Turret(1),
+          arc: None,
+        },
+        Weapon {
+          kind: WeaponType::Beam,
+          mount: WeaponMount::Turret(1),
+          arc: None,
+        },
+      ],
+      ..ShipDesignTemplate::default()
+    });
+    let target_design = Arc::new(ShipDesignTemplate {
+      name: "Target".to_string(),
+      armor: 0,
+      ..ShipDesignTemplate::default()
+    });
+    let mut attacker = Ship::new(
+      "Attacker".to_string(),
+      Vec3::new(-1000.0, 0.0, 0.0),
+      Vec3::zero(),
+      &attacker_design,
+      None,
+    );
+    let crew = attacker.get_crew_mut();
+    crew.add_member(CrewMember::new("Ben", Station::Gunner, &[(Skills::Gunnery, 1)]));
+    crew.assign_gunner("Ben", &[0, 1]).unwrap();
+
+    let target = Ship::new(
+      "Target".to_string(),
+      Vec3::new(1000.0, 0.0, 0.0),
+      Vec3::zero(),
+      &target_design,
+      None,
+    );
+    let mut ships: HashMap<String, Arc<RwLock<Ship>>> = HashMap::new();
+    ships.insert("Target".to_string(), Arc::new(RwLock::new(target.clone())));
+    let mut sand_input: HashMap<String, Ship> = HashMap::new();
+    sand_input.insert("Target".to_string(), target);
+    let mut sand_counts = create_sand_counts(&sand_input);
+
+    let fire = |weapon_id, multiple_attacks| ShipAction::FireAction {
+      weapon_id,
+      target: "Target".to_string(),
+      called_shot_system: None,
+      multiple_attacks,
+    };
+    let (_, effects) = do_fire_actions(
+      &attacker,
+      &mut ships,
+      &mut sand_counts,
+      &[fire(0, true), fire(1, false), fire(2, false)],
+      &BoostMap::default(),
+      &RulesTables::default(),
+      &mut StdRng::seed_from_u64(7),
+    );
+
+    let weapon = |id| String::from(&attacker_design.weapons[id]);
+    let idle = effects
+      .iter()
+      .filter(|effect| matches!(effect, EffectMsg::GunnerBusy { .. } | EffectMsg::NoGunner { .. }))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      idle,
+      vec![
+        &EffectMsg::GunnerBusy {
+          gunner: "Ben".to_string(),
+          ship: "Attacker".to_string(),
+          weapon_name: weapon(1),
+        },
+        &EffectMsg::NoGunner {
+          ship: "Attacker".to_string(),
+          weapon_name: weapon(2),
+        },
+      ]
+    );
+    assert_eq!(
+      idle[0].to_string(),
+      format!("Ben is already firing this turn so Attacker's {} can't fire.", weapon(1))
+    );
+    let firing = effects
+      .iter()
+      .filter_map(|effect| match effect {
+        EffectMsg::GunnerFires {
+          gunner,
+          weapon_name,
+          target,
+          ..
+        } => Some((gunner.as_str(), weapon_name.clone(), target.as_str())),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(firing, vec![("Ben", weapon(0), "Target")]);
+    // The triple turret fires as three separate attacks.
+    let attack_rolls = effects
+      .iter()
+      .filter(|effect| matches!(effect, EffectMsg::DiceRoll { record } if record.purpose == RollPurpose::Attack))
+      .count();
+    assert_eq!(attack_rolls, 3);
+
+    let odds = attack_odds(
+      &attacker,
+      0,
+      &ships["Target"].read().unwrap(),
+      None,
+      &BoostMap::default(),
+      &RulesTables::default(),
+    );
+    assert_eq!(odds.gunner.as_deref(), Some("Ben"));
+    assert_eq!(odds.gunnery, 1);
+
+    // Point defense follows the same rule: Ben can't run it having fired, nor on two turrets, and an unmanned one can't.
+    let point_defense = |weapon_id| ShipAction::PointDefenseAction { weapon_id };
+    let all_point_defense = [point_defense(0), point_defense(1), point_defense(2)];
+    let tallies = build_point_defense_tallies(
+      &attacker,
+      &all_point_defense,
+      &[fire(0, true)],
+      &BoostMap::default(),
+      "Attacker",
+    );
+    assert!(tallies.is_empty());
+    let tallies = build_point_defense_tallies(&attacker, &all_point_defense, &[], &BoostMap::default(), "Attacker");
+    assert_eq!(tallies.iter().map(|(weapon_id, _)| *weapon_id).collect::<Vec<_>>(), vec![0]);
+  }
+
+  #[test_log::test]
+  fn test_do_fire_actions_arcs() {
+    use crate::ship::{AccelPair, FiringArc, FlightPlan};
+
+    let attacker_design = Arc::new(ShipDesignTemplate {
+      name: "Attacker".to_string(),
+      weapons: vec![
+        Weapon {
+          kind: WeaponType::Particle,
+          mount: WeaponMount::Spinal,
+          arc: None,
+        },
+        Weapon {
+          kind: WeaponType::Beam,
+          mount: WeaponMount::Turret(1),
+          arc: Some(FiringArc { from: 60, to: 120 }),
+        },
+      ],
+      power: 1000,
+      ..ShipDesignTemplate::default()
+    });
+    let mut attacker = Ship::new("Attacker".to_string(), Vec3::zero(), Vec3::zero(), &attacker_design, None);
+    let target = Ship::new(
+      "Target".to_string(),
+      Vec3::new(1000.0, 0.0, 0.0),
+      Vec3::zero(),
+      &Arc::new(ShipDesignTemplate::default()),
+      None,
+    );
+    let mut ships: HashMap<String, Arc<RwLock<Ship>>> = HashMap::new();
+    ships.insert("Target".to_string(), Arc::new(RwLock::new(target.clone())));
+    let mut sand_input: HashMap<String, Ship> = HashMap::new();
+    sand_input.insert("Target".to_string(), target);
+
+    let fire = |weapon_id| ShipAction::FireAction {
+      weapon_id,
+      target: "Target".to_string(),
+      called_shot_system: None,
+      multiple_attacks: false,
+    };
+    let weapon = |id| String::from(&attacker_design.weapons[id]);
+    // The weapons that can't bear on the target this turn.
+    let mut out_of_arc = |attacker: &Ship| {
+      let (_, effects) = do_fire_actions(
+        attacker,
+        &mut ships,
+        &mut create_sand_counts(&sand_input),
+        &[fire(0), fire(1)],
+        &BoostMap::default(),
+        &RulesTables::default(),
+        &mut StdRng::seed_from_u64(7),
+      );
+      (0..2)
+        .filter(|id| {
+          effects.contains(&EffectMsg::OutOfArc {
+            ship: "Attacker".to_string(),
+            weapon: weapon(*id),
+            target: "Target".to_string(),
+          })
+        })
+        .collect::<Vec<_>>()
+    };
+
+    // Drifting with no facing, neither weapon can be brought to bear.
+    assert_eq!(attacker.axis(), None);
+    assert_eq!(out_of_arc(&attacker), vec![0, 1]);
+
+    // Turned toward the target, the spinal mount bears but the broadside weapon doesn't.
+    attacker.set_facing(Some(Vec3::new(5.0, 0.0, 0.0))).unwrap();
+    assert_eq!(attacker.axis(), Some(Vec3::new(1.0, 0.0, 0.0)));
+    assert_eq!(out_of_arc(&attacker), vec![1]);
+    let odds = |attacker: &Ship, weapon_id| {
+      attack_odds(
+        attacker,
+        weapon_id,
+        &sand_input["Target"],
+        None,
+        &BoostMap::default(),
+        &RulesTables::default(),
+      )
+    };
+    assert!(odds(&attacker, 0).in_arc);
+    assert!(!odds(&attacker, 1).in_arc);
+    assert!(odds(&attacker, 1).hit_probability.abs() < f64::EPSILON);
+
+    // Thrusting at right angles to the target, the ship points along its thrust rather than its facing.
+    attacker.plan = FlightPlan::from(vec![AccelPair(Vec3::new(0.0, 10.0, 0.0), 1000)]);
+    assert_eq!(out_of_arc(&attacker), vec![0]);
+    assert!(attacker.set_facing(Some(Vec3::zero())).is_err());
+  }
+
+  #[test_log::test]
+  fn test_attack_odds() {
+    let attacker = Ship::new(
+      "Attacker".to_string(),
+      Vec3::zero(),
+      Vec3::zero(),
+      &Arc::new(ShipDesignTemplate::default()),
+      None,
+    );
+    let defender = Ship::new(
+      "Defender".to_string(),
+      Vec3::new(1_000_000.0, 0.0, 0.0),
+      Vec3::zero(),
+      &Arc::new(ShipDesignTemplate::default()),
+      None,
+    );
+
+    // Pulse turret(2) at short range: +2 weapon, +1 range, so 2d6 of 5 or more hits.
+    let odds = attack_odds(&attacker, 0, &defender, None, &BoostMap::default(), &RulesTables::default());
+    assert_eq!(odds.modifiers.range, Range::Short);
+    assert_eq!(odds.modifiers.total_hit_mod(), 3);
+    assert!((odds.hit_probability - 30.0 / 36.0).abs() < 1e-9);
+
+    // Brute force the expected damage: 2d6 damage plus effect, less 5 armor, plus one die for the second turret.
+    let mut expected = 0.0;
+    for (a, b, c, d) in itertools::iproduct!(1..=6, 1..=6, 1..=6, 1..=6) {
+      let hit_roll = a + b + 3;
+      if hit_roll >= STANDARD_ROLL_THRESHOLD {
+        let through = c + d + hit_roll - STANDARD_ROLL_THRESHOLD - 5;
+        if through > 0 {
+          expected += f64::from(through + 2) / 1296.0;
+        }
+      }
+    }
+    assert!((odds.expected_damage - expected).abs() < 1e-9);
+
+    // A called shot costs -2.
+    let called = attack_odds(
+      &attacker,
+      0,
+      &defender,
+      Some(&ShipSystem::Sensors),
+      &BoostMap::default(),
+      &RulesTables::default(),
+    );
+    assert_eq!(called.modifiers.called_mod, -2);
+    assert!((called.hit_probability - 21.0 / 36.0).abs() < 1e-9);
+
+    // Out of range means no chance at all.
+    let mut far = defender.clone();
+    far.set_position(Vec3::new(30_000_000.0, 0.0, 0.0));
+    let odds_far = attack_odds(&attacker, 0, &far, None, &BoostMap::default(), &RulesTables::default());
+    assert!(!odds_far.modifiers.in_range);
+    assert!(odds_far.hit_probability.abs() < f64::EPSILON);
+    assert!(odds_far.expected_damage.abs() < f64::EPSILON);
+
+    // The calculator and a live attack must agree.
+    let mut rng = StdRng::seed_from_u64(36);
+    let trials = 4000;
+    let hits = (0..trials)
+      .filter(|_| {
+        let mut target = defender.clone();
+        let effects = attack(
+          odds.modifiers.hit_mod,
+          0,
+          &attacker,
+          &mut target,
+          attacker.get_weapon(0),
+          None,
+          &BoostMap::default(),
+          &RulesTables::default(),
+          &mut rng,
+        );
+        !effects.iter().any(|e| e.to_string().contains("misses"))
+      })
+      .count();
+    #[allow(clippy::cast_precision_loss)]
+    let rate = hits as f64 / f64::from(trials);
+    assert!(
+      (rate - odds.hit_probability).abs() < 0.03,
+      "hit rate {rate} vs {}",
+      odds.hit_probability
+    );
   }
 }
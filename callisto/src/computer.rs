@@ -4,6 +4,9 @@ use gomez::{Domain, Problem, SolverDriver, System};
 use std::error::Error;
 use std::f64::consts::PI;
 use std::fmt::Debug;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
 use na::{Dyn, IsContiguous};
 use serde::{Deserialize, Serialize};
@@ -11,11 +14,14 @@ use serde_with::serde_as;
 
 use egobox_doe::{Lhs, LhsKind, SamplingMethod};
 use ndarray::{arr2, Array2};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-use crate::entity::{Vec3, DELTA_TIME_F64, G};
+use crate::entity::{Vec3, DELTA_TIME, DELTA_TIME_F64, G};
+use crate::metrics;
 use crate::missile::IMPACT_DISTANCE;
 use crate::payloads::Vec3asVec;
-use crate::ship::FlightPlan;
+use crate::ship::{AccelPair, FlightPlan};
 use crate::{debug, error, info, warn};
 
 // Wiggle room when setting thrust limits as floating point numbers can make reasonable conversations not work.
@@ -29,6 +35,22 @@ const SOLVE_TOLERANCE: f64 = 1e-4;
 const ANS_PERCENT_OFF: f64 = 0.01;
 const MAX_ITERATIONS: usize = 100;
 const MAX_SAMPLES: usize = 100;
+// Fixed seed for the sampled guesses, so the same flight always gets the same plan.
+const LHS_SEED: u64 = 0;
+// How far past the minimum-time arrival we look for the earliest arrival a fixed-time plan can make.
+const MAX_ARRIVAL_SEARCH_TURNS: u64 = 50;
+// Latest arrival, in turns, a fixed-time plan may be asked for.
+pub const MAX_ARRIVAL_TURNS: u64 = 1000;
+// Closed form flight paths: how closely start and end velocity must match for it to apply, and the longest
+// burn (seconds) we'll look for before leaving it to the solver.
+const CLOSED_FORM_VELOCITY_TOLERANCE: f64 = 1e-6;
+const CLOSED_FORM_MAX_TIME: f64 = 1e9;
+// Keep both closed form burns at least this fraction of the trip, and refine the split this many times.
+const CLOSED_FORM_MIN_SPLIT: f64 = 1e-9;
+const CLOSED_FORM_SPLIT_STEPS: usize = 80;
+
+/// A candidate two-burn solution `(a_1, a_2, t_1, t_2)`, used to seed the solver.
+pub type FlightSeed = (Vec3, Vec3, f64, f64);
 
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -40,6 +62,77 @@ pub struct FlightPathResult {
   pub plan: FlightPlan,
 }
 
+impl FlightPathResult {
+  /// This result as a solver seed, if it is a plain two-burn plan.
+  #[must_use]
+  pub fn as_seed(&self) -> Option<FlightSeed> {
+    match self.plan.segments() {
+      #[allow(clippy::cast_precision_loss)]
+      [first, second] => Some((first.0, second.0, first.1 as f64, second.1 as f64)),
+      _ => None,
+    }
+  }
+}
+
+/// An intermediate stop on a multi-leg route. The ship rendezvous with `position` at `velocity`, then coasts
+/// for `coast` seconds before starting the next leg.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Waypoint {
+  #[serde_as(as = "Vec3asVec")]
+  pub position: Vec3,
+  #[serde_as(as = "Vec3asVec")]
+  #[serde(default = "Vec3::zero")]
+  pub velocity: Vec3,
+  #[serde(default)]
+  pub coast: u64,
+}
+
+/// What a computed flight path optimizes for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(tag = "kind")]
+pub enum FlightGoal {
+  /// Arrive as soon as possible, burning at full thrust throughout.
+  #[default]
+  MinTime,
+  /// Arrive at the end of `arrival_turns` turns from now, using as little delta-v as the ship's thrust allows.
+  MinDeltaV { arrival_turns: u64 },
+  /// Arrive as soon as possible without exceeding `thrust` (in G), e.g. to stay under a detection threshold.
+  ThrustCap { thrust: f32 },
+}
+
+impl FlightGoal {
+  #[must_use]
+  pub fn is_min_time(&self) -> bool {
+    matches!(self, FlightGoal::MinTime)
+  }
+}
+
+/// A fixed-time rendezvous that cannot be made.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfeasibleArrival {
+  pub requested_turns: u64,
+  /// The soonest arrival, in turns, that a fixed-time plan can make. `None` if none was found.
+  pub earliest_turns: Option<u64>,
+}
+
+impl std::fmt::Display for InfeasibleArrival {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.earliest_turns {
+      Some(earliest) => write!(
+        f,
+        "Cannot arrive in {} turns; the earliest feasible arrival is in {earliest} turns",
+        self.requested_turns
+      ),
+      None => write!(
+        f,
+        "Cannot arrive in {} turns; no feasible arrival time found",
+        self.requested_turns
+      ),
+    }
+  }
+}
+
 /**
  * Parameters for this are mostly as you might expect. The starting and ending position, the starting and ending velocity.
  * Max acceleration limits the solution to use no more than this specified acceleration..
@@ -65,6 +158,7 @@ pub struct FlightParams {
 
   sample_cache: Option<Array2<f64>>,
   max_samples: usize,
+  warm_start: Option<FlightSeed>,
 }
 
 impl Debug for FlightParams {
@@ -87,9 +181,17 @@ impl FlightParams {
       max_acceleration,
       sample_cache: None,
       max_samples: MAX_SAMPLES,
+      warm_start: None,
     }
   }
 
+  /// Try `seed` before any other guesses, typically the solution for this ship's last, nearby, request.
+  #[must_use]
+  pub fn with_warm_start(mut self, seed: Option<FlightSeed>) -> Self {
+    self.warm_start = seed;
+    self
+  }
+
   pub fn pos_eq(&self, a_1: Vec3, a_2: Vec3, t_1: f64, t_2: f64) -> Vec3 {
     a_1 * t_1 * t_1 / 2.0
       + a_2 * t_2 * t_2 / 2.0
@@ -282,7 +384,12 @@ impl FlightParams {
         [0.0, 200_000.0],
       ]);
 
-      self.sample_cache = Some(Lhs::new(&xlimits).kind(LhsKind::Centered).sample(self.max_samples));
+      self.sample_cache = Some(
+        Lhs::new(&xlimits)
+          .kind(LhsKind::Centered)
+          .with_rng(StdRng::seed_from_u64(LHS_SEED))
+          .sample(self.max_samples),
+      );
     }
 
     let sample = self.sample_cache.as_ref().unwrap();
@@ -304,6 +411,16 @@ impl FlightParams {
    * Returns a `FlightPathResult` which contains the path, the end velocity and the plan.
    */
   pub fn compute_flight_path(&mut self) -> Result<FlightPathResult, f64> {
+    let started = Instant::now();
+    let iterations = AtomicUsize::new(0);
+    let result = self.solve_flight_path(&iterations);
+    metrics::record_flight_solve(started.elapsed(), iterations.load(Ordering::Relaxed), result.is_ok());
+    result
+  }
+
+  /// The solver loop behind [`FlightParams::compute_flight_path`]. Uses the closed form when it applies,
+  /// otherwise tries seeds until one converges, counting every solver iteration in `iterations`.
+  fn solve_flight_path(&mut self, iterations: &AtomicUsize) -> Result<FlightPathResult, f64> {
     // Corner case eliminated here as all these zeros otherwise mess up solution finding.
     if cgmath::ulps_eq!(self.start_pos, self.end_pos) && cgmath::ulps_eq!(self.start_vel, self.end_vel) {
       info!("(compute_flight_path) No need to compute flight path.");
@@ -314,176 +431,454 @@ impl FlightParams {
       });
     }
 
-    let mut attempt = 0;
+    if let Some(result) = self.closed_form_path() {
+      return Ok(result);
+    }
+
+    // Warm start first, then the sampled seeds. Collected up front as sampling needs `&mut self`.
+    let mut seeds: Vec<FlightSeed> = self.warm_start.into_iter().collect();
+    seeds.extend((0..self.max_samples).map_while(|attempt| self.best_guess_lhs(attempt)));
 
-    // Debugging only
+    // Seeds are solved a batch at a time, one per core. The earliest seed in a batch that converges wins, so
+    // the answer doesn't depend on thread timing.
+    let workers = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let params = &*self;
     let mut best_norm = f64::MAX;
+    for batch in seeds.chunks(workers) {
+      let outcomes = std::thread::scope(|scope| {
+        let handles = batch
+          .iter()
+          .map(|seed| scope.spawn(move || params.solve_from_seed(*seed, iterations)))
+          .collect::<Vec<_>>();
+        handles
+          .into_iter()
+          .map(|handle| handle.join().expect("(compute_flight_path) Solver thread panicked"))
+          .collect::<Vec<_>>()
+      });
+
+      for outcome in outcomes {
+        match outcome {
+          Ok((a_1, a_2, t_1, t_2)) => return Ok(self.finish_path(a_1, a_2, t_1, t_2)),
+          Err(norm) => best_norm = best_norm.min(norm),
+        }
+      }
+    }
+
+    error!("(compute_flight_path) Unable to compute flight path.  Out of guesses.");
+    Err(best_norm)
+  }
+
+  /// Direct solution when the target isn't moving and we want to end either at the velocity we have now or at rest,
+  /// which covers most interactive moves. For a trip of length `T` split into burns of `t_1` and `t_2`, both
+  /// accelerations follow in closed form; we search the split for the one needing the least thrust, and `T` for
+  /// where that reaches `max_acceleration`. Other end velocities are left to the numeric solver.
+  ///
+  /// Returns `None` if the case doesn't apply, leaving the numeric solver to handle it.
+  fn closed_form_path(&self) -> Option<FlightPathResult> {
+    let moving = |v: Option<Vec3>| v.is_some_and(|v| !cgmath::ulps_eq!(v, Vec3::zero()));
+    if moving(self.target_velocity)
+      || moving(self.target_acceleration)
+      || ((self.end_vel - self.start_vel).magnitude() > CLOSED_FORM_VELOCITY_TOLERANCE
+        && self.end_vel.magnitude() > CLOSED_FORM_VELOCITY_TOLERANCE)
+      || self.max_acceleration <= 0.0
+    {
+      return None;
+    }
+
+    // With end_vel = start_vel + a_1 * t_1 + a_2 * t_2, the end position works out to
+    // end_pos = start_pos + start_vel * T + delta_v * t_2 / 2 + a_1 * t_1 * T / 2, which gives a_1 and then a_2.
+    let delta_s = self.end_pos - self.start_pos;
+    let delta_v = self.end_vel - self.start_vel;
+    let burns = |total: f64, split: f64| {
+      let (t_1, t_2) = (total * split, total * (1.0 - split));
+      let a_1 = (delta_s - self.start_vel * total - delta_v * (t_2 / 2.0)) * (2.0 / (t_1 * total));
+      let a_2 = (delta_v - a_1 * t_1) / t_2;
+      (a_1, a_2, t_1, t_2)
+    };
+    let peak = |total: f64, split: f64| {
+      let (a_1, a_2, _, _) = burns(total, split);
+      a_1.magnitude().max(a_2.magnitude())
+    };
+    // Golden section search for the split needing the least thrust.
+    let best_split = |total: f64| {
+      let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+      let (mut low, mut high) = (CLOSED_FORM_MIN_SPLIT, 1.0 - CLOSED_FORM_MIN_SPLIT);
+      for _ in 0..CLOSED_FORM_SPLIT_STEPS {
+        let (left, right) = (high - ratio * (high - low), low + ratio * (high - low));
+        if peak(total, left) < peak(total, right) {
+          high = right;
+        } else {
+          low = left;
+        }
+      }
+      f64::midpoint(low, high)
+    };
+    let accel_needed = |total: f64| peak(total, best_split(total));
 
+    let mut high = 1.0;
+    while accel_needed(high) > self.max_acceleration {
+      high *= 2.0;
+      if high > CLOSED_FORM_MAX_TIME {
+        return None;
+      }
+    }
+    // Bisect to full precision, i.e. until the midpoint can no longer be told apart from an end.
+    let mut low = 0.0;
     loop {
-      info!("(compute_flight_path) Attempt {}", attempt);
+      let mid = f64::midpoint(low, high);
+      if approx::ulps_eq!(mid, low) || approx::ulps_eq!(mid, high) {
+        break;
+      }
+      if accel_needed(mid) > self.max_acceleration {
+        low = mid;
+      } else {
+        high = mid;
+      }
+    }
 
-      // Get the next guess. If its None then we're done as we're out of guesses and have failed. ? triggers the return of None.
-      let (guess_accel_1, guess_accel_2, guess_t_1, guess_t_2) = self.best_guess_lhs(attempt).ok_or_else(|| {
-        error!("(compute_flight_path) Unable to compute flight path.  Out of guesses.");
-        best_norm
-      })?;
+    let (a_1, a_2, t_1, t_2) = burns(high, best_split(high));
+    debug!(
+      "(compute_flight_path) Closed form solution a_1: {a_1:0.2?}, a_2: {a_2:0.2?}, t_1: {t_1:0.2?}, t_2: {t_2:0.2?}"
+    );
+    Some(self.finish_path(a_1, a_2, t_1, t_2))
+  }
 
-      info!(
-        "(compute_flight_path) Guess is a1={:?} a2={:?} t1={:?} t2={:?}",
-        guess_accel_1, guess_accel_2, guess_t_1, guess_t_2
+  /// Run the numeric solver from one seed. Returns the solution, or the best norm reached if it didn't converge
+  /// to a usable answer.
+  fn solve_from_seed(&self, seed: FlightSeed, iterations: &AtomicUsize) -> Result<FlightSeed, f64> {
+    let (guess_accel_1, guess_accel_2, guess_t_1, guess_t_2) = seed;
+    info!(
+      "(compute_flight_path) Guess is a1={:?} a2={:?} t1={:?} t2={:?}",
+      guess_accel_1, guess_accel_2, guess_t_1, guess_t_2
+    );
+
+    let mut initial: Vec<f64> = Into::<[f64; 3]>::into(guess_accel_1).into();
+    initial.append(&mut Into::<[f64; 3]>::into(guess_accel_2).into());
+    initial.push(guess_t_1);
+    initial.push(guess_t_2);
+
+    info!("(compute_flight_path) Params is {:?}", self);
+    info!("(compute_flight_path) Initial is {:?}", initial);
+
+    let mut solver = SolverDriver::builder(self).with_initial(initial).build();
+    let solver_result = solver.find(|state| {
+      iterations.fetch_add(1, Ordering::Relaxed);
+      let x = state.x();
+      let rx = state.rx();
+      debug!(
+        "iter = {} || |r(x)|={:0.1?} a1={:0.2?} a2={:0.2?} t1={:0.2?} t2={:0.2?} ds={:0.2?} dv={:0.2?} da1={:0.2?} da2={:0.2?}",
+        state.iter(), state.norm(),
+        &x[0..3], &x[3..6], x[6], x[7],
+        &rx[0..3], &rx[3..6], rx[6], rx[7],
       );
-      attempt += 1;
+      state.norm() <= SOLVE_TOLERANCE || state.iter() >= MAX_ITERATIONS
+    });
 
-      let mut initial: Vec<f64> = Into::<[f64; 3]>::into(guess_accel_1).into();
-      initial.append(&mut Into::<[f64; 3]>::into(guess_accel_2).into());
-      initial.push(guess_t_1);
-      initial.push(guess_t_2);
+    let (answer, norm) = match solver_result {
+      Err(e) => {
+        warn!("Unable to solve flight path with params: {self:?} with error: {e}.");
+        return Err(f64::MAX);
+      }
+      Ok(ans) => ans,
+    };
+    // Unpack the answer.
+    let a_1 = Vec3::from(
+      <&[f64] as TryInto<[f64; 3]>>::try_into(&answer[0..3])
+        .expect("(compute_flight_path) Unable to convert to fixed array"),
+    );
 
-      info!("(compute_flight_path) Params is {:?}", self);
-      info!("(compute_flight_path) Initial is {:?}", initial);
+    let a_2 = Vec3::from(
+      <&[f64] as TryInto<[f64; 3]>>::try_into(&answer[3..6])
+        .expect("(compute_flight_path) Unable to convert to fixed array"),
+    );
 
-      let mut solver = SolverDriver::builder(self).with_initial(initial).build();
-      let solver_result = solver.find(|state| {
-        let x = state.x();
-        let rx = state.rx();
-        debug!(
-          "iter = {} || |r(x)|={:0.1?} a1={:0.2?} a2={:0.2?} t1={:0.2?} t2={:0.2?} ds={:0.2?} dv={:0.2?} da1={:0.2?} da2={:0.2?}",
-          state.iter(), state.norm(),
-          &x[0..3], &x[3..6], x[6], x[7],
-          &rx[0..3], &rx[3..6], rx[6], rx[7],
-        );
-        state.norm() <= SOLVE_TOLERANCE || state.iter() >= MAX_ITERATIONS
-      });
+    let t_1 = answer[6];
+    let t_2 = answer[7];
 
-      let (answer, norm) = match solver_result {
-        Err(e) => {
-          warn!("Unable to solve flight path with params: {self:?} with error: {e}.");
-          continue;
-        }
-        Ok(ans) => ans,
-      };
-      // Unpack the answer.
-      let mut a_1 = Vec3::from(
-        <&[f64] as TryInto<[f64; 3]>>::try_into(&answer[0..3])
-          .expect("(compute_flight_path) Unable to convert to fixed array"),
-      );
+    if norm > SOLVE_TOLERANCE {
+      // Case where magnitude of position or velocity are so large, the norm will be a lot larger but still
+      // be effectively correct.
 
-      let mut a_2 = Vec3::from(
-        <&[f64] as TryInto<[f64; 3]>>::try_into(&answer[3..6])
-          .expect("(compute_flight_path) Unable to convert to fixed array"),
+      // Check the ratio of how far the calculated position is from the end position to the start position to the end position.
+      let pos_percent_off = self.pos_eq(a_1, a_2, t_1, t_2).magnitude() / (self.start_pos - self.end_pos).magnitude();
+      let vel_percent_off = self.vel_eq(a_1, a_2, t_1, t_2).magnitude() / (self.start_vel - self.end_vel).magnitude();
+      debug!(
+        "(compute_flight_path) Position percent off: {:0.4?}, Velocity percent off: {:0.4?}",
+        pos_percent_off, vel_percent_off
       );
+      if pos_percent_off > ANS_PERCENT_OFF || vel_percent_off > ANS_PERCENT_OFF {
+        warn!("Unable to solve flight path with params: {:?} with norm: {:0.4?}.", self, norm);
+        return Err(norm);
+      }
+    }
 
-      let t_1 = answer[6];
-      let t_2 = answer[7];
+    if t_1 < 0.0 || t_2 < 0.0 {
+      warn!("(compute_flight_path) Unable to solve flight path with params: {self:?} with negative time.");
+      return Err(norm);
+    }
 
-      // Debugging only
-      best_norm = norm.min(best_norm);
+    Ok((a_1, a_2, t_1, t_2))
+  }
 
-      if norm > SOLVE_TOLERANCE {
-        // Case where magnitude of position or velocity are so large, the norm will be a lot larger but still
-        // be effectively correct.
+  /// Turn a two-burn solution into a path and a plan with whole-second durations.
+  fn finish_path(&self, mut a_1: Vec3, mut a_2: Vec3, t_1: f64, t_2: f64) -> FlightPathResult {
+    info!(
+      "(compute_flight_path) Computed path with a_1: {a_1:0.2?}, a_2: {a_2:0.2?}, t_1: {t_1:0.2?}, t_2: {t_2:0.2?}"
+    );
 
-        // Check the ratio of how far the calculated position is from the end position to the start position to the end position.
-        let pos_percent_off = self.pos_eq(Vec3::from(a_1), Vec3::from(a_2), t_1, t_2).magnitude()
-          / (Vec3::from(self.start_pos) - Vec3::from(self.end_pos)).magnitude();
-        let vel_percent_off = self.vel_eq(Vec3::from(a_1), Vec3::from(a_2), t_1, t_2).magnitude()
-          / (Vec3::from(self.start_vel) - Vec3::from(self.end_vel)).magnitude();
-        debug!(
-          "(compute_flight_path) Position percent off: {:0.4?}, Velocity percent off: {:0.4?}",
-          pos_percent_off, vel_percent_off
-        );
-        if pos_percent_off > ANS_PERCENT_OFF || vel_percent_off > ANS_PERCENT_OFF {
-          warn!("Unable to solve flight path with params: {:?} with norm: {:0.4?}.", self, norm);
-          continue;
-        }
-      }
+    // Debugging only...
+    if a_1.magnitude() > self.max_acceleration + MAX_ACCEL_WIGGLE_ROOM
+      || a_2.magnitude() > self.max_acceleration + MAX_ACCEL_WIGGLE_ROOM
+    {
+      warn!("(compute_flight_path) Path acceleration greater than max.  a_1: {a_1:0.2?}, a_2: {a_2:0.2?} |a_1|: {:0.2?}, |a_2|: {:0.2?} max_acceleration: {:0.2?}", a_1.magnitude(), a_2.magnitude(), self.max_acceleration);
+      // Trim the accelerations.
+      a_1 = a_1.normalize() * (self.max_acceleration - 1e-12).max(0.0);
+      a_2 = a_2.normalize() * (self.max_acceleration - 1e-12).max(0.0);
+      info!("(compute_flight_path) Trimmed path to a_1: {a_1:0.2?}, a_2: {a_2:0.2?}");
+    }
 
-      if t_1 < 0.0 || t_2 < 0.0 {
-        warn!("(compute_flight_path) Unable to solve flight path with params: {self:?} with negative time.");
-        continue;
-      }
+    let (path, end_velocity) = self.build_path(&a_1, &a_2, t_1, t_2);
 
-      info!(
-        "(compute_flight_path) Computed path with a_1: {a_1:0.2?}, a_2: {a_2:0.2?}, t_1: {t_1:0.2?}, t_2: {t_2:0.2?}"
-      );
+    // Convert time into an unsigned integer.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    let t_1 = t_1.round() as u64;
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    let t_2 = t_2.round() as u64;
+
+    FlightPathResult {
+      path,
+      end_velocity,
+      // The server always works in m/s^2; UX must convert.
+      plan: FlightPlan::new((a_1, t_1).into(), Some((a_2, t_2).into())),
+    }
+  }
 
-      // Debugging only...
-      if a_1.magnitude() > self.max_acceleration + MAX_ACCEL_WIGGLE_ROOM
-        || a_2.magnitude() > self.max_acceleration + MAX_ACCEL_WIGGLE_ROOM
-      {
-        warn!("(compute_flight_path) Path acceleration greater than max.  a_1: {a_1:0.2?}, a_2: {a_2:0.2?} |a_1|: {:0.2?}, |a_2|: {:0.2?} max_acceleration: {:0.2?}", a_1.magnitude(), a_2.magnitude(), self.max_acceleration);
-        // Trim the accelerations.
-        a_1 = a_1.normalize() * (self.max_acceleration - 1e-12).max(0.0);
-        a_2 = a_2.normalize() * (self.max_acceleration - 1e-12).max(0.0);
-        info!("(compute_flight_path) Trimmed path to a_1: {a_1:0.2?}, a_2: {a_2:0.2?}");
+  /// Plan a route that visits each of `waypoints` in turn and then makes the rendezvous described by these
+  /// params. Each leg is an ordinary two-burn solution; any coast at a waypoint becomes a zero-thrust segment.
+  /// Target velocity and acceleration apply only to the final leg, offset by the time spent on earlier legs.
+  ///
+  /// With no waypoints this is exactly [`FlightParams::compute_flight_path`].
+  ///
+  /// # Errors
+  /// Returns the best norm reached if any leg cannot be solved.
+  pub fn compute_waypoint_path(&mut self, waypoints: &[Waypoint]) -> Result<FlightPathResult, f64> {
+    if waypoints.is_empty() {
+      return self.compute_flight_path();
+    }
+
+    let mut segments: Vec<AccelPair> = Vec::new();
+    let mut pos = self.start_pos;
+    let mut vel = self.start_vel;
+    for waypoint in waypoints {
+      let mut leg = FlightParams::new(
+        pos,
+        waypoint.position,
+        vel,
+        waypoint.velocity,
+        None,
+        None,
+        self.max_acceleration,
+      );
+      let result = leg.compute_flight_path()?;
+      (pos, vel) = Self::extend_route(&mut segments, pos, vel, result.plan.segments());
+      if waypoint.coast > 0 {
+        (pos, vel) = Self::extend_route(&mut segments, pos, vel, &[AccelPair::coast(waypoint.coast)]);
       }
+      debug!("(compute_waypoint_path) Reached waypoint {waypoint:?} at {pos:0.0?} moving {vel:0.0?}");
+    }
 
-      let (path, end_velocity) = self.build_path(&a_1, &a_2, t_1, t_2);
+    // The target keeps moving while we fly the earlier legs.
+    #[allow(clippy::cast_precision_loss)]
+    let elapsed = segments.iter().map(|segment| segment.1).sum::<u64>() as f64;
+    let target_accel = self.target_acceleration.unwrap_or_else(Vec3::zero);
+    let mut final_leg = FlightParams::new(
+      pos,
+      self.end_pos + self.target_velocity.unwrap_or_else(Vec3::zero) * elapsed + target_accel * elapsed * elapsed / 2.0,
+      vel,
+      self.end_vel + target_accel * elapsed,
+      self.target_velocity.map(|target_vel| target_vel + target_accel * elapsed),
+      self.target_acceleration,
+      self.max_acceleration,
+    );
+    let result = final_leg.compute_flight_path()?;
+    Self::extend_route(&mut segments, pos, vel, result.plan.segments());
 
-      // Convert time into an unsigned integer.
-      #[allow(clippy::cast_possible_truncation)]
-      #[allow(clippy::cast_sign_loss)]
-      let t_1 = t_1.round() as u64;
-      #[allow(clippy::cast_possible_truncation)]
-      #[allow(clippy::cast_sign_loss)]
-      let t_2 = t_2.round() as u64;
+    let plan = FlightPlan::from(segments);
+    #[allow(clippy::cast_precision_loss)]
+    let (path, end_velocity) = integrate_segments(
+      self.start_pos,
+      self.start_vel,
+      plan.iter().map(|segment| (segment.0, segment.1 as f64)),
+    );
+    Ok(FlightPathResult {
+      path,
+      end_velocity,
+      plan,
+    })
+  }
 
-      return Ok(FlightPathResult {
-        path,
-        end_velocity,
-        // The server always works in m/s^2; UX must convert.
-        plan: FlightPlan::new((a_1, t_1).into(), Some((a_2, t_2).into())),
-      });
+  /// Append the non-empty `new` segments to `segments`, returning the state they leave the ship in.
+  /// Legs chain from where the rounded plan actually ends up, so rounding doesn't compound across legs.
+  fn extend_route(segments: &mut Vec<AccelPair>, pos: Vec3, vel: Vec3, new: &[AccelPair]) -> (Vec3, Vec3) {
+    let new = new.iter().filter(|segment| segment.1 > 0).cloned().collect::<Vec<_>>();
+    #[allow(clippy::cast_precision_loss)]
+    let (path, vel) = integrate_segments(pos, vel, new.iter().map(|segment| (segment.0, segment.1 as f64)));
+    segments.extend(new);
+    (*path.last().unwrap_or(&pos), vel)
+  }
+
+  /// Plan a rendezvous that arrives exactly at the end of `arrival_turns` turns, spending as little delta-v as
+  /// possible. The plan burns for equal times at each end with a coast between; shorter burns cost less, so we use
+  /// the shortest burns that fit under `max_acceleration`.
+  ///
+  /// # Errors
+  /// Returns [`InfeasibleArrival`], with the earliest arrival that would work, if the ship cannot make it in time.
+  pub fn compute_fixed_arrival_path(&mut self, arrival_turns: u64) -> Result<FlightPathResult, InfeasibleArrival> {
+    let Some(plan) = arrival_turns
+      .checked_mul(DELTA_TIME)
+      .and_then(|time| self.fixed_arrival_plan(time))
+    else {
+      let infeasible = InfeasibleArrival {
+        requested_turns: arrival_turns,
+        earliest_turns: self.earliest_fixed_arrival(),
+      };
+      debug!("(compute_fixed_arrival_path) {infeasible}");
+      return Err(infeasible);
+    };
+
+    #[allow(clippy::cast_precision_loss)]
+    let (path, end_velocity) = integrate_segments(
+      self.start_pos,
+      self.start_vel,
+      plan.iter().map(|segment| (segment.0, segment.1 as f64)),
+    );
+    Ok(FlightPathResult {
+      path,
+      end_velocity,
+      plan,
+    })
+  }
+
+  /// The fewest whole turns a fixed-time plan needs. Starts from the minimum-time solution, which no fixed-time
+  /// plan can beat, and steps forward a turn at a time.
+  fn earliest_fixed_arrival(&mut self) -> Option<u64> {
+    let first_turn = self
+      .compute_flight_path()
+      .map_or(1, |result| result.plan.duration().div_ceil(DELTA_TIME).max(1));
+    (first_turn..first_turn + MAX_ARRIVAL_SEARCH_TURNS)
+      .find(|turns| self.fixed_arrival_plan(turns * DELTA_TIME).is_some())
+  }
+
+  /// Where the target is, and the velocity we need to match, `time` seconds from now.
+  fn end_state_at(&self, time: f64) -> (Vec3, Vec3) {
+    let target_vel = self.target_velocity.unwrap_or_else(Vec3::zero);
+    let target_accel = self.target_acceleration.unwrap_or_else(Vec3::zero);
+    (
+      self.end_pos + target_vel * time + target_accel * time * time / 2.0,
+      self.end_vel + target_accel * time,
+    )
+  }
+
+  /// Accelerations for a `duration` second trip that burns for `burn` seconds, coasts, then burns for `burn`
+  /// seconds again. With the times fixed the boundary conditions are linear in the two accelerations.
+  fn coast_burns(&self, duration: f64, burn: f64) -> (Vec3, Vec3) {
+    let (end_pos, end_vel) = self.end_state_at(duration);
+    let delta_s = end_pos - self.start_pos - self.start_vel * duration;
+    let delta_v = end_vel - self.start_vel;
+    let a_1 = (delta_s - delta_v * burn / 2.0) / (burn * (duration - burn));
+    let a_2 = delta_v / burn - a_1;
+    (a_1, a_2)
+  }
+
+  /// Build the burn-coast-burn plan for a trip of exactly `duration` seconds, or `None` if no burn length keeps
+  /// both burns within `max_acceleration`.
+  #[allow(clippy::cast_precision_loss)]
+  fn fixed_arrival_plan(&self, duration: u64) -> Option<FlightPlan> {
+    let duration_f64 = duration as f64;
+    let fits = |burn: u64| {
+      let (a_1, a_2) = self.coast_burns(duration_f64, burn as f64);
+      a_1.magnitude().max(a_2.magnitude()) <= self.max_acceleration
+    };
+
+    // Longest burns (no coast) need the least thrust. If those don't fit, nothing does.
+    let mut high = duration / 2;
+    if high == 0 || !fits(high) {
+      return None;
     }
+    // Required thrust falls as burns lengthen, so bisect for the shortest burn that fits.
+    let mut low = 0;
+    while high - low > 1 {
+      let mid = u64::midpoint(low, high);
+      if fits(mid) {
+        high = mid;
+      } else {
+        low = mid;
+      }
+    }
+
+    let (a_1, a_2) = self.coast_burns(duration_f64, high as f64);
+    let mut plan = FlightPlan::new(AccelPair(a_1, high), Some(AccelPair::coast(duration - 2 * high)));
+    plan.push(AccelPair(a_2, high));
+    debug!("(fixed_arrival_plan) Arrive in {duration}s with plan {plan:?}");
+    Some(plan)
   }
 
   fn build_path(&self, a_1: &Vec3, a_2: &Vec3, t_1: f64, t_2: f64) -> (Vec<Vec3>, Vec3) {
-    // Now that we've solved for acceleration lets create a path and end velocity
-    let mut path = Vec::new();
-    let mut vel = self.start_vel;
-    let mut pos = self.start_pos;
-
-    let mut left_over_time = 0.;
-    // Every path starts with the starting position
-    path.push(pos);
-    for (accel, duration) in [(a_1, t_1), (a_2, t_2)] {
-      // Clock to advance through everything.
-      let mut time = 0.0;
-
-      while approx::relative_ne!(time - duration, 0.0, epsilon = 1e-4) && time < duration {
-        // Time step to use at the current acceleration.
-        // By default is the time for a turn `DELTA_TIME_F64` but when we don't
-        // have enough time left in this acceleration period, we reduce it.
-        let mut step: f64 = DELTA_TIME_F64;
-
-        // If we have left over time from the last acceleration period, use that up first to keep us
-        // on DELTA_TIME boundaries.
-        if left_over_time > 0. {
-          step = left_over_time;
-          left_over_time = 0.;
-        }
-        // If we don't have enough time left in this acceleration period, reduce the time step.
-        // Save the rest of the time in left_over_time so that we are always having an ending on a DELTA_TIME boundary.
-        if time + step > duration {
-          step = duration - time;
-          left_over_time = (DELTA_TIME_F64 - step).max(0.);
-        }
-        let new_pos = pos + vel * step + accel * step * step / 2.0;
-        let new_vel = vel + accel * step;
+    integrate_segments(self.start_pos, self.start_vel, [(*a_1, t_1), (*a_2, t_2)])
+  }
+}
 
-        info!(
-          "(compute_path)\tAccelerate from {:0.0?} at {:0.1?} m/s^2 for {:0.0?}s. New Pos: {:0.0?}, New Vel: {:0.0?}",
-          pos, accel, step, new_pos, new_vel
-        );
+/// Fly `segments` of (acceleration, duration) from `start_pos` at `start_vel`. Returns the positions along the way,
+/// one per turn, and the final velocity. Steps stay on `DELTA_TIME` boundaries across segment changes.
+fn integrate_segments(
+  start_pos: Vec3, start_vel: Vec3, segments: impl IntoIterator<Item = (Vec3, f64)>,
+) -> (Vec<Vec3>, Vec3) {
+  let mut path = Vec::new();
+  let mut vel = start_vel;
+  let mut pos = start_pos;
+
+  let mut left_over_time = 0.;
+  // Every path starts with the starting position
+  path.push(pos);
+  for (accel, duration) in segments {
+    // Clock to advance through everything.
+    let mut time = 0.0;
 
-        path.push(new_pos);
-        pos = new_pos;
-        vel = new_vel;
-        time += step;
+    while approx::relative_ne!(time - duration, 0.0, epsilon = 1e-4) && time < duration {
+      // Time step to use at the current acceleration.
+      // By default is the time for a turn `DELTA_TIME_F64` but when we don't
+      // have enough time left in this acceleration period, we reduce it.
+      let mut step: f64 = DELTA_TIME_F64;
+
+      // If we have left over time from the last acceleration period, use that up first to keep us
+      // on DELTA_TIME boundaries.
+      if left_over_time > 0. {
+        step = left_over_time;
+        left_over_time = 0.;
+      }
+      // If we don't have enough time left in this acceleration period, reduce the time step.
+      // Save the rest of the time in left_over_time so that we are always having an ending on a DELTA_TIME boundary.
+      if time + step > duration {
+        step = duration - time;
+        left_over_time = (DELTA_TIME_F64 - step).max(0.);
       }
+      let new_pos = pos + vel * step + accel * step * step / 2.0;
+      let new_vel = vel + accel * step;
+
+      info!(
+        "(compute_path)\tAccelerate from {:0.0?} at {:0.1?} m/s^2 for {:0.0?}s. New Pos: {:0.0?}, New Vel: {:0.0?}",
+        pos, accel, step, new_pos, new_vel
+      );
+
+      path.push(new_pos);
+      pos = new_pos;
+      vel = new_vel;
+      time += step;
     }
-    (path, vel)
   }
+  (path, vel)
 }
 
 impl Problem for FlightParams {
@@ -782,7 +1177,7 @@ impl System for TargetParams {
 
 #[cfg(test)]
 mod tests {
-  use super::super::entity::G;
+  use super::super::entity::{DELTA_TIME, G};
   use super::*;
   use cgmath::assert_relative_eq;
   use rand::Rng;
@@ -1324,8 +1719,8 @@ mod tests {
     assert_relative_eq!(result.end_velocity, current_vel);
 
     // Check that the flight plan has zero zero duration; acceleration could be anything.
-    assert_eq!(result.plan.0 .1, 0);
-    if let Some(accel) = &result.plan.1 {
+    assert_eq!(result.plan.first().1, 0);
+    if let Some(accel) = result.plan.second() {
       assert_eq!(accel.1, 0);
     } else {
       panic!("Expecting first acceleration.")
@@ -1583,4 +1978,243 @@ mod tests {
     info!("Start Vel: {:?}\nEnd Vel: {:?}", params.start_vel, params.end_vel);
     info!("Path: {:?}\nVel{:?}", result.path, result.end_velocity);
   }
+
+  #[test_log::test]
+  fn test_compute_waypoint_path() {
+    let start_pos = Vec3::zero();
+    let waypoint = Waypoint {
+      position: Vec3 { x: 2e7, y: 0.0, z: 0.0 },
+      velocity: Vec3 {
+        x: 1000.0,
+        y: 0.0,
+        z: 0.0,
+      },
+      coast: 3 * DELTA_TIME,
+    };
+    let end_pos = Vec3 { x: 4e7, y: 2e7, z: 0.0 };
+    let mut params = FlightParams::new(start_pos, end_pos, Vec3::zero(), Vec3::zero(), None, None, 3.0 * G);
+
+    let result = params.compute_waypoint_path(std::slice::from_ref(&waypoint)).unwrap();
+    info!(
+      "Plan: {:?}\nPath: {:?}\nVel {:?}",
+      result.plan, result.path, result.end_velocity
+    );
+
+    // Burn to the waypoint, coast, then burn to the destination: at least one coast segment amid the thrust.
+    assert!(result.plan.segments().len() >= 3);
+    assert!(result
+      .plan
+      .segments()
+      .iter()
+      .any(|segment| segment.is_coast() && segment.1 == waypoint.coast));
+    assert_eq!(result.plan.duration(), result.plan.iter().map(|segment| segment.1).sum::<u64>());
+
+    let p_error = pos_error(&start_pos, &end_pos, result.path.last().unwrap());
+    assert!(p_error < 0.01, "Position error is {p_error} > 0.01");
+    // Burn durations round to whole seconds, leaving up to half a second of thrust error per burn.
+    assert!(
+      result.end_velocity.magnitude() < 3.0 * G,
+      "End velocity {:?} should be near zero",
+      result.end_velocity
+    );
+
+    // The route passes close to the waypoint on the way.
+    let closest = result
+      .path
+      .iter()
+      .map(|pos| (pos - waypoint.position).magnitude())
+      .fold(f64::MAX, f64::min);
+    assert!(closest < 2e5, "Route never came within {closest}m of the waypoint");
+
+    // No waypoints is the plain two-burn solution.
+    let direct = params.compute_waypoint_path(&[]).unwrap();
+    assert!(direct.plan.segments().len() <= 2);
+  }
+
+  #[test_log::test]
+  fn test_compute_fixed_arrival_path() {
+    let start_pos = Vec3::zero();
+    let end_pos = Vec3 { x: 1e7, y: 0.0, z: 0.0 };
+    let mut params = FlightParams::new(start_pos, end_pos, Vec3::zero(), Vec3::zero(), None, None, 3.0 * G);
+    let fastest = params.compute_flight_path().unwrap();
+    #[allow(clippy::cast_precision_loss)]
+    let fastest_delta_v = fastest
+      .plan
+      .iter()
+      .map(|segment| segment.0.magnitude() * segment.1 as f64)
+      .sum::<f64>();
+
+    // Taking twice as long needs far less delta-v than the full-thrust solution.
+    let result = params.compute_fixed_arrival_path(8).unwrap();
+    info!(
+      "Plan: {:?}\nPath: {:?}\nVel {:?}",
+      result.plan, result.path, result.end_velocity
+    );
+    assert_eq!(result.plan.duration(), 8 * DELTA_TIME);
+    assert!(result.plan.iter().all(|segment| segment.in_limits(params.max_acceleration)));
+    let p_error = pos_error(&start_pos, &end_pos, result.path.last().unwrap());
+    assert!(p_error < 0.001, "Position error is {p_error} > 0.001");
+    assert!(result.end_velocity.magnitude() < 1e-6, "End velocity {:?}", result.end_velocity);
+    #[allow(clippy::cast_precision_loss)]
+    let delta_v = result
+      .plan
+      .iter()
+      .map(|segment| segment.0.magnitude() * segment.1 as f64)
+      .sum::<f64>();
+    assert!(
+      delta_v < fastest_delta_v / 2.0,
+      "Delta-v {delta_v} vs full thrust {fastest_delta_v}"
+    );
+
+    // Too soon: report the earliest arrival we can make instead.
+    assert_eq!(
+      params.compute_fixed_arrival_path(2),
+      Err(InfeasibleArrival {
+        requested_turns: 2,
+        earliest_turns: Some(4),
+      })
+    );
+    assert!(params.compute_fixed_arrival_path(4).is_ok());
+    assert!(params.compute_fixed_arrival_path(0).is_err());
+
+    // A moving target is met where it will be on arrival, at its velocity.
+    let target_velocity = Vec3 {
+      x: 0.0,
+      y: 1000.0,
+      z: 0.0,
+    };
+    let mut params = FlightParams::new(
+      start_pos,
+      end_pos,
+      Vec3::zero(),
+      target_velocity,
+      Some(target_velocity),
+      None,
+      3.0 * G,
+    );
+    let result = params.compute_fixed_arrival_path(10).unwrap();
+    #[allow(clippy::cast_precision_loss)]
+    let arrival = end_pos + target_velocity * (10 * DELTA_TIME) as f64;
+    let p_error = pos_error(&start_pos, &arrival, result.path.last().unwrap());
+    assert!(p_error < 0.001, "Position error is {p_error} > 0.001");
+    assert!((result.end_velocity - target_velocity).magnitude() < 1e-6);
+  }
+
+  #[test_log::test]
+  fn test_closed_form_flight_path() {
+    let start_vel = Vec3 {
+      x: 1000.0,
+      y: -500.0,
+      z: 0.0,
+    };
+    let end_pos = Vec3 {
+      x: 3e7,
+      y: 1e7,
+      z: -2e6,
+    };
+    let params = FlightParams::new(Vec3::zero(), end_pos, start_vel, start_vel, None, None, 2.0 * G);
+    let result = params.closed_form_path().unwrap();
+    info!(
+      "Plan: {:?}\nPath: {:?}\nVel {:?}",
+      result.plan, result.path, result.end_velocity
+    );
+
+    let (a_1, a_2) = (result.plan.first().0, result.plan.second().unwrap().0);
+    assert_relative_eq!(a_1.magnitude(), 2.0 * G, max_relative = 1e-6);
+    assert_relative_eq!(a_1, -a_2, max_relative = 1e-6);
+    let p_error = pos_error(&Vec3::zero(), &end_pos, result.path.last().unwrap());
+    assert!(p_error < 1e-4, "Position error is {p_error}");
+    assert!((result.end_velocity - start_vel).magnitude() < 1e-6);
+
+    // Only applies to a stationary target, ending at our current velocity or at rest.
+    let moving_target = FlightParams::new(Vec3::zero(), end_pos, start_vel, start_vel, Some(start_vel), None, 2.0 * G);
+    assert!(moving_target.closed_form_path().is_none());
+    let turning = FlightParams::new(Vec3::zero(), end_pos, start_vel, start_vel * 2.0, None, None, 2.0 * G);
+    assert!(turning.closed_form_path().is_none());
+  }
+
+  #[test_log::test]
+  fn test_closed_form_flight_path_to_rest() {
+    // Moving fast away from a stationary point that we want to come to rest at.
+    let start_vel = Vec3 {
+      x: -20000.0,
+      y: 5000.0,
+      z: 1000.0,
+    };
+    let end_pos = Vec3 {
+      x: 3e7,
+      y: 1e7,
+      z: -2e6,
+    };
+    let params = FlightParams::new(Vec3::zero(), end_pos, start_vel, Vec3::zero(), None, None, 2.0 * G);
+    let result = params.closed_form_path().unwrap();
+    info!(
+      "Plan: {:?}\nPath: {:?}\nVel {:?}",
+      result.plan, result.path, result.end_velocity
+    );
+
+    let (first, second) = (result.plan.first(), result.plan.second().unwrap());
+    assert_relative_eq!(first.0.magnitude().max(second.0.magnitude()), 2.0 * G, max_relative = 1e-6);
+    assert!(first.0.magnitude() <= 2.0 * G + MAX_ACCEL_WIGGLE_ROOM);
+    assert!(second.0.magnitude() <= 2.0 * G + MAX_ACCEL_WIGGLE_ROOM);
+
+    let p_error = pos_error(&Vec3::zero(), &end_pos, result.path.last().unwrap());
+    assert!(p_error < 1e-4, "Position error is {p_error}");
+    assert!(
+      result.end_velocity.magnitude() < 1e-6,
+      "End velocity is {:?}",
+      result.end_velocity
+    );
+
+    // It's as quick as what the numeric solver finds, up to the slack the solver allows itself; a tiny end velocity
+    // keeps the closed form out of that.
+    let mut numeric = FlightParams::new(Vec3::zero(), end_pos, start_vel, Vec3::zero(), None, None, 2.0 * G);
+    numeric.end_vel = Vec3::new(0.0, 0.0, 1e-3);
+    let numeric = numeric.compute_flight_path().unwrap();
+    let duration = |plan: &FlightPlan| plan.first().1 + plan.second().map_or(0, |accel| accel.1);
+    let (closed, solved) = (duration(&result.plan), duration(&numeric.plan));
+    info!("Closed form takes {closed}s, numeric solver takes {solved}s");
+    #[allow(clippy::cast_precision_loss)]
+    let within_slack = closed as f64 <= solved as f64 * (1.0 + ANS_PERCENT_OFF);
+    assert!(within_slack, "Closed form {closed}s vs numeric {solved}s");
+  }
+
+  #[test_log::test]
+  fn test_warm_start_flight_path() {
+    let start_pos = Vec3 {
+      x: -2e7,
+      y: 1e6,
+      z: 1.5e7,
+    };
+    let start_vel = Vec3 {
+      x: 500.0,
+      y: 0.0,
+      z: 0.0,
+    };
+    let end_vel = Vec3 {
+      x: 500.0,
+      y: 0.0,
+      z: 100.0,
+    };
+    let end_pos = Vec3 {
+      x: 1e7,
+      y: -2e6,
+      z: -2.0e7,
+    };
+    let mut params = FlightParams::new(start_pos, end_pos, start_vel, end_vel, None, None, 4.0 * G);
+    let first = params.compute_flight_path().unwrap();
+
+    // Nudge the target. With no sampled seeds at all, the previous answer is enough to solve from.
+    let nudged_pos = end_pos + Vec3 { x: 1e5, y: 5e4, z: 0.0 };
+    let mut nudged = FlightParams::new(start_pos, nudged_pos, start_vel, end_vel, None, None, 4.0 * G)
+      .with_warm_start(first.as_seed());
+    nudged.max_samples = 0;
+    let result = nudged.compute_flight_path().unwrap();
+    let p_error = pos_error(&start_pos, &nudged_pos, result.path.last().unwrap());
+    assert!(p_error < 0.01, "Position error is {p_error} > 0.01");
+
+    let mut cold = FlightParams::new(start_pos, nudged_pos, start_vel, end_vel, None, None, 4.0 * G);
+    cold.max_samples = 0;
+    assert!(cold.compute_flight_path().is_err());
+  }
 }
@@ -1,6 +1,19 @@
+use std::collections::BTreeMap;
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// DM to a task attempted by someone without the skill for it.
+pub const UNTRAINED_DM: i8 = -3;
+
+// Turns a dying crew member lasts before they die unless stabilized.
+const DYING_TURNS: u8 = 3;
+
+// Damage from a single injury at or above which it takes a crew member two steps down the health ladder, not one.
+const SERIOUS_INJURY: u8 = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Skills {
   Pilot,
   EngineeringJump,
@@ -9,6 +22,116 @@ pub enum Skills {
   Gunnery,
   Sensors,
   Leadership,
+  Medic,
+}
+
+/// Where a crew member serves.  Anyone not at a station is general crew.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Station {
+  Captain,
+  Pilot,
+  Engineer,
+  Sensors,
+  Gunner,
+  Medic,
+  #[default]
+  Crew,
+}
+
+impl Station {
+  /// True for the stations on the bridge, whose crew are at risk when a bridge station is destroyed.
+  #[must_use]
+  pub fn on_bridge(self) -> bool {
+    matches!(self, Station::Captain | Station::Pilot | Station::Sensors)
+  }
+}
+
+/// How badly hurt a crew member is.  Each injury moves them down the ladder; a dying crew member dies after a few
+/// turns unless a medic stabilizes them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Health {
+  #[default]
+  Fit,
+  /// Still at their station, but at -1 to everything.
+  Wounded,
+  Dying {
+    turns_left: u8,
+  },
+  /// Out of action, but no longer dying.
+  Stable,
+  Dead,
+}
+
+impl Health {
+  #[must_use]
+  pub fn is_fit(&self) -> bool {
+    *self == Health::Fit
+  }
+
+  // One step down the health ladder.
+  fn worsen(self) -> Health {
+    match self {
+      Health::Fit => Health::Wounded,
+      Health::Wounded | Health::Stable => Health::Dying {
+        turns_left: DYING_TURNS,
+      },
+      Health::Dying { .. } | Health::Dead => Health::Dead,
+    }
+  }
+}
+
+/// A named member of a ship's crew.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CrewMember {
+  pub name: String,
+  #[serde(default)]
+  pub station: Station,
+  /// Skills the crew member has.  Level 0 is trained; a skill not listed at all is untrained.
+  #[serde(default)]
+  pub skills: BTreeMap<Skills, u8>,
+  #[serde(default, skip_serializing_if = "Health::is_fit")]
+  pub health: Health,
+  /// Ids of the weapons a gunner controls.  Each weapon has at most one gunner.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub weapons: Vec<usize>,
+}
+
+impl CrewMember {
+  #[must_use]
+  pub fn new(name: &str, station: Station, skills: &[(Skills, u8)]) -> Self {
+    CrewMember {
+      name: name.to_string(),
+      station,
+      skills: skills.iter().copied().collect(),
+      health: Health::Fit,
+      weapons: vec![],
+    }
+  }
+
+  /// Only the fit and the walking wounded can man a station.
+  #[must_use]
+  pub fn can_serve(&self) -> bool {
+    matches!(self.health, Health::Fit | Health::Wounded)
+  }
+
+  /// The DM this crew member brings to a task using `skill`: their level (or the untrained DM), less 1 if wounded.
+  #[must_use]
+  pub fn skill_dm(&self, skill: Skills) -> i8 {
+    let level = self
+      .skills
+      .get(&skill)
+      .map_or(UNTRAINED_DM, |level| i8::try_from(*level).unwrap_or(i8::MAX));
+    level - i8::from(self.health == Health::Wounded)
+  }
+
+  /// Injure the crew member with an injury doing `damage`, returning their health after it.
+  pub fn injure(&mut self, damage: u8) -> Health {
+    self.health = self.health.worsen();
+    if damage >= SERIOUS_INJURY {
+      self.health = self.health.worsen();
+    }
+    self.health
+  }
 }
 
 // Helper used by serde `skip_serializing_if` so that zero-valued integer
@@ -37,6 +160,17 @@ pub struct Crew {
   gunnery: Vec<u8>,
   #[serde(default, skip_serializing_if = "is_zero")]
   leadership: u8,
+  #[serde(default, skip_serializing_if = "is_zero")]
+  medic: u8,
+  // Named crew members.  When there are any, each skill comes from whoever is fit to serve at the matching station
+  // rather than from the flat skill levels above.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  members: Vec<CrewMember>,
+}
+
+// A DM as a skill level for the checks that only ever add skill: an untrained stand-in adds nothing.
+fn trained_level(dm: i8) -> u8 {
+  u8::try_from(dm).unwrap_or(0)
 }
 
 // Function just to provide a default value for gunnery deserialization
@@ -55,6 +189,8 @@ impl Crew {
       sensors: 0,
       gunnery: vec![],
       leadership: 0,
+      medic: 0,
+      members: vec![],
     }
   }
 
@@ -76,48 +212,209 @@ impl Crew {
       Skills::EngineeringManeuver => self.engineering_maneuver,
       Skills::Sensors => self.sensors,
       Skills::Leadership => self.leadership,
+      Skills::Medic => self.medic,
       Skills::Gunnery => panic!("(Crew.getSkill) Multiple gunners possible."),
     }
   }
 
+  /// The DM `skill` brings to the work of `station`, or `None` for a crew without named members.  The best of
+  /// those able to serve at the station does the work; if no one can, the best qualified crew member elsewhere on
+  /// the ship stands in; failing that, someone untrained has a go.
+  #[must_use]
+  pub fn station_skill(&self, station: Station, skill: Skills) -> Option<i8> {
+    if self.members.is_empty() {
+      return None;
+    }
+    let able = || self.members.iter().filter(|member| member.can_serve());
+    let best = able()
+      .filter(|member| member.station == station)
+      .map(|member| member.skill_dm(skill))
+      .max()
+      .or_else(|| {
+        able()
+          .filter(|member| member.skills.contains_key(&skill))
+          .map(|member| member.skill_dm(skill))
+          .max()
+      })
+      .unwrap_or(UNTRAINED_DM);
+    Some(best)
+  }
+
+  /// The pilot's DM.  With named crew this falls to the next qualified person if the pilot is out of action, and is
+  /// the untrained DM if no one is left who can fly.
   #[must_use]
-  pub fn get_pilot(&self) -> u8 {
-    self.pilot
+  pub fn get_pilot(&self) -> i8 {
+    self
+      .station_skill(Station::Pilot, Skills::Pilot)
+      .unwrap_or_else(|| i8::try_from(self.pilot).unwrap_or(i8::MAX))
   }
 
   #[must_use]
   pub fn get_engineering_jump(&self) -> u8 {
-    self.engineering_jump
+    self
+      .station_skill(Station::Engineer, Skills::EngineeringJump)
+      .map_or(self.engineering_jump, trained_level)
   }
 
   #[must_use]
   pub fn get_engineering_power(&self) -> u8 {
-    self.engineering_power
+    self
+      .station_skill(Station::Engineer, Skills::EngineeringPower)
+      .map_or(self.engineering_power, trained_level)
   }
 
   #[must_use]
   pub fn get_engineering_maneuver(&self) -> u8 {
-    self.engineering_maneuver
+    self
+      .station_skill(Station::Engineer, Skills::EngineeringManeuver)
+      .map_or(self.engineering_maneuver, trained_level)
   }
 
   #[must_use]
   pub fn get_sensors(&self) -> u8 {
-    self.sensors
+    self
+      .station_skill(Station::Sensors, Skills::Sensors)
+      .map_or(self.sensors, trained_level)
   }
 
   #[must_use]
   pub fn get_leadership(&self) -> u8 {
-    self.leadership
+    self
+      .station_skill(Station::Captain, Skills::Leadership)
+      .map_or(self.leadership, trained_level)
+  }
+
+  /// The medic's DM, with the same fallbacks as [`Crew::get_pilot`].
+  #[must_use]
+  pub fn get_medic(&self) -> i8 {
+    self
+      .station_skill(Station::Medic, Skills::Medic)
+      .unwrap_or_else(|| i8::try_from(self.medic).unwrap_or(i8::MAX))
   }
 
+  /// Gunnery skill for weapon `gun`.  With named crew this is the skill of the gunner assigned to it.
   #[must_use]
   pub fn get_gunnery(&self, gun: usize) -> u8 {
+    if !self.members.is_empty() {
+      return self
+        .gunner_for(gun)
+        .map_or(0, |member| trained_level(member.skill_dm(Skills::Gunnery)));
+    }
     if gun >= self.gunnery.len() {
       return 0;
     }
     self.gunnery[gun]
   }
 
+  /// True when the crew are named individuals, so each weapon needs a gunner assigned to fire it.
+  #[must_use]
+  pub fn has_members(&self) -> bool {
+    !self.members.is_empty()
+  }
+
+  /// The gunner able to serve who is assigned weapon `gun`, if there is one.
+  #[must_use]
+  pub fn gunner_for(&self, gun: usize) -> Option<&CrewMember> {
+    self
+      .members
+      .iter()
+      .find(|member| member.station == Station::Gunner && member.can_serve() && member.weapons.contains(&gun))
+  }
+
+  /// Put crew member `name` at a gunner station in control of `weapons`, taking those weapons from any other gunner.
+  ///
+  /// # Errors
+  /// Returns an error if there is no such crew member or they are in no state to serve.
+  pub fn assign_gunner(&mut self, name: &str, weapons: &[usize]) -> Result<(), String> {
+    let member = self
+      .members
+      .iter()
+      .find(|member| member.name == name)
+      .ok_or_else(|| format!("No crew member named {name}."))?;
+    if !member.can_serve() {
+      return Err(format!("{name} is in no state to man a gun."));
+    }
+    for member in &mut self.members {
+      if member.name == name {
+        member.station = Station::Gunner;
+        member.weapons = weapons.to_vec();
+      } else {
+        member.weapons.retain(|weapon| !weapons.contains(weapon));
+      }
+    }
+    Ok(())
+  }
+
+  #[must_use]
+  pub fn get_members(&self) -> &[CrewMember] {
+    &self.members
+  }
+
+  pub fn add_member(&mut self, member: CrewMember) {
+    self.members.push(member);
+  }
+
+  /// Injure one living crew member, picked at random from those for whom `at_risk` is true, with an injury doing
+  /// `damage`.  Returns their name and health after the injury, or `None` if no one was at risk.
+  pub fn injure_random(
+    &mut self, damage: u8, at_risk: impl Fn(&CrewMember) -> bool, rng: &mut dyn RngCore,
+  ) -> Option<(String, Health)> {
+    let candidates = self
+      .members
+      .iter()
+      .enumerate()
+      .filter(|(_, member)| member.health != Health::Dead && at_risk(member))
+      .map(|(index, _)| index)
+      .collect::<Vec<_>>();
+    let member = &mut self.members[*candidates.choose(rng)?];
+    let health = member.injure(damage);
+    Some((member.name.clone(), health))
+  }
+
+  /// Injure the crew member `name` with an injury doing `damage`, returning their health after it.
+  pub fn injure(&mut self, name: &str, damage: u8) -> Option<Health> {
+    self
+      .members
+      .iter_mut()
+      .find(|member| member.name == name)
+      .map(|member| member.injure(damage))
+  }
+
+  /// Stabilize the dying crew member `name` so they no longer lose ground.
+  ///
+  /// # Errors
+  /// Returns an error if there is no such crew member or they are not dying.
+  pub fn stabilize(&mut self, name: &str) -> Result<(), String> {
+    let member = self
+      .members
+      .iter_mut()
+      .find(|member| member.name == name)
+      .ok_or_else(|| format!("No crew member named {name}."))?;
+    if !matches!(member.health, Health::Dying { .. }) {
+      return Err(format!("{name} is not dying."));
+    }
+    member.health = Health::Stable;
+    Ok(())
+  }
+
+  /// The end of a turn for the dying: each loses a turn, and those out of time die.  Returns the names of the dead.
+  pub fn bleed_out(&mut self) -> Vec<String> {
+    let mut dead = vec![];
+    for member in &mut self.members {
+      if let Health::Dying { turns_left } = member.health {
+        member.health = if turns_left <= 1 {
+          dead.push(member.name.clone());
+          Health::Dead
+        } else {
+          Health::Dying {
+            turns_left: turns_left - 1,
+          }
+        };
+      }
+    }
+    dead
+  }
+
   /// Sets a crew skill level.  Note that setting a skill this way for gunnery is not allowed.
   /// Instead use `add_gunnery`.
   ///
@@ -135,6 +432,7 @@ impl Crew {
       Skills::EngineeringManeuver => self.engineering_maneuver = value,
       Skills::Sensors => self.sensors = value,
       Skills::Leadership => self.leadership = value,
+      Skills::Medic => self.medic = value,
       Skills::Gunnery => panic!("Cannot use set_skill for gunnery. Use add_gunnery instead."),
     }
   }
@@ -287,4 +585,120 @@ mod tests {
     assert_eq!(crew.gunnery, deserialized.gunnery);
     assert_eq!(crew.leadership, deserialized.leadership);
   }
+
+  #[test_log::test]
+  fn test_station_skills_fall_back() {
+    let mut crew = Crew::new();
+    crew.add_member(CrewMember::new("Ada", Station::Pilot, &[(Skills::Pilot, 2)]));
+    crew.add_member(CrewMember::new(
+      "Ben",
+      Station::Gunner,
+      &[(Skills::Pilot, 1), (Skills::Gunnery, 2)],
+    ));
+    crew.add_member(CrewMember::new("Cy", Station::Engineer, &[(Skills::EngineeringPower, 1)]));
+    crew.assign_gunner("Ben", &[0]).unwrap();
+
+    assert_eq!(crew.get_pilot(), 2);
+    assert_eq!(crew.get_gunnery(0), 2);
+    assert_eq!(crew.get_gunnery(1), 0);
+    assert_eq!(crew.get_engineering_power(), 1);
+    // No one on the bridge and no one trained: the untrained DM, clamped to nothing where only skill is added.
+    assert_eq!(crew.get_medic(), UNTRAINED_DM);
+    assert_eq!(crew.get_sensors(), 0);
+
+    // A wounded pilot flies at -1.
+    assert_eq!(crew.injure("Ada", 1), Some(Health::Wounded));
+    assert_eq!(crew.get_pilot(), 1);
+
+    // With the pilot down, the gunner who can fly takes over.
+    assert_eq!(
+      crew.injure("Ada", SERIOUS_INJURY),
+      Some(Health::Dead),
+      "Two steps down from wounded is dead."
+    );
+    assert_eq!(crew.get_pilot(), 1);
+
+    assert_eq!(crew.injure("Ben", 1), Some(Health::Wounded), "One step from fit is wounded.");
+    assert_eq!(
+      crew.injure("Ben", 1),
+      Some(Health::Dying {
+        turns_left: DYING_TURNS
+      })
+    );
+    assert_eq!(crew.get_pilot(), UNTRAINED_DM);
+    assert_eq!(crew.get_gunnery(0), 0);
+    assert_eq!(crew.injure("Nobody", 1), None);
+  }
+
+  #[test_log::test]
+  fn test_stabilize_and_bleed_out() {
+    let mut crew = Crew::new();
+    crew.add_member(CrewMember::new("Ada", Station::Pilot, &[(Skills::Pilot, 2)]));
+    crew.add_member(CrewMember::new("Ben", Station::Gunner, &[(Skills::Gunnery, 1)]));
+    crew.injure("Ada", SERIOUS_INJURY);
+    crew.injure("Ben", SERIOUS_INJURY);
+
+    assert!(crew.stabilize("Ada").is_ok());
+    assert!(crew.stabilize("Ada").is_err(), "Ada is no longer dying.");
+    assert!(crew.stabilize("Nobody").is_err());
+    assert!(!crew.get_members()[0].can_serve());
+
+    for _ in 1..DYING_TURNS {
+      assert!(crew.bleed_out().is_empty());
+    }
+    assert_eq!(crew.bleed_out(), vec!["Ben".to_string()]);
+    assert_eq!(crew.get_members()[0].health, Health::Stable);
+    assert_eq!(crew.get_members()[1].health, Health::Dead);
+    assert!(crew.bleed_out().is_empty());
+  }
+
+  #[test_log::test]
+  fn test_crew_members_serialization() {
+    // A crew without members serializes as it always has.
+    let mut crew = Crew::new();
+    crew.pilot = 2;
+    assert!(serde_json::to_value(&crew).unwrap().get("members").is_none());
+
+    crew.add_member(CrewMember::new("Ada", Station::Pilot, &[(Skills::Pilot, 2)]));
+    crew.injure("Ada", 1);
+    let serialized = serde_json::to_value(&crew).unwrap();
+    assert_eq!(
+      serialized["members"],
+      serde_json::json!([{"name": "Ada", "station": "Pilot", "skills": {"Pilot": 2}, "health": "Wounded"}])
+    );
+    let deserialized: Crew = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized.get_members(), crew.get_members());
+
+    let minimal: CrewMember = serde_json::from_str(r#"{"name": "Ben"}"#).unwrap();
+    assert_eq!(minimal, CrewMember::new("Ben", Station::Crew, &[]));
+  }
+
+  #[test_log::test]
+  fn test_assign_gunner() {
+    let mut crew = Crew::new();
+    crew.add_member(CrewMember::new("Ben", Station::Gunner, &[(Skills::Gunnery, 2)]));
+    crew.add_member(CrewMember::new("Cy", Station::Crew, &[(Skills::Gunnery, 1)]));
+
+    // No one is assigned a weapon until told to be.
+    assert!(crew.has_members());
+    assert!(crew.gunner_for(0).is_none());
+    assert_eq!(crew.get_gunnery(0), 0);
+
+    crew.assign_gunner("Ben", &[0, 1]).unwrap();
+    assert_eq!(crew.gunner_for(1).unwrap().name, "Ben");
+    assert_eq!(crew.get_gunnery(1), 2);
+
+    // Reassigning a weapon takes it from its old gunner, and puts the new one at a gunner station.
+    crew.assign_gunner("Cy", &[1]).unwrap();
+    assert_eq!(crew.gunner_for(0).unwrap().name, "Ben");
+    assert_eq!(crew.gunner_for(1).unwrap().name, "Cy");
+    assert_eq!(crew.get_members()[0].weapons, vec![0]);
+    assert_eq!(crew.get_members()[1].station, Station::Gunner);
+
+    // A gunner out of action leaves their guns unmanned.
+    crew.injure("Ben", SERIOUS_INJURY);
+    assert!(crew.gunner_for(0).is_none());
+    assert!(crew.assign_gunner("Ben", &[0]).is_err());
+    assert!(crew.assign_gunner("Nobody", &[0]).is_err());
+  }
 }
@@ -1,7 +1,10 @@
 use cgmath::{InnerSpace, Vector3};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::payloads::{EffectMsg, EngineerActionResult};
+use crate::payloads::{
+  ApproachMsg, EffectMsg, EngagingWeaponMsg, EngineerActionResult, JumpMishap, MissileInterceptMsg, RangeWindowMsg,
+  RollPurpose, RollRecord, TrajectoriesMsg, Vec3asVec,
+};
 use rand::seq::SliceRandom;
 use rand::RngCore;
 
@@ -13,16 +16,21 @@ use tracing::{event, Level};
 
 use crate::action::{boost_for_engineer, boost_for_sensor, BoostMap, ShipAction, ShipActionList};
 use crate::combat::{
-  attack, build_point_defense_tallies, create_sand_counts, do_fire_actions, roll_dice, use_next_point_defense,
+  apply_crit, build_point_defense_tallies, create_sand_counts, do_fire_actions, roll_dice, smart_missile_bonus,
+  use_next_point_defense, STANDARD_ROLL_THRESHOLD,
 };
-use crate::crew::Crew;
+use crate::crew::{Crew, Health};
 use crate::missile::Missile;
 use crate::planet::{Planet, PlanetVisualEffect};
-use crate::read_local_or_cloud_file;
-use crate::rules_tables::{countermeasures_mod, stealth_mod, SENSOR_QUALITY_MOD};
+use crate::roster::RosterPlacement;
+use crate::rules_tables::{default_rules, RulesTables};
+use crate::ruleset::{EngineeringCheck, SensorCheck};
 use crate::ship::get_ship_templates_snapshot;
-use crate::ship::{with_ship_templates_for_deserialization, FlightPlan, Ship, ShipDesignTemplate, ShipSystem};
+use crate::ship::{
+  with_ship_templates_for_deserialization, FlightPlan, PowerAllocation, Ship, ShipDesignTemplate, ShipSystem,
+};
 use crate::ship::{Weapon, WeaponMount, WeaponType};
+use crate::storage::StorageBackend;
 
 #[allow(unused_imports)]
 use crate::{debug, error, info, warn, LOG_FILE_USE};
@@ -31,11 +39,19 @@ pub const DELTA_TIME: u64 = 360;
 pub const DELTA_TIME_F64: f64 = 360.0;
 
 pub const DEFAULT_ACCEL_DURATION: u64 = 50000;
+// Longest trajectory projection we'll compute, in turns.
+pub const MAX_PROJECTION_TURNS: u16 = 100;
 // We will use 4 sig figs for every physics constant we import.
 // This is the value of 1 (earth) gravity in m/s^2
 pub const G: f64 = 9.807_000_000;
 pub type Vec3 = Vector3<f64>;
 
+// Skimming fills at most this fraction of a ship's tanks a turn, so filling up from empty takes several turns.
+const SKIM_TURNS_TO_FILL: u32 = 4;
+// How close (in m) and how well matched in velocity (in m/s) two ships must be to pump fuel between them.
+const FUEL_TRANSFER_RANGE: f64 = 10_000.0;
+const FUEL_TRANSFER_MAX_SPEED: f64 = 100.0;
+
 pub trait Entity: Debug + PartialEq + Serialize + Send + Sync {
   fn get_name(&self) -> &str;
   fn set_name(&mut self, name: String);
@@ -66,6 +82,17 @@ pub struct Entities {
   // so we store them here so that Entities the single global-state object for a server.
   pub actions: ShipActionList,
   pub metadata: MetaData,
+  // House rules for this scenario; `None` plays by the server's default rules.
+  pub rules: Option<Box<RulesTables>>,
+  // Other systems ships can jump to from this scenario.
+  pub jump_destinations: Vec<JumpDestination>,
+  // Campaign roster ships this scenario brings in, placed by `Server::with_roster`.
+  pub roster: Vec<RosterPlacement>,
+  // Ships that jumped cleanly for one of `jump_destinations` this turn, waiting to be sent on.  Never serialized.
+  pub departures: Vec<Departure>,
+  // Campaign roster ships that left this turn, destroyed or jumped away, to be written back to the roster.  Never
+  // serialized.
+  pub roster_departures: Vec<Ship>,
 
   // Basename of the scenario file this Entities was loaded from (e.g.
   // "planetfun.json"). Empty for scenarios created from scratch in the builder.
@@ -76,6 +103,29 @@ pub struct Entities {
   pub filename: String,
 }
 
+/// Another system a ship can jump to from this scenario.  The ship emerges at `emergence_point` in the running
+/// scenario named `server` once `transit_turns` turns of that scenario have passed.  If that scenario isn't running
+/// it is started from the scenario file `scenario` (or empty if there is none).
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JumpDestination {
+  pub name: String,
+  pub server: String,
+  #[serde(default)]
+  pub scenario: String,
+  #[serde_as(as = "Vec3asVec")]
+  pub emergence_point: Vec3,
+  #[serde(default)]
+  pub transit_turns: u32,
+}
+
+/// A ship that has jumped out of this scenario, with its full state, and where it is bound.
+#[derive(Debug, Clone)]
+pub struct Departure {
+  pub ship: Ship,
+  pub destination: JumpDestination,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct MetaData {
   // All fields default to empty so legacy scenario files (e.g. ones written
@@ -123,10 +173,21 @@ impl Entities {
       next_missile_id: 0,
       actions: vec![],
       metadata: MetaData::default(),
+      rules: None,
+      jump_destinations: vec![],
+      roster: vec![],
+      departures: vec![],
+      roster_departures: vec![],
       filename: String::new(),
     }
   }
 
+  /// The rules tables combat and sensor checks in this scenario use.
+  #[must_use]
+  pub fn rules(&self) -> &RulesTables {
+    self.rules.as_deref().unwrap_or_else(|| default_rules())
+  }
+
   #[must_use]
   pub fn len(&self) -> usize {
     self.ships.len() + self.missiles.len() + self.planets.len()
@@ -177,6 +238,9 @@ impl Entities {
 
     dest.next_missile_id = self.next_missile_id;
     dest.actions.clone_from(&self.actions);
+    dest.rules.clone_from(&self.rules);
+    dest.jump_destinations.clone_from(&self.jump_destinations);
+    dest.roster.clone_from(&self.roster);
 
     dest.fixup_pointers().unwrap();
     dest.reset_gravity_wells();
@@ -194,6 +258,7 @@ impl Entities {
   /// Load a scenario file.  A scenario file is just a JSON encoding of a set of entities.
   /// After loading the file, the pointers are fixed up and the gravity wells are reset.
   /// # Arguments
+  /// * `storage` - The backend holding the scenario directory.
   /// * `file_name` - The name of the file to load.
   ///
   /// # Errors
@@ -201,8 +266,10 @@ impl Entities {
   ///
   /// # Panics
   /// Panics if the lock cannot be obtained to read a ship, missile, or planet.
-  pub async fn load_from_file(file_name: &str) -> Result<Self, Box<dyn std::error::Error>> {
-    Self::load_from_file_with_ship_templates(file_name, get_ship_templates_snapshot()).await
+  pub async fn load_from_file(
+    storage: &dyn StorageBackend, file_name: &str,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    Self::load_from_file_with_ship_templates(storage, file_name, get_ship_templates_snapshot()).await
   }
 
   /// Load a scenario file using the provided ship-template snapshot.
@@ -216,11 +283,11 @@ impl Entities {
   /// # Panics
   /// Panics if the lock cannot be obtained to read a ship, missile, or planet.
   pub async fn load_from_file_with_ship_templates(
-    file_name: &str, ship_templates: Arc<HashMap<String, Arc<ShipDesignTemplate>>>,
+    storage: &dyn StorageBackend, file_name: &str, ship_templates: Arc<HashMap<String, Arc<ShipDesignTemplate>>>,
   ) -> Result<Self, Box<dyn std::error::Error>> {
     event!(target: LOG_FILE_USE, Level::INFO, file_name, use = "Load scenario.");
 
-    let scenario_contents = read_local_or_cloud_file(file_name).await?;
+    let scenario_contents = storage.read(file_name).await.map_err(|e| e as Box<dyn std::error::Error>)?;
     let mut entities: Entities =
       with_ship_templates_for_deserialization(ship_templates, || serde_json::from_slice(&scenario_contents))?;
 
@@ -262,7 +329,7 @@ impl Entities {
   /// The wire-level [`Serialize`] impl (used by `EntityResponse` over the WebSocket)
   /// intentionally omits `metadata` and `actions`, since neither is meaningful to a
   /// connected client. Scenario files on disk include `metadata` and follow the
-  /// shape `{ metadata, ships, planets, missiles? }` — this helper emits exactly
+  /// shape `{ metadata, ships, roster?, planets, missiles? }` — this helper emits exactly
   /// that, with empty arrays elided.
   ///
   /// # Errors
@@ -277,12 +344,29 @@ impl Entities {
       #[serde(skip_serializing_if = "Vec::is_empty")]
       ships: Vec<crate::ship::Ship>,
       #[serde(skip_serializing_if = "Vec::is_empty")]
+      roster: Vec<RosterPlacement>,
+      #[serde(skip_serializing_if = "Vec::is_empty")]
       planets: Vec<Planet>,
       #[serde(skip_serializing_if = "Vec::is_empty")]
       missiles: Vec<Missile>,
     }
 
-    let mut ships: Vec<_> = self.ships.values().map(|s| s.read().unwrap().clone()).collect();
+    // Roster ships are saved as placements: their state belongs to the roster, and a copy in the scenario file would
+    // come back repaired.
+    let (roster_ships, mut ships): (Vec<_>, Vec<_>) = self
+      .ships
+      .values()
+      .map(|s| s.read().unwrap().clone())
+      .partition(|ship| ship.get_roster_id().is_some());
+    let mut roster: Vec<_> = roster_ships
+      .iter()
+      .map(|ship| RosterPlacement {
+        id: ship.get_roster_id().unwrap_or_default().to_string(),
+        position: ship.get_position(),
+        velocity: ship.get_velocity(),
+      })
+      .collect();
+    roster.sort_by(|a, b| a.id.cmp(&b.id));
     let mut planets: Vec<_> = self.planets.values().map(|p| p.read().unwrap().clone()).collect();
     let mut missiles: Vec<_> = self.missiles.values().map(|m| m.read().unwrap().clone()).collect();
     // Stable ordering matches the existing wire-level Serialize impl, so file diffs are clean.
@@ -293,6 +377,7 @@ impl Entities {
     let payload = ScenarioFile {
       metadata: &self.metadata,
       ships,
+      roster,
       planets,
       missiles,
     };
@@ -484,6 +569,78 @@ impl Entities {
     }
   }
 
+  /// Put crew member `gunner` of ship `name` in control of `weapons`, taking them from whoever had them.
+  ///
+  /// # Errors
+  /// Returns an error if the ship or crew member is not found, the crew member can't serve, or a weapon isn't one of
+  /// the ship's.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to write the ship.
+  pub fn assign_gunner(&mut self, name: &str, gunner: &str, weapons: &[usize]) -> Result<(), String> {
+    let Some(ship) = self.ships.get(name) else {
+      return Err(format!("Could not assign a gunner for non-existent ship {name}"));
+    };
+    let mut ship = ship.write().unwrap();
+    if let Some(weapon) = weapons.iter().find(|weapon| **weapon >= ship.design.weapons.len()) {
+      return Err(format!("{name} has no weapon {weapon}"));
+    }
+    ship.get_crew_mut().assign_gunner(gunner, weapons)
+  }
+
+  /// Plot the jump of ship `name` for one of this scenario's jump destinations, or clear it with `None`.
+  ///
+  /// # Errors
+  /// Returns an error if the ship is not found or the scenario has no such destination.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to write the ship.
+  pub fn set_jump_destination(&mut self, name: &str, destination: Option<&str>) -> Result<(), String> {
+    let Some(ship) = self.ships.get(name) else {
+      return Err(format!("Could not set jump destination for non-existent ship {name}"));
+    };
+    if let Some(destination) = destination {
+      if !self.jump_destinations.iter().any(|d| d.name == destination) {
+        return Err(format!("No jump destination named {destination} in this scenario"));
+      }
+    }
+    ship.write().unwrap().set_jump_destination(destination.map(str::to_string));
+    Ok(())
+  }
+
+  /// Turn ship `name` to point along `facing` whenever it isn't thrusting, or clear its facing with `None`.
+  ///
+  /// # Errors
+  /// Returns an error if the ship is not found or `facing` has no direction.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to write the ship.
+  pub fn set_facing(&mut self, name: &str, facing: Option<Vec3>) -> Result<(), String> {
+    let Some(ship) = self.ships.get(name) else {
+      return Err(format!("Could not set facing for non-existent ship {name}"));
+    };
+    ship.write().unwrap().set_facing(facing)
+  }
+
+  /// The ships that have jumped for another scenario since this was last called.
+  pub fn take_departures(&mut self) -> Vec<Departure> {
+    std::mem::take(&mut self.departures)
+  }
+
+  /// The campaign roster ships that have left the scenario, as they left it, since this was last called.
+  pub fn take_roster_departures(&mut self) -> Vec<Ship> {
+    std::mem::take(&mut self.roster_departures)
+  }
+
+  /// Take `name` out of the scenario, keeping its final state if it is a roster ship.
+  fn remove_ship(&mut self, name: &str) -> Option<Ship> {
+    let ship = self.ships.remove(name)?.read().unwrap().clone();
+    if ship.get_roster_id().is_some() {
+      self.roster_departures.push(ship.clone());
+    }
+    Some(ship)
+  }
+
   /// Process all fire actions and turn them into either missile launches or attacks.
   ///
   /// # Arguments
@@ -503,22 +660,10 @@ impl Entities {
   ) -> Vec<EffectMsg> {
     // Create a snapshot of all the sand capabilities of each ship.
     let mut sand_counts = create_sand_counts(ship_snapshot);
+    let rules = self.rules().clone();
 
-    // From our list of point defense actions, go into each ship and build up a proper list of usable point defense actions.
-    // These then get used and cleared in `Entities::update_all` after all missiles have been updated.
-    for (defender, actions) in point_defense_actions {
-      let Some(ship) = self.ships.get(defender) else {
-        warn!(
-          "(Entities.fire_actions) Cannot find attacker {} for point defense actions.",
-          defender
-        );
-        continue;
-      };
-
-      let mut ship = ship.write().unwrap();
-      let tallies = build_point_defense_tallies(&ship, actions, boost_map, defender);
-      ship.set_point_defense_list(tallies);
-    }
+    // The fire actions each ship actually attempts, so point defense knows which gunners are already busy.
+    let mut attempted = HashMap::new();
 
     let effects = fire_actions
       .iter()
@@ -528,8 +673,34 @@ impl Entities {
           return vec![];
         };
 
-        let (missiles, effects) =
-          do_fire_actions(attack_ship, &mut self.ships, &mut sand_counts, actions, boost_map, rng);
+        // A planet in the way blocks every kind of fire.  Missiles too can't acquire a target hidden behind one.
+        let mut effects = Vec::new();
+        let actions = actions
+          .iter()
+          .filter(|action| {
+            let ShipAction::FireAction { target, .. } = action else {
+              return true;
+            };
+            let Some(target_ship) = ship_snapshot.get(target) else {
+              return true;
+            };
+            let Some(planet) = self.occluding_planet(attack_ship.get_position(), target_ship.get_position()) else {
+              return true;
+            };
+            effects.push(EffectMsg::LineOfSightBlocked {
+              ship: attacker.clone(),
+              target: target.clone(),
+              planet,
+            });
+            false
+          })
+          .cloned()
+          .collect::<Vec<_>>();
+        attempted.insert(attacker.clone(), actions.clone());
+
+        let (missiles, mut fire_effects) =
+          do_fire_actions(attack_ship, &mut self.ships, &mut sand_counts, &actions, boost_map, &rules, rng);
+        effects.append(&mut fire_effects);
         for missile in missiles {
           if let Err(msg) = self.launch_missile(&missile.source, &missile.target) {
             warn!("Could not launch missile: {}", msg);
@@ -538,9 +709,47 @@ impl Entities {
         effects
       })
       .collect();
+
+    // From our list of point defense actions, go into each ship and build up a proper list of usable point defense actions.
+    // These then get used and cleared in `Entities::update_all` after all missiles have been updated.
+    for (defender, actions) in point_defense_actions {
+      let Some(ship) = self.ships.get(defender) else {
+        warn!(
+          "(Entities.fire_actions) Cannot find attacker {} for point defense actions.",
+          defender
+        );
+        continue;
+      };
+
+      let mut ship = ship.write().unwrap();
+      let fired = attempted.get(defender).map_or(&[][..], Vec::as_slice);
+      let tallies = build_point_defense_tallies(&ship, actions, fired, boost_map, defender);
+      ship.set_point_defense_list(tallies);
+    }
+
     effects
   }
 
+  /// The planet, if any, standing between `from` and `to` so that neither can see the other.  With several in the
+  /// way it's the one nearest `from`.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to read a planet.
+  #[must_use]
+  pub fn occluding_planet(&self, from: Vec3, to: Vec3) -> Option<String> {
+    self
+      .planets
+      .values()
+      .map(|planet| planet.read().unwrap())
+      .filter(|planet| planet.blocks_line_of_sight(from, to))
+      .min_by(|a, b| {
+        (a.get_position() - from)
+          .magnitude()
+          .total_cmp(&(b.get_position() - from).magnitude())
+      })
+      .map(|planet| planet.get_name().to_string())
+  }
+
   /// Check which ships are jump enabled.  This is done at the end of each round.  It is done
   /// by checking if the ship is more than 100 diameters (200 radii) away from every planet.
   ///
@@ -620,6 +829,7 @@ impl Entities {
     // check should destroy that many missiles. So before we expend another weapon, we burn down that effect.
     // This memory stores that value.
     let mut point_defense_memory = HashMap::new();
+    let rules = self.rules().clone();
 
     // Now update all (remaining) missiles.
     let mut effects = sorted_missiles
@@ -645,6 +855,7 @@ impl Entities {
             const FAKE_MISSILE_LAUNCHER: Weapon = Weapon {
               kind: WeaponType::Missile,
               mount: WeaponMount::Turret(1),
+              arc: None,
             };
             debug!("(Entity.update_all) Missile impact on {} by missile {}.", target_name, missile);
             let target = self.ships.get(&target_name).map_or_else(
@@ -657,8 +868,7 @@ impl Entities {
 
             if let Some(target) = target {
               // For now assume all missiles are smart missiles.
-              let smart_missile_bonus =
-                i32::from(missile_source.design.tl.saturating_sub(target.read().unwrap().design.tl)).clamp(1, 6);
+              let smart_missile_bonus = smart_missile_bonus(missile_source, &target.read().unwrap());
 
               debug!(
                 "(Entity.update_all) Missile {} impacted target {} with smart missile bonus {} (attacker TL {}, target TL {}).",
@@ -671,9 +881,10 @@ impl Entities {
               let mut target = target.write().unwrap();
 
               // See if point defense works!
+              let mut rolls = vec![];
               let available_point_defense = point_defense_memory
                 .remove(&target_name)
-                .unwrap_or_else(|| use_next_point_defense(&mut target.point_defense_list, rng));
+                .unwrap_or_else(|| use_next_point_defense(&target_name, &mut target.point_defense_list, rng, &mut rolls));
 
               // This stops the attack
               if available_point_defense > 0 {
@@ -690,10 +901,11 @@ impl Entities {
                   missile, target_name
                 );
                 cleanup_missile_list.push(missile.clone());
-                Some(vec![EffectMsg::ExhaustedMissile { position: target.get_position() }, EffectMsg::message(format!("Missile {missile} destroyed by {target_name}'s point defense"))])
+                rolls.extend([EffectMsg::ExhaustedMissile { position: target.get_position() }, EffectMsg::PointDefenseIntercept { ship: target_name.clone(), missile: missile.clone() }]);
+                Some(rolls)
               } else {
                 // The attack gets through point defense
-                let effects = attack(
+                let effects = rules.ruleset().attack(
                   smart_missile_bonus,
                   0,
                   missile_source,
@@ -702,11 +914,13 @@ impl Entities {
                   // Missiles cannot do called shots
                   None,
                   boost_map,
+                  &rules,
                   rng,
                 );
                 cleanup_missile_list.push(missile);
 
-                Some(effects)
+                rolls.extend(effects);
+                Some(rolls)
               }
             } else {
               debug!(
@@ -770,7 +984,7 @@ impl Entities {
 
     for name in &cleanup_ships_list {
       debug!("(Entity.update_all) Removing ship {}", name);
-      self.ships.remove(name);
+      self.remove_ship(name);
     }
 
     // Update which ships are jump enabled
@@ -778,6 +992,172 @@ impl Entities {
     effects
   }
 
+  /// Predict where every ship, missile and planet will be over the next `turns` turns, moving them just as
+  /// [`Entities::update_all`] does: planets first, then missiles guiding on their targets, then ships flying their
+  /// plans. No combat is resolved; a missile reaching its target is reported as an intercept and stops there.
+  ///
+  /// This moves the entities, so call it on a deep copy.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained on a ship, missile, or planet.
+  #[must_use]
+  pub fn project_trajectories(self, turns: u16) -> TrajectoriesMsg {
+    fn record<T: Entity>(entities: &HashMap<String, Arc<RwLock<T>>>, into: &mut HashMap<String, Vec<Vec3>>) {
+      for (name, entity) in entities {
+        into
+          .entry(name.clone())
+          .or_default()
+          .push(entity.read().unwrap().get_position());
+      }
+    }
+
+    let mut projection = self;
+    let mut result = TrajectoriesMsg {
+      turns,
+      ..TrajectoriesMsg::default()
+    };
+    record(&projection.ships, &mut result.ships);
+    record(&projection.missiles, &mut result.missiles);
+    record(&projection.planets, &mut result.planets);
+
+    let mut planets = projection.planets.values().cloned().collect::<Vec<_>>();
+    planets.sort_by_key(|planet| planet.read().unwrap().dependency);
+
+    for turn in 1..=turns {
+      for planet in &planets {
+        planet.write().unwrap().update();
+      }
+
+      let mut missile_names = projection.missiles.keys().cloned().collect::<Vec<_>>();
+      missile_names.sort();
+      for name in missile_names {
+        let mut missile = projection.missiles[&name].write().unwrap();
+        let update = missile.update();
+        result.missiles.entry(name.clone()).or_default().push(missile.get_position());
+        match update {
+          Some(UpdateAction::ShipImpact {
+            ship,
+            missile: missile_name,
+          }) => {
+            result.intercepts.push(MissileInterceptMsg {
+              missile: missile_name,
+              target: ship,
+              turn,
+              position: missile.get_position(),
+            });
+          }
+          Some(UpdateAction::ExhaustedMissile { .. }) => {}
+          _ => continue,
+        }
+        drop(missile);
+        projection.missiles.remove(&name);
+      }
+
+      for ship in projection.ships.values() {
+        ship.write().unwrap().update();
+      }
+
+      record(&projection.ships, &mut result.ships);
+      record(&projection.planets, &mut result.planets);
+    }
+    result
+  }
+
+  /// Find when entities `first` and `second` come closest over the next `turns` turns, on their current plans,
+  /// and the range bands they pass through on the way. Between turns both are taken to move in a straight line,
+  /// so the closest approach can fall mid-turn. Like [`Entities::project_trajectories`] this moves the
+  /// entities, so call it on a deep copy.
+  ///
+  /// # Errors
+  /// Returns an error if either entity doesn't exist.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained on a ship, missile, or planet.
+  pub fn closest_approach(self, first: &str, second: &str, turns: u16) -> Result<ApproachMsg, String> {
+    // Each ship's active weapons, fixed for the whole projection.
+    let weapons_of = |name: &str| {
+      self.ships.get(name).map_or_else(Vec::new, |ship| {
+        let ship = ship.read().unwrap();
+        ship
+          .design
+          .weapons
+          .iter()
+          .enumerate()
+          .filter(|(index, _)| ship.active_weapons.get(*index).copied().unwrap_or(false))
+          .map(|(index, weapon)| EngagingWeaponMsg {
+            ship: name.to_string(),
+            weapon_index: index,
+            weapon: weapon.clone(),
+          })
+          .collect::<Vec<_>>()
+      })
+    };
+    let weapons = [weapons_of(first), weapons_of(second)].concat();
+    let rules = self.rules().clone();
+
+    let projection = self.project_trajectories(turns);
+    let track = |name: &str| {
+      projection
+        .ships
+        .get(name)
+        .or_else(|| projection.missiles.get(name))
+        .or_else(|| projection.planets.get(name))
+        .ok_or_else(|| format!("No entity named '{name}' to compute a closest approach for"))
+    };
+    let (first_track, second_track) = (track(first)?, track(second)?);
+    // A missile's track ends when it hits or burns out, so only compare while both exist.
+    let separation = first_track
+      .iter()
+      .zip(second_track)
+      .map(|(first_pos, second_pos)| second_pos - first_pos)
+      .collect::<Vec<_>>();
+
+    // Closest point on each turn's straight-line segment of relative motion.
+    let (mut time, mut distance) = (0.0, separation[0].magnitude());
+    for (turn, pair) in separation.windows(2).enumerate() {
+      let step = pair[1] - pair[0];
+      let fraction = if step.magnitude2() > 0.0 {
+        (-pair[0].dot(step) / step.magnitude2()).clamp(0.0, 1.0)
+      } else {
+        0.0
+      };
+      let nearest = (pair[0] + step * fraction).magnitude();
+      if nearest < distance {
+        distance = nearest;
+        #[allow(clippy::cast_precision_loss)]
+        let turn = turn as f64;
+        time = (turn + fraction) * DELTA_TIME_F64;
+      }
+    }
+
+    let mut windows: Vec<RangeWindowMsg> = Vec::new();
+    for (turn, offset) in (0u16..).zip(&separation) {
+      #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+      let range = rules.range_band(offset.magnitude() as u32);
+      match windows.last_mut() {
+        Some(window) if window.range == range => window.to_turn = turn,
+        _ => windows.push(RangeWindowMsg {
+          range,
+          from_turn: turn,
+          to_turn: turn,
+          weapons: weapons
+            .iter()
+            .filter(|engaging| engaging.weapon.kind.in_range(range))
+            .cloned()
+            .collect(),
+        }),
+      }
+    }
+
+    Ok(ApproachMsg {
+      first: first.to_string(),
+      second: second.to_string(),
+      time,
+      distance,
+      windows,
+    })
+  }
+
   /// Do all sensor actions.  These activities are done before any combat in a round
   /// as they impact combat in that round (remove missiles, etc).
   ///
@@ -786,13 +1166,16 @@ impl Entities {
   /// * `rng` - The random number generator to use.
   ///
   /// # Returns
-  /// A list of all the effects resulting from the sensor actions.
+  /// A list of all the effects resulting from the sensor actions, and from any sensor locks a planet has broken.
   ///
   /// # Panics
   /// Panics if the lock cannot be obtained to read a ship.
   pub fn sensor_actions(
     &mut self, actions: &[(String, Vec<ShipAction>)], boost_map: &BoostMap, rng: &mut dyn RngCore,
   ) -> Vec<EffectMsg> {
+    // Locks on ships that have since gone behind a planet don't survive into this round.
+    let mut effects = self.break_blocked_sensor_locks();
+
     // First build a table that, for each ship, notes all the ships that have senor locks on it (i.e. we're reversing
     // the structure).  That is for any ship name (entry), provide a list of every ship that has a sensor lock on the entry.
     // We do this once, up front, to avoid rebuilding on each ShipAction::BreakSensorLock action.
@@ -806,9 +1189,17 @@ impl Entities {
       }
     }
 
-    let mut effects = Vec::<EffectMsg>::new();
-
     for (ship_name, actions) in actions {
+      if self
+        .ships
+        .get(ship_name)
+        .is_some_and(|ship| !ship.read().unwrap().powered_systems().sensors)
+      {
+        effects.push(EffectMsg::SensorsUnpowered {
+          ship: ship_name.clone(),
+        });
+        continue;
+      }
       let boost = boost_for_sensor(boost_map, ship_name);
       // Process the actions for each ship.
       for action in actions {
@@ -818,20 +1209,16 @@ impl Entities {
             self.break_sensor_lock(ship_name, target, &reverse_sensor_locks, boost, rng)
           }
 
-          ShipAction::SensorLock { target } => {
-            if !self.ships.contains_key(target) {
-              warn!("(Entity.do_sensor_actions) Cannot find target {} for sensor lock.", target);
-              return Vec::default();
-            }
-            self.sensor_lock(ship_name, target, boost, rng)
+          ShipAction::SensorLock { target } if !self.ships.contains_key(target) => {
+            warn!("(Entity.do_sensor_actions) Cannot find target {} for sensor lock.", target);
+            vec![]
           }
-          ShipAction::JamComms { target } => {
-            if !self.ships.contains_key(target) {
-              warn!("(Entity.do_sensor_actions) Cannot find target {} for jamming comms.", target);
-              return Vec::default();
-            }
-            self.jam_comms(ship_name, target, boost, rng)
+          ShipAction::SensorLock { target } => self.sensor_lock(ship_name, target, boost, rng),
+          ShipAction::JamComms { target } if !self.ships.contains_key(target) => {
+            warn!("(Entity.do_sensor_actions) Cannot find target {} for jamming comms.", target);
+            vec![]
           }
+          ShipAction::JamComms { target } => self.jam_comms(ship_name, target, boost, rng),
           ShipAction::PointDefenseAction { .. }
           | ShipAction::FireAction { .. }
           | ShipAction::DeleteFireAction { .. }
@@ -839,6 +1226,10 @@ impl Entities {
           | ShipAction::OverloadDrive
           | ShipAction::OverloadPlant
           | ShipAction::Repair { .. }
+          | ShipAction::SkimFuel
+          | ShipAction::TransferFuel { .. }
+          | ShipAction::AllocatePower { .. }
+          | ShipAction::Stabilize { .. }
           | ShipAction::LeadershipCheck { .. }
           | ShipAction::ClearSensorAction
           | ShipAction::ClearEngineerAction
@@ -859,7 +1250,7 @@ impl Entities {
     // The result has to be negative.  You never get a bonus for "bad" stealth.
     if target_ship.design.stealth.is_some() {
       let delta_tl = i16::from(attack_ship.design.tl) - i16::from(target_ship.design.tl);
-      (stealth_mod(target_ship.design.stealth) + delta_tl).min(0)
+      (self.rules().stealth_mod(target_ship.design.stealth) + delta_tl).min(0)
     } else {
       0
     }
@@ -868,7 +1259,56 @@ impl Entities {
   // Quality modifiers are the level of sensors as well as skill of the crew
   fn sensor_quality_modifiers(&self, ship_name: &str) -> i16 {
     let ship = self.ships.get(ship_name).unwrap().read().unwrap();
-    SENSOR_QUALITY_MOD[ship.current_sensors as usize] + i16::from(ship.crew.get_sensors())
+    self.rules().sensor_quality_mod(ship.current_sensors as usize) + i16::from(ship.crew.get_sensors())
+  }
+
+  /// The effect for `ship_name` turning its sensors on `target` with a planet in the way, or `None` if it has a
+  /// clear line of sight.
+  fn sensors_blocked(&self, ship_name: &str, target: &str) -> Option<EffectMsg> {
+    let from = self.ships[ship_name].read().unwrap().get_position();
+    let to = self.ships[target].read().unwrap().get_position();
+    self.occluding_planet(from, to).map(|planet| EffectMsg::LineOfSightBlocked {
+      ship: ship_name.to_string(),
+      target: target.to_string(),
+      planet,
+    })
+  }
+
+  /// Drop every sensor lock whose target has gone behind a planet.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to read or write a ship.
+  fn break_blocked_sensor_locks(&mut self) -> Vec<EffectMsg> {
+    let mut names = self.ships.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    let mut effects = Vec::new();
+    for ship_name in names {
+      let (from, locks) = {
+        let ship = self.ships[&ship_name].read().unwrap();
+        (ship.get_position(), ship.sensor_locks.clone())
+      };
+      let lost = locks
+        .into_iter()
+        .filter_map(|target| {
+          let to = self.ships.get(&target)?.read().unwrap().get_position();
+          self.occluding_planet(from, to).map(|planet| (target, planet))
+        })
+        .collect::<Vec<_>>();
+      if lost.is_empty() {
+        continue;
+      }
+      self.ships[&ship_name]
+        .write()
+        .unwrap()
+        .sensor_locks
+        .retain(|target| lost.iter().all(|(blocked, _)| blocked != target));
+      effects.extend(lost.into_iter().map(|(target, planet)| EffectMsg::SensorLockLost {
+        ship: ship_name.clone(),
+        target,
+        planet,
+      }));
+    }
+    effects
   }
 
   fn sensor_lock(&mut self, ship_name: &String, target: &str, boost: i16, rng: &mut dyn RngCore) -> Vec<EffectMsg> {
@@ -881,14 +1321,27 @@ impl Entities {
       return Vec::default();
     }
 
-    // Check if sensor lock is achieved.
-    let check = i16::from(roll_dice(2, rng))
-      + self.sensor_quality_modifiers(ship_name)
-      + self.sensor_stealth_modifiers(ship_name, target)
-      + boost
-      - 8;
+    if let Some(blocked) = self.sensors_blocked(ship_name, target) {
+      return vec![blocked];
+    }
 
-    if check > 0 {
+    // Check if sensor lock is achieved.
+    let roll = roll_dice(2, rng);
+    let quality = self.sensor_quality_modifiers(ship_name);
+    let stealth = self.sensor_stealth_modifiers(ship_name, target);
+    let modifier = quality + stealth + boost;
+    let action = ShipAction::SensorLock {
+      target: target.to_string(),
+    };
+    let check = self.rules().ruleset().sensor_check(&action, roll, modifier, None);
+    let success = check.effect >= 0;
+    let record = RollRecord::new(RollPurpose::SensorLock, ship_name, 2, roll)
+      .with_modifier("sensors", quality)
+      .with_modifier("stealth", stealth)
+      .with_modifier("boost", boost)
+      .with_threshold(check.threshold);
+
+    if success {
       // If there is sensor lock, record it.
       // Scope the write lock so we don't hold it - its the only place we need to write.
       {
@@ -903,34 +1356,71 @@ impl Entities {
           .sensor_locks
           .push(target.to_string());
       }
-      vec![EffectMsg::Message {
-        content: format!("Sensor lock on {target} established by {ship_name}."),
-      }]
-    } else {
-      vec![EffectMsg::Message {
-        content: format!("Sensor lock on {target} not established by {ship_name}."),
-      }]
     }
+    vec![
+      record.into(),
+      EffectMsg::SensorResult {
+        ship: ship_name.clone(),
+        action,
+        roll,
+        opposed_roll: None,
+        modifier,
+        threshold: check.threshold,
+        success,
+      },
+    ]
   }
 
-  fn jam_comms(&self, ship_name: &String, target: &str, boost: i16, rng: &mut dyn RngCore) -> Vec<EffectMsg> {
-    let check = i16::from(roll_dice(2, rng))
-      + self.sensor_quality_modifiers(ship_name)
-      + countermeasures_mod(self.ships.get(ship_name).unwrap().read().unwrap().design.countermeasures)
-      + boost
-      - i16::from(roll_dice(2, rng))
-      - self.sensor_quality_modifiers(target)
-      - countermeasures_mod(self.ships.get(target).unwrap().read().unwrap().design.countermeasures);
-
-    if check >= 0 {
-      vec![EffectMsg::Message {
-        content: format!("{ship_name} is jamming comms on {target}."),
-      }]
-    } else {
-      vec![EffectMsg::Message {
-        content: format!("{ship_name} failed to jam comms on {target}."),
-      }]
+  fn jam_comms(&self, ship_name: &str, target: &str, boost: i16, rng: &mut dyn RngCore) -> Vec<EffectMsg> {
+    if let Some(blocked) = self.sensors_blocked(ship_name, target) {
+      return vec![blocked];
     }
+
+    let roll = roll_dice(2, rng);
+    let opposed_roll = roll_dice(2, rng);
+    let rolls = self.opposed_sensor_rolls(RollPurpose::JamComms, ship_name, target, boost, 0, roll, opposed_roll);
+    let modifier = rolls[0].total() - i32::from(roll);
+    #[allow(clippy::cast_possible_truncation)]
+    let modifier = modifier as i16;
+    let action = ShipAction::JamComms {
+      target: target.to_string(),
+    };
+    let check = self.rules().ruleset().sensor_check(&action, roll, modifier, Some(opposed_roll));
+
+    let mut effects = rolls.into_iter().map(EffectMsg::from).collect::<Vec<_>>();
+    effects.push(EffectMsg::SensorResult {
+      ship: ship_name.to_string(),
+      action,
+      roll,
+      opposed_roll: Some(opposed_roll),
+      modifier,
+      threshold: check.threshold,
+      success: check.effect >= 0,
+    });
+    effects
+  }
+
+  /// Record both sides of an opposed sensor check.  The initiator's roll carries all the named modifiers (the
+  /// target's are subtracted) so its total can be compared directly against the target's raw roll.
+  #[allow(clippy::too_many_arguments)]
+  fn opposed_sensor_rolls(
+    &self, purpose: RollPurpose, ship_name: &str, target: &str, boost: i16, stealth: i16, roll: u8, opposed_roll: u8,
+  ) -> [RollRecord; 2] {
+    let countermeasures = |name: &str| {
+      self
+        .rules()
+        .countermeasures_mod(self.ships.get(name).unwrap().read().unwrap().design.countermeasures)
+    };
+    [
+      RollRecord::new(purpose, ship_name, 2, roll)
+        .with_modifier("sensors", self.sensor_quality_modifiers(ship_name))
+        .with_modifier("countermeasures", countermeasures(ship_name))
+        .with_modifier("boost", boost)
+        .with_modifier("target sensors", -self.sensor_quality_modifiers(target))
+        .with_modifier("stealth", -stealth)
+        .with_modifier("target countermeasures", -countermeasures(target)),
+      RollRecord::new(purpose, target, 2, opposed_roll),
+    ]
   }
   fn jam_missiles(&mut self, ship_name: &String, boost: i16, rng: &mut dyn RngCore) -> Vec<EffectMsg> {
     let mut effects = Vec::<EffectMsg>::new();
@@ -943,16 +1433,45 @@ impl Entities {
       .collect::<Vec<_>>();
 
     let dice = roll_dice(2, rng);
-    let check = i16::from(dice)
-      + self.sensor_quality_modifiers(ship_name)
-      + countermeasures_mod(self.ships.get(ship_name).unwrap().read().unwrap().design.countermeasures)
-      + boost
-      - 10;
+    let modifier = self.sensor_quality_modifiers(ship_name)
+      + self
+        .rules()
+        .countermeasures_mod(self.ships.get(ship_name).unwrap().read().unwrap().design.countermeasures)
+      + boost;
+    let SensorCheck {
+      threshold,
+      effect: check,
+    } = self
+      .rules()
+      .ruleset()
+      .sensor_check(&ShipAction::JamMissiles, dice, modifier, None);
+    effects.push(
+      RollRecord::new(RollPurpose::JamMissiles, ship_name, 2, dice)
+        .with_modifier("sensors", self.sensor_quality_modifiers(ship_name))
+        .with_modifier(
+          "countermeasures",
+          self
+            .rules()
+            .countermeasures_mod(self.ships.get(ship_name).unwrap().read().unwrap().design.countermeasures),
+        )
+        .with_modifier("boost", boost)
+        .with_threshold(threshold)
+        .into(),
+    );
+    effects.push(EffectMsg::SensorResult {
+      ship: ship_name.clone(),
+      action: ShipAction::JamMissiles,
+      roll: dice,
+      opposed_roll: None,
+      modifier,
+      threshold,
+      success: check >= 0,
+    });
 
     debug!(
       "(Entity.jam_missiles) Missile jamming attempt by {ship_name} rolled {dice}, sensor_quality mod {}, countermeasures mod {} gives an effect of {check}.",
       self.sensor_quality_modifiers(ship_name),
-      countermeasures_mod(self.ships.get(ship_name).unwrap().read().unwrap().design.countermeasures),
+      self.rules().countermeasures_mod(self.ships.get(ship_name).unwrap().read().unwrap().design.countermeasures),
     );
 
     if check >= 0 {
@@ -973,19 +1492,15 @@ impl Entities {
               EffectMsg::ExhaustedMissile {
                 position: missile.get_position(),
               },
-              EffectMsg::Message {
-                content: format!("Missile {} destroyed by jamming.", missile.get_name()),
+              EffectMsg::MissileJammed {
+                ship: ship_name.clone(),
+                missile: missile.get_name().to_string(),
               },
             ]
           })
           .collect::<Vec<_>>(),
       );
       // Remove the destroyed missiles from the list of all missiles.
-    } else {
-      // If the EW check failed, just let the users know.
-      effects.push(EffectMsg::Message {
-        content: format!("Missile jamming attempt by {ship_name} failed."),
-      });
     }
     effects
   }
@@ -1001,15 +1516,26 @@ impl Entities {
       .and_then(|ships_with_locks| ships_with_locks.iter().find(|&s| *s == target));
     if valid_lock.is_some() {
       // Make an opposed check - this ship vs the one with the lock..
-      let check = i16::from(roll_dice(2, rng)) + self.sensor_quality_modifiers(ship_name)
-        + countermeasures_mod(self.ships.get(ship_name).unwrap().read().unwrap().design.countermeasures)
-        + boost
-        - self.sensor_quality_modifiers(target)
-        // In this case the steal modifiers (which will be negative or 0) are a bonus.
-        - self.sensor_stealth_modifiers(ship_name, target)
-        - countermeasures_mod(self.ships.get(target).unwrap().read().unwrap().design.countermeasures)
-        - i16::from(roll_dice(2, rng));
-      if check >= 0 {
+      let roll = roll_dice(2, rng);
+      let opposed_roll = roll_dice(2, rng);
+      // In this case the stealth modifiers (which will be negative or 0) are a bonus.
+      let rolls = self.opposed_sensor_rolls(
+        RollPurpose::BreakSensorLock,
+        ship_name,
+        target,
+        boost,
+        self.sensor_stealth_modifiers(ship_name, target),
+        roll,
+        opposed_roll,
+      );
+      #[allow(clippy::cast_possible_truncation)]
+      let modifier = (rolls[0].total() - i32::from(roll)) as i16;
+      let action = ShipAction::BreakSensorLock {
+        target: target.to_string(),
+      };
+      let check = self.rules().ruleset().sensor_check(&action, roll, modifier, Some(opposed_roll));
+      let success = check.effect >= 0;
+      if success {
         self
           .ships
           .get(target)
@@ -1018,14 +1544,18 @@ impl Entities {
           .unwrap()
           .sensor_locks
           .retain(|s| s != ship_name);
-        vec![EffectMsg::Message {
-          content: format!("{ship_name} broke {target}'s sensor lock!"),
-        }]
-      } else {
-        vec![EffectMsg::Message {
-          content: format!("{ship_name} failed to break {target}'s sensor lock."),
-        }]
       }
+      let mut effects = rolls.into_iter().map(EffectMsg::from).collect::<Vec<_>>();
+      effects.push(EffectMsg::SensorResult {
+        ship: ship_name.clone(),
+        action,
+        roll,
+        opposed_roll: Some(opposed_roll),
+        modifier,
+        threshold: check.threshold,
+        success,
+      });
+      effects
     } else {
       Vec::default()
     }
@@ -1157,8 +1687,8 @@ impl Entities {
           // Keep JamMissiles and PointDefense in all cases.
           ShipAction::PointDefenseAction { .. } | ShipAction::JamMissiles => true,
           // Engineer actions should be scrubbed each turn - they are one-time actions.
-          // LeadershipCheck is also one-shot (the captain re-queues it each
-          // turn through the Captain HUD).
+          // LeadershipCheck is also one-shot (the captain re-queues it each turn
+          // through the Captain HUD), as is a medic's Stabilize.
           // Anti-actions are consumed by `merge` and should never reach here, but
           // strip them defensively if they do.
           ShipAction::DeleteFireAction { .. }
@@ -1166,6 +1696,10 @@ impl Entities {
           | ShipAction::OverloadDrive
           | ShipAction::OverloadPlant
           | ShipAction::Repair { .. }
+          | ShipAction::SkimFuel
+          | ShipAction::TransferFuel { .. }
+          | ShipAction::AllocatePower { .. }
+          | ShipAction::Stabilize { .. }
           | ShipAction::LeadershipCheck { .. }
           | ShipAction::ClearSensorAction
           | ShipAction::ClearEngineerAction
@@ -1202,7 +1736,8 @@ impl Entities {
   /// * `rng` - The random number generator used for skill checks.
   ///
   /// # Returns
-  /// One `EffectMsg::EngineerAction` per evaluated action, in input order.
+  /// One `EffectMsg::EngineerAction` per evaluated action, in input order, each preceded by the
+  /// `EffectMsg::DiceRoll` of its check (if one was made).
   ///
   /// # Panics
   /// Panics if the lock cannot be obtained to read or write the ship.
@@ -1210,9 +1745,11 @@ impl Entities {
     &mut self, actions: &[(String, Vec<ShipAction>)], boost_map: &BoostMap, rng: &mut dyn RngCore,
   ) -> Vec<EffectMsg> {
     let mut effects = Vec::new();
-    // Ships that successfully jumped this turn — removed from the world after
-    // the loop, since iteration borrows `self.ships`.
-    let mut jumped_ships = Vec::<String>::new();
+    // Ships that jumped this turn, and any mishap on the way — removed from the
+    // world after the loop, since iteration borrows `self.ships`.
+    let mut jumped_ships = Vec::<(String, Option<JumpMishap>)>::new();
+    // The system each engineer worked alongside a damage control team this turn, with their boost.
+    let mut boosted_repairs = HashMap::<String, (ShipSystem, i16)>::new();
 
     for (ship_name, ship_actions) in actions {
       if !self.ships.contains_key(ship_name) {
@@ -1236,26 +1773,74 @@ impl Entities {
           ship.set_engineer_action_taken(true);
         }
 
+        let mut rolls = Vec::new();
         let result = match action {
-          ShipAction::OverloadDrive => self.process_overload_drive(ship_name, boost, rng),
-          ShipAction::OverloadPlant => self.process_overload_plant(ship_name, boost, rng),
-          ShipAction::Repair { system } => self.process_repair(ship_name, *system, boost, rng),
+          ShipAction::OverloadDrive => self.process_overload_drive(ship_name, boost, rng, &mut rolls),
+          ShipAction::OverloadPlant => self.process_overload_plant(ship_name, boost, rng, &mut rolls),
+          ShipAction::Repair { system } => {
+            boosted_repairs.insert(ship_name.clone(), (*system, boost));
+            self.process_repair(ship_name, *system)
+          }
+          ShipAction::SkimFuel => self.process_skim_fuel(ship_name, boost, rng, &mut rolls),
+          ShipAction::TransferFuel { target, amount } => self.process_transfer_fuel(ship_name, target, *amount),
+          ShipAction::AllocatePower { allocation } => self.process_allocate_power(ship_name, allocation),
           ShipAction::Jump => {
-            let (result, jumped) = self.process_jump(ship_name, boost, rng);
+            let (result, jumped) = self.process_jump(ship_name, boost, rng, &mut rolls);
             if jumped {
-              jumped_ships.push(ship_name.clone());
+              jumped_ships.push((ship_name.clone(), result.mishap.clone()));
             }
             result
           }
           // Caller is expected to filter, but be defensive.
           _ => continue,
         };
+        effects.append(&mut rolls);
         effects.push(EffectMsg::EngineerAction { result });
       }
     }
 
-    for ship_name in jumped_ships {
-      self.ships.remove(&ship_name);
+    for (ship_name, mishap) in jumped_ships {
+      let Some(ship) = self.remove_ship(&ship_name) else {
+        continue;
+      };
+      // A misjumped ship is lost; one that jumped for a destination goes on to it, off course if displaced.
+      let Some(mut destination) = ship
+        .get_jump_destination()
+        .and_then(|name| self.jump_destinations.iter().find(|d| d.name == name))
+        .cloned()
+      else {
+        continue;
+      };
+      let off_course = match mishap {
+        None => false,
+        Some(JumpMishap::Displaced { offset, extra_turns }) => {
+          destination.emergence_point += offset;
+          destination.transit_turns += extra_turns;
+          true
+        }
+        Some(_) => continue,
+      };
+      effects.push(EffectMsg::ShipJumped {
+        ship: ship_name,
+        destination: destination.name.clone(),
+        transit_turns: destination.transit_turns,
+        off_course,
+      });
+      self.departures.push(Departure { ship, destination });
+    }
+
+    self.run_repair_tasks(&boosted_repairs, rng, &mut effects);
+
+    // With this turn's damage, repairs and overloads all in, anything a power plant can no longer carry goes offline.
+    let mut names = self.ships.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    for ship_name in names {
+      if let Some(offline) = self.ships[&ship_name].write().unwrap().report_offline_systems() {
+        effects.push(EffectMsg::PowerShortfall {
+          ship: ship_name,
+          offline,
+        });
+      }
     }
 
     effects
@@ -1265,16 +1850,23 @@ impl Entities {
   /// whether the ship actually jumped (so the caller can remove it from
   /// `self.ships` once iteration finishes).
   ///
-  /// Mechanics: requires `can_jump == true` and enough fuel. Engineering check
-  /// is `2d6 + engineering_jump + boost − 6`. Pass = clean jump. Fail = misjump
-  /// (the ship still leaves the system but flagged as `critical_failure`).
+  /// Mechanics: requires a working jump drive, enough fuel, and enough power to
+  /// charge the drive on top of the basic systems.  A ship may jump
+  /// inside a gravity well or with a damaged jump drive or fuel system, but each
+  /// is a DM on the check of `2d6 + engineering_jump + boost` against the
+  /// ruleset's target.  Pass = clean jump.  A critical failure rolls on the
+  /// ruleset's mishap table: the ship may still leave (displaced, or lost to a
+  /// misjump) or stay with a damaged drive or stressed hull.
+  /// A ship that jumps uses a tenth of its hull in fuel.
   /// If preconditions aren't met the ship doesn't jump at all (`success: false`,
   /// no critical failure).
-  fn process_jump(&mut self, ship_name: &str, boost: i16, rng: &mut dyn RngCore) -> (EngineerActionResult, bool) {
+  fn process_jump(
+    &mut self, ship_name: &str, boost: i16, rng: &mut dyn RngCore, rolls: &mut Vec<EffectMsg>,
+  ) -> (EngineerActionResult, bool) {
     let ship = self.ships.get(ship_name).unwrap().read().unwrap();
     let action = ShipAction::Jump;
 
-    if !ship.can_jump() || ship.current_fuel <= ship.design.hull / 10 {
+    if ship.current_jump == 0 || ship.current_fuel <= ship.design.hull / 10 || !ship.can_power_jump() {
       return (
         EngineerActionResult {
           ship_name: ship_name.to_string(),
@@ -1282,48 +1874,85 @@ impl Entities {
           success: false,
           check: 0,
           target: 0,
-          message: format!("{ship_name} cannot jump: insufficient fuel or not clear of gravity wells."),
+          message: format!("{ship_name} cannot jump: insufficient fuel, not enough power or jump drive out of action."),
           critical_failure: false,
+          mishap: None,
         },
         false,
       );
     }
 
     let skill = ship.crew.get_engineering_jump();
+    let well_dm = self
+      .planets
+      .values()
+      .map(|planet| planet.read().unwrap().jump_dm(ship.get_position()))
+      .min()
+      .unwrap_or(0);
+    let drive_dm = -i16::from(ship.crit_level[ShipSystem::Jump as usize]);
+    let fuel_dm = -i16::from(ship.crit_level[ShipSystem::Fuel as usize]);
     drop(ship);
 
     let roll = roll_dice(2, rng);
+    let total = i16::from(roll) + i16::from(skill) + boost.max(0) + well_dm + drive_dm + fuel_dm;
     #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-    let total = roll + skill + boost.max(0) as u8;
-    let target: u8 = 6;
+    let total = total.clamp(0, i16::from(u8::MAX)) as u8;
+    let ruleset = self.rules().ruleset();
+    let EngineeringCheck {
+      target,
+      success,
+      critical_failure,
+    } = ruleset.engineering_check(&action, total);
+    rolls.push(
+      engineering_roll(RollPurpose::Jump, ship_name, roll, skill, boost, 0, target)
+        .with_modifier("gravity well", well_dm)
+        .with_modifier("jump drive damage", drive_dm)
+        .with_modifier("fuel system damage", fuel_dm)
+        .into(),
+    );
 
-    if total >= target {
-      (
-        EngineerActionResult {
-          ship_name: ship_name.to_string(),
-          action,
-          success: true,
-          check: total,
-          target,
-          message: format!("{ship_name} jumps successfully!"),
-          critical_failure: false,
-        },
-        true,
-      )
-    } else {
-      (
-        EngineerActionResult {
-          ship_name: ship_name.to_string(),
-          action,
-          success: false,
-          check: total,
-          target,
-          message: format!("{ship_name} misjumps! Ship is lost in jump space."),
-          critical_failure: true,
-        },
-        true,
-      )
+    let mishap =
+      critical_failure.then(|| ruleset.jump_mishap(ship_name, i16::from(total) - i16::from(target), rng, rolls));
+    let mut ship = self.ships.get(ship_name).unwrap().write().unwrap();
+    let (message, jumped) = match &mishap {
+      None if success => (format!("{ship_name} jumps successfully!"), true),
+      None => (format!("{ship_name} fails to jump."), false),
+      Some(JumpMishap::Displaced { .. }) => (format!("{ship_name} jumps, but the jump field is unstable."), true),
+      Some(JumpMishap::DriveDamage) => {
+        rolls.append(&mut apply_crit(1, ShipSystem::Jump, &mut ship, rng));
+        (format!("{ship_name} fails to jump and damages its jump drive."), false)
+      }
+      Some(JumpMishap::HullStress { damage }) => {
+        let hull = ship.get_current_hull_points();
+        ship.set_hull_points(hull.saturating_sub(*damage));
+        (
+          format!("{ship_name} fails to jump and the collapsing field does {damage} damage to the hull."),
+          false,
+        )
+      }
+      Some(JumpMishap::Misjump) => {
+        // The ship is gone for good, so a roster ship must not be written back fit to fight again.
+        ship.set_hull_points(0);
+        (format!("{ship_name} misjumps! Ship is lost in jump space."), true)
+      }
+    };
+    if jumped {
+      ship.current_fuel -= ship.design.hull / 10;
     }
+
+    (
+      EngineerActionResult {
+        ship_name: ship_name.to_string(),
+        action,
+        success,
+        check: total,
+        target,
+        message,
+        critical_failure,
+        mishap,
+      },
+      jumped,
+    )
   }
 
   /// Process an overload drive engineer action.
@@ -1337,18 +1966,24 @@ impl Entities {
   ///
   /// # Panics
   /// Panics if the lock cannot be obtained to read or write the ship.
-  fn process_overload_drive(&mut self, ship_name: &str, boost: i16, rng: &mut dyn RngCore) -> EngineerActionResult {
+  fn process_overload_drive(
+    &mut self, ship_name: &str, boost: i16, rng: &mut dyn RngCore, rolls: &mut Vec<EffectMsg>,
+  ) -> EngineerActionResult {
     let ship = self.ships.get(ship_name).unwrap();
     let skill = ship.read().unwrap().get_crew().get_engineering_maneuver();
     let roll = roll_dice(2, rng);
     // Boost is 0 or 1 (HashSet membership); cast through u8 is safe.
     #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
     let total = roll + skill + boost.max(0) as u8;
-    let target: u8 = 10;
-
     let action = ShipAction::OverloadDrive;
-
-    if total >= target {
+    let EngineeringCheck {
+      target,
+      success,
+      critical_failure,
+    } = self.rules().ruleset().engineering_check(&action, total);
+    rolls.push(engineering_roll(RollPurpose::OverloadDrive, ship_name, roll, skill, boost, 0, target).into());
+
+    if success {
       // Success - set temporary_maneuver = 1
       ship.write().unwrap().set_temporary_maneuver(1);
       EngineerActionResult {
@@ -1359,9 +1994,10 @@ impl Entities {
         target,
         message: format!("{ship_name} overloaded maneuver drive successfully! Temporary +1 maneuver."),
         critical_failure: false,
+        mishap: None,
       }
-    } else if total <= 4 {
-      // Critical failure (fail by 6+) - apply crit to maneuver drive
+    } else if critical_failure {
+      // Critical failure - apply crit to maneuver drive
       ship.write().unwrap().crit_level[ShipSystem::Maneuver as usize] += 1;
       EngineerActionResult {
         ship_name: ship_name.to_string(),
@@ -1371,6 +2007,7 @@ impl Entities {
         target,
         message: format!("{ship_name} critically failed overloading maneuver drive! Drive damaged."),
         critical_failure: true,
+        mishap: None,
       }
     } else {
       // Normal failure
@@ -1382,6 +2019,7 @@ impl Entities {
         target,
         message: format!("{ship_name} failed to overload maneuver drive."),
         critical_failure: false,
+        mishap: None,
       }
     }
   }
@@ -1397,17 +2035,23 @@ impl Entities {
   ///
   /// # Panics
   /// Panics if the lock cannot be obtained to read or write the ship.
-  fn process_overload_plant(&mut self, ship_name: &str, boost: i16, rng: &mut dyn RngCore) -> EngineerActionResult {
+  fn process_overload_plant(
+    &mut self, ship_name: &str, boost: i16, rng: &mut dyn RngCore, rolls: &mut Vec<EffectMsg>,
+  ) -> EngineerActionResult {
     let ship = self.ships.get(ship_name).unwrap();
     let skill = ship.read().unwrap().get_crew().get_engineering_power();
     let roll = roll_dice(2, rng);
     #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
     let total = roll + skill + boost.max(0) as u8;
-    let target: u8 = 10;
-
     let action = ShipAction::OverloadPlant;
-
-    if total >= target {
+    let EngineeringCheck {
+      target,
+      success,
+      critical_failure,
+    } = self.rules().ruleset().engineering_check(&action, total);
+    rolls.push(engineering_roll(RollPurpose::OverloadPlant, ship_name, roll, skill, boost, 0, target).into());
+
+    if success {
       // Success - set temporary_power_multiplier = 1.1
       ship.write().unwrap().set_temporary_power_multiplier(1.1);
       EngineerActionResult {
@@ -1418,9 +2062,10 @@ impl Entities {
         target,
         message: format!("{ship_name} overloaded power plant successfully! Temporary +10% power."),
         critical_failure: false,
+        mishap: None,
       }
-    } else if total <= 4 {
-      // Critical failure (fail by 6+) - apply crit to powerplant
+    } else if critical_failure {
+      // Critical failure - apply crit to powerplant
       ship.write().unwrap().crit_level[ShipSystem::Powerplant as usize] += 1;
       EngineerActionResult {
         ship_name: ship_name.to_string(),
@@ -1430,6 +2075,7 @@ impl Entities {
         target,
         message: format!("{ship_name} critically failed overloading power plant! Plant damaged."),
         critical_failure: true,
+        mishap: None,
       }
     } else {
       // Normal failure
@@ -1441,100 +2087,389 @@ impl Entities {
         target,
         message: format!("{ship_name} failed to overload power plant."),
         critical_failure: false,
+        mishap: None,
       }
     }
   }
 
-  /// Process a repair engineer action.
-  ///
-  /// # Arguments
-  /// * `ship_name` - The name of the ship performing the action.
-  /// * `system` - The ship system to repair.
-  /// * `rng` - The random number generator to use.
+  /// Process a repair engineer action: the engineer puts a free damage control team to work on `system`.
   ///
-  /// # Returns
-  /// The result of the repair action.
+  /// The team doesn't roll here; every team at work rolls in [`Entities::run_repair_tasks`] at the end of the
+  /// engineering phase, this one included.
   ///
   /// # Panics
-  /// Panics if the lock cannot be obtained to read or write the ship.
-  fn process_repair(
-    &mut self, ship_name: &str, system: ShipSystem, boost: i16, rng: &mut dyn RngCore,
-  ) -> EngineerActionResult {
+  /// Panics if the lock cannot be obtained to write the ship.
+  fn process_repair(&mut self, ship_name: &str, system: ShipSystem) -> EngineerActionResult {
     let action = ShipAction::Repair { system };
+    let result = |success: bool, message: String| EngineerActionResult {
+      ship_name: ship_name.to_string(),
+      action: action.clone(),
+      success,
+      check: 0,
+      target: 0,
+      message,
+      critical_failure: false,
+      mishap: None,
+    };
 
     // Cannot repair Hull
     if system == ShipSystem::Hull {
-      return EngineerActionResult {
-        ship_name: ship_name.to_string(),
-        action,
-        success: false,
-        check: 0,
-        target: 0,
-        message: format!("{ship_name} cannot repair hull damage."),
-        critical_failure: false,
-      };
+      return result(false, format!("{ship_name} cannot repair hull damage."));
     }
 
-    let ship = self.ships.get(ship_name).unwrap();
-    let mut ship_write = ship.write().unwrap();
+    let mut ship = self.ships.get(ship_name).unwrap().write().unwrap();
+    if ship.crit_level[system as usize] == 0 {
+      return result(false, format!("{ship_name}'s {system:?} is undamaged."));
+    }
+    if ship.get_repair_tasks().iter().any(|task| task.system == system) {
+      return result(true, format!("{ship_name}'s damage control team keeps working on {system:?}."));
+    }
+    match ship.start_repair(system) {
+      Ok(()) => result(true, format!("{ship_name} assigns a damage control team to {system:?}.")),
+      Err(e) => result(false, format!("{ship_name} cannot repair {system:?}: {e}.")),
+    }
+  }
 
-    // Get the appropriate skill based on system
-    let skill = match system {
-      ShipSystem::Jump => ship_write.get_crew().get_engineering_jump(),
-      ShipSystem::Powerplant => ship_write.get_crew().get_engineering_power(),
-      _ => ship_write.get_crew().get_engineering_maneuver(),
-    };
+  /// Every damage control team at work makes its repair check for the turn.
+  ///
+  /// Mechanics: `2d6 + engineering + bonus - damage` against the ruleset's target, where the engineering skill is
+  /// the one for the system (jump, power, or maneuver for the rest), the bonus grows by one with each failure and
+  /// damage is the system's crit level.  Only the team on the system the engineer worked this turn (`boosted`) gets
+  /// the engineer's boost.  Success takes one level of damage off the system; a team is stood down once its system
+  /// is fully repaired.  If losses leave the ship with fewer teams than tasks, the newest tasks are abandoned.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to write a ship.
+  fn run_repair_tasks(
+    &mut self, boosted: &HashMap<String, (ShipSystem, i16)>, rng: &mut dyn RngCore, effects: &mut Vec<EffectMsg>,
+  ) {
+    let mut names = self.ships.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    for ship_name in names {
+      let mut ship = self.ships[&ship_name].write().unwrap();
+      let crit_level = ship.crit_level;
+      ship.get_repair_tasks_mut().retain(|task| crit_level[task.system as usize] > 0);
+
+      let teams = ship.damage_control_teams();
+      if ship.get_repair_tasks().len() > teams {
+        let abandoned = ship.get_repair_tasks_mut().split_off(teams);
+        effects.push(EffectMsg::RepairsAbandoned {
+          ship: ship_name.clone(),
+          teams,
+          systems: abandoned.iter().map(|task| task.system).collect(),
+        });
+      }
 
-    // Get current crit level for the system
-    let crit_level = ship_write.crit_level[system as usize];
+      for index in 0..ship.get_repair_tasks().len() {
+        let system = ship.get_repair_tasks()[index].system;
+        let bonus = ship.get_repair_tasks()[index].bonus;
+        let boost = boosted
+          .get(&ship_name)
+          .filter(|(worked, _)| *worked == system)
+          .map_or(0, |(_, boost)| *boost);
+        let skill = match system {
+          ShipSystem::Jump => ship.get_crew().get_engineering_jump(),
+          ShipSystem::Powerplant => ship.get_crew().get_engineering_power(),
+          _ => ship.get_crew().get_engineering_maneuver(),
+        };
+        let crit_level = ship.crit_level[system as usize];
+
+        let action = ShipAction::Repair { system };
+        let roll = roll_dice(2, rng);
+        let total = u8::try_from(
+          (i32::from(roll) + i32::from(skill) + i32::from(bonus) + i32::from(boost.max(0)) - i32::from(crit_level))
+            .max(0),
+        )
+        .unwrap_or(u8::MAX);
+        let EngineeringCheck { target, success, .. } = self.rules().ruleset().engineering_check(&action, total);
+        effects.push(
+          engineering_roll(RollPurpose::Repair, &ship_name, roll, skill, boost, bonus, target)
+            .with_modifier("damage", -i32::from(crit_level))
+            .into(),
+        );
 
-    // Get repair bonus if last_repair_component matches this system
-    let repair_bonus = if ship_write.get_last_repair_component() == Some(system) {
-      ship_write.get_repair_bonus()
-    } else {
-      ship_write.set_repair_bonus(0);
-      0
+        let message = if success {
+          ship.crit_level[system as usize] -= 1;
+          ship.get_repair_tasks_mut()[index].bonus = 0;
+          if ship.crit_level[system as usize] == 0 {
+            format!("{ship_name} has fully repaired {system:?}.")
+          } else {
+            format!("{ship_name} successfully repaired {system:?}.")
+          }
+        } else {
+          ship.get_repair_tasks_mut()[index].bonus = bonus.saturating_add(1);
+          format!("{ship_name} failed to repair {system:?}.")
+        };
+        effects.push(EffectMsg::EngineerAction {
+          result: EngineerActionResult {
+            ship_name: ship_name.clone(),
+            action,
+            success,
+            check: total,
+            target,
+            message,
+            critical_failure: false,
+            mishap: None,
+          },
+        });
+      }
+      // Teams whose system is now fully repaired stand down.
+      let crit_level = ship.crit_level;
+      ship.get_repair_tasks_mut().retain(|task| crit_level[task.system as usize] > 0);
+    }
+  }
+
+  /// Process a fuel skimming engineer action.
+  ///
+  /// Mechanics: the ship must be in a skimming orbit of a gas giant and have room in its tanks.  The pilot holds
+  /// the ship in the upper atmosphere while the engineer runs the scoops: `2d6 + pilot + boost` against the
+  /// ruleset's target.  Whatever the outcome the ship is committed to its orbit, so until next turn it can't dodge
+  /// and is easier to hit.  Success takes on a quarter of the ship's fuel capacity; a critical failure means the
+  /// ship is buffeted by turbulence for 1D hull damage.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to read or write the ship or a planet.
+  fn process_skim_fuel(
+    &mut self, ship_name: &str, boost: i16, rng: &mut dyn RngCore, rolls: &mut Vec<EffectMsg>,
+  ) -> EngineerActionResult {
+    let action = ShipAction::SkimFuel;
+    let mut ship = self.ships.get(ship_name).unwrap().write().unwrap();
+
+    let gas_giant = self.planets.values().find_map(|planet| {
+      let planet = planet.read().unwrap();
+      (planet.is_gas_giant() && planet.in_skimming_orbit(ship.get_position(), ship.get_velocity()))
+        .then(|| planet.get_name().to_string())
+    });
+    let failure = |message: String| EngineerActionResult {
+      ship_name: ship_name.to_string(),
+      action: action.clone(),
+      success: false,
+      check: 0,
+      target: 0,
+      message,
+      critical_failure: false,
+      mishap: None,
+    };
+    let Some(gas_giant) = gas_giant else {
+      return failure(format!("{ship_name} cannot skim fuel: not in a skimming orbit of a gas giant."));
     };
+    if ship.current_fuel >= ship.design.fuel {
+      return failure(format!("{ship_name} cannot skim fuel: tanks are already full."));
+    }
 
+    let skill = ship.get_crew().get_pilot();
     let roll = roll_dice(2, rng);
-    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-    let total = u8::saturating_sub(roll + skill + repair_bonus + boost.max(0) as u8, crit_level);
-    let target: u8 = 8;
+    let total = u8::try_from(i32::from(roll) + i32::from(skill) + i32::from(boost.max(0))).unwrap_or(0);
+    let EngineeringCheck {
+      target,
+      success,
+      critical_failure,
+    } = self.rules().ruleset().engineering_check(&action, total);
+    rolls.push(
+      RollRecord::new(RollPurpose::SkimFuel, ship_name, 2, roll)
+        .with_modifier("pilot", skill)
+        .with_modifier("boost", boost.max(0))
+        .with_threshold(target)
+        .into(),
+    );
+    ship.set_skimming(true);
+
+    let message = if success {
+      let fuel = ship
+        .design
+        .fuel
+        .div_ceil(SKIM_TURNS_TO_FILL)
+        .min(ship.design.fuel - ship.current_fuel);
+      ship.current_fuel += fuel;
+      format!("{ship_name} skims {fuel} tons of fuel from {gas_giant}.")
+    } else if critical_failure {
+      let damage = roll_dice(1, rng);
+      rolls.push(RollRecord::new(RollPurpose::Damage, ship_name, 1, damage).into());
+      let hull = ship.get_current_hull_points();
+      ship.set_hull_points(hull.saturating_sub(u32::from(damage)));
+      format!("{ship_name} is caught by turbulence skimming {gas_giant} and takes {damage} hull damage.")
+    } else {
+      format!("{ship_name} fails to skim any fuel from {gas_giant}.")
+    };
 
-    if total >= target {
-      // Success - reduce crit level by 1
-      if ship_write.crit_level[system as usize] > 0 {
-        ship_write.crit_level[system as usize] -= 1;
-      }
-      ship_write.set_repair_bonus(0);
-      ship_write.set_last_repair_component(Some(system));
-      EngineerActionResult {
-        ship_name: ship_name.to_string(),
-        action,
-        success: true,
-        check: total,
-        target,
-        message: format!("{ship_name} successfully repaired {system:?}."),
-        critical_failure: false,
+    EngineerActionResult {
+      ship_name: ship_name.to_string(),
+      action,
+      success,
+      check: total,
+      target,
+      message,
+      critical_failure,
+      mishap: None,
+    }
+  }
+
+  /// Process a fuel transfer engineer action: pump `amount` tons of fuel (or as much as will fit if `None`) from
+  /// `ship_name` to `target`.  The ships must be alongside each other with matched velocity.  No check is needed;
+  /// the transfer is limited by the fuel the ship has and the room in the target's tanks.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to write either ship.
+  fn process_transfer_fuel(&mut self, ship_name: &str, target: &str, amount: Option<u32>) -> EngineerActionResult {
+    let action = ShipAction::TransferFuel {
+      target: target.to_string(),
+      amount,
+    };
+    let result = |success: bool, message: String| EngineerActionResult {
+      ship_name: ship_name.to_string(),
+      action: action.clone(),
+      success,
+      check: 0,
+      target: 0,
+      message,
+      critical_failure: false,
+      mishap: None,
+    };
+
+    let Some(target_ship) = self.ships.get(target).filter(|_| target != ship_name) else {
+      return result(
+        false,
+        format!("{ship_name} cannot transfer fuel: no ship {target} to transfer to."),
+      );
+    };
+    let mut ship = self.ships.get(ship_name).unwrap().write().unwrap();
+    let mut target_ship = target_ship.write().unwrap();
+
+    let distance = (target_ship.get_position() - ship.get_position()).magnitude();
+    let speed = (target_ship.get_velocity() - ship.get_velocity()).magnitude();
+    if distance > FUEL_TRANSFER_RANGE || speed > FUEL_TRANSFER_MAX_SPEED {
+      return result(
+        false,
+        format!("{ship_name} cannot transfer fuel: {target} is not alongside with matched velocity."),
+      );
+    }
+
+    let room = target_ship.design.fuel.saturating_sub(target_ship.current_fuel);
+    let fuel = amount.unwrap_or(u32::MAX).min(ship.current_fuel).min(room);
+    if fuel == 0 {
+      return result(
+        false,
+        format!("{ship_name} has no fuel to transfer or {target}'s tanks are full."),
+      );
+    }
+    ship.current_fuel -= fuel;
+    target_ship.current_fuel += fuel;
+    result(true, format!("{ship_name} transfers {fuel} tons of fuel to {target}."))
+  }
+
+  /// Process a power allocation engineer action: from now on the power plant's output goes to the systems in
+  /// `allocation`.  No check is needed, but anything the plant can't carry is reported (and left offline).
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to write the ship.
+  fn process_allocate_power(&mut self, ship_name: &str, allocation: &PowerAllocation) -> EngineerActionResult {
+    let mut ship = self.ships.get(ship_name).unwrap().write().unwrap();
+    ship.set_power_allocation(Some(allocation.clone()));
+    let message = format!(
+      "{ship_name} allocates {} of {} power.",
+      ship.power_draw(&ship.powered_systems()),
+      ship.get_effective_power()
+    );
+    EngineerActionResult {
+      ship_name: ship_name.to_string(),
+      action: ShipAction::AllocatePower {
+        allocation: allocation.clone(),
+      },
+      success: true,
+      check: 0,
+      target: 0,
+      message,
+      critical_failure: false,
+      mishap: None,
+    }
+  }
+
+  /// Evaluate all queued medical actions at end-of-turn, then let every ship's dying crew lose ground.
+  ///
+  /// Stabilizing a dying crew member is a 2D + medic check against 8; on a success they no longer lose ground.  After
+  /// the medics have worked, the dying on every ship each lose a turn, and those out of time die.
+  ///
+  /// # Returns
+  /// The roll and the outcome of each medical action, then the death of each crew member who bled out.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to write a ship.
+  pub fn medical_actions(&mut self, actions: &[(String, Vec<ShipAction>)], rng: &mut dyn RngCore) -> Vec<EffectMsg> {
+    let mut effects = Vec::new();
+    for (ship_name, ship_actions) in actions {
+      let Some(ship) = self.ships.get(ship_name) else {
+        warn!("(medical_actions) Cannot find ship {ship_name} for medical action.");
+        continue;
+      };
+      let mut ship = ship.write().unwrap();
+      for action in ship_actions {
+        let ShipAction::Stabilize { member } = action else {
+          continue;
+        };
+        let dying = ship
+          .get_crew()
+          .get_members()
+          .iter()
+          .any(|m| m.name == *member && matches!(m.health, Health::Dying { .. }));
+        if !dying {
+          effects.push(EffectMsg::message(format!(
+            "{ship_name} has no dying crew member {member} to stabilize."
+          )));
+          continue;
+        }
+        let skill = ship.get_crew().get_medic();
+        let roll = roll_dice(2, rng);
+        effects.push(
+          RollRecord::new(RollPurpose::Medical, ship_name, 2, roll)
+            .with_modifier("medic", skill)
+            .with_threshold(STANDARD_ROLL_THRESHOLD)
+            .into(),
+        );
+        if i32::from(roll) + i32::from(skill) >= STANDARD_ROLL_THRESHOLD {
+          if let Err(msg) = ship.get_crew_mut().stabilize(member) {
+            error!("(medical_actions) Unable to stabilize {member} on {ship_name}: {msg}");
+          }
+        }
+        // Whether or not the medic succeeded, report how the patient now stands.
+        if let Some(patient) = ship.get_crew().get_members().iter().find(|m| m.name == *member) {
+          effects.push(EffectMsg::CrewInjury {
+            ship: ship_name.clone(),
+            member: member.clone(),
+            health: patient.health,
+          });
+        }
       }
-    } else {
-      // Failure - increment repair bonus
-      let current_bonus = ship_write.get_repair_bonus();
-      ship_write.set_repair_bonus(current_bonus.saturating_add(1));
-      ship_write.set_last_repair_component(Some(system));
-      EngineerActionResult {
-        ship_name: ship_name.to_string(),
-        action,
-        success: false,
-        check: total,
-        target,
-        message: format!("{ship_name} failed to repair {system:?}."),
-        critical_failure: false,
+    }
+
+    let mut names = self.ships.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    for ship_name in names {
+      let mut ship = self.ships[&ship_name].write().unwrap();
+      let dead = ship.get_crew_mut().bleed_out();
+      ship.current_crew = ship.current_crew.saturating_sub(u32::try_from(dead.len()).unwrap_or(u32::MAX));
+      for member in dead {
+        effects.push(EffectMsg::CrewInjury {
+          ship: ship_name.clone(),
+          member,
+          health: Health::Dead,
+        });
       }
     }
+    effects
   }
 }
 
+/// The audit record of an engineering check.  Only a positive boost counts, as in the checks themselves.
+fn engineering_roll(
+  purpose: RollPurpose, ship_name: &str, roll: u8, skill: u8, boost: i16, repair_bonus: u8, target: u8,
+) -> RollRecord {
+  RollRecord::new(purpose, ship_name, 2, roll)
+    .with_modifier("engineering", skill)
+    .with_modifier("boost", boost.max(0))
+    .with_modifier("repeated repair", repair_bonus)
+    .with_threshold(target)
+}
+
 use std::fmt::{Display, Error, Formatter};
 impl std::fmt::Debug for Entities {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), Error> {
@@ -1579,6 +2514,12 @@ impl Serialize for Entities {
     #[derive(Serialize)]
     struct Entities<'a> {
       metadata: &'a MetaData,
+      #[serde(skip_serializing_if = "Option::is_none")]
+      rules: &'a Option<Box<RulesTables>>,
+      #[serde(skip_serializing_if = "<[_]>::is_empty")]
+      jump_destinations: &'a [JumpDestination],
+      #[serde(skip_serializing_if = "<[_]>::is_empty")]
+      roster: &'a [RosterPlacement],
       filename: &'a str,
       ships: Vec<Ship>,
       missiles: Vec<Missile>,
@@ -1588,6 +2529,9 @@ impl Serialize for Entities {
 
     let mut entities = Entities {
       metadata: &self.metadata,
+      rules: &self.rules,
+      jump_destinations: &self.jump_destinations,
+      roster: &self.roster,
       filename: &self.filename,
       ships: self.ships.values().map(|s| s.read().unwrap().clone()).collect::<Vec<Ship>>(),
       missiles: self
@@ -1634,6 +2578,12 @@ impl<'de> Deserialize<'de> for Entities {
       actions: ShipActionList,
       #[serde(default)]
       metadata: MetaData,
+      #[serde(default)]
+      rules: Option<Box<RulesTables>>,
+      #[serde(default)]
+      jump_destinations: Vec<JumpDestination>,
+      #[serde(default)]
+      roster: Vec<RosterPlacement>,
     }
 
     let guts = Entities::deserialize(deserializer)?;
@@ -1656,6 +2606,11 @@ impl<'de> Deserialize<'de> for Entities {
       next_missile_id: 0,
       actions: guts.actions,
       metadata: guts.metadata,
+      rules: guts.rules,
+      jump_destinations: guts.jump_destinations,
+      roster: guts.roster,
+      departures: vec![],
+      roster_departures: vec![],
       // Scenario files don't carry their own basename; load_from_file populates it.
       filename: String::new(),
     })
@@ -1665,12 +2620,15 @@ impl<'de> Deserialize<'de> for Entities {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::crew::{Crew, Skills};
+  use crate::combat::AttackModifiers;
+  use crate::crew::{Crew, CrewMember, Skills, Station, UNTRAINED_DM};
   use crate::debug;
+  use crate::ruleset::Edition;
   use crate::ship::{
-    config_test_ship_templates, get_ship_template, get_ship_templates_snapshot, replace_ship_templates,
+    config_test_ship_templates, get_ship_template, get_ship_templates_snapshot, replace_ship_templates, Range,
     ShipDesignTemplate, ShipTemplateTable,
   };
+  use crate::storage::LocalStorage;
   use assert_json_diff::assert_json_eq;
   use cgmath::assert_relative_eq;
   use cgmath::{Vector2, Zero};
@@ -1801,13 +2759,13 @@ mod tests {
     let acceleration2 = Vec3::new(2.0, 1.0, -2.0) * G;
     let acceleration3 = Vec3::new(-1.0, -1.0, -0.0) * G;
     entities
-      .set_flight_plan("Ship1", &FlightPlan((acceleration1, 50000).into(), None))
+      .set_flight_plan("Ship1", &FlightPlan::new((acceleration1, 50000).into(), None))
       .unwrap();
     entities
-      .set_flight_plan("Ship2", &FlightPlan((acceleration2, 50000).into(), None))
+      .set_flight_plan("Ship2", &FlightPlan::new((acceleration2, 50000).into(), None))
       .unwrap();
     entities
-      .set_flight_plan("Ship3", &FlightPlan((acceleration3, 50000).into(), None))
+      .set_flight_plan("Ship3", &FlightPlan::new((acceleration3, 50000).into(), None))
       .unwrap();
 
     // Update the entities a few times
@@ -1839,6 +2797,111 @@ mod tests {
     );
   }
 
+  #[test_log::test]
+  fn test_project_trajectories() -> Result<(), String> {
+    let mut entities = Entities::new();
+    let design = Arc::new(ShipDesignTemplate::default());
+    let velocity = Vec3::new(1000.0, 0.0, 0.0);
+    entities.add_ship(String::from("Ship1"), Vec3::zero(), Vec3::zero(), &design, None);
+    entities.add_ship(String::from("Ship2"), Vec3::new(1e6, 0.0, 0.0), velocity, &design, None);
+    entities.add_planet(
+      String::from("Planet1"),
+      Vec3::new(0.0, 5e7, 0.0),
+      String::from("blue"),
+      None,
+      6.371e6,
+      6e24,
+      vec![],
+    )?;
+    entities.launch_missile("Ship1", "Ship2")?;
+    let missile_name = entities.missiles.keys().next().unwrap().clone();
+
+    let projection = entities.deep_copy().project_trajectories(5);
+    assert_eq!(projection.turns, 5);
+
+    // Every ship and planet gets its current position plus one per turn.
+    let ship2 = &projection.ships["Ship2"];
+    assert_eq!(ship2.len(), 6);
+    for (turn, pos) in ship2.iter().enumerate() {
+      #[allow(clippy::cast_precision_loss)]
+      let expected = Vec3::new(1e6, 0.0, 0.0) + velocity * DELTA_TIME_F64 * turn as f64;
+      assert_relative_eq!(*pos, expected, epsilon = 1e-6);
+    }
+    assert_eq!(projection.planets["Planet1"].len(), 6);
+
+    // The missile stops at its projected intercept.
+    assert_eq!(projection.intercepts.len(), 1);
+    let intercept = &projection.intercepts[0];
+    assert_eq!(intercept.missile, missile_name);
+    assert_eq!(intercept.target, "Ship2");
+    assert_eq!(projection.missiles[&missile_name].len(), usize::from(intercept.turn) + 1);
+    assert_eq!(projection.missiles[&missile_name].last(), Some(&intercept.position));
+
+    // The scenario itself hasn't moved.
+    assert_eq!(entities.ships["Ship2"].read().unwrap().get_position(), Vec3::new(1e6, 0.0, 0.0));
+    assert_eq!(entities.missiles.len(), 1);
+    Ok(())
+  }
+
+  #[test_log::test]
+  fn test_closest_approach() {
+    let mut entities = Entities::new();
+    let design = Arc::new(ShipDesignTemplate::default());
+    entities.add_ship(String::from("Ship1"), Vec3::zero(), Vec3::zero(), &design, None);
+    // Flies past Ship1 at 1000km, directly abeam at the end of turn 8.
+    entities.add_ship(
+      String::from("Ship2"),
+      Vec3::new(2.88e7, 1e6, 0.0),
+      Vec3::new(-1e4, 0.0, 0.0),
+      &design,
+      None,
+    );
+
+    let approach = entities.deep_copy().closest_approach("Ship1", "Ship2", 10).unwrap();
+    assert_relative_eq!(approach.time, 8.0 * DELTA_TIME_F64, epsilon = 1e-6);
+    assert_relative_eq!(approach.distance, 1e6, epsilon = 1e-3);
+
+    let bands = approach
+      .windows
+      .iter()
+      .map(|window| (window.range, window.from_turn, window.to_turn))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      bands,
+      vec![
+        (Range::VeryLong, 0, 1),
+        (Range::Long, 2, 5),
+        (Range::Medium, 6, 7),
+        (Range::Short, 8, 8),
+        (Range::Medium, 9, 10)
+      ]
+    );
+    // Only sand reaches at very long range; pulse lasers join at long range.
+    assert!(approach.windows[0]
+      .weapons
+      .iter()
+      .all(|engaging| engaging.weapon.kind == WeaponType::Sand));
+    assert_eq!(approach.windows[0].weapons.len(), 4);
+    assert_eq!(approach.windows[1].weapons.len(), 8);
+    assert!(approach.windows[1].weapons.iter().any(|engaging| engaging.ship == "Ship2"));
+
+    // A closest approach mid-turn is found between turn ends.
+    let mut entities = Entities::new();
+    entities.add_ship(String::from("Ship1"), Vec3::zero(), Vec3::zero(), &design, None);
+    entities.add_ship(
+      String::from("Ship2"),
+      Vec3::new(1.8e6, 5e5, 0.0),
+      Vec3::new(-1e4, 0.0, 0.0),
+      &design,
+      None,
+    );
+    let approach = entities.deep_copy().closest_approach("Ship1", "Ship2", 2).unwrap();
+    assert_relative_eq!(approach.time, 180.0, epsilon = 1e-6);
+    assert_relative_eq!(approach.distance, 5e5, epsilon = 1e-3);
+
+    assert!(entities.deep_copy().closest_approach("Ship1", "Nobody", 2).is_err());
+  }
+
   #[test_log::test]
   fn test_entities_validate() -> Result<(), String> {
     let mut entities = Entities::new();
@@ -2292,10 +3355,62 @@ mod tests {
     let _ = pretty_env_logger::try_init();
     config_test_ship_templates().await;
 
-    let entities = Entities::load_from_file("./tests/scenarios/test-scenario.json").await.unwrap();
+    let entities = Entities::load_from_file(&LocalStorage, "./tests/scenarios/test-scenario.json")
+      .await
+      .unwrap();
     assert!(entities.validate(), "Scenario file failed validation");
   }
 
+  #[test_log::test(tokio::test)]
+  async fn test_scenario_rules() {
+    config_test_ship_templates().await;
+
+    // Without rules of its own a scenario plays by the defaults and doesn't write them out.
+    let scenario = json!({
+      "ships": [
+        {"name": "ship1", "position": [0, 0, 0], "velocity": [0, 0, 0], "plan": [[[0, 0, 0], 50000]], "design": "Buccaneer"},
+        {"name": "ship2", "position": [2e6, 0, 0], "velocity": [0, 0, 0], "plan": [[[0, 0, 0], 50000]], "design": "Buccaneer"}
+      ]
+    });
+    let entities: Entities = serde_json::from_value(scenario.clone()).unwrap();
+    assert_eq!(entities.rules, None);
+    assert_eq!(entities.rules(), &RulesTables::builtin());
+    assert!(serde_json::to_value(&entities).unwrap().get("rules").is_none());
+
+    // House rules stretch short range to 5,000km; only the tables named change.
+    let mut scenario = scenario;
+    scenario["rules"] = json!({"range_bands": [5_000_000, 10_000_000, 25_000_000, 50_000_000]});
+    let entities: Entities = serde_json::from_value(scenario).unwrap();
+    assert_eq!(entities.rules().range_band(2_000_000), Range::Short);
+    assert_eq!(entities.rules().damage_weapon_dice, RulesTables::builtin().damage_weapon_dice);
+
+    // The rules survive the copy a server makes of its scenario, and saving it.
+    let copy = entities.deep_copy();
+    assert_eq!(copy.rules, entities.rules);
+    let saved = serde_json::to_value(&copy).unwrap();
+    assert_eq!(
+      saved["rules"]["range_bands"],
+      json!([5_000_000, 10_000_000, 25_000_000, 50_000_000])
+    );
+
+    // And combat uses them: at 2,000km a beam laser is now at short range.
+    let ship1 = copy.ships.get("ship1").unwrap().read().unwrap();
+    let ship2 = copy.ships.get("ship2").unwrap().read().unwrap();
+    let house = crate::combat::attack_odds(&ship1, 0, &ship2, None, &BoostMap::default(), copy.rules());
+    let standard = crate::combat::attack_odds(&ship1, 0, &ship2, None, &BoostMap::default(), &RulesTables::builtin());
+    assert_eq!(house.modifiers.range, Range::Short);
+    assert_eq!(standard.modifiers.range, Range::Medium);
+    assert!(house.hit_probability > standard.hit_probability);
+
+    // A scenario can also pick its edition of the rules; the tables still come from the defaults.
+    let mut scenario = saved;
+    scenario["rules"] = json!({"edition": "HighGuard"});
+    let entities: Entities = serde_json::from_value(scenario).unwrap();
+    assert_eq!(entities.rules().edition, Edition::HighGuard);
+    assert_eq!(copy.rules().edition, Edition::Core);
+    assert_eq!(entities.rules().range_bands, RulesTables::builtin().range_bands);
+  }
+
   #[test_log::test(tokio::test)]
   async fn test_load_from_file_uses_provided_ship_template_snapshot() {
     config_test_ship_templates().await;
@@ -2353,9 +3468,10 @@ mod tests {
     });
     fs::write(&scenario_path, serde_json::to_vec(&scenario).unwrap()).unwrap();
 
-    let entities = Entities::load_from_file_with_ship_templates(scenario_path.to_str().unwrap(), scenario_templates)
-      .await
-      .unwrap();
+    let entities =
+      Entities::load_from_file_with_ship_templates(&LocalStorage, scenario_path.to_str().unwrap(), scenario_templates)
+        .await
+        .unwrap();
 
     let _ = fs::remove_file(&scenario_path);
 
@@ -2664,9 +3780,9 @@ mod tests {
     // Verify that the flight plan was set correctly
     if let Some(ship) = entities.ships.get("TestShip") {
       let ship_plan = &ship.read().unwrap().plan;
-      assert_eq!(ship_plan.0 .0, acceleration, "Acceleration should match");
-      assert_eq!(ship_plan.0 .1, duration, "Duration should match");
-      assert!(ship_plan.1.is_none(), "Second acceleration should be None");
+      assert_eq!(ship_plan.first().0, acceleration, "Acceleration should match");
+      assert_eq!(ship_plan.first().1, duration, "Duration should match");
+      assert!(ship_plan.second().is_none(), "Second acceleration should be None");
     } else {
       panic!("TestShip not found in entities");
     }
@@ -2717,10 +3833,9 @@ mod tests {
     // With a roll of 6 and sensor skill of 4, check should be positive
     // resulting in successful jamming
     assert_eq!(entities.missiles.len(), 1); // Only one missile should be left
-    assert_eq!(effects.len(), 14); // One message for jamming success and one for missile destruction
-    assert!(effects.iter().any(|e| matches!(e,
-        EffectMsg::Message { content } if content.contains("destroyed by jamming")
-    )));
+                                            // The jamming roll and check, then an exhaustion and a message for each missile destroyed
+    assert_eq!(effects.len(), 16);
+    assert!(effects.iter().any(|e| matches!(e, EffectMsg::MissileJammed { .. })));
 
     let mut entities = Entities::default();
     let mut rng = StepRng::new(1, 0); // Will always roll 1 for predictable results
@@ -2747,9 +3862,14 @@ mod tests {
     // With a roll of 1 and sensor skill of 4, check should be negative
     // resulting in failed jamming
     assert_eq!(entities.missiles.len(), 2); // No missiles should be destroyed due to check result
-    assert_eq!(effects.len(), 1); // Only one message for jamming failure
-    assert!(effects.iter().any(|e| matches!(e,
-        EffectMsg::Message { content } if content.contains("jamming attempt by defender failed")
+    assert_eq!(effects.len(), 2); // Only the roll and a message for jamming failure
+    assert!(effects.iter().any(|e| matches!(
+      e,
+      EffectMsg::SensorResult {
+        action: ShipAction::JamMissiles,
+        success: false,
+        ..
+      }
     )));
   }
 
@@ -2773,8 +3893,13 @@ mod tests {
 
     let boost_map = BoostMap::default();
     let effects = entities.sensor_actions(&actions, &boost_map, &mut rng);
-    assert!(effects.iter().any(|e| matches!(e,
-        EffectMsg::Message { content } if content.contains("not established by")
+    assert!(effects.iter().any(|e| matches!(
+      e,
+      EffectMsg::SensorResult {
+        action: ShipAction::SensorLock { .. },
+        success: false,
+        ..
+      }
     )));
     let mut rng = StepRng::new(5, 0); // Will always roll 6 for predictable results
 
@@ -2782,8 +3907,13 @@ mod tests {
     let effects = entities.sensor_actions(&actions, &boost_map, &mut rng);
 
     // With a roll of 6 and sensor skill of 4, the lock should be established
-    assert!(effects.iter().any(|e| matches!(e,
-        EffectMsg::Message { content } if content.contains("lock on target established by")
+    assert!(effects.iter().any(|e| matches!(
+      e,
+      EffectMsg::SensorResult {
+        action: ShipAction::SensorLock { .. },
+        success: true,
+        ..
+      }
     )));
 
     let attacker = entities.ships.get("attacker").unwrap().read().unwrap();
@@ -2818,8 +3948,13 @@ mod tests {
     let effects = entities.sensor_actions(&actions, &boost_map, &mut rng);
 
     // Check that the lock was broken
-    assert!(effects.iter().any(|e| matches!(e,
-        EffectMsg::Message { content } if content.contains("broke")
+    assert!(effects.iter().any(|e| matches!(
+      e,
+      EffectMsg::SensorResult {
+        action: ShipAction::BreakSensorLock { .. },
+        success: true,
+        ..
+      }
     )));
 
     {
@@ -2840,8 +3975,13 @@ mod tests {
     let effects = entities.sensor_actions(&actions, &boost_map, &mut rng);
 
     // Check that the lock was not broken
-    assert!(effects.iter().any(|e| matches!(e,
-        EffectMsg::Message { content } if content.contains("failed to break")
+    assert!(effects.iter().any(|e| matches!(
+      e,
+      EffectMsg::SensorResult {
+        action: ShipAction::BreakSensorLock { .. },
+        success: false,
+        ..
+      }
     )));
   }
 
@@ -2867,16 +4007,26 @@ mod tests {
     let boost_map = BoostMap::default();
     let effects = entities.sensor_actions(&actions, &boost_map, &mut rng);
 
-    assert!(effects.iter().any(|e| matches!(e,
-        EffectMsg::Message { content } if content.contains("failed to jam comms on")
+    assert!(effects.iter().any(|e| matches!(
+      e,
+      EffectMsg::SensorResult {
+        action: ShipAction::JamComms { .. },
+        success: false,
+        ..
+      }
     )));
 
     let mut rng = StepRng::new(4, 1); // Going past 6 on second two rolls ensures jammer wins
     let boost_map = BoostMap::default();
     let effects = entities.sensor_actions(&actions, &boost_map, &mut rng);
     // With high sensor skill and good roll, jamming should succeed
-    assert!(effects.iter().any(|e| matches!(e,
-        EffectMsg::Message { content } if content.contains("is jamming comms on")
+    assert!(effects.iter().any(|e| matches!(
+      e,
+      EffectMsg::SensorResult {
+        action: ShipAction::JamComms { .. },
+        success: true,
+        ..
+      }
     )));
   }
 
@@ -2885,7 +4035,7 @@ mod tests {
     target_design: &str,
   ) -> Entities {
     // Load ship templates
-    let templates = crate::ship::load_ship_templates_from_dir(crate::ship::DEFAULT_SHIP_TEMPLATES_DIR)
+    let templates = crate::ship::load_ship_templates_from_dir(&LocalStorage, crate::ship::DEFAULT_SHIP_TEMPLATES_DIR)
       .await
       .expect("Unable to load ship templates directory.");
 
@@ -2965,6 +4115,585 @@ mod tests {
     }
   }
 
+  #[test_log::test(tokio::test)]
+  async fn test_jump_in_gravity_well() {
+    config_test_ship_templates().await;
+    let design = get_ship_template("Gazelle").unwrap();
+
+    let mut misjumps = 0;
+    for seed in 0..20 {
+      let mut entities = Entities::new();
+      entities
+        .add_planet(
+          "Earth".to_string(),
+          Vec3::zero(),
+          "blue".to_string(),
+          None,
+          6.371e6,
+          5.972e24,
+          vec![],
+        )
+        .unwrap();
+      // Just above the surface, inside the 1G radius, with damaged drive and fuel systems.
+      entities.add_ship("ship1".to_string(), Vec3::new(6.373e6, 0.0, 0.0), Vec3::zero(), &design, None);
+      {
+        let mut ship = entities.ships["ship1"].write().unwrap();
+        ship.crit_level[ShipSystem::Jump as usize] = 1;
+        ship.crit_level[ShipSystem::Fuel as usize] = 1;
+        ship.set_roster_id(Some("ship1".to_string()));
+      }
+
+      let mut rng = SmallRng::seed_from_u64(seed);
+      let effects =
+        entities.engineer_actions(&[("ship1".to_string(), vec![ShipAction::Jump])], &BoostMap::default(), &mut rng);
+
+      let jump_roll = effects
+        .iter()
+        .find_map(|effect| match effect {
+          EffectMsg::DiceRoll { record } if record.purpose == RollPurpose::Jump => Some(record),
+          _ => None,
+        })
+        .unwrap();
+      for dm in [
+        ("gravity well", -5),
+        ("jump drive damage", -1),
+        ("fuel system damage", -1),
+      ] {
+        assert!(jump_roll.modifiers.contains(&(dm.0.to_string(), dm.1)), "Missing {dm:?}");
+      }
+
+      // Nothing rolled on 2D makes a jump that deep in the well with that damage, so it is always a mishap.
+      let result = effects
+        .iter()
+        .find_map(|effect| match effect {
+          EffectMsg::EngineerAction { result } => Some(result),
+          _ => None,
+        })
+        .unwrap();
+      assert!(!result.success && result.critical_failure);
+      match result.mishap.as_ref().unwrap() {
+        JumpMishap::Displaced { .. } => {
+          assert!(!entities.ships.contains_key("ship1"));
+          assert_eq!(entities.take_roster_departures()[0].current_hull, design.hull);
+        }
+        JumpMishap::Misjump => {
+          // A roster ship lost in jump space goes back to the roster as a wreck.
+          misjumps += 1;
+          assert!(!entities.ships.contains_key("ship1"));
+          assert_eq!(entities.take_roster_departures()[0].current_hull, 0);
+        }
+        JumpMishap::DriveDamage => {
+          let ship = entities.ships["ship1"].read().unwrap();
+          assert_eq!(ship.crit_level[ShipSystem::Jump as usize], 2);
+          assert_eq!(ship.current_jump, 0);
+        }
+        JumpMishap::HullStress { damage } => {
+          let ship = entities.ships["ship1"].read().unwrap();
+          assert_eq!(ship.get_current_hull_points(), design.hull - damage);
+          assert_eq!(ship.current_fuel, design.fuel);
+        }
+      }
+    }
+    assert!(misjumps > 0);
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_skim_fuel() {
+    config_test_ship_templates().await;
+    let design = get_ship_template("Buccaneer").unwrap();
+    let (radius, mass): (f64, f64) = (6.9911e7, 1.898e27);
+    let altitude = radius * 1.05;
+    let orbital_speed = (6.673e-11 * mass / altitude).sqrt();
+
+    for seed in 0..20 {
+      let mut entities = Entities::new();
+      entities
+        .add_planet(
+          "Jupiter".to_string(),
+          Vec3::zero(),
+          "tan".to_string(),
+          None,
+          radius,
+          mass,
+          vec![],
+        )
+        .unwrap();
+      entities.add_ship(
+        "skimmer".to_string(),
+        Vec3::new(altitude, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, orbital_speed),
+        &design,
+        None,
+      );
+      // Well above the atmosphere, so out of reach of it.
+      entities.add_ship(
+        "flyby".to_string(),
+        Vec3::new(radius * 2.0, 0.0, 0.0),
+        Vec3::zero(),
+        &design,
+        None,
+      );
+      for ship in entities.ships.values() {
+        ship.write().unwrap().current_fuel = 0;
+      }
+
+      let mut rng = SmallRng::seed_from_u64(seed);
+      let effects = entities.engineer_actions(
+        &[
+          ("skimmer".to_string(), vec![ShipAction::SkimFuel]),
+          ("flyby".to_string(), vec![ShipAction::SkimFuel]),
+        ],
+        &BoostMap::default(),
+        &mut rng,
+      );
+      let results = effects
+        .iter()
+        .filter_map(|effect| match effect {
+          EffectMsg::EngineerAction { result } => Some(result),
+          _ => None,
+        })
+        .collect::<Vec<_>>();
+      assert_eq!(results.len(), 2);
+
+      // Skimming takes several turns to fill the tanks, and leaves the ship a sitting duck whatever the outcome.
+      let skimmer = entities.ships["skimmer"].read().unwrap();
+      assert!(skimmer.is_skimming());
+      let expected_fuel = if results[0].success { design.fuel.div_ceil(4) } else { 0 };
+      assert_eq!(skimmer.current_fuel, expected_fuel);
+      if results[0].critical_failure {
+        assert!(skimmer.get_current_hull_points() < design.hull);
+      }
+      let attacker = entities.ships["flyby"].read().unwrap();
+      let modifiers = AttackModifiers::new(0, 0, &attacker, &skimmer, &design.weapons[0], None, 0, default_rules());
+      assert_eq!(modifiers.skimming_mod, 2);
+
+      assert!(!results[1].success && results[1].check == 0);
+      assert!(!attacker.is_skimming());
+      assert_eq!(attacker.current_fuel, 0);
+    }
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_transfer_fuel() {
+    config_test_ship_templates().await;
+    let design = get_ship_template("Buccaneer").unwrap();
+    let mut entities = Entities::new();
+    entities.add_ship("tanker".to_string(), Vec3::zero(), Vec3::new(1000.0, 0.0, 0.0), &design, None);
+    entities.add_ship(
+      "alongside".to_string(),
+      Vec3::new(500.0, 0.0, 0.0),
+      Vec3::new(1010.0, 0.0, 0.0),
+      &design,
+      None,
+    );
+    entities.add_ship("distant".to_string(), Vec3::new(1e6, 0.0, 0.0), Vec3::zero(), &design, None);
+    entities.ships["alongside"].write().unwrap().current_fuel = design.fuel - 11;
+    entities.ships["distant"].write().unwrap().current_fuel = 0;
+
+    let transfer = |target: &str, amount| ShipAction::TransferFuel {
+      target: target.to_string(),
+      amount,
+    };
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut turn = |entities: &mut Entities, action| {
+      let effects = entities.engineer_actions(&[("tanker".to_string(), vec![action])], &BoostMap::default(), &mut rng);
+      entities.ships["tanker"].write().unwrap().reset_temporary_bonuses();
+      match &effects[..] {
+        [EffectMsg::EngineerAction { result }] => result.success,
+        _ => panic!("Expected a single engineer action result, got {effects:?}"),
+      }
+    };
+
+    // Only as much fuel as the target has room for is sent.
+    assert!(turn(&mut entities, transfer("alongside", None)));
+    assert_eq!(entities.ships["alongside"].read().unwrap().current_fuel, design.fuel);
+    assert_eq!(entities.ships["tanker"].read().unwrap().current_fuel, design.fuel - 11);
+
+    // Nothing more fits, the distant ship isn't alongside, and a ship can't fuel itself.
+    assert!(!turn(&mut entities, transfer("alongside", Some(5))));
+    assert!(!turn(&mut entities, transfer("distant", Some(5))));
+    assert!(!turn(&mut entities, transfer("tanker", None)));
+    assert_eq!(entities.ships["distant"].read().unwrap().current_fuel, 0);
+    assert_eq!(entities.ships["tanker"].read().unwrap().current_fuel, design.fuel - 11);
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_allocate_power() {
+    config_test_ship_templates().await;
+    let design = get_ship_template("Buccaneer").unwrap();
+    let mut entities = Entities::new();
+    entities.add_ship("ship1".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+    entities.ships["ship1"].write().unwrap().current_power = design.power / 2;
+
+    // At half power the Buccaneer's lasers go dark first, and it still can't run its drive at full thrust.
+    let effects = entities.engineer_actions(&[], &BoostMap::default(), &mut SmallRng::seed_from_u64(0));
+    let shortfall = EffectMsg::PowerShortfall {
+      ship: "ship1".to_string(),
+      offline: vec![
+        "pulse laser double turret".to_string(),
+        "pulse laser double turret".to_string(),
+        "2G of thrust".to_string(),
+      ],
+    };
+    assert_eq!(
+      shortfall.to_string(),
+      "ship1 is drawing more power than its plant makes: pulse laser double turret, pulse laser double turret, 2G of \
+       thrust offline."
+    );
+    assert_eq!(effects, vec![shortfall]);
+
+    // A laser without power can't fire.
+    entities.add_ship("ship2".to_string(), Vec3::new(1000.0, 0.0, 0.0), Vec3::zero(), &design, None);
+    let snapshot = entities.ship_deep_copy();
+    let fire = ShipAction::FireAction {
+      weapon_id: 0,
+      target: "ship2".to_string(),
+      called_shot_system: None,
+      multiple_attacks: false,
+    };
+    let effects = entities.fire_actions(
+      &[("ship1".to_string(), vec![fire])],
+      &[],
+      &snapshot,
+      &BoostMap::default(),
+      &mut SmallRng::seed_from_u64(0),
+    );
+    let unpowered = EffectMsg::WeaponUnpowered {
+      ship: "ship1".to_string(),
+      weapon_name: "pulse laser double turret".to_string(),
+    };
+    assert_eq!(
+      unpowered.to_string(),
+      "ship1's pulse laser double turret has no power and can't fire."
+    );
+    assert_eq!(effects, vec![unpowered]);
+
+    // It isn't reported again while nothing changes.
+    let effects = entities.engineer_actions(&[], &BoostMap::default(), &mut SmallRng::seed_from_u64(0));
+    assert!(effects.is_empty());
+
+    // Cutting the drive back to 1G keeps both turrets firing.
+    let allocation = PowerAllocation {
+      maneuver: 1,
+      sensors: true,
+      weapons: vec![true; design.weapons.len()],
+    };
+    let effects = entities.engineer_actions(
+      &[("ship1".to_string(), vec![ShipAction::AllocatePower { allocation }])],
+      &BoostMap::default(),
+      &mut SmallRng::seed_from_u64(0),
+    );
+    let [EffectMsg::EngineerAction { result }, EffectMsg::PowerShortfall { offline, .. }] = &effects[..] else {
+      panic!("Expected the allocation result and the shortfall ending, got {effects:?}");
+    };
+    assert!(result.success);
+    assert_eq!(result.message, "ship1 allocates 140 of 150 power.");
+    assert!(offline.is_empty());
+    let ship = entities.ships["ship1"].read().unwrap();
+    assert_eq!(ship.max_acceleration(), 1);
+    assert!(ship.weapon_powered(0) && ship.weapon_powered(1));
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_assign_gunner() {
+    config_test_ship_templates().await;
+    let design = get_ship_template("Buccaneer").unwrap();
+    let mut entities = Entities::new();
+    entities.add_ship("ship1".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+    entities.ships["ship1"]
+      .write()
+      .unwrap()
+      .get_crew_mut()
+      .add_member(CrewMember::new("Ben", Station::Crew, &[(Skills::Gunnery, 2)]));
+
+    assert!(entities.assign_gunner("ship1", "Ben", &[design.weapons.len()]).is_err());
+    assert!(entities.assign_gunner("ship2", "Ben", &[0]).is_err());
+    assert!(entities.assign_gunner("ship1", "Nobody", &[0]).is_err());
+    entities.assign_gunner("ship1", "Ben", &[0, 1]).unwrap();
+    let ship = entities.ships["ship1"].read().unwrap();
+    assert_eq!(ship.get_crew().get_gunnery(1), 2);
+    assert_eq!(ship.get_crew().get_gunnery(2), 0);
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_medical_actions() {
+    config_test_ship_templates().await;
+    let design = get_ship_template("Buccaneer").unwrap();
+    let mut entities = Entities::new();
+    entities.add_ship("ship1".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+    {
+      let mut ship = entities.ships["ship1"].write().unwrap();
+      let crew = ship.get_crew_mut();
+      crew.add_member(CrewMember::new("Ada", Station::Pilot, &[(Skills::Pilot, 2)]));
+      crew.add_member(CrewMember::new("Ben", Station::Gunner, &[(Skills::Gunnery, 1)]));
+      crew.add_member(CrewMember::new("Doc", Station::Medic, &[(Skills::Medic, 6)]));
+      crew.injure("Ada", 12);
+      crew.injure("Ben", 12);
+    }
+    let stabilize = |member: &str| {
+      vec![(
+        "ship1".to_string(),
+        vec![ShipAction::Stabilize {
+          member: member.to_string(),
+        }],
+      )]
+    };
+
+    // Doc can't fail at 2D+6 against 8.  Ben, untreated, loses a turn.
+    let effects = entities.medical_actions(&stabilize("Ada"), &mut SmallRng::seed_from_u64(0));
+    assert!(matches!(&effects[..], [EffectMsg::DiceRoll { .. }, _]));
+    assert_eq!(
+      effects[1],
+      EffectMsg::CrewInjury {
+        ship: "ship1".to_string(),
+        member: "Ada".to_string(),
+        health: Health::Stable
+      }
+    );
+
+    let effects = entities.medical_actions(&stabilize("Doc"), &mut SmallRng::seed_from_u64(0));
+    assert_eq!(
+      effects,
+      vec![EffectMsg::message(
+        "ship1 has no dying crew member Doc to stabilize.".to_string()
+      )]
+    );
+
+    let effects = entities.medical_actions(&[], &mut SmallRng::seed_from_u64(0));
+    assert_eq!(
+      effects,
+      vec![EffectMsg::CrewInjury {
+        ship: "ship1".to_string(),
+        member: "Ben".to_string(),
+        health: Health::Dead
+      }]
+    );
+    assert_eq!(effects[0].to_string(), "Ben of ship1 is dead.");
+    let ship = entities.ships["ship1"].read().unwrap();
+    assert_eq!(ship.current_crew, design.crew - 1);
+    assert_eq!(ship.get_crew().get_members()[0].health, Health::Stable);
+    // With Ada out of action and no one else able to fly, the ship has an untrained pilot.
+    assert_eq!(ship.get_crew().get_pilot(), UNTRAINED_DM);
+  }
+
+  /// Jupiter between "attacker" and "hidden", with "clear" in plain sight of "attacker".
+  async fn line_of_sight_scene() -> Entities {
+    config_test_ship_templates().await;
+    let design = get_ship_template("Buccaneer").unwrap();
+    let mut entities = Entities::new();
+    entities
+      .add_planet(
+        "Jupiter".to_string(),
+        Vec3::zero(),
+        "tan".to_string(),
+        None,
+        6.9911e7,
+        1.898e27,
+        vec![],
+      )
+      .unwrap();
+    entities.add_ship("attacker".to_string(), Vec3::new(-1e8, 0.0, 0.0), Vec3::zero(), &design, None);
+    entities.add_ship("hidden".to_string(), Vec3::new(1e8, 0.0, 0.0), Vec3::zero(), &design, None);
+    entities.add_ship("clear".to_string(), Vec3::new(-1e8, 1e6, 0.0), Vec3::zero(), &design, None);
+    entities
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_planet_blocks_line_of_sight() {
+    let mut entities = line_of_sight_scene().await;
+
+    assert_eq!(
+      entities.occluding_planet(Vec3::new(-1e8, 0.0, 0.0), Vec3::new(1e8, 0.0, 0.0)),
+      Some("Jupiter".to_string())
+    );
+    assert_eq!(
+      entities.occluding_planet(Vec3::new(-1e8, 0.0, 0.0), Vec3::new(-1e8, 1e6, 0.0)),
+      None
+    );
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    let hidden = EffectMsg::LineOfSightBlocked {
+      ship: "attacker".to_string(),
+      target: "hidden".to_string(),
+      planet: "Jupiter".to_string(),
+    };
+    let effects = entities.sensor_actions(
+      &[(
+        "attacker".to_string(),
+        vec![
+          ShipAction::SensorLock {
+            target: "hidden".to_string(),
+          },
+          ShipAction::JamComms {
+            target: "hidden".to_string(),
+          },
+          ShipAction::SensorLock {
+            target: "clear".to_string(),
+          },
+        ],
+      )],
+      &BoostMap::default(),
+      &mut rng,
+    );
+    assert_eq!(effects[0], hidden);
+    assert_eq!(effects[1], hidden);
+    assert_eq!(effects[0].to_string(), "hidden is hidden from attacker behind Jupiter.");
+    assert!(matches!(
+      &effects[2..],
+      [EffectMsg::DiceRoll { .. }, EffectMsg::SensorResult { .. }]
+    ));
+    assert!(!entities.ships["attacker"]
+      .read()
+      .unwrap()
+      .sensor_locks
+      .contains(&"hidden".to_string()));
+
+    let snapshot = entities
+      .ships
+      .iter()
+      .map(|(name, ship)| (name.clone(), ship.read().unwrap().clone()))
+      .collect::<HashMap<_, _>>();
+    let fire = |target: &str| ShipAction::FireAction {
+      weapon_id: 0,
+      target: target.to_string(),
+      called_shot_system: None,
+      multiple_attacks: false,
+    };
+    let effects = entities.fire_actions(
+      &[("attacker".to_string(), vec![fire("hidden")])],
+      &[],
+      &snapshot,
+      &BoostMap::default(),
+      &mut rng,
+    );
+    assert_eq!(effects, vec![hidden]);
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_planet_breaks_sensor_lock() {
+    let mut entities = line_of_sight_scene().await;
+    entities.ships["attacker"].write().unwrap().sensor_locks = vec!["hidden".to_string(), "clear".to_string()];
+
+    // The lock on the ship behind Jupiter breaks whatever else the sensors do this round, even trying to lock on to
+    // a ship that isn't there.
+    let effects = entities.sensor_actions(
+      &[(
+        "attacker".to_string(),
+        vec![ShipAction::SensorLock {
+          target: "gone".to_string(),
+        }],
+      )],
+      &BoostMap::default(),
+      &mut SmallRng::seed_from_u64(0),
+    );
+    assert_eq!(
+      effects,
+      vec![EffectMsg::SensorLockLost {
+        ship: "attacker".to_string(),
+        target: "hidden".to_string(),
+        planet: "Jupiter".to_string(),
+      }]
+    );
+    assert_eq!(
+      entities.ships["attacker"].read().unwrap().sensor_locks,
+      vec!["clear".to_string()]
+    );
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_damage_control_teams() {
+    config_test_ship_templates().await;
+    let design = get_ship_template("Buccaneer").unwrap();
+    let mut big = (*design).clone();
+    big.displacement = 2000;
+    let big = Arc::new(big);
+
+    let mut entities = Entities::new();
+    entities.add_ship("scout".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+    entities.add_ship("cruiser".to_string(), Vec3::new(1e6, 0.0, 0.0), Vec3::zero(), &big, None);
+    for ship in entities.ships.values() {
+      let mut ship = ship.write().unwrap();
+      ship.crit_level[ShipSystem::Sensors as usize] = 6;
+      ship.crit_level[ShipSystem::Maneuver as usize] = 6;
+    }
+    entities.ships["cruiser"].write().unwrap().current_crew = 50;
+    assert_eq!(entities.ships["scout"].read().unwrap().damage_control_teams(), 1);
+    assert_eq!(entities.ships["cruiser"].read().unwrap().damage_control_teams(), 5);
+
+    let repair = |system| {
+      vec![
+        ("scout".to_string(), vec![ShipAction::Repair { system }]),
+        ("cruiser".to_string(), vec![ShipAction::Repair { system }]),
+      ]
+    };
+    let mut rng = SmallRng::seed_from_u64(1);
+    entities.engineer_actions(&repair(ShipSystem::Sensors), &BoostMap::default(), &mut rng);
+    for ship in entities.ships.values() {
+      ship.write().unwrap().reset_temporary_bonuses();
+    }
+
+    // The scout's only team is busy; the cruiser puts a second team to work and both roll.
+    let before = entities.ships["cruiser"].read().unwrap().clone();
+    let effects = entities.engineer_actions(&repair(ShipSystem::Maneuver), &BoostMap::default(), &mut rng);
+    assert!(effects.contains(&EffectMsg::EngineerAction {
+      result: EngineerActionResult {
+        ship_name: "scout".to_string(),
+        action: ShipAction::Repair {
+          system: ShipSystem::Maneuver
+        },
+        success: false,
+        check: 0,
+        target: 0,
+        message: "scout cannot repair Maneuver: all 1 damage control teams are busy.".to_string(),
+        critical_failure: false,
+        mishap: None,
+      }
+    }));
+    let rolls = |ship: &str| {
+      effects
+        .iter()
+        .filter(|effect| {
+          matches!(effect, EffectMsg::EngineerAction { result } if result.ship_name == ship && result.target > 0)
+        })
+        .count()
+    };
+    assert_eq!(rolls("scout"), 1);
+    assert_eq!(rolls("cruiser"), 2);
+
+    // Each task either took a level of damage off its system or built up its bonus for next turn.
+    let after = entities.ships["cruiser"].read().unwrap();
+    assert_eq!(after.get_repair_tasks().len(), 2);
+    for task in after.get_repair_tasks() {
+      let was = before
+        .get_repair_tasks()
+        .iter()
+        .find(|t| t.system == task.system)
+        .map_or(0, |t| t.bonus);
+      let system = task.system as usize;
+      assert!(
+        (after.crit_level[system] + 1 == before.crit_level[system] && task.bonus == 0)
+          || (after.crit_level[system] == before.crit_level[system] && task.bonus == was + 1),
+        "{task:?}"
+      );
+    }
+    drop(after);
+
+    // Losing most of the crew leaves the cruiser one team, so the newer repair is abandoned.
+    entities.ships["cruiser"].write().unwrap().current_crew = 5;
+    let effects = entities.engineer_actions(&[], &BoostMap::default(), &mut rng);
+    assert_eq!(
+      effects[0],
+      EffectMsg::RepairsAbandoned {
+        ship: "cruiser".to_string(),
+        teams: 1,
+        systems: vec![ShipSystem::Maneuver],
+      }
+    );
+    let cruiser = entities.ships["cruiser"].read().unwrap();
+    assert_eq!(cruiser.get_repair_tasks().len(), 1);
+    assert_eq!(cruiser.get_repair_tasks()[0].system, ShipSystem::Sensors);
+  }
+
   #[test]
   #[should_panic(expected = "called `Option::unwrap()` on a `None` value")]
   fn test_sensor_quality_modifiers_invalid_ship() {
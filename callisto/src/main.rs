@@ -26,25 +26,41 @@ use callisto::{debug, error, info, warn, LOG_FILE_USE};
 use clap::Parser;
 use tracing::{event, Level};
 //use tracing_gcp::GcpLayer;
-use tracing_subscriber::{layer::SubscriberExt, EnvFilter};
+use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Layer};
 
 extern crate callisto;
 
 use callisto::authentication::{
-  load_user_directory, Authenticator, GoogleAuthenticator, HeaderCallback, MockAuthenticator, UserDirectory,
+  load_user_directory, Authenticator, GoogleAuthenticator, HeaderCallback, LocalAuthenticator, MockAuthenticator,
+  OidcAuthenticator, OidcConfig, UserDirectory, UserDirectoryStore,
 };
 
 use callisto::entity::Entities;
+use callisto::metrics;
 use callisto::processor::{Processor, ReloadNotification};
+use callisto::replace_scenarios;
+use callisto::roster::DEFAULT_ROSTER_FILE;
+use callisto::rules_tables::{load_default_rules, DEFAULT_RULES_FILE};
 use callisto::ship::DEFAULT_SHIP_TEMPLATES_DIR;
 use callisto::ship::{load_ship_templates_from_dir, merge_ship_templates};
-use callisto::{get_local_or_cloud_dir_fingerprint, replace_scenarios};
+use callisto::storage::{join_path, storage_for, StorageBackend};
 
 const DEFAULT_AUTHORIZED_USERS_FILE: &str = "./config/authorized_users.json";
 
 const MAX_CHANNEL_DEPTH: usize = 10;
 const RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
+/// How users prove who they are. Every choice is gated by the same users file.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum AuthProvider {
+  /// Google OAuth, using the `--oauth-creds` client credentials.
+  Google,
+  /// Username/password accounts, with salted hashes stored next to the users file.
+  Local,
+  /// Any `OpenID` Connect provider, configured with the `--oidc-*` flags.
+  Oidc,
+}
+
 /// Server to implement physically pseudo-realistic spaceflight and possibly combat.
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -57,7 +73,8 @@ struct Args {
   #[arg(short, long, default_value = "0.0.0.0")]
   address: String,
 
-  /// Directory for all possible scenarios
+  /// Directory for all possible scenarios. Like `--design-dir` and `--users-file`, this may be a
+  /// local path, `gs://bucket/dir` or `s3://bucket/dir` (S3 credentials come from the `AWS_*` environment).
   #[arg(short, long, default_value = "./scenarios/")]
   scenario_dir: String,
 
@@ -88,9 +105,44 @@ struct Args {
   #[arg(long, default_value = "keys/localhost.crt")]
   tls_keys_public: String,
 
-  // Google Cloud Storage bucket to use in lieu of config directory
+  /// Rules tables (range bands, weapon modifiers, etc.) used by every scenario that doesn't set its own. Tables
+  /// missing from the file, or the whole file if it doesn't exist, keep their built-in values.
+  #[arg(long, default_value = DEFAULT_RULES_FILE)]
+  rules_file: String,
+
+  /// Campaign roster of ships that persist between scenarios.  Like `--users-file` this may be a local path, or a
+  /// gs:// or s3:// object.  A missing file is an empty roster.
+  #[arg(long, default_value = DEFAULT_ROSTER_FILE)]
+  roster_file: String,
+
+  // Authorized users file: a local path, or a gs:// or s3:// object.
   #[arg(short, long, default_value = DEFAULT_AUTHORIZED_USERS_FILE)]
   users_file: String,
+
+  /// Login provider. Ignored in test mode, which always uses the mock authenticator.
+  #[arg(long, value_enum, default_value_t = AuthProvider::Google)]
+  auth: AuthProvider,
+
+  /// OIDC issuer URL. Its discovery document supplies the token endpoint.
+  #[arg(long, required_if_eq("auth", "oidc"))]
+  oidc_issuer: Option<String>,
+
+  /// URL of the OIDC provider's signing keys (JWKS).
+  #[arg(long, required_if_eq("auth", "oidc"))]
+  oidc_jwks_url: Option<String>,
+
+  /// OAuth client id registered with the OIDC provider.
+  #[arg(long, required_if_eq("auth", "oidc"))]
+  oidc_client_id: Option<String>,
+
+  // Location of the OIDC client secret.  As with `oauth_creds`, convenient with Docker secrets.
+  #[arg(long, default_value = "./secrets/oidc_client_secret")]
+  oidc_client_secret_file: String,
+
+  /// Port for the Prometheus metrics endpoint (`GET /metrics`, plain HTTP on `--address`).
+  /// Metrics are not served unless this is given.
+  #[arg(long)]
+  metrics_port: Option<u16>,
 }
 
 #[cfg(feature = "no_tls_upgrade")]
@@ -154,13 +206,22 @@ async fn handle_connection(
 #[tokio::main]
 #[quit::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+  // RUST_LOG only filters the log output; auth metrics see every auth result whatever the log level.
   let subscriber = tracing_subscriber::Registry::default()
-    .with(EnvFilter::from_default_env())
-    .with(tracing_stackdriver::layer());
+    .with(tracing_stackdriver::layer().with_filter(EnvFilter::from_default_env()))
+    .with(metrics::AuthMetricsLayer);
   tracing::subscriber::set_global_default(subscriber)?;
   let args = Args::parse();
   let design_dir = args.design_dir.clone();
   let scenario_dir = args.scenario_dir.clone();
+  let roster_file = args.roster_file.clone();
+
+  // Resolve each location to its storage backend once; everything downstream
+  // reads and writes through the backend rather than sniffing the path.
+  let design_storage = storage_for(&design_dir)?;
+  let scenario_storage = storage_for(&scenario_dir)?;
+  let users_storage = storage_for(&args.users_file)?;
+  let roster_storage = storage_for(&args.roster_file)?;
 
   let port = args.port;
 
@@ -211,6 +272,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
   let listener = TcpListener::bind(&addr).await?;
 
   info!("(main) Bound to address (tcp): {}", addr);
+
+  if let Some(metrics_port) = args.metrics_port {
+    let metrics_listener = TcpListener::bind((args.address.as_str(), metrics_port)).await?;
+    info!("(main) Serving metrics on {}:{metrics_port}/metrics", args.address);
+    tokio::spawn(metrics::serve(metrics_listener));
+  }
   let test_mode = args.test;
   if test_mode {
     info!("(main) Server in TEST mode.");
@@ -236,6 +303,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
     }));
   }
 
+  // Must come before any scenario is loaded as scenarios fill in their rules from these.
+  match load_default_rules(storage_for(&args.rules_file)?.as_ref(), &args.rules_file).await {
+    Ok(()) => {
+      event!(target: LOG_FILE_USE, Level::INFO, file_name = &args.rules_file, use = "Load rules.");
+    }
+    Err(e) => {
+      warn!("(main) {e}. Using built-in rules.");
+    }
+  }
+
   debug!("(main) Loading ship templates from {}...", &args.design_dir);
   // Soft-fail like scenarios: a transient GCS hiccup on cold start
   // shouldn't wedge the instance. The watcher polls the directory
@@ -243,7 +320,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
   // failed. Per-file parse errors inside `load_ship_templates_from_dir`
   // are already swallowed and logged; the only error path here is the
   // listing itself failing.
-  let initial_design_load_ok = match load_ship_templates_from_dir(&args.design_dir).await {
+  let initial_design_load_ok = match load_ship_templates_from_dir(design_storage.as_ref(), &args.design_dir).await {
     Ok(templates) => {
       let count = templates.len();
       merge_ship_templates(templates);
@@ -274,7 +351,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
   // reload watcher (5s polling, fingerprint-based) is told to seed its
   // fingerprint as empty when the initial load failed, so the next poll
   // triggers a retry. This is the self-healing path.
-  let initial_scenario_load_ok = match load_scenarios_and_metadata(&args.scenario_dir).await {
+  let initial_scenario_load_ok = match load_scenarios_and_metadata(scenario_storage.as_ref(), &args.scenario_dir).await
+  {
     Ok(scenarios) => {
       replace_scenarios(scenarios);
       true
@@ -293,7 +371,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
   // watcher takes the original.
   let users_reload_sender = reload_sender.clone();
   tokio::spawn(watch_reloadable_data(
+    design_storage,
     design_dir.clone(),
+    scenario_storage.clone(),
     scenario_dir.clone(),
     initial_scenario_load_ok,
     initial_design_load_ok,
@@ -306,9 +386,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
   let (mut connection_sender, connection_receiver) = channel(MAX_CHANNEL_DEPTH);
 
   // Shared user directory + register lock. Both must be created BEFORE the
-  // authenticator so the GoogleAuthenticator constructor can seed the cell
-  // and the Processor can read from the same Arc. The register lock is only
-  // consumed by GoogleAuthenticator; mock mode owns its own swap discipline.
+  // authenticator so its constructor can seed the cell and the Processor can
+  // read from the same Arc. The register lock is only consumed by the
+  // file-backed authenticators; mock mode owns its own swap discipline.
   let user_directory: Arc<RwLock<Arc<UserDirectory>>> = Arc::new(RwLock::new(Arc::new(UserDirectory::default())));
 
   // Seed the directory cell from the users file. In test mode the
@@ -316,7 +396,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
   // pre-seeded blacklists by writing the file. Failure is non-fatal — an
   // empty cell behaves as "everyone allowed" under MockAuthenticator and
   // "nobody allowed" under GoogleAuthenticator (which then errors below).
-  match load_user_directory(&args.users_file).await {
+  match load_user_directory(users_storage.as_ref(), &args.users_file).await {
     Ok(initial) => {
       *user_directory.write().expect("(main) user_directory lock poisoned at startup") = Arc::new(initial);
       event!(target: LOG_FILE_USE, Level::INFO, file_name = args.users_file.as_str(), use = "Seed authorized users");
@@ -330,28 +410,52 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
   }
 
   // Create an Authenticator to be cloned on each new connection.
+  let register_lock = Arc::new(tokio::sync::Mutex::new(()));
   let auth_template: Box<dyn Authenticator> = if test_mode {
     Box::new(MockAuthenticator::new(&args.address).with_directory(user_directory.clone()))
   } else {
-    // All the data shared between authenticators.
-    let my_credentials = GoogleAuthenticator::load_google_credentials(&args.oauth_creds);
-
-    let my_credentials = Arc::new(my_credentials);
-    let google_keys = GoogleAuthenticator::fetch_google_public_keys().await;
-
-    let register_lock = Arc::new(tokio::sync::Mutex::new(()));
-    Box::new(
-      GoogleAuthenticator::new(
+    match args.auth {
+      AuthProvider::Google => {
+        // All the data shared between authenticators.
+        let my_credentials = GoogleAuthenticator::load_google_credentials(&args.oauth_creds);
+
+        let my_credentials = Arc::new(my_credentials);
+        let google_keys = GoogleAuthenticator::fetch_google_public_keys().await;
+
+        Box::new(GoogleAuthenticator::new(
+          &args.web_server,
+          my_credentials,
+          google_keys,
+          load_users_store(users_storage.clone(), &args.users_file, user_directory.clone(), register_lock).await,
+        ))
+      }
+      AuthProvider::Local => Box::new(LocalAuthenticator::new(
         &args.web_server,
-        my_credentials,
-        google_keys,
-        &args.users_file,
-        user_directory.clone(),
-        register_lock,
-      )
-      .await
-      .expect("Failed to create GoogleAuthenticator"),
-    )
+        load_users_store(users_storage.clone(), &args.users_file, user_directory.clone(), register_lock).await,
+      )),
+      AuthProvider::Oidc => {
+        let client_secret = std::fs::read_to_string(&args.oidc_client_secret_file)
+          .unwrap_or_else(|e| panic!("Error {e:?} reading OIDC client secret file {}", args.oidc_client_secret_file));
+        // Clap enforces these when `--auth oidc` is given.
+        let config = OidcConfig::discover(
+          args.oidc_issuer.as_deref().unwrap_or_default(),
+          args.oidc_jwks_url.as_deref().unwrap_or_default(),
+          args.oidc_client_id.as_deref().unwrap_or_default(),
+          client_secret.trim(),
+        )
+        .await
+        .expect("Failed to discover OIDC provider configuration");
+        Box::new(
+          OidcAuthenticator::new(
+            &args.web_server,
+            Arc::new(config),
+            load_users_store(users_storage.clone(), &args.users_file, user_directory.clone(), register_lock).await,
+          )
+          .await
+          .expect("Failed to create OidcAuthenticator"),
+        )
+      }
+    }
   };
 
   // Spawn the users-file watcher (separate from the design/scenario watcher
@@ -359,6 +463,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
   let users_file_clone = args.users_file.clone();
   let user_directory_for_watcher = user_directory.clone();
   tokio::spawn(watch_users_file(
+    users_storage,
     users_file_clone,
     user_directory_for_watcher,
     users_reload_sender,
@@ -373,6 +478,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
       auth_template,
       session_keys_clone,
       &scenario_dir,
+      scenario_storage,
+      &roster_file,
+      roster_storage,
       test_mode,
       directory_handle,
     );
@@ -417,13 +525,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
 }
 
 async fn watch_reloadable_data(
-  design_dir: String, scenario_dir: String, initial_scenario_load_ok: bool, initial_design_load_ok: bool,
+  design_storage: Arc<dyn StorageBackend>, design_dir: String, scenario_storage: Arc<dyn StorageBackend>,
+  scenario_dir: String, initial_scenario_load_ok: bool, initial_design_load_ok: bool,
   reload_sender: UnboundedSender<ReloadNotification>,
 ) {
   // Same self-healing fingerprint seed as scenarios: if the initial load
   // failed, seed the fingerprint as empty so the very next poll retries.
   let mut last_design_fingerprint = if initial_design_load_ok {
-    get_local_or_cloud_dir_fingerprint(&design_dir).await.unwrap_or_else(|e| {
+    design_storage.fingerprint(&design_dir).await.unwrap_or_else(|e| {
       warn!("(main) Unable to fingerprint design directory {design_dir}: {e}");
       Vec::new()
     })
@@ -438,7 +547,7 @@ async fn watch_reloadable_data(
   // self-healing path: a transient failure clears once the metadata server
   // recovers, without needing the bucket to be touched.
   let mut last_scenario_fingerprint = if initial_scenario_load_ok {
-    get_local_or_cloud_dir_fingerprint(&scenario_dir).await.unwrap_or_else(|e| {
+    scenario_storage.fingerprint(&scenario_dir).await.unwrap_or_else(|e| {
       warn!("(main) Unable to fingerprint scenario directory {scenario_dir}: {e}");
       Vec::new()
     })
@@ -449,7 +558,7 @@ async fn watch_reloadable_data(
   loop {
     tokio::time::sleep(RELOAD_POLL_INTERVAL).await;
 
-    let design_reload = match get_local_or_cloud_dir_fingerprint(&design_dir).await {
+    let design_reload = match design_storage.fingerprint(&design_dir).await {
       Ok(fingerprint) if fingerprint != last_design_fingerprint => Some(fingerprint),
       Ok(_) => None,
       Err(e) => {
@@ -459,11 +568,12 @@ async fn watch_reloadable_data(
     };
 
     if let Some(fingerprint) = design_reload {
-      match load_ship_templates_from_dir(&design_dir).await {
+      match load_ship_templates_from_dir(design_storage.as_ref(), &design_dir).await {
         Ok(templates) => {
           let count = templates.len();
           merge_ship_templates(templates);
           last_design_fingerprint = fingerprint;
+          metrics::record_reload(metrics::RELOAD_SHIP_TEMPLATES, true);
           event!(
             target: LOG_FILE_USE,
             Level::INFO,
@@ -477,12 +587,13 @@ async fn watch_reloadable_data(
           }
         }
         Err(e) => {
+          metrics::record_reload(metrics::RELOAD_SHIP_TEMPLATES, false);
           warn!("(main) Unable to reload ship templates from {design_dir}: {e:?}");
         }
       }
     }
 
-    let scenario_reload = match get_local_or_cloud_dir_fingerprint(&scenario_dir).await {
+    let scenario_reload = match scenario_storage.fingerprint(&scenario_dir).await {
       Ok(fingerprint) if fingerprint != last_scenario_fingerprint => Some(fingerprint),
       Ok(_) => None,
       Err(e) => {
@@ -492,10 +603,11 @@ async fn watch_reloadable_data(
     };
 
     if let Some(fingerprint) = scenario_reload {
-      match load_scenarios_and_metadata(&scenario_dir).await {
+      match load_scenarios_and_metadata(scenario_storage.as_ref(), &scenario_dir).await {
         Ok(scenarios) => {
           replace_scenarios(scenarios);
           last_scenario_fingerprint = fingerprint;
+          metrics::record_reload(metrics::RELOAD_SCENARIOS, true);
           event!(target: LOG_FILE_USE, Level::INFO, file_name = &scenario_dir, use = "Reloaded scenarios");
           if let Err(e) = reload_sender.unbounded_send(ReloadNotification::Scenarios) {
             warn!("(main) Unable to notify processor of scenario reload: {e:?}");
@@ -503,6 +615,7 @@ async fn watch_reloadable_data(
           }
         }
         Err(e) => {
+          metrics::record_reload(metrics::RELOAD_SCENARIOS, false);
           warn!("(main) Unable to reload scenarios from {scenario_dir}: {e:?}");
         }
       }
@@ -515,7 +628,7 @@ async fn watch_reloadable_data(
 /// the design-template watcher (one file, mtime-based) but runs on its own
 /// task so a slow GCS poll on one watcher doesn't starve the other.
 async fn watch_users_file(
-  users_file: String, directory_handle: Arc<RwLock<Arc<UserDirectory>>>,
+  users_storage: Arc<dyn StorageBackend>, users_file: String, directory_handle: Arc<RwLock<Arc<UserDirectory>>>,
   reload_sender: UnboundedSender<ReloadNotification>,
 ) {
   // Seed last-known mtime from whatever the GoogleAuthenticator already
@@ -529,7 +642,7 @@ async fn watch_users_file(
   loop {
     tokio::time::sleep(RELOAD_POLL_INTERVAL).await;
 
-    let users_reload = match callisto::get_file_last_modified_timestamp(&users_file).await {
+    let users_reload = match users_storage.last_modified(&users_file).await {
       Ok(Some(last_modified)) if last_modified > last_users_modified => Some(last_modified),
       Ok(_) => None,
       Err(e) => {
@@ -539,9 +652,10 @@ async fn watch_users_file(
     };
 
     if let Some(last_modified) = users_reload {
-      match load_user_directory(&users_file).await {
+      match load_user_directory(users_storage.as_ref(), &users_file).await {
         Ok(new_dir) => {
           last_users_modified = last_modified;
+          metrics::record_reload(metrics::RELOAD_AUTHORIZED_USERS, true);
           *directory_handle
             .write()
             .expect("(watch_users_file) directory_handle lock poisoned for write") = Arc::new(new_dir);
@@ -552,6 +666,7 @@ async fn watch_users_file(
           }
         }
         Err(e) => {
+          metrics::record_reload(metrics::RELOAD_AUTHORIZED_USERS, false);
           warn!("(watch_users_file) Unable to reload users file {users_file}: {e:?}");
         }
       }
@@ -559,14 +674,25 @@ async fn watch_users_file(
   }
 }
 
-fn join_dir_entry_path(dir: &str, entry: &str) -> String {
-  format!("{}/{entry}", dir.trim_end_matches('/'))
+/// Build the users store shared by the file-backed authenticators, seeding the
+/// directory cell. Panics if the users file can't be loaded: a real
+/// authenticator without its allow-list can't admit anyone.
+async fn load_users_store(
+  users_storage: Arc<dyn StorageBackend>, users_file: &str, directory: Arc<RwLock<Arc<UserDirectory>>>,
+  register_lock: Arc<tokio::sync::Mutex<()>>,
+) -> UserDirectoryStore {
+  UserDirectoryStore::new(users_storage, users_file, directory, register_lock)
+    .await
+    .unwrap_or_else(|e| panic!("Failed to load authorized users file {users_file}: {e}"))
 }
 
 async fn load_scenarios_and_metadata(
-  scenario_dir: &str,
+  scenario_storage: &dyn StorageBackend, scenario_dir: &str,
 ) -> Result<Vec<(String, callisto::entity::MetaData)>, Box<dyn std::error::Error>> {
-  let scenarios_list = callisto::list_local_or_cloud_dir(scenario_dir).await?;
+  let scenarios_list = scenario_storage
+    .list(scenario_dir)
+    .await
+    .map_err(|e| e as Box<dyn std::error::Error>)?;
 
   event!(target: LOG_FILE_USE, Level::INFO, file_name = &scenario_dir, use = "Loaded scenario");
 
@@ -578,8 +704,8 @@ async fn load_scenarios_and_metadata(
   // metadata-server flake at startup no longer wedges SCENARIOS as empty
   // until the bucket happens to change.
   let results = join_all(scenarios_list.iter().map(async |scenario| {
-    let scenario_path = join_dir_entry_path(scenario_dir, scenario);
-    let load_result = Entities::load_from_file(&scenario_path).await;
+    let scenario_path = join_path(scenario_dir, scenario);
+    let load_result = Entities::load_from_file(scenario_storage, &scenario_path).await;
     match load_result {
       Ok(entities) => Ok((scenario.clone(), entities.metadata.clone())),
       Err(e) => {
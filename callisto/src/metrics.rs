@@ -0,0 +1,289 @@
+//! Prometheus metrics for the game server.
+//!
+//! All metrics live in one process-wide registry, prefixed `callisto_`. Call sites record through the free functions
+//! here so they never touch `prometheus` types directly. When `--metrics-port` is given, [`serve`] exposes the
+//! registry in the Prometheus text format at `GET /metrics`.
+//!
+//! Auth outcomes are counted by [`AuthMetricsLayer`], a `tracing` layer that watches the existing
+//! [`LOG_AUTH_RESULT`] audit events, so the authenticators need no extra instrumentation.
+use once_cell::sync::Lazy;
+use prometheus::{
+  Histogram, HistogramOpts, HistogramTimer, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+  TextEncoder,
+};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+#[allow(unused_imports)]
+use crate::{debug, error, info, warn, LOG_AUTH_RESULT};
+
+/// Reload-watcher sources, used as the `source` label on `callisto_reload_events_total`.
+pub const RELOAD_SHIP_TEMPLATES: &str = "ship_templates";
+pub const RELOAD_SCENARIOS: &str = "scenarios";
+pub const RELOAD_AUTHORIZED_USERS: &str = "authorized_users";
+
+// Longest request head we'll read before giving up on a scrape.
+const MAX_REQUEST_HEAD: usize = 8192;
+
+// Results of admin actions on API tokens.  They are logged as auth results for the audit trail but aren't login or
+// registration attempts, so they aren't counted.
+const TOKEN_ADMIN_RESULTS: [&str; 2] = ["TokenMinted", "TokenRevoked"];
+
+struct Metrics {
+  registry: Registry,
+  connections: IntGauge,
+  servers: IntGauge,
+  server_members: IntGaugeVec,
+  requests: IntCounterVec,
+  turn_update_seconds: Histogram,
+  flight_solver_seconds: Histogram,
+  flight_solver_iterations: Histogram,
+  flight_solver_failures: IntCounter,
+  reload_events: IntCounterVec,
+  auth_results: IntCounterVec,
+}
+
+impl Metrics {
+  fn new() -> prometheus::Result<Self> {
+    let registry = Registry::new_custom(Some("callisto".to_string()), None)?;
+    let metrics = Metrics {
+      connections: IntGauge::new("connections", "Open WebSocket connections.")?,
+      servers: IntGauge::new("servers", "Scenario servers currently running.")?,
+      server_members: IntGaugeVec::new(Opts::new("server_members", "Players joined to each server."), &["server"])?,
+      requests: IntCounterVec::new(Opts::new("requests_total", "Requests handled, by message type."), &["request"])?,
+      turn_update_seconds: Histogram::with_opts(
+        HistogramOpts::new("turn_update_seconds", "Time to resolve a turn in PlayerManager::update.")
+          .buckets(prometheus::exponential_buckets(0.001, 2.0, 14)?),
+      )?,
+      flight_solver_seconds: Histogram::with_opts(
+        HistogramOpts::new("flight_solver_seconds", "Time spent in compute_flight_path.")
+          .buckets(prometheus::exponential_buckets(0.001, 2.0, 14)?),
+      )?,
+      flight_solver_iterations: Histogram::with_opts(
+        HistogramOpts::new(
+          "flight_solver_iterations",
+          "Solver iterations per compute_flight_path call, across all seeds.",
+        )
+        .buckets(prometheus::exponential_buckets(1.0, 2.0, 16)?),
+      )?,
+      flight_solver_failures: IntCounter::new(
+        "flight_solver_failures_total",
+        "compute_flight_path calls that found no plan.",
+      )?,
+      reload_events: IntCounterVec::new(
+        Opts::new("reload_events_total", "Reload-watcher reloads, by source and outcome."),
+        &["source", "outcome"],
+      )?,
+      auth_results: IntCounterVec::new(
+        Opts::new("auth_results_total", "Login and registration attempts, by result."),
+        &["result"],
+      )?,
+      registry,
+    };
+
+    metrics.registry.register(Box::new(metrics.connections.clone()))?;
+    metrics.registry.register(Box::new(metrics.servers.clone()))?;
+    metrics.registry.register(Box::new(metrics.server_members.clone()))?;
+    metrics.registry.register(Box::new(metrics.requests.clone()))?;
+    metrics.registry.register(Box::new(metrics.turn_update_seconds.clone()))?;
+    metrics.registry.register(Box::new(metrics.flight_solver_seconds.clone()))?;
+    metrics.registry.register(Box::new(metrics.flight_solver_iterations.clone()))?;
+    metrics.registry.register(Box::new(metrics.flight_solver_failures.clone()))?;
+    metrics.registry.register(Box::new(metrics.reload_events.clone()))?;
+    metrics.registry.register(Box::new(metrics.auth_results.clone()))?;
+    Ok(metrics)
+  }
+}
+
+static METRICS: Lazy<Metrics> = Lazy::new(|| Metrics::new().expect("(metrics) Unable to build metrics registry"));
+
+/// Count one request of the given `RequestMsg` variant.
+pub fn record_request(request: &str) {
+  METRICS.requests.with_label_values(&[request]).inc();
+}
+
+/// Snapshot the processor's state: open connections and the members of each live server.
+pub fn record_processor_state(connections: usize, server_members: &[(String, usize)]) {
+  let metrics = &*METRICS;
+  metrics.connections.set(i64::try_from(connections).unwrap_or(i64::MAX));
+  metrics.servers.set(i64::try_from(server_members.len()).unwrap_or(i64::MAX));
+  // Reset first so servers that have expired drop out rather than reporting a stale count.
+  metrics.server_members.reset();
+  for (server, members) in server_members {
+    metrics
+      .server_members
+      .with_label_values(&[server.as_str()])
+      .set(i64::try_from(*members).unwrap_or(i64::MAX));
+  }
+}
+
+/// Start timing a turn resolution. The duration is recorded when the timer is dropped.
+pub fn turn_update_timer() -> HistogramTimer {
+  METRICS.turn_update_seconds.start_timer()
+}
+
+/// Record one flight-solver run.
+#[allow(clippy::cast_precision_loss)]
+pub fn record_flight_solve(duration: Duration, iterations: usize, solved: bool) {
+  let metrics = &*METRICS;
+  metrics.flight_solver_seconds.observe(duration.as_secs_f64());
+  metrics.flight_solver_iterations.observe(iterations as f64);
+  if !solved {
+    metrics.flight_solver_failures.inc();
+  }
+}
+
+/// Record a reload-watcher reload of `source` (one of the `RELOAD_*` constants).
+pub fn record_reload(source: &str, succeeded: bool) {
+  let outcome = if succeeded { "success" } else { "failure" };
+  METRICS.reload_events.with_label_values(&[source, outcome]).inc();
+}
+
+/// Render every metric in the Prometheus text exposition format.
+///
+/// # Panics
+/// Panics if the encoder fails, which only happens on malformed metric families.
+#[must_use]
+pub fn render() -> String {
+  TextEncoder::new()
+    .encode_to_string(&METRICS.registry.gather())
+    .expect("(metrics.render) Unable to encode metrics")
+}
+
+/// A `tracing` layer counting [`LOG_AUTH_RESULT`] events by their `result` field
+/// (`Success`, `Failure`, `Blacklisted`, `Registered`, ...).  Install it without a filter so the count doesn't
+/// depend on the log level.
+pub struct AuthMetricsLayer;
+
+#[derive(Default)]
+struct ResultVisitor(Option<String>);
+
+impl Visit for ResultVisitor {
+  fn record_str(&mut self, field: &Field, value: &str) {
+    if field.name() == "result" {
+      self.0 = Some(value.to_string());
+    }
+  }
+
+  fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+    if field.name() == "result" {
+      self.0 = Some(format!("{value:?}").trim_matches('"').to_string());
+    }
+  }
+}
+
+impl<S: Subscriber> Layer<S> for AuthMetricsLayer {
+  fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+    if event.metadata().target() != LOG_AUTH_RESULT {
+      return;
+    }
+    let mut visitor = ResultVisitor::default();
+    event.record(&mut visitor);
+    if let Some(result) = visitor.0.filter(|result| !TOKEN_ADMIN_RESULTS.contains(&result.as_str())) {
+      METRICS.auth_results.with_label_values(&[result.as_str()]).inc();
+    }
+  }
+}
+
+/// Serve `GET /metrics` on `listener` until the process exits. Each scrape is one
+/// request on its own connection; anything else gets a 404.
+pub async fn serve(listener: TcpListener) {
+  loop {
+    match listener.accept().await {
+      Ok((stream, _)) => {
+        tokio::spawn(async move {
+          if let Err(e) = handle_scrape(stream).await {
+            debug!("(metrics.serve) Scrape failed: {e}");
+          }
+        });
+      }
+      Err(e) => {
+        warn!("(metrics.serve) Unable to accept metrics connection: {e}");
+      }
+    }
+  }
+}
+
+async fn handle_scrape(mut stream: TcpStream) -> std::io::Result<()> {
+  let mut head = Vec::new();
+  let mut buf = [0u8; 1024];
+  while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_REQUEST_HEAD {
+    let n = stream.read(&mut buf).await?;
+    if n == 0 {
+      break;
+    }
+    head.extend_from_slice(&buf[..n]);
+  }
+
+  let request_line = String::from_utf8_lossy(&head);
+  let mut parts = request_line.split_whitespace();
+  let (status, body) = match (parts.next(), parts.next()) {
+    (Some("GET"), Some("/metrics")) => ("200 OK", render().into_bytes()),
+    _ => ("404 Not Found", Vec::new()),
+  };
+  let header = format!(
+    "HTTP/1.1 {status}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+    prometheus::TEXT_FORMAT,
+    body.len()
+  );
+  stream.write_all(header.as_bytes()).await?;
+  stream.write_all(&body).await?;
+  stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tracing::{event, Level};
+  use tracing_subscriber::layer::SubscriberExt;
+  use tracing_subscriber::EnvFilter;
+
+  #[test]
+  fn test_render_records() {
+    record_request("ComputePath");
+    record_processor_state(3, &[("alpha".to_string(), 2)]);
+    record_flight_solve(Duration::from_millis(5), 12, false);
+    record_reload(RELOAD_SCENARIOS, true);
+    drop(turn_update_timer());
+
+    let text = render();
+    assert!(text.contains("callisto_requests_total{request=\"ComputePath\"}"));
+    assert!(text.contains("callisto_connections 3"));
+    assert!(text.contains("callisto_server_members{server=\"alpha\"} 2"));
+    assert!(text.contains("callisto_flight_solver_failures_total"));
+    assert!(text.contains("callisto_reload_events_total{outcome=\"success\",source=\"scenarios\"}"));
+    assert!(text.contains("callisto_turn_update_seconds_count"));
+
+    // Expired servers drop out of the per-server gauge.
+    record_processor_state(0, &[]);
+    assert!(!render().contains("server=\"alpha\""));
+  }
+
+  #[test]
+  fn test_auth_metrics_layer() {
+    let count = |result: &str| METRICS.auth_results.with_label_values(&[result]).get();
+    let before = count("Blacklisted");
+
+    // Logging only warnings doesn't stop auth results being counted.
+    let subscriber = tracing_subscriber::registry()
+      .with(
+        tracing_subscriber::fmt::layer()
+          .with_writer(std::io::sink)
+          .with_filter(EnvFilter::new("warn")),
+      )
+      .with(AuthMetricsLayer);
+    tracing::subscriber::with_default(subscriber, || {
+      event!(target: LOG_AUTH_RESULT, Level::INFO, email = "mallory@example.com", result = "Blacklisted");
+      event!(Level::INFO, result = "Blacklisted");
+      event!(target: LOG_AUTH_RESULT, Level::INFO, email = "bot@example.com", result = "TokenMinted");
+    });
+
+    assert_eq!(count("Blacklisted"), before + 1);
+    assert_eq!(count("TokenMinted"), 0);
+  }
+}
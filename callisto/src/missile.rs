@@ -68,7 +68,7 @@ impl Missile {
         );
 
     let acceleration = if let Some(path) = params.compute_target_path() {
-      path.plan.0 .0
+      path.plan.first().0
     } else {
       Vec3::zero()
     };
@@ -165,7 +165,7 @@ impl Entity for Missile {
       );
 
       // This is only safe because of the assertion above.
-      let (accel, time) = next.0.into();
+      let (accel, time) = next.first().clone().into();
       self.acceleration = accel;
 
       let old_velocity: Vec3 = self.velocity;
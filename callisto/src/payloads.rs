@@ -5,11 +5,14 @@
 use std::collections::HashMap;
 
 use super::action::{BoostTarget, ShipAction, ShipActionList};
-use super::computer::FlightPathResult;
-use super::crew::Crew;
+use super::authentication::ApiTokenScope;
+use super::combat::{AttackModifiers, AttackOdds};
+use super::computer::{FlightGoal, FlightPathResult, Waypoint};
+use super::crew::{Crew, Health};
 use super::entity::{Entities, MetaData};
 use super::planet::PlanetVisualEffect;
-use super::ship::ShipDesignTemplate;
+use super::roster::Roster;
+use super::ship::{Range, Sensors, ShipDesignTemplate, ShipSystem, Weapon, WeaponType};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_with::{serde_as, skip_serializing_none};
 use std::fmt::Debug;
@@ -50,6 +53,47 @@ impl Debug for LoginMsg {
   }
 }
 
+/// Log in with a long-lived API token rather than an OAuth code. Used by bots
+/// and other non-browser clients.
+#[derive(Serialize, Deserialize)]
+pub struct ApiTokenLoginMsg {
+  pub token: String,
+}
+
+impl Debug for ApiTokenLoginMsg {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "ApiTokenLoginMsg {{ token: [REDACTED] }}")
+  }
+}
+
+/// Admin-only request to mint an API token acting as `email`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MintApiTokenMsg {
+  pub email: String,
+  #[serde(default)]
+  pub label: String,
+  #[serde(default)]
+  pub scope: ApiTokenScope,
+}
+
+/// Reply to `MintApiToken`. `token` is only ever sent this once.
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct ApiTokenMintedMsg {
+  pub id: String,
+  pub email: String,
+  pub token: String,
+}
+
+impl Debug for ApiTokenMintedMsg {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "ApiTokenMintedMsg {{ id: {}, email: {}, token: [REDACTED] }}",
+      self.id, self.email
+    )
+  }
+}
+
 #[serde_as]
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -123,6 +167,47 @@ pub struct SetPlanMsg {
   pub plan: FlightPlan,
 }
 
+/// Plot the jump of ship `name` for one of the scenario's jump destinations, or clear it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetJumpDestinationMsg {
+  pub name: String,
+  pub destination: Option<String>,
+}
+
+/// Turn ship `name` to point along `facing` whenever it isn't thrusting, or clear its facing.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetFacingMsg {
+  pub name: String,
+  #[serde_as(as = "Option<Vec3asVec>")]
+  pub facing: Option<Vec3>,
+}
+
+/// Put crew member `gunner` of ship `ship_name` at a gunner station in control of `weapons`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssignGunnerMsg {
+  pub ship_name: String,
+  pub gunner: String,
+  pub weapons: Vec<usize>,
+}
+
+/// Put ship `name` from the current scenario on the campaign roster as `id`, owned by the requesting player.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EnlistShipMsg {
+  pub name: String,
+  pub id: String,
+}
+
+/// Repair and/or refuel the player's roster ship `id` between sessions.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RefitMsg {
+  pub id: String,
+  #[serde(default)]
+  pub repair: bool,
+  #[serde(default)]
+  pub refuel: bool,
+}
+
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ComputePathMsg {
@@ -144,9 +229,97 @@ pub struct ComputePathMsg {
   )]
   pub target_acceleration: Option<Vec3>,
   pub standoff_distance: f64,
+  /// Intermediate stops, visited in order before the final rendezvous at `end_pos`.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub waypoints: Vec<WaypointMsg>,
+  /// What to optimize the path for. Defaults to arriving as soon as possible.
+  #[serde(default, skip_serializing_if = "FlightGoal::is_min_time")]
+  pub goal: FlightGoalMsg,
+}
+
+/// Ask for every entity's predicted positions over the next `turns` turns.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectTrajectoriesMsg {
+  pub turns: u16,
+}
+
+/// Predicted positions keyed by entity name. Each list starts with the current position, followed by one
+/// position per projected turn. A missile's list stops on the turn it hits or runs out of fuel.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TrajectoriesMsg {
+  pub turns: u16,
+  #[serde_as(as = "HashMap<_, Vec<Vec3asVec>>")]
+  pub ships: HashMap<String, Vec<Vec3>>,
+  #[serde_as(as = "HashMap<_, Vec<Vec3asVec>>")]
+  pub missiles: HashMap<String, Vec<Vec3>>,
+  #[serde_as(as = "HashMap<_, Vec<Vec3asVec>>")]
+  pub planets: HashMap<String, Vec<Vec3>>,
+  pub intercepts: Vec<MissileInterceptMsg>,
+}
+
+/// A projected missile impact: `missile` reaches `target` at `position` at the end of turn `turn`.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MissileInterceptMsg {
+  pub missile: String,
+  pub target: String,
+  pub turn: u16,
+  #[serde_as(as = "Vec3asVec")]
+  pub position: Vec3,
+}
+
+/// Ask when `first` and `second` (any two entity names) will be nearest over the next `turns` turns.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClosestApproachMsg {
+  pub first: String,
+  pub second: String,
+  pub turns: u16,
+}
+
+/// Closest approach between two entities on their current plans, and the range bands they pass through.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApproachMsg {
+  pub first: String,
+  pub second: String,
+  /// Seconds from now until the closest approach.
+  pub time: f64,
+  /// Distance in meters at the closest approach.
+  pub distance: f64,
+  pub windows: Vec<RangeWindowMsg>,
+}
+
+/// A run of turns, `from_turn` to `to_turn` inclusive (0 is now), spent in one range band.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RangeWindowMsg {
+  pub range: Range,
+  pub from_turn: u16,
+  pub to_turn: u16,
+  /// Weapons on either ship that can reach the other in this band.
+  pub weapons: Vec<EngagingWeaponMsg>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EngagingWeaponMsg {
+  pub ship: String,
+  pub weapon_index: usize,
+  pub weapon: Weapon,
+}
+
+/// Ask for the odds of `attacker`'s weapon `weapon_id` hitting `target` if fired this turn.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HitProbabilityMsg {
+  pub attacker: String,
+  pub weapon_id: usize,
+  pub target: String,
+  #[serde(default)]
+  pub called_shot_system: Option<ShipSystem>,
 }
 
 pub type FlightPathMsg = FlightPathResult;
+pub type AttackOddsMsg = AttackOdds;
+pub type WaypointMsg = Waypoint;
+pub type FlightGoalMsg = FlightGoal;
 pub type ShipActionMsg = ShipActionList;
 
 pub const EMPTY_FIRE_ACTIONS_MSG: ShipActionMsg = vec![];
@@ -198,6 +371,338 @@ pub enum EffectMsg {
     points: i16,
     boosts_applied: Vec<BoostTarget>,
   },
+  /// An attack whose 2d6 `roll` plus `modifiers` fell short of the standard threshold.
+  AttackMiss {
+    attacker: String,
+    target: String,
+    weapon: WeaponType,
+    roll: u8,
+    modifiers: AttackModifiers,
+  },
+  /// A hit whose damage did not get through the target's armor.
+  ArmorAbsorbed {
+    attacker: String,
+    target: String,
+    weapon: WeaponType,
+    roll: u8,
+    modifiers: AttackModifiers,
+    damage_roll: u32,
+  },
+  /// A hit doing `damage` to the target's hull after armor and mount multipliers.
+  AttackHit {
+    attacker: String,
+    target: String,
+    weapon: WeaponType,
+    roll: u8,
+    modifiers: AttackModifiers,
+    damage_roll: u32,
+    damage: u32,
+  },
+  CriticalHit {
+    ship: String,
+    system: ShipSystem,
+    level: u8,
+    effect: CritEffect,
+  },
+  /// `ship` threw sand against `attacker`'s laser.  `reduction` is zero when the sand failed.
+  SandDeployed {
+    ship: String,
+    attacker: String,
+    roll: u8,
+    modifier: i32,
+    success: bool,
+    reduction: i32,
+  },
+  PointDefenseIntercept {
+    ship: String,
+    missile: String,
+  },
+  MissileJammed {
+    ship: String,
+    missile: String,
+  },
+  /// Outcome of a sensor check (`SensorLock`, `JamComms`, `JamMissiles` or `BreakSensorLock`).  Opposed checks
+  /// subtract `opposed_roll`; `modifier` is the net of both sides' modifiers.
+  SensorResult {
+    ship: String,
+    action: ShipAction,
+    roll: u8,
+    opposed_roll: Option<u8>,
+    modifier: i16,
+    threshold: i16,
+    success: bool,
+  },
+  /// Crew member `member` of `ship` is now in `health`, after an injury, a medic's work or bleeding out.
+  CrewInjury {
+    ship: String,
+    member: String,
+    health: Health,
+  },
+  /// The systems `ship`'s power plant can no longer carry, reported when they change; empty once it carries everything
+  /// again.
+  PowerShortfall {
+    ship: String,
+    offline: Vec<String>,
+  },
+  /// `ship`'s sensor actions were dropped because its sensors have no power.
+  SensorsUnpowered {
+    ship: String,
+  },
+  /// `ship`'s `weapon_name` can't fire as it has no power.
+  WeaponUnpowered {
+    ship: String,
+    weapon_name: String,
+  },
+  /// `ship` entered jump space bound for `destination`, where it emerges after `transit_turns` turns; `off_course`
+  /// if a mishap displaced it.
+  ShipJumped {
+    ship: String,
+    destination: String,
+    transit_turns: u32,
+    off_course: bool,
+  },
+  /// `ship` came out of jump space into this scenario.
+  ShipEmerged {
+    ship: String,
+  },
+  /// `target` is outside the firing arc of `ship`'s `weapon`.
+  OutOfArc {
+    ship: String,
+    weapon: String,
+    target: String,
+  },
+  /// `planet` stands between `ship` and `target`, so `ship` can neither fire on nor use its sensors against `target`.
+  LineOfSightBlocked {
+    ship: String,
+    target: String,
+    planet: String,
+  },
+  /// `ship`'s sensor lock on `target` broke when `target` went behind `planet`.
+  SensorLockLost {
+    ship: String,
+    target: String,
+    planet: String,
+  },
+  /// `ship` has only `teams` damage control teams left, too few for its repairs, so it stopped work on `systems`.
+  RepairsAbandoned {
+    ship: String,
+    teams: usize,
+    systems: Vec<ShipSystem>,
+  },
+  /// `ship`'s `weapon_name` can't fire as no gunner is assigned to it.
+  NoGunner {
+    ship: String,
+    weapon_name: String,
+  },
+  /// `ship`'s `weapon_name` can't fire as its gunner `gunner` is already firing another weapon this turn.
+  GunnerBusy {
+    gunner: String,
+    ship: String,
+    weapon_name: String,
+  },
+  /// Named gunner `gunner` takes their attack this turn, firing `ship`'s `weapon_name` at `target`.
+  GunnerFires {
+    gunner: String,
+    ship: String,
+    weapon_name: String,
+    target: String,
+  },
+  /// One entry in the dice audit trail.  Clients may hide these; they are also kept on the server (see `RollLogMsg`).
+  DiceRoll {
+    record: RollRecord,
+  },
+}
+
+/// Why a roll was made.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollPurpose {
+  Attack,
+  Damage,
+  CritLocation,
+  CritEffect,
+  PilotAssist,
+  Sand,
+  SandReduction,
+  PointDefense,
+  SensorLock,
+  JamComms,
+  JamMissiles,
+  BreakSensorLock,
+  Jump,
+  JumpMishap,
+  OverloadDrive,
+  OverloadPlant,
+  Repair,
+  SkimFuel,
+  Medical,
+  Leadership,
+}
+
+/// A single roll of the dice: who rolled, why, the raw result, and every named modifier and the threshold it was
+/// checked against.  Rolls that are not checks (e.g. damage) have no threshold.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RollRecord {
+  pub purpose: RollPurpose,
+  pub actor: String,
+  pub dice: u8,
+  pub roll: u8,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub modifiers: Vec<(String, i32)>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub threshold: Option<i32>,
+}
+
+impl RollRecord {
+  #[must_use]
+  pub fn new(purpose: RollPurpose, actor: &str, dice: u8, roll: u8) -> Self {
+    RollRecord {
+      purpose,
+      actor: actor.to_string(),
+      dice,
+      roll,
+      modifiers: vec![],
+      threshold: None,
+    }
+  }
+
+  /// Add a named modifier.  Modifiers of zero did not affect the roll so are left out.
+  #[must_use]
+  pub fn with_modifier(mut self, name: &str, value: impl Into<i32>) -> Self {
+    let value = value.into();
+    if value != 0 {
+      self.modifiers.push((name.to_string(), value));
+    }
+    self
+  }
+
+  #[must_use]
+  pub fn with_threshold(mut self, threshold: impl Into<i32>) -> Self {
+    self.threshold = Some(threshold.into());
+    self
+  }
+
+  /// The roll plus all its modifiers.
+  #[must_use]
+  pub fn total(&self) -> i32 {
+    i32::from(self.roll) + self.modifiers.iter().map(|(_, value)| value).sum::<i32>()
+  }
+}
+
+/// e.g. "Buccaneer rolled 2D for Attack: 7 +2 skill -1 range = 8 vs 8".
+impl std::fmt::Display for RollRecord {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} rolled {}D for {:?}: {}", self.actor, self.dice, self.purpose, self.roll)?;
+    for (name, value) in &self.modifiers {
+      write!(f, " {value:+} {name}")?;
+    }
+    if let Some(threshold) = self.threshold {
+      write!(f, " = {} vs {threshold}", self.total())?;
+    }
+    Ok(())
+  }
+}
+
+impl From<RollRecord> for EffectMsg {
+  fn from(record: RollRecord) -> Self {
+    EffectMsg::DiceRoll { record }
+  }
+}
+
+/// All the rolls of one game on a server, turn by turn.  The seed of the server's random number generator is only
+/// revealed once the game is over (the scenario is reset), so that every roll can then be re-verified.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GameRollsMsg {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub seed: Option<u64>,
+  pub turns: Vec<Vec<RollRecord>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RollLogMsg {
+  /// Earlier games first; the last entry is the game in progress.
+  pub games: Vec<GameRollsMsg>,
+}
+
+/// What a critical hit did to the system it struck.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "outcome")]
+pub enum CritEffect {
+  /// Crits beyond level 6 go straight to the hull.
+  HullDamage {
+    damage: u32,
+  },
+  AttackDmReduced,
+  Offline,
+  SensorsReduced {
+    sensors: Sensors,
+  },
+  ReducedBy {
+    amount: u32,
+  },
+  ReducedByPercent {
+    percent: u32,
+  },
+  /// `None` when every weapon was already disabled.
+  WeaponDisabled {
+    weapon: Option<Weapon>,
+  },
+  CargoDestroyed {
+    percent: u32,
+  },
+  OccupantsDamaged {
+    damage: Vec<u8>,
+  },
+  AllOccupantsDamaged {
+    dice: u8,
+  },
+  LifeSupportFailsInHours {
+    hours: u8,
+  },
+  LifeSupportFailsInRounds {
+    rounds: u8,
+  },
+  LifeSupportFailed,
+  BridgeSystemDisabled,
+  ComputerReboot,
+  BridgeStationDestroyed {
+    damage: u8,
+  },
+}
+
+impl CritEffect {
+  fn describe(&self) -> String {
+    match self {
+      CritEffect::HullDamage { damage } => format!("caused {damage} damage"),
+      CritEffect::AttackDmReduced => "attack DM reduced by 1".to_string(),
+      CritEffect::Offline => "offline".to_string(),
+      CritEffect::SensorsReduced { sensors } => format!("reduced to {}", String::from(*sensors)),
+      CritEffect::ReducedBy { amount } => format!("reduced by {amount}"),
+      CritEffect::ReducedByPercent { percent } => format!("reduced by {percent}%"),
+      CritEffect::WeaponDisabled { weapon: Some(weapon) } => format!("{} disabled", String::from(weapon)),
+      CritEffect::WeaponDisabled { weapon: None } => "all weapons already disabled".to_string(),
+      CritEffect::CargoDestroyed { percent } if *percent >= 100 => "all cargo destroyed".to_string(),
+      CritEffect::CargoDestroyed { percent } => format!("{percent}% of cargo destroyed"),
+      CritEffect::OccupantsDamaged { damage } if damage.len() == 1 => {
+        format!("random occupant takes {} damage", damage[0])
+      }
+      CritEffect::OccupantsDamaged { damage } => format!(
+        "{} occupants take {} points of damage",
+        damage.len(),
+        damage.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+      ),
+      CritEffect::AllOccupantsDamaged { dice } => {
+        format!("all occupants take {dice}D damage (roll each separately)")
+      }
+      CritEffect::LifeSupportFailsInHours { hours } => format!("life support fails within {hours} hours"),
+      CritEffect::LifeSupportFailsInRounds { rounds } => format!("life support fails in {rounds} rounds"),
+      CritEffect::LifeSupportFailed => "life support fails".to_string(),
+      CritEffect::BridgeSystemDisabled => "random bridge system disabled".to_string(),
+      CritEffect::ComputerReboot => "computer reboots, all software unavailable this round and next".to_string(),
+      CritEffect::BridgeStationDestroyed { damage } => {
+        format!("random bridge station destroyed: occupant takes {damage} damage")
+      }
+    }
+  }
 }
 
 impl EffectMsg {
@@ -205,11 +710,166 @@ impl EffectMsg {
   pub fn message(content: String) -> EffectMsg {
     EffectMsg::Message { content }
   }
+
+  /// Human readable text for effects that are reported to players.  Purely visual effects have none.
+  #[must_use]
+  #[allow(clippy::too_many_lines)]
+  pub fn render(&self) -> Option<String> {
+    match self {
+      EffectMsg::ShipImpact { .. }
+      | EffectMsg::ExhaustedMissile { .. }
+      | EffectMsg::ShipDestroyed { .. }
+      | EffectMsg::BeamHit { .. }
+      | EffectMsg::LeadershipAction { .. } => None,
+      EffectMsg::Message { content } => Some(content.clone()),
+      EffectMsg::EngineerAction { result } => Some(result.message.clone()),
+      EffectMsg::AttackMiss {
+        attacker,
+        target,
+        weapon,
+        ..
+      } => Some(format!("{attacker}'s {} attack misses {target}.", String::from(weapon))),
+      EffectMsg::ArmorAbsorbed {
+        attacker,
+        target,
+        weapon,
+        ..
+      } => Some(format!(
+        "{target} hit by {attacker}'s {} but damage absorbed by armor.",
+        String::from(weapon)
+      )),
+      EffectMsg::AttackHit {
+        target,
+        weapon: WeaponType::Missile,
+        damage,
+        ..
+      } => Some(format!("{target} hit by a missile for {damage} damage.")),
+      EffectMsg::AttackHit {
+        target, weapon, damage, ..
+      } => Some(format!("{target} hit by {} for {damage} damage.", String::from(weapon))),
+      EffectMsg::CriticalHit {
+        ship,
+        level,
+        effect: effect @ CritEffect::HullDamage { .. },
+        ..
+      } => Some(format!("{ship}'s critical hit at level {level} {}.", effect.describe())),
+      EffectMsg::CriticalHit {
+        ship,
+        system,
+        level,
+        effect,
+      } => Some(format!(
+        "{ship}'s {} critical hit (level {level}) and {}.",
+        format!("{system:?}").to_lowercase(),
+        effect.describe()
+      )),
+      EffectMsg::SandDeployed {
+        ship,
+        attacker,
+        success: true,
+        reduction,
+        ..
+      } => Some(format!(
+        "{ship}'s sand successfully deployed against {attacker} reducing damage by {reduction}."
+      )),
+      EffectMsg::SandDeployed { ship, attacker, .. } => {
+        Some(format!("{ship}'s sand failed to deploy against {attacker}."))
+      }
+      EffectMsg::PointDefenseIntercept { ship, missile } => {
+        Some(format!("Missile {missile} destroyed by {ship}'s point defense."))
+      }
+      EffectMsg::MissileJammed { missile, .. } => Some(format!("Missile {missile} destroyed by jamming.")),
+      EffectMsg::DiceRoll { record } => Some(record.to_string()),
+      EffectMsg::ShipJumped {
+        ship,
+        destination,
+        transit_turns,
+        off_course: false,
+      } => Some(format!(
+        "{ship} is in jump space bound for {destination} and will emerge in {transit_turns} turns."
+      )),
+      EffectMsg::ShipJumped {
+        ship,
+        destination,
+        transit_turns,
+        ..
+      } => Some(format!(
+        "{ship} is in jump space bound for {destination} but will emerge off course in {transit_turns} turns."
+      )),
+      EffectMsg::ShipEmerged { ship } => Some(format!("{ship} emerges from jump space.")),
+      EffectMsg::CrewInjury { ship, member, health } => Some(match health {
+        Health::Fit => format!("{member} of {ship} is unhurt."),
+        Health::Wounded => format!("{member} of {ship} is wounded."),
+        Health::Dying { turns_left } => format!("{member} of {ship} is dying with {turns_left} turns left."),
+        Health::Stable => format!("{member} of {ship} is stable but out of action."),
+        Health::Dead => format!("{member} of {ship} is dead."),
+      }),
+      EffectMsg::PowerShortfall { ship, offline } if offline.is_empty() => {
+        Some(format!("{ship}'s power plant carries all its systems again."))
+      }
+      EffectMsg::PowerShortfall { ship, offline } => Some(format!(
+        "{ship} is drawing more power than its plant makes: {} offline.",
+        offline.join(", ")
+      )),
+      EffectMsg::SensorsUnpowered { ship } => Some(format!("{ship}'s sensors have no power and can't be used.")),
+      EffectMsg::WeaponUnpowered { ship, weapon_name } => {
+        Some(format!("{ship}'s {weapon_name} has no power and can't fire."))
+      }
+      EffectMsg::OutOfArc { ship, weapon, target } => {
+        Some(format!("{target} is outside the firing arc of {ship}'s {weapon}."))
+      }
+      EffectMsg::LineOfSightBlocked { ship, target, planet } => {
+        Some(format!("{target} is hidden from {ship} behind {planet}."))
+      }
+      EffectMsg::SensorLockLost { ship, target, planet } => {
+        Some(format!("{ship} loses its sensor lock on {target} behind {planet}."))
+      }
+      EffectMsg::RepairsAbandoned { ship, teams, systems } => Some(format!(
+        "{ship} can only field {teams} damage control teams and abandons repairs to {}.",
+        systems
+          .iter()
+          .map(|system| format!("{system:?}"))
+          .collect::<Vec<_>>()
+          .join(", ")
+      )),
+      EffectMsg::NoGunner { ship, weapon_name } => {
+        Some(format!("{ship}'s {weapon_name} has no gunner and can't fire."))
+      }
+      EffectMsg::GunnerBusy {
+        gunner,
+        ship,
+        weapon_name,
+      } => Some(format!(
+        "{gunner} is already firing this turn so {ship}'s {weapon_name} can't fire."
+      )),
+      EffectMsg::GunnerFires {
+        gunner,
+        ship,
+        weapon_name,
+        target,
+      } => Some(format!("{gunner} fires {ship}'s {weapon_name} at {target}.")),
+      EffectMsg::SensorResult {
+        ship, action, success, ..
+      } => Some(match (action, success) {
+        (ShipAction::SensorLock { target }, true) => format!("Sensor lock on {target} established by {ship}."),
+        (ShipAction::SensorLock { target }, false) => format!("Sensor lock on {target} not established by {ship}."),
+        (ShipAction::JamComms { target }, true) => format!("{ship} is jamming comms on {target}."),
+        (ShipAction::JamComms { target }, false) => format!("{ship} failed to jam comms on {target}."),
+        (ShipAction::BreakSensorLock { target }, true) => format!("{ship} broke {target}'s sensor lock!"),
+        (ShipAction::BreakSensorLock { target }, false) => format!("{ship} failed to break {target}'s sensor lock."),
+        (_, true) => format!("Missile jamming attempt by {ship} succeeded."),
+        (_, false) => format!("Missile jamming attempt by {ship} failed."),
+      }),
+    }
+  }
 }
 
 impl Display for EffectMsg {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(f, "{self:?}")
+    match self.render() {
+      Some(text) => write!(f, "{text}"),
+      None => write!(f, "{self:?}"),
+    }
   }
 }
 
@@ -227,6 +887,27 @@ pub struct EngineerActionResult {
   pub target: u8,
   pub message: String,
   pub critical_failure: bool,
+  /// What went wrong when a failed jump rolled on the mishap table.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub mishap: Option<JumpMishap>,
+}
+
+/// An outcome of the jump mishap table.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum JumpMishap {
+  /// The ship jumps but emerges `offset` from where it was bound, `extra_turns` late.
+  Displaced {
+    #[serde_as(as = "Vec3asVec")]
+    offset: Vec3,
+    extra_turns: u32,
+  },
+  /// The jump fails and damages the drive.
+  DriveDamage,
+  /// The jump fails and the collapsing field does `damage` straight to the hull.
+  HullStress { damage: u32 },
+  /// The ship is lost in jump space.
+  Misjump,
 }
 
 /// Captain hits the "Captain Action" button → server rolls the leadership
@@ -339,12 +1020,26 @@ serde_with::serde_conv!(
 pub enum RequestMsg {
   Login(LoginMsg),
   Register(LoginMsg),
+  ApiTokenLogin(ApiTokenLoginMsg),
+  MintApiToken(MintApiTokenMsg),
+  // Id of the token to revoke.
+  RevokeApiToken(String),
   AddShip(AddShipMsg),
   AddPlanet(AddPlanetMsg),
   Remove(RemoveEntityMsg),
   SetPlan(SetPlanMsg),
+  SetJumpDestination(SetJumpDestinationMsg),
+  SetFacing(SetFacingMsg),
+  RosterRequest,
+  EnlistShip(EnlistShipMsg),
+  Refit(RefitMsg),
   ComputePath(ComputePathMsg),
+  ProjectTrajectories(ProjectTrajectoriesMsg),
+  ClosestApproach(ClosestApproachMsg),
+  HitProbability(HitProbabilityMsg),
+  RollLogRequest,
   SetPilotActions(SetPilotActions),
+  AssignGunner(AssignGunnerMsg),
   SetRole(ChangeRole),
   ModifyActions(ShipActionMsg),
   CaptainAction(CaptainActionMsg),
@@ -368,12 +1063,19 @@ pub enum ResponseMsg {
   DesignTemplateResponse(ShipDesignTemplateMsg),
   EntityResponse(Entities),
   FlightPath(FlightPathMsg),
+  Trajectories(TrajectoriesMsg),
+  Approach(ApproachMsg),
+  AttackOdds(AttackOddsMsg),
+  RollLog(RollLogMsg),
+  Roster(Roster),
   Effects(Vec<EffectMsg>),
   Users(Vec<UserData>),
   LaunchMissile(LaunchMissileMsg),
   Scenarios(ScenariosMsg),
   JoinedScenario(String),
   ScenarioSaved(String),
+  ApiTokenMinted(ApiTokenMintedMsg),
+  ApiTokenRevoked(String),
   CaptainActionResult(CaptainActionResult),
   SimpleMsg(String),
   // LogoutResponse is a faux message never sent back.  However,
@@ -500,6 +1202,8 @@ mod tests {
       target_velocity: None,
       target_acceleration: None,
       standoff_distance: 0.0,
+      waypoints: Vec::new(),
+      goal: FlightGoalMsg::MinTime,
     };
 
     let json = json!({
@@ -525,6 +1229,8 @@ mod tests {
       }),
       target_acceleration: None,
       standoff_distance: 100.0,
+      waypoints: Vec::new(),
+      goal: FlightGoalMsg::MinTime,
     };
 
     let json2 = json!({
@@ -551,6 +1257,8 @@ mod tests {
       }),
       target_acceleration: Some(Vec3 { x: -10.0, y: 0., z: 0. }),
       standoff_distance: 100.0,
+      waypoints: Vec::new(),
+      goal: FlightGoalMsg::MinTime,
     };
 
     let json3 = json!({
@@ -566,6 +1274,60 @@ mod tests {
     assert_eq!(json_str3, json3.to_string());
 
     let _response_msg3 = serde_json::from_str::<ComputePathMsg>(json_str3.as_str()).unwrap();
+
+    // Waypoint velocity and coast are optional.
+    let json4 = json!({
+        "entity_name": "ship1",
+        "end_pos": [0.0, 0.0, 0.0],
+        "end_vel": [0.0, 0.0, 0.0],
+        "standoff_distance": 0.0,
+        "waypoints": [{"position": [1000.0, 0.0, 0.0]}, {"position": [0.0, 2000.0, 0.0], "velocity": [5.0, 0.0, 0.0], "coast": 360}],
+    });
+    let msg4 = serde_json::from_str::<ComputePathMsg>(json4.to_string().as_str()).unwrap();
+    assert_eq!(
+      msg4.waypoints,
+      vec![
+        WaypointMsg {
+          position: Vec3 {
+            x: 1000.0,
+            y: 0.0,
+            z: 0.0
+          },
+          velocity: Vec3::zero(),
+          coast: 0,
+        },
+        WaypointMsg {
+          position: Vec3 {
+            x: 0.0,
+            y: 2000.0,
+            z: 0.0
+          },
+          velocity: Vec3 { x: 5.0, y: 0.0, z: 0.0 },
+          coast: 360,
+        },
+      ]
+    );
+    let json_str4 = serde_json::to_string(&msg4).unwrap();
+    assert_eq!(
+      serde_json::from_str::<ComputePathMsg>(json_str4.as_str()).unwrap().waypoints,
+      msg4.waypoints
+    );
+
+    // Goals other than the default are tagged by kind.
+    let json5 = json!({
+        "entity_name": "ship1",
+        "end_pos": [0.0, 0.0, 0.0],
+        "end_vel": [0.0, 0.0, 0.0],
+        "standoff_distance": 0.0,
+        "goal": {"kind": "MinDeltaV", "arrival_turns": 6},
+    });
+    let msg5 = serde_json::from_str::<ComputePathMsg>(json5.to_string().as_str()).unwrap();
+    assert_eq!(msg5.goal, FlightGoalMsg::MinDeltaV { arrival_turns: 6 });
+    assert_eq!(serde_json::to_value(&msg5).unwrap(), json5);
+    assert_eq!(
+      serde_json::from_value::<FlightGoalMsg>(json!({"kind": "ThrustCap", "thrust": 1.5})).unwrap(),
+      FlightGoalMsg::ThrustCap { thrust: 1.5 }
+    );
   }
 
   #[test_log::test]
@@ -604,6 +1366,74 @@ mod tests {
     assert_eq!(json_str, json.to_string());
   }
 
+  #[test_log::test]
+  fn test_combat_effect_msg() {
+    let msg = EffectMsg::CriticalHit {
+      ship: "ship2".to_string(),
+      system: ShipSystem::Maneuver,
+      level: 2,
+      effect: CritEffect::ReducedBy { amount: 1 },
+    };
+    let json = json!({
+        "kind" : "CriticalHit",
+        "ship" : "ship2",
+        "system" : "Maneuver",
+        "level" : 2,
+        "effect" : { "outcome" : "ReducedBy", "amount" : 1 }
+    });
+    assert_eq!(serde_json::to_value(&msg).unwrap(), json);
+    assert_eq!(serde_json::from_value::<EffectMsg>(json).unwrap(), msg);
+    assert_eq!(msg.to_string(), "ship2's maneuver critical hit (level 2) and reduced by 1.");
+
+    let msg = EffectMsg::SensorResult {
+      ship: "ship1".to_string(),
+      action: ShipAction::SensorLock {
+        target: "ship2".to_string(),
+      },
+      roll: 7,
+      opposed_roll: None,
+      modifier: 1,
+      threshold: 9,
+      success: false,
+    };
+    assert_eq!(msg.to_string(), "Sensor lock on ship2 not established by ship1.");
+
+    let msg = EffectMsg::SandDeployed {
+      ship: "ship2".to_string(),
+      attacker: "ship1".to_string(),
+      roll: 9,
+      modifier: 0,
+      success: true,
+      reduction: 4,
+    };
+    assert_eq!(
+      msg.to_string(),
+      "ship2's sand successfully deployed against ship1 reducing damage by 4."
+    );
+
+    let msg = EffectMsg::from(
+      RollRecord::new(RollPurpose::Attack, "ship1", 2, 7)
+        .with_modifier("skill", 2)
+        .with_modifier("dodge", 0)
+        .with_modifier("range", -1)
+        .with_threshold(8),
+    );
+    assert_eq!(msg.to_string(), "ship1 rolled 2D for Attack: 7 +2 skill -1 range = 8 vs 8");
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+      json,
+      r#"{"kind":"DiceRoll","record":{"purpose":"Attack","actor":"ship1","dice":2,"roll":7,"modifiers":[["skill",2],["range",-1]],"threshold":8}}"#
+    );
+    assert_eq!(serde_json::from_str::<EffectMsg>(&json).unwrap(), msg);
+    assert_eq!(
+      EffectMsg::from(RollRecord::new(RollPurpose::CritEffect, "ship2", 1, 4)).to_string(),
+      "ship2 rolled 1D for CritEffect: 4"
+    );
+
+    // Purely visual effects have nothing to report.
+    assert!(EffectMsg::ExhaustedMissile { position: Vec3::zero() }.render().is_none());
+  }
+
   #[test_log::test]
   fn test_serialize_fire_actions_msg() {
     let msg = vec![
@@ -613,6 +1443,7 @@ mod tests {
           weapon_id: 0,
           target: "ship2".to_string(),
           called_shot_system: None,
+          multiple_attacks: false,
         }],
       ),
       (
@@ -621,6 +1452,7 @@ mod tests {
           weapon_id: 1,
           target: "ship1".to_string(),
           called_shot_system: None,
+          multiple_attacks: false,
         }],
       ),
     ];
@@ -13,6 +13,16 @@ use crate::payloads::Vec3asVec;
 // more widely in this codebase.  So intentionally not "pub"
 const G_CONST: f64 = 6.673e-11;
 
+// Mass bounds (in kg) of a gas giant: 10 Earth masses up to 13 Jupiter masses.
+const GAS_GIANT_MIN_MASS: f64 = 5.97e25;
+const GAS_GIANT_MAX_MASS: f64 = 2.47e28;
+
+// Depth of the atmosphere band a ship can skim fuel from, as a fraction of the planet's radius.
+const ATMOSPHERE_BAND: f64 = 0.1;
+
+// How much faster than circular orbital speed a ship can be moving relative to a planet and still be skimming.
+const SKIMMING_SPEED_TOLERANCE: f64 = 1.25;
+
 /// Deserialize f64 from either a number or a string (including scientific notation)
 fn deserialize_f64_flexible<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
@@ -175,6 +185,58 @@ impl Planet {
     self.gravity_radius_025 = gravity_radius_025;
   }
 
+  /// The DM to a jump made at `position` in this body's gravity well.  None beyond 100 diameters, -2 within them,
+  /// and worse the deeper the ship is: down to -6 inside the 2G radius.
+  #[must_use]
+  pub fn jump_dm(&self, position: Vec3) -> i16 {
+    let distance = (position - self.position).magnitude();
+    if distance > self.radius * 200.0 {
+      return 0;
+    }
+    [
+      (self.gravity_radius_2, -6),
+      (self.gravity_radius_1, -5),
+      (self.gravity_radius_05, -4),
+      (self.gravity_radius_025, -3),
+    ]
+    .into_iter()
+    .find(|(radius, _)| radius.is_some_and(|radius| distance <= radius))
+    .map_or(-2, |(_, dm)| dm)
+  }
+
+  /// A gas giant is anything between 10 Earth masses and the 13 Jupiter masses at which a body starts to burn as a
+  /// (brown dwarf) star.
+  #[must_use]
+  pub fn is_gas_giant(&self) -> bool {
+    (GAS_GIANT_MIN_MASS..GAS_GIANT_MAX_MASS).contains(&self.mass)
+  }
+
+  /// True if a ship at `position` moving at `velocity` is in a skimming orbit of this planet: inside the band of
+  /// upper atmosphere just above its radius, and moving with it no faster than a little over circular orbital speed
+  /// (i.e. not just diving through on a flyby).
+  #[must_use]
+  pub fn in_skimming_orbit(&self, position: Vec3, velocity: Vec3) -> bool {
+    let distance = (position - self.position).magnitude();
+    if distance <= self.radius || distance > self.radius * (1.0 + ATMOSPHERE_BAND) {
+      return false;
+    }
+    let orbital_speed = (G_CONST * self.mass / distance).sqrt();
+    (velocity - self.velocity).magnitude() <= orbital_speed * SKIMMING_SPEED_TOLERANCE
+  }
+
+  /// True if the planet stands between `from` and `to`, i.e. the straight line between them passes through it.
+  #[must_use]
+  pub fn blocks_line_of_sight(&self, from: Vec3, to: Vec3) -> bool {
+    let segment = to - from;
+    let length2 = segment.magnitude2();
+    let along = if length2 > 0.0 {
+      ((self.position - from).dot(segment) / length2).clamp(0.0, 1.0)
+    } else {
+      0.0
+    };
+    (from + segment * along - self.position).magnitude() < self.radius
+  }
+
   /// Get the visual effects as a bitmask for efficient checking
   #[must_use]
   pub fn get_visual_effects_bitmask(&self) -> u32 {
@@ -449,4 +511,25 @@ mod tests {
       vec![PlanetVisualEffect::Continents, PlanetVisualEffect::AtmosphereRing]
     );
   }
+
+  #[test_log::test]
+  fn test_blocks_line_of_sight() {
+    let planet = Planet::new(
+      String::from("Jupiter"),
+      Vec3::zero(),
+      String::from("tan"),
+      6.9911e7,
+      1.898e27,
+      None,
+      &None,
+      0,
+    );
+    let far_side = |x: f64, y: f64| planet.blocks_line_of_sight(Vec3::new(-1e8, 0.0, 0.0), Vec3::new(x, y, 0.0));
+
+    assert!(far_side(1e8, 0.0));
+    assert!(far_side(1e8, 1e8));
+    // A line that passes clear of the planet, or stops short of it.
+    assert!(!far_side(1e8, 3e8));
+    assert!(!far_side(-8e7, 0.0));
+  }
 }
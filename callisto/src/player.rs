@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::result::Result;
 use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use cgmath::InnerSpace;
 use itertools::multiunzip;
@@ -8,16 +9,22 @@ use rand::rngs::SmallRng;
 use rand::SeedableRng;
 
 use crate::action::{boost_target_alive, boost_target_sort_key, merge, BoostMap, BoostTarget, ShipAction};
-use crate::authentication::Authenticator;
-use crate::computer::FlightParams;
-use crate::entity::{Entities, Entity, G};
+use crate::authentication::{mint_api_token, revoke_api_token, ApiTokenGrant, Authenticator};
+use crate::combat::attack_odds;
+use crate::computer::{FlightGoal, FlightParams, MAX_ARRIVAL_TURNS};
+use crate::entity::{Entities, Entity, G, MAX_PROJECTION_TURNS};
+use crate::metrics;
 use crate::payloads::{
-  AddPlanetMsg, AddShipMsg, AuthResponse, CaptainActionMsg, CaptainActionResult, ChangeRole, ComputePathMsg, EffectMsg,
-  FlightPathMsg, LoginMsg, RemoveEntityMsg, Role, SetPilotActions, SetPlanMsg, ShipActionMsg, ShipDesignTemplateMsg,
+  AddPlanetMsg, AddShipMsg, ApiTokenLoginMsg, ApiTokenMintedMsg, ApproachMsg, AssignGunnerMsg, AttackOddsMsg,
+  AuthResponse, CaptainActionMsg, CaptainActionResult, ChangeRole, ClosestApproachMsg, ComputePathMsg, EffectMsg,
+  FlightPathMsg, HitProbabilityMsg, LoginMsg, MintApiTokenMsg, ProjectTrajectoriesMsg, RemoveEntityMsg, Role,
+  RollLogMsg, RollPurpose, RollRecord, SetFacingMsg, SetJumpDestinationMsg, SetPilotActions, SetPlanMsg, ShipActionMsg,
+  ShipDesignTemplateMsg, TrajectoriesMsg,
 };
 use crate::server::Server;
 use crate::ship::{get_ship_templates_snapshot, Ship, ShipDesignTemplate};
-use crate::{debug, info, warn};
+use crate::{debug, info, warn, LOG_AUTH_RESULT};
+use tracing::{event, Level};
 
 /// `PlayerManager` represents a distinct user connected to the server.
 /// It can belong to a single `Server` at a time, or to none.
@@ -32,6 +39,8 @@ pub struct PlayerManager {
   role: Role,
   // Ship this player may have assumed a crew position on.
   ship: Option<String>,
+  // Set when this player logged in with an API token rather than OAuth; limits roles and scenarios.
+  api_token: Option<ApiTokenGrant>,
   test_mode: bool,
 }
 
@@ -45,6 +54,7 @@ impl PlayerManager {
       test_mode,
       role: Role::General,
       ship: None,
+      api_token: None,
     }
   }
 
@@ -53,6 +63,22 @@ impl PlayerManager {
     self.ship = ship;
   }
 
+  /// Put the player back in the default role with no ship: `General`, or the first role their API token allows if
+  /// its scope excludes `General`.
+  pub fn reset_role(&mut self) {
+    let role = match &self.api_token {
+      Some(grant) if !grant.scope.allows_role(Role::General) => grant.scope.roles[0],
+      _ => Role::General,
+    };
+    self.set_role_ship(role, None);
+  }
+
+  /// Whether the player is running the scenario: in the `General` role and not seated on any ship.
+  #[must_use]
+  pub fn is_gm(&self) -> bool {
+    self.role == Role::General && self.ship.is_none()
+  }
+
   pub fn set_server(&mut self, server: Arc<Server>) {
     self.server = Some(server);
   }
@@ -146,6 +172,129 @@ impl PlayerManager {
     })
   }
 
+  /// Log in with a long-lived API token. The token's scope then limits which
+  /// roles this player may take and which scenarios they may join or create.
+  /// If the scope does not allow the default `General` role, the player starts
+  /// in the first role it does allow.
+  ///
+  /// # Errors
+  /// Returns `"NOT_AUTHORIZED"` if the token's owner is blacklisted, otherwise
+  /// an "Unable to authenticate" message.
+  pub async fn login_with_api_token(&mut self, msg: ApiTokenLoginMsg) -> Result<AuthResponse, String> {
+    info!("(PlayerManager.login_with_api_token) Received and processing API token login request.");
+
+    let grant = self.authenticator.authenticate_api_token(&msg.token).await.map_err(|e| {
+      if e.is::<crate::authentication::BlacklistedUserError>() {
+        "NOT_AUTHORIZED".to_string()
+      } else {
+        format!("(PlayerManager.login_with_api_token) Unable to authenticate user: {e:?}")
+      }
+    })?;
+
+    if !grant.scope.allows_role(self.role) {
+      self.set_role_ship(grant.scope.roles[0], None);
+    }
+    let email = grant.email.clone();
+    let role = self.role;
+    self.api_token = Some(grant);
+
+    Ok(AuthResponse {
+      email,
+      scenario: None,
+      role: Some(role),
+      ship: None,
+    })
+  }
+
+  /// Mint an API token for another user. Only admins in the user directory
+  /// may do this, and never from an API token session.
+  ///
+  /// # Errors
+  /// Returns an error if the caller is not an admin, the target user is not
+  /// active, or the users file cannot be updated.
+  pub async fn mint_api_token(&mut self, msg: MintApiTokenMsg) -> Result<ApiTokenMintedMsg, String> {
+    let admin = self.require_admin("mint")?;
+    let now = unix_now();
+    let mut minted = None;
+    self
+      .authenticator
+      .update_user_records(&mut |records| {
+        minted = Some(mint_api_token(records, &msg.email, &msg.label, msg.scope.clone(), now)?);
+        Ok(())
+      })
+      .await?;
+    let (id, token) = minted.ok_or("(PlayerManager.mint_api_token) Token was not minted.")?;
+
+    let email = msg.email.to_lowercase();
+    event!(
+      target: LOG_AUTH_RESULT,
+      Level::INFO,
+      email = email.as_str(),
+      admin = admin.as_str(),
+      token_id = id.as_str(),
+      result = "TokenMinted"
+    );
+    Ok(ApiTokenMintedMsg { id, email, token })
+  }
+
+  /// Revoke an API token by id. Admin only, as for [`PlayerManager::mint_api_token`].
+  /// Live sessions using the token are disconnected on the next users-file reload.
+  ///
+  /// # Errors
+  /// Returns an error if the caller is not an admin, the token does not exist,
+  /// or the users file cannot be updated.
+  pub async fn revoke_api_token(&mut self, id: &str) -> Result<String, String> {
+    let admin = self.require_admin("revoke")?;
+    let now = unix_now();
+    let mut owner = None;
+    self
+      .authenticator
+      .update_user_records(&mut |records| {
+        owner = Some(revoke_api_token(records, id, now)?);
+        Ok(())
+      })
+      .await?;
+
+    event!(
+      target: LOG_AUTH_RESULT,
+      Level::INFO,
+      email = owner.unwrap_or_default().as_str(),
+      admin = admin.as_str(),
+      token_id = id,
+      result = "TokenRevoked"
+    );
+    Ok(id.to_string())
+  }
+
+  fn require_admin(&self, verb: &str) -> Result<String, String> {
+    let email = self.get_email().unwrap_or_default();
+    let is_admin = self
+      .authenticator
+      .directory_snapshot()
+      .is_some_and(|directory| directory.is_admin(&email));
+    if self.api_token.is_some() || !is_admin {
+      warn!("(PlayerManager.require_admin) {email} is not allowed to {verb} API tokens.");
+      return Err("NOT_AUTHORIZED".to_string());
+    }
+    Ok(email)
+  }
+
+  /// Id of the API token this player logged in with, if any.
+  #[must_use]
+  pub fn api_token_id(&self) -> Option<&str> {
+    self.api_token.as_ref().map(|grant| grant.id.as_str())
+  }
+
+  /// Whether this player may join or create `scenario`. Always true unless
+  /// logged in with a scenario-scoped API token.
+  #[must_use]
+  pub fn scenario_allowed(&self, scenario: &str) -> bool {
+    self
+      .api_token
+      .as_ref()
+      .is_none_or(|grant| grant.scope.allows_scenario(scenario))
+  }
+
   /// Reset a server to its initial configuration.
   ///
   /// # Errors
@@ -154,14 +303,18 @@ impl PlayerManager {
   /// # Panics
   /// Panics if the lock on entities cannot be obtained or if the server has never been initialized.
   pub fn reset(&self) -> Result<String, String> {
-    if self.role == Role::General && self.ship.is_none() {
+    if self.is_gm() {
       info!("(PlayerManager.reset) Received and processing reset request: Resetting server!");
       self
         .server
         .as_ref()
         .unwrap()
         .initial_scenario
+        .lock()
+        .unwrap()
         .deep_copy_into(&mut self.server.as_ref().unwrap().get_unlocked_entities().unwrap());
+      // The scenario is over, so reveal the seed behind its rolls.
+      self.server.as_ref().unwrap().end_game();
       Ok("Server reset.".to_string())
     } else {
       warn!(
@@ -181,6 +334,7 @@ impl PlayerManager {
   pub fn logout(&mut self, session_keys: &Arc<Mutex<HashMap<String, Option<String>>>>) {
     info!("(PlayerManager.logout) Received and processing logout request.",);
     self.authenticator.set_email(None);
+    self.api_token = None;
     let mut keys = session_keys.lock().unwrap();
     if let Some(session_key) = self.authenticator.get_session_key() {
       keys.remove(&session_key);
@@ -271,6 +425,92 @@ impl PlayerManager {
     self.server.as_ref().unwrap().get_unlocked_entities().unwrap().clone()
   }
 
+  /// Project every ship, missile and planet `msg.turns` turns ahead, without touching the live scenario.
+  ///
+  /// # Errors
+  /// Returns an error if the player hasn't joined a scenario or asks for too many (or no) turns.
+  ///
+  /// # Panics
+  /// Panics if the lock on entities cannot be obtained.
+  pub fn project_trajectories(&self, msg: &ProjectTrajectoriesMsg) -> Result<TrajectoriesMsg, String> {
+    let Some(server) = self.server.as_ref() else {
+      return Err("Cannot project trajectories without joining a scenario".to_string());
+    };
+    if msg.turns == 0 || msg.turns > MAX_PROJECTION_TURNS {
+      return Err(format!(
+        "Trajectories can be projected 1 to {MAX_PROJECTION_TURNS} turns ahead, not {}",
+        msg.turns
+      ));
+    }
+
+    // Copy under the lock, then project on the copy so turn processing isn't held up.
+    let snapshot = server.get_unlocked_entities().unwrap().deep_copy();
+    Ok(snapshot.project_trajectories(msg.turns))
+  }
+
+  /// Find when two entities will be nearest over the next `msg.turns` turns and the range bands they pass through.
+  ///
+  /// # Errors
+  /// Returns an error if the player hasn't joined a scenario, asks for too many (or no) turns, or names an
+  /// entity that doesn't exist.
+  ///
+  /// # Panics
+  /// Panics if the lock on entities cannot be obtained.
+  pub fn closest_approach(&self, msg: &ClosestApproachMsg) -> Result<ApproachMsg, String> {
+    let Some(server) = self.server.as_ref() else {
+      return Err("Cannot compute a closest approach without joining a scenario".to_string());
+    };
+    if msg.turns == 0 || msg.turns > MAX_PROJECTION_TURNS {
+      return Err(format!(
+        "Closest approach can look 1 to {MAX_PROJECTION_TURNS} turns ahead, not {}",
+        msg.turns
+      ));
+    }
+
+    let snapshot = server.get_unlocked_entities().unwrap().deep_copy();
+    snapshot.closest_approach(&msg.first, &msg.second, msg.turns)
+  }
+
+  /// Odds of one ship's weapon hitting another this turn.  Leadership boosts are not known until the captain's checks
+  /// resolve at the end of the turn, so none are assumed.  A planet between the ships hides the target, so the odds
+  /// are nil.
+  ///
+  /// # Errors
+  /// Returns an error if not in a scenario, either ship does not exist, or the weapon does not exist.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to read the entities.
+  pub fn hit_probability(&self, msg: &HitProbabilityMsg) -> Result<AttackOddsMsg, String> {
+    let Some(server) = self.server.as_ref() else {
+      return Err("Cannot compute hit probability without joining a scenario".to_string());
+    };
+    let entities = server.get_unlocked_entities().unwrap();
+    let Some(attacker) = entities.ships.get(&msg.attacker) else {
+      return Err(format!("No such ship {}", msg.attacker));
+    };
+    let Some(target) = entities.ships.get(&msg.target) else {
+      return Err(format!("No such ship {}", msg.target));
+    };
+    let attacker = attacker.read().unwrap();
+    if msg.weapon_id >= attacker.design.weapons.len() {
+      return Err(format!("{} has no weapon {}", msg.attacker, msg.weapon_id));
+    }
+    let target = target.read().unwrap();
+
+    let occluded_by = entities.occluding_planet(attacker.get_position(), target.get_position());
+    Ok(
+      attack_odds(
+        &attacker,
+        msg.weapon_id,
+        &target,
+        msg.called_shot_system.as_ref(),
+        &BoostMap::default(),
+        entities.rules(),
+      )
+      .with_occlusion(occluded_by),
+    )
+  }
+
   /// Get the entities marshalled into JSON
   ///
   /// # Panics
@@ -379,6 +619,63 @@ impl PlayerManager {
       .map(|()| "Set acceleration action executed".to_string())
   }
 
+  /// Plots a ship's jump for one of the scenario's jump destinations.
+  ///
+  /// # Errors
+  /// Returns an error if the ship or destination doesn't exist.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to read the entities or if the server
+  /// has not yet been initialized.
+  pub fn set_jump_destination(&self, msg: &SetJumpDestinationMsg) -> Result<String, String> {
+    self
+      .server
+      .as_ref()
+      .unwrap()
+      .get_unlocked_entities()
+      .unwrap()
+      .set_jump_destination(&msg.name, msg.destination.as_deref())
+      .map(|()| "Set jump destination action executed".to_string())
+  }
+
+  /// Turns a ship to a facing it keeps whenever it isn't thrusting.
+  ///
+  /// # Errors
+  /// Returns an error if the ship doesn't exist or the facing has no direction.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to read the entities or if the server
+  /// has not yet been initialized.
+  pub fn set_facing(&self, msg: &SetFacingMsg) -> Result<String, String> {
+    self
+      .server
+      .as_ref()
+      .unwrap()
+      .get_unlocked_entities()
+      .unwrap()
+      .set_facing(&msg.name, msg.facing)
+      .map(|()| "Set facing action executed".to_string())
+  }
+
+  /// Reassigns a ship's weapons to one of its gunners.
+  ///
+  /// # Errors
+  /// Returns an error if the ship, gunner or a weapon doesn't exist, or the gunner can't serve.
+  ///
+  /// # Panics
+  /// Panics if the lock cannot be obtained to read the entities or if the server
+  /// has not yet been initialized.
+  pub fn assign_gunner(&self, msg: &AssignGunnerMsg) -> Result<String, String> {
+    self
+      .server
+      .as_ref()
+      .unwrap()
+      .get_unlocked_entities()
+      .unwrap()
+      .assign_gunner(&msg.ship_name, &msg.gunner, &msg.weapons)
+      .map(|()| "Assign gunner action executed".to_string())
+  }
+
   /// Merge in new actions (orders) for ships in the next round.  These may come for the same ship from
   /// different clients depending on how the clients are being used.  We save these till the next update action.
   ///
@@ -410,7 +707,7 @@ impl PlayerManager {
   /// is not initialized.
   #[must_use]
   pub fn captain_action(&self, msg: &CaptainActionMsg) -> CaptainActionResult {
-    let mut rng = get_rng(self.test_mode);
+    let mut rng = self.rng();
     let entities = self.server.as_ref().unwrap().get_unlocked_entities().unwrap();
     let Some(ship) = entities.ships.get(&msg.ship_name) else {
       return CaptainActionResult {
@@ -420,9 +717,17 @@ impl PlayerManager {
       };
     };
     let leadership = i16::from(ship.read().unwrap().get_crew().get_leadership());
-    let roll = i16::from(crate::combat::roll_dice(2, &mut rng));
+    let dice = crate::combat::roll_dice(2, &mut rng);
+    let roll = i16::from(dice);
     let points = roll + leadership - 8;
     ship.write().unwrap().set_leadership_points(points);
+    self
+      .server
+      .as_ref()
+      .unwrap()
+      .log_rolls([RollRecord::new(RollPurpose::Leadership, &msg.ship_name, 2, dice)
+        .with_modifier("leadership", leadership)
+        .with_threshold(8)]);
 
     let message = if points > 0 {
       format!(
@@ -450,7 +755,8 @@ impl PlayerManager {
   #[must_use]
   #[allow(clippy::too_many_lines)]
   pub fn update(&self) -> Vec<EffectMsg> {
-    let mut rng = get_rng(self.test_mode);
+    let _timer = metrics::turn_update_timer();
+    let mut rng = self.rng();
 
     // Grab the lock on entities
     let mut entities = self
@@ -516,9 +822,10 @@ impl PlayerManager {
     debug!("(/update) Ship actions: {:?}", actions);
 
     // Sort all the actions by type.  Slice into fire / sensor / point-defense /
-    // engineer (Jump is an engineer action, so it lands in the engineer slice).
+    // engineer / medical (Jump is an engineer action, so it lands in the engineer slice).
     #[allow(clippy::type_complexity)]
-    let (fire_actions, sensor_actions, point_defense_actions, engineer_actions): (
+    let (fire_actions, sensor_actions, point_defense_actions, engineer_actions, medical_actions): (
+      Vec<(String, Vec<ShipAction>)>,
       Vec<(String, Vec<ShipAction>)>,
       Vec<(String, Vec<ShipAction>)>,
       Vec<(String, Vec<ShipAction>)>,
@@ -528,29 +835,37 @@ impl PlayerManager {
         warn!("(update) Cannot find ship {} for actions.", ship_name);
         return None;
       }
-      let (f_actions, s_actions, p_actions, e_actions): (
+      let (f_actions, s_actions, p_actions, e_actions, m_actions): (
+        Vec<Option<ShipAction>>,
         Vec<Option<ShipAction>>,
         Vec<Option<ShipAction>>,
         Vec<Option<ShipAction>>,
         Vec<Option<ShipAction>>,
       ) = multiunzip(actions.iter().map(|action| match action {
-        ShipAction::FireAction { .. } | ShipAction::DeleteFireAction { .. } => (Some(action.clone()), None, None, None),
-        ShipAction::PointDefenseAction { .. } => (None, None, Some(action.clone()), None),
+        ShipAction::FireAction { .. } | ShipAction::DeleteFireAction { .. } => {
+          (Some(action.clone()), None, None, None, None)
+        }
+        ShipAction::PointDefenseAction { .. } => (None, None, Some(action.clone()), None, None),
         ShipAction::JamMissiles
         | ShipAction::BreakSensorLock { .. }
         | ShipAction::SensorLock { .. }
-        | ShipAction::JamComms { .. } => (None, Some(action.clone()), None, None),
+        | ShipAction::JamComms { .. } => (None, Some(action.clone()), None, None, None),
         // Engineer actions (including Jump) are deferred to end-of-turn evaluation.
-        ShipAction::OverloadDrive | ShipAction::OverloadPlant | ShipAction::Repair { .. } | ShipAction::Jump => {
-          (None, None, None, Some(action.clone()))
-        }
+        ShipAction::OverloadDrive
+        | ShipAction::OverloadPlant
+        | ShipAction::Repair { .. }
+        | ShipAction::Jump
+        | ShipAction::SkimFuel
+        | ShipAction::TransferFuel { .. }
+        | ShipAction::AllocatePower { .. } => (None, None, None, Some(action.clone()), None),
+        ShipAction::Stabilize { .. } => (None, None, None, None, Some(action.clone())),
         // LeadershipCheck is consumed in Phase 0 below; it does not flow into
         // any of the per-category slices.
-        ShipAction::LeadershipCheck { .. } => (None, None, None, None),
+        ShipAction::LeadershipCheck { .. } => (None, None, None, None, None),
         // Anti-actions are consumed by `merge` and should never reach the queue.
         // If one slips through, drop it from every slice.
         ShipAction::ClearSensorAction | ShipAction::ClearEngineerAction | ShipAction::ClearLeadershipCheck => {
-          (None, None, None, None)
+          (None, None, None, None, None)
         }
       }));
       Some((
@@ -558,6 +873,7 @@ impl PlayerManager {
         (ship_name.clone(), s_actions.into_iter().flatten().collect::<Vec<ShipAction>>()),
         (ship_name.clone(), p_actions.into_iter().flatten().collect::<Vec<ShipAction>>()),
         (ship_name.clone(), e_actions.into_iter().flatten().collect::<Vec<ShipAction>>()),
+        (ship_name.clone(), m_actions.into_iter().flatten().collect::<Vec<ShipAction>>()),
       ))
     }));
 
@@ -611,11 +927,48 @@ impl PlayerManager {
     // existing Effects channel.
     effects.append(&mut entities.engineer_actions(&engineer_actions, &boost_map, &mut rng));
 
+    // Medics see to the dying, and those no one saved lose ground.
+    effects.append(&mut entities.medical_actions(&medical_actions, &mut rng));
+
+    // Ships that jumped here from another scenario come out of jump space once their transit is over.
+    effects.append(&mut self.server.as_ref().unwrap().emerge_arrivals(&mut entities));
+
     entities.reset_actions();
 
+    let server = self.server.as_ref().unwrap();
+    server.log_rolls(effects.iter().filter_map(|effect| match effect {
+      EffectMsg::DiceRoll { record } => Some(record.clone()),
+      _ => None,
+    }));
+    server.end_turn();
+
     effects
   }
 
+  /// Random number generator for a batch of rolls, drawn from the server's generator.
+  fn rng(&self) -> SmallRng {
+    if self.test_mode {
+      info!("(Player.rng) Server in TEST mode for random numbers (constant seed of 0).");
+      // Use 0 to seed all test case random number generators.
+      SmallRng::seed_from_u64(0)
+    } else {
+      debug!("(Player.rng) Server in standard mode for random numbers.");
+      self.server.as_ref().unwrap().next_rng()
+    }
+  }
+
+  /// The audit trail of every roll made on this server.
+  ///
+  /// # Errors
+  /// Returns an error if not in a scenario.
+  pub fn roll_log(&self) -> Result<RollLogMsg, String> {
+    self
+      .server
+      .as_ref()
+      .map(|server| server.roll_log())
+      .ok_or_else(|| "Cannot get the roll log without joining a scenario".to_string())
+  }
+
   /// Computes a flight path for a ship.
   ///
   /// # Arguments
@@ -651,8 +1004,18 @@ impl PlayerManager {
       )
     };
 
+    let max_accel = match msg.goal {
+      FlightGoal::ThrustCap { thrust } if thrust <= 0.0 => {
+        return Err(format!("Thrust cap must be positive, not {thrust}"));
+      }
+      FlightGoal::ThrustCap { thrust } => max_accel.min(G * f64::from(thrust)),
+      FlightGoal::MinTime | FlightGoal::MinDeltaV { .. } => max_accel,
+    };
+
+    // Standoff is measured along the final leg, so from the last waypoint when there is one.
+    let final_leg_start = msg.waypoints.last().map_or(start_pos, |waypoint| waypoint.position);
     let adjusted_end_pos = if msg.standoff_distance > 0.0 {
-      msg.end_pos - (msg.end_pos - start_pos).normalize() * msg.standoff_distance
+      msg.end_pos - (msg.end_pos - final_leg_start).normalize() * msg.standoff_distance
     } else {
       msg.end_pos
     };
@@ -670,18 +1033,36 @@ impl PlayerManager {
       msg.target_velocity,
       msg.target_acceleration,
       max_accel,
-    );
+    )
+    .with_warm_start(self.server.as_ref().unwrap().flight_seed(&msg.entity_name));
 
     debug!("(/compute_path) Call computer with params: {:?}", params);
 
-    let Ok(plan) = params.compute_flight_path() else {
-      return Err(format!("Unable to compute flight path: {params:?}"));
+    let plan = if let FlightGoal::MinDeltaV { arrival_turns } = msg.goal {
+      if !msg.waypoints.is_empty() {
+        return Err("An arrival time cannot be combined with waypoints".to_string());
+      }
+      if arrival_turns == 0 || arrival_turns > MAX_ARRIVAL_TURNS {
+        return Err(format!(
+          "Arrival can be planned 1 to {MAX_ARRIVAL_TURNS} turns ahead, not {arrival_turns}"
+        ));
+      }
+      params.compute_fixed_arrival_path(arrival_turns).map_err(|e| e.to_string())?
+    } else {
+      let Ok(plan) = params.compute_waypoint_path(&msg.waypoints) else {
+        return Err(format!("Unable to compute flight path: {params:?}"));
+      };
+      plan
     };
 
+    if let Some(seed) = plan.as_seed() {
+      self.server.as_ref().unwrap().remember_flight_seed(&msg.entity_name, seed);
+    }
+
     debug!("(/compute_path) Plan: {:?}", plan);
     debug!(
       "(/compute_path) Plan has real acceleration of {} vs max_accel of {}",
-      plan.plan.0 .0.magnitude(),
+      plan.plan.first().0.magnitude(),
       max_accel
     );
 
@@ -698,22 +1079,24 @@ impl PlayerManager {
     (self.role, self.ship.clone())
   }
 
-  pub fn set_role(&mut self, msg: &ChangeRole) -> String {
+  /// Set the role (and ship) this player has assumed.
+  ///
+  /// # Errors
+  /// Returns an error if the player logged in with an API token whose scope excludes the role.
+  pub fn set_role(&mut self, msg: &ChangeRole) -> Result<String, String> {
+    if self.api_token.as_ref().is_some_and(|grant| !grant.scope.allows_role(msg.role)) {
+      return Err(format!("API token is not scoped for role {:?}.", msg.role));
+    }
     self.role = msg.role;
     self.ship.clone_from(&msg.ship);
-    "Role set".to_string()
+    Ok("Role set".to_string())
   }
 }
 
-fn get_rng(test_mode: bool) -> SmallRng {
-  if test_mode {
-    info!("(lib.get_rng) Server in TEST mode for random numbers (constant seed of 0).");
-    // Use 0 to seed all test case random number generators.
-    SmallRng::seed_from_u64(0)
-  } else {
-    debug!("(lib.get_rng) Server in standard mode for random numbers.");
-    SmallRng::from_entropy()
-  }
+fn unix_now() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use std::boxed::Box;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
 
 use futures::channel::mpsc::{Receiver, UnboundedReceiver};
 use futures::select;
@@ -20,11 +21,15 @@ use dyn_clone::clone_box;
 
 use crate::authentication::{Authenticator, UserDirectory};
 
-use crate::entity::MetaData;
-use crate::payloads::{AuthResponse, RequestMsg, ResponseMsg, SaveScenarioMsg, ScenariosMsg};
+use crate::entity::{Departure, Entity, MetaData};
+use crate::metrics;
+use crate::payloads::{AuthResponse, EnlistShipMsg, RefitMsg, RequestMsg, ResponseMsg, SaveScenarioMsg, ScenariosMsg};
 use crate::player::PlayerManager;
+use crate::roster::{load_roster, update_roster, Roster};
 use crate::server::{Server, ServerMembersTable};
-use crate::{get_scenarios_snapshot, read_local_or_cloud_file, replace_scenarios, write_local_or_cloud_file};
+use crate::ship::Ship;
+use crate::storage::{join_path, StorageBackend};
+use crate::{get_scenarios_snapshot, replace_scenarios};
 use crate::{LOGOUT, LOG_FILE_USE};
 
 #[cfg(feature = "no_tls_upgrade")]
@@ -46,6 +51,11 @@ pub struct Processor {
 
   // Unchanging value with directory for all scenarios.
   scenario_dir: String,
+  // Backend holding `scenario_dir`.
+  scenario_storage: Arc<dyn StorageBackend>,
+  // Campaign roster file and the backend holding it.
+  roster_file: String,
+  roster_storage: Arc<dyn StorageBackend>,
 
   /// Shared user-directory cell. Mirrors the one inside the auth template so
   /// kick-on-reload can read the latest blacklist without going through an
@@ -101,11 +111,13 @@ impl Processor {
   /// Panics if the `directory_handle` `RwLock` is poisoned at construction
   /// (process startup; never expected in normal operation).
   #[must_use]
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     connection_receiver: Receiver<(WebSocketStream<SubStream>, String, Option<String>)>,
     reload_receiver: UnboundedReceiver<ReloadNotification>, auth_template: Box<dyn Authenticator>,
-    session_keys: Arc<Mutex<HashMap<String, Option<String>>>>, scenario_dir: &str, test_mode: bool,
-    directory_handle: Arc<RwLock<Arc<UserDirectory>>>,
+    session_keys: Arc<Mutex<HashMap<String, Option<String>>>>, scenario_dir: &str,
+    scenario_storage: Arc<dyn StorageBackend>, roster_file: &str, roster_storage: Arc<dyn StorageBackend>,
+    test_mode: bool, directory_handle: Arc<RwLock<Arc<UserDirectory>>>,
   ) -> Self {
     // Clean up scenario_dir so that it does not have a trailing slash.
     let scenario_dir = scenario_dir.trim_end_matches('/').to_string();
@@ -123,6 +135,9 @@ impl Processor {
       servers: HashMap::new(),
       members: ServerMembersTable::new(),
       scenario_dir,
+      scenario_storage,
+      roster_file: roster_file.to_string(),
+      roster_storage,
       directory_handle,
       last_directory_seen,
       test_mode,
@@ -149,7 +164,9 @@ impl Processor {
 
     loop {
       // In here, clean up old scenarios that haven't had anyone in them for 5 minutes.
-      let removed_scenario = self.members.clean_expired_scenarios();
+      let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+      let removed_scenario = self.expire_scenarios(now).await;
+      metrics::record_processor_state(connections.len(), &self.members.member_counts());
 
       // If there are no connections, then we wait for one to come in.
       // Special case as waiting on an empty FuturesUnordered will not wait - just returns None.
@@ -392,28 +409,25 @@ impl Processor {
           .clone();
 
         // Walk connections and force-disconnect any whose email is now in
-        // the blacklist set. We collect indexes first so we can mutate the
-        // vector after iteration without trying to hold a mutable borrow
-        // across the await boundary.
-        let mut to_disconnect: Vec<usize> = Vec::new();
+        // the blacklist set, or whose API token is no longer live. We collect
+        // indexes first so we can mutate the vector after iteration without
+        // trying to hold a mutable borrow across the await boundary.
+        let mut to_disconnect: Vec<(usize, &str)> = Vec::new();
         for (i, connection) in connections.iter().enumerate() {
           let Some(email) = connection.player.get_email() else {
             continue;
           };
           if new_dir.is_blacklisted(&email) {
-            to_disconnect.push(i);
+            to_disconnect.push((i, "forced: blacklisted"));
+          } else if connection.player.api_token_id().is_some_and(|id| !new_dir.api_token_live(id)) {
+            to_disconnect.push((i, "forced: api token revoked"));
           }
         }
 
         // Iterate in reverse so removals don't shift later indexes.
-        for &i in to_disconnect.iter().rev() {
+        for &(i, action) in to_disconnect.iter().rev() {
           let email = connections[i].player.get_email().unwrap_or_default();
-          event!(
-            target: LOGOUT,
-            Level::INFO,
-            email = email.as_str(),
-            action = "forced: blacklisted"
-          );
+          event!(target: LOGOUT, Level::INFO, email = email.as_str(), action);
           // Clear the session key entry then close the socket. Mirrors
           // RequestMsg::Logout.
           connections[i].player.logout(&self.session_keys);
@@ -580,7 +594,9 @@ impl Processor {
   // a lot of the codebase.  So excluding those two clippy warnings.
   #[allow(clippy::too_many_lines, clippy::needless_lifetimes, clippy::implicit_hasher)]
   pub async fn handle_request(&mut self, message: RequestMsg, player: &mut PlayerManager) -> Vec<ResponseMsg> {
-    event!(Level::INFO, request = Into::<&str>::into(&message), contents = ?message);
+    let request_name: &str = (&message).into();
+    event!(Level::INFO, request = request_name, contents = ?message);
+    metrics::record_request(request_name);
 
     // If the connection has not logged in yet, that is the priority.
     // Nothing else is processed until login is complete. Register also
@@ -588,6 +604,7 @@ impl Processor {
     if !player.validated_user()
       && !matches!(message, RequestMsg::Login(_))
       && !matches!(message, RequestMsg::Register(_))
+      && !matches!(message, RequestMsg::ApiTokenLogin(_))
       && !matches!(message, RequestMsg::Quit)
       && !matches!(message, RequestMsg::ValidateSession)
     {
@@ -614,12 +631,35 @@ impl Processor {
           self.build_successful_auth_msgs(player, auth_response)
         }),
 
-      RequestMsg::Reset => response_with_update(player, player.reset()),
+      RequestMsg::ApiTokenLogin(token_msg) => player
+        .login_with_api_token(token_msg)
+        .await
+        .map_or_else(error_msg, |auth_response| {
+          self.build_successful_auth_msgs(player, auth_response)
+        }),
+      RequestMsg::MintApiToken(mint_msg) => player
+        .mint_api_token(mint_msg)
+        .await
+        .map_or_else(error_msg, |minted| vec![ResponseMsg::ApiTokenMinted(minted)]),
+      RequestMsg::RevokeApiToken(id) => player
+        .revoke_api_token(&id)
+        .await
+        .map_or_else(error_msg, |id| vec![ResponseMsg::ApiTokenRevoked(id)]),
+
+      RequestMsg::Reset => self.handle_reset(player).await,
       RequestMsg::AddShip(ship) => response_with_update(player, player.add_ship(ship)),
       RequestMsg::SetPilotActions(request) => response_with_update(player, player.set_pilot_actions(&request)),
+      RequestMsg::AssignGunner(msg) => response_with_update(player, player.assign_gunner(&msg)),
       RequestMsg::AddPlanet(planet) => response_with_update(player, player.add_planet(planet)),
       RequestMsg::Remove(name) => response_with_update(player, player.remove(&name)),
       RequestMsg::SetPlan(plan) => response_with_update(player, player.set_plan(&plan)),
+      RequestMsg::SetJumpDestination(msg) => response_with_update(player, player.set_jump_destination(&msg)),
+      RequestMsg::SetFacing(msg) => response_with_update(player, player.set_facing(&msg)),
+      RequestMsg::RosterRequest => load_roster(self.roster_storage.as_ref(), &self.roster_file)
+        .await
+        .map_or_else(error_msg, |(roster, _)| vec![ResponseMsg::Roster(roster)]),
+      RequestMsg::EnlistShip(msg) => self.handle_enlist_ship(player, msg).await,
+      RequestMsg::Refit(msg) => self.handle_refit(player, msg).await,
       RequestMsg::SetRole(role) => {
         if player.get_email().is_none() {
           error!("(handle_request) Attempt to set role without being logged in.  Ignoring.");
@@ -627,7 +667,11 @@ impl Processor {
             "Attempt to set role without being logged in.  Ignoring.".to_string(),
           )]
         } else {
-          let mut msgs = simple_response(Ok(player.set_role(&role)));
+          let result = player.set_role(&role);
+          if result.is_err() {
+            return simple_response(result);
+          }
+          let mut msgs = simple_response(result);
           msgs.append(&mut player.server.as_ref().map_or_else(
             || error_msg("Cannot set role when no server has yet been joined.".to_string()),
             |server| {
@@ -661,14 +705,32 @@ impl Processor {
       }
       RequestMsg::Update => {
         let effects = player.update();
-        vec![
+        let mut msgs = vec![
           ResponseMsg::Effects(effects),
           ResponseMsg::EntityResponse(player.clone_entities()),
-        ]
+        ];
+        if let Some(server) = player.server.clone() {
+          // Roster ships destroyed or jumped away this turn are written back before they are gone.
+          let departed = server.get_unlocked_entities().unwrap().take_roster_departures();
+          self.record_roster_ships(&departed).await;
+          self.advance_unattended_arrivals(&server);
+          msgs.append(&mut self.dispatch_departures(&server).await);
+        }
+        msgs
       }
       RequestMsg::ComputePath(path_goal) => player
         .compute_path(&path_goal)
         .map_or_else(error_msg, |path| vec![ResponseMsg::FlightPath(path)]),
+      RequestMsg::ProjectTrajectories(projection) => player
+        .project_trajectories(&projection)
+        .map_or_else(error_msg, |trajectories| vec![ResponseMsg::Trajectories(trajectories)]),
+      RequestMsg::ClosestApproach(approach) => player
+        .closest_approach(&approach)
+        .map_or_else(error_msg, |approach| vec![ResponseMsg::Approach(approach)]),
+      RequestMsg::HitProbability(odds) => player
+        .hit_probability(&odds)
+        .map_or_else(error_msg, |odds| vec![ResponseMsg::AttackOdds(odds)]),
+      RequestMsg::RollLogRequest => player.roll_log().map_or_else(error_msg, |log| vec![ResponseMsg::RollLog(log)]),
       RequestMsg::Exit => {
         info!("Received and processing Exit request.");
         let mut old_server = None;
@@ -696,9 +758,14 @@ impl Processor {
           scenario = server_id,
           action = "exit"
         );
-        player.set_role_ship(crate::payloads::Role::General, None);
+        player.reset_role();
 
-        vec![ResponseMsg::Users(self.members.get_user_context(server_id))]
+        // With the last player gone the game is over for now, so its roster ships are written back.
+        let users = self.members.get_user_context(server_id);
+        if users.is_empty() {
+          self.record_roster_ships(&server.roster_ships()).await;
+        }
+        vec![ResponseMsg::Users(users)]
       }
       RequestMsg::Logout => {
         info!("Received and processing logout request.");
@@ -753,6 +820,12 @@ impl Processor {
           vec![ResponseMsg::PleaseLogin]
         }
       }
+      RequestMsg::JoinScenario(join_scenario) if !player.scenario_allowed(&join_scenario.scenario_name) => {
+        vec![ResponseMsg::Error(format!(
+          "API token is not scoped for scenario {}.",
+          join_scenario.scenario_name
+        ))]
+      }
       RequestMsg::JoinScenario(join_scenario) => {
         if let Some(server) = self.servers.get(&join_scenario.scenario_name) {
           player.set_server(server.clone());
@@ -783,23 +856,20 @@ impl Processor {
           vec![ResponseMsg::Error("Scenario does not exist.".to_string())]
         }
       }
+      RequestMsg::CreateScenario(create_scenario) if !player.scenario_allowed(&create_scenario.name) => {
+        vec![ResponseMsg::Error(format!(
+          "API token is not scoped for scenario {}.",
+          create_scenario.name
+        ))]
+      }
       RequestMsg::CreateScenario(create_scenario) => {
         debug!("(Processor.handle_request) Creating scenario {}", create_scenario.name);
         if self.servers.contains_key(&create_scenario.name) {
           return vec![ResponseMsg::Error("Scenario name already exists.".to_string())];
         }
 
-        let scenario_full_name = if create_scenario.scenario.is_empty() {
-          String::new()
-        } else {
-          format!("{}/{}", self.scenario_dir, create_scenario.scenario)
-        };
-        debug!(
-          "(Processor.handle_request) Creating scenario {} from {scenario_full_name}",
-          create_scenario.name
-        );
         // Create the new server, register it in the servers tables, in the membership table, and with the player structure.
-        let server = Arc::new(Server::new(&create_scenario.name, &scenario_full_name).await);
+        let server = Arc::new(self.start_server(&create_scenario.name, &create_scenario.scenario).await);
         self.servers.insert(create_scenario.name.clone(), server.clone());
         event!(
           target: LOG_SCENARIO_ACTIVITY,
@@ -914,6 +984,230 @@ impl Processor {
     }
   }
 
+  /// Build the server for a new running scenario `id` from the scenario file `scenario` (empty for none), with the
+  /// campaign roster ships it calls for.
+  async fn start_server(&self, id: &str, scenario: &str) -> Server {
+    let scenario_full_name = if scenario.is_empty() {
+      String::new()
+    } else {
+      join_path(&self.scenario_dir, scenario)
+    };
+    debug!("(Processor.start_server) Creating scenario {id} from {scenario_full_name}");
+    let server = Server::new(id, &scenario_full_name, self.scenario_storage.as_ref()).await;
+    if server.initial_scenario.lock().unwrap().roster.is_empty() {
+      return server;
+    }
+    let roster = load_roster(self.roster_storage.as_ref(), &self.roster_file).await.map_or_else(
+      |e| {
+        error!("(Processor.start_server) Scenario {id} starts without its roster ships: {e}");
+        Roster::default()
+      },
+      |(roster, _)| roster,
+    );
+    server.with_roster(&roster)
+  }
+
+  /// Reset the player's scenario.  That ends the game, so the roster ships in it are written back to the roster as
+  /// they finished, and the next game starts them from there.
+  async fn handle_reset(&self, player: &PlayerManager) -> Vec<ResponseMsg> {
+    let campaign_ships = player.server.as_ref().map(|server| server.roster_ships()).unwrap_or_default();
+    let result = player.reset();
+    if let (Ok(_), Some(server)) = (&result, &player.server) {
+      self.record_roster_ships(&campaign_ships).await;
+      if !server.initial_scenario.lock().unwrap().roster.is_empty() {
+        match load_roster(self.roster_storage.as_ref(), &self.roster_file).await {
+          Ok((roster, _)) => server.place_roster_ships(&roster),
+          Err(e) => {
+            error!(
+              "(Processor.handle_reset) Scenario {} keeps its old roster ships: {e}",
+              server.get_id()
+            );
+          }
+        }
+      }
+    }
+    response_with_update(player, result)
+  }
+
+  /// Write campaign roster ships back to the roster as they stand.
+  async fn record_roster_ships(&self, ships: &[Ship]) {
+    if ships.is_empty() {
+      return;
+    }
+    let written = update_roster(self.roster_storage.as_ref(), &self.roster_file, &mut |roster| {
+      for ship in ships {
+        if let Err(e) = roster.record(ship) {
+          warn!("(Processor.record_roster_ships) {e}");
+        }
+      }
+      Ok(())
+    })
+    .await;
+    if let Err(e) = written {
+      error!("(Processor.record_roster_ships) Unable to write roster ships back: {e}");
+    }
+  }
+
+  /// Tear down the scenarios that have been empty too long as of the unix time `now`, writing their roster ships back
+  /// first.  Scenarios that ships are jumping to are kept until they arrive.
+  ///
+  /// # Returns
+  /// True if any scenarios were removed.
+  async fn expire_scenarios(&mut self, now: u64) -> bool {
+    let awaited = self
+      .servers
+      .iter()
+      .filter(|(_, server)| !server.ships_in_transit().is_empty())
+      .map(|(id, _)| id.clone())
+      .collect::<HashSet<_>>();
+    let expired = self.members.clean_expired_scenarios(now, &awaited);
+    for id in &expired {
+      if let Some(server) = self.servers.remove(id) {
+        self.record_roster_ships(&server.roster_ships()).await;
+      }
+    }
+    !expired.is_empty()
+  }
+
+  /// Put a ship from the player's scenario on the campaign roster, owned by the player.  From then on the ship in
+  /// the scenario is that roster ship and is written back when the scenario ends.  Players can only enlist the ship
+  /// they are seated on; the GM can enlist any ship.
+  ///
+  /// # Panics
+  /// Panics if the server entities or the ship cannot be locked.
+  async fn handle_enlist_ship(&self, player: &PlayerManager, msg: EnlistShipMsg) -> Vec<ResponseMsg> {
+    let Some(owner) = player.get_email() else {
+      return vec![ResponseMsg::Error(
+        "Must be authenticated to enlist a ship.".to_string(),
+      )];
+    };
+    let Some(server) = player.server.clone() else {
+      return vec![ResponseMsg::Error("Not in a scenario; no ship to enlist.".to_string())];
+    };
+    let gm = player.is_gm();
+    if !gm && player.get_role().1.as_ref() != Some(&msg.name) {
+      return vec![ResponseMsg::Error(format!(
+        "Only the GM or the crew of {} can enlist it.",
+        msg.name
+      ))];
+    }
+    let Some(ship) = server.get_unlocked_entities().unwrap().ships.get(&msg.name).cloned() else {
+      return vec![ResponseMsg::Error(format!("No ship {} in this scenario.", msg.name))];
+    };
+    let snapshot = ship.read().unwrap().clone();
+
+    match update_roster(self.roster_storage.as_ref(), &self.roster_file, &mut |roster| {
+      roster.enlist(&msg.id, &owner, &snapshot, gm)
+    })
+    .await
+    {
+      Ok(roster) => {
+        ship.write().unwrap().set_roster_id(Some(msg.id.clone()));
+        info!(
+          "(Processor.handle_enlist_ship) {owner} enlisted {} as roster ship {}.",
+          msg.name, msg.id
+        );
+        vec![
+          ResponseMsg::Roster(roster),
+          ResponseMsg::EntityResponse(player.clone_entities()),
+        ]
+      }
+      Err(e) => error_msg(e),
+    }
+  }
+
+  /// Repair and/or refuel one of the player's roster ships, paying from their credits.
+  async fn handle_refit(&self, player: &PlayerManager, msg: RefitMsg) -> Vec<ResponseMsg> {
+    let Some(owner) = player.get_email() else {
+      return vec![ResponseMsg::Error("Must be authenticated to refit a ship.".to_string())];
+    };
+    let mut cost = 0;
+    match update_roster(self.roster_storage.as_ref(), &self.roster_file, &mut |roster| {
+      cost = roster.refit(&msg.id, &owner, msg.repair, msg.refuel)?;
+      Ok(())
+    })
+    .await
+    {
+      Ok(roster) => vec![
+        ResponseMsg::SimpleMsg(format!("Refit of {} cost {cost} credits.", msg.id)),
+        ResponseMsg::Roster(roster),
+      ],
+      Err(e) => error_msg(e),
+    }
+  }
+
+  /// Count down the transit of ships bound for scenarios no one is playing, which would otherwise never take a turn
+  /// to bring them out of jump space.  Each turn of `played` counts as a turn for the ships that jumped from it.
+  ///
+  /// # Panics
+  /// Panics if the server entities cannot be unlocked.
+  fn advance_unattended_arrivals(&self, played: &Server) {
+    let members = self.members.member_counts().into_iter().collect::<HashMap<_, _>>();
+    for (id, server) in &self.servers {
+      if id == played.get_id()
+        || members.get(id).is_some_and(|count| *count > 0)
+        || server.ships_in_transit().is_empty()
+      {
+        continue;
+      }
+      for effect in server.emerge_arrivals_from(played.get_id(), &mut server.get_unlocked_entities().unwrap()) {
+        info!("(Processor.advance_unattended_arrivals) In unattended scenario {id}: {effect}");
+      }
+    }
+  }
+
+  /// Send the ships that jumped out of `server` this turn on to their destinations, starting any destination
+  /// scenario that isn't running yet.  Players follow their ship by joining the destination scenario.
+  ///
+  /// # Returns
+  /// An updated scenario list if a destination scenario was started or listed again.
+  ///
+  /// # Panics
+  /// Panics if the server entities cannot be unlocked.
+  async fn dispatch_departures(&mut self, server: &Server) -> Vec<ResponseMsg> {
+    let departures = server.get_unlocked_entities().unwrap().take_departures();
+    let mut started = false;
+    for Departure { ship, destination } in departures {
+      let target = if let Some(target) = self.servers.get(&destination.server) {
+        target.clone()
+      } else {
+        let target = Arc::new(self.start_server(&destination.server, &destination.scenario).await);
+        self.servers.insert(destination.server.clone(), target.clone());
+        self.members.register(&destination.server, &destination.scenario);
+        event!(
+          target: LOG_SCENARIO_ACTIVITY,
+          Level::INFO,
+          scenario = destination.server,
+          action = "create for jump"
+        );
+        started = true;
+        target
+      };
+      // The destination may have expired while it was empty; it is listed again while the ship is on its way.
+      started |= self.members.open(&destination.server);
+
+      let ship_name = ship.get_name().to_string();
+      if let Err(e) = target.queue_arrival(ship, server.get_id(), &destination) {
+        error!(
+          "(Processor.dispatch_departures) {ship_name} lost jumping to {}: {e}",
+          destination.server
+        );
+      } else {
+        info!(
+          "(Processor.dispatch_departures) {ship_name} jumped from {} to {}.",
+          server.get_id(),
+          destination.server
+        );
+      }
+    }
+
+    if started {
+      vec![ResponseMsg::Scenarios(self.build_scenarios_msg())]
+    } else {
+      vec![]
+    }
+  }
+
   /// Persist the player's currently-loaded scenario to the configured scenario
   /// directory (local FS or `gs://...`). Performs name sanitization, ownership
   /// enforcement, and force-overwrite handshake. On success, refreshes the
@@ -956,9 +1250,9 @@ impl Processor {
       format!("{raw}.json")
     };
     let file_stem = file_name.trim_end_matches(".json").to_string();
-    let full_path = format!("{}/{}", self.scenario_dir, file_name);
+    let full_path = join_path(&self.scenario_dir, &file_name);
 
-    if let Ok(existing_bytes) = read_local_or_cloud_file(&full_path).await {
+    if let Ok(existing_bytes) = self.scenario_storage.read(&full_path).await {
       if let Ok(existing) = serde_json::from_slice::<ExistingMeta>(&existing_bytes) {
         let existing_owner = existing.metadata.owner;
         // Empty owner = pre-ownership scenario; treat as orphaned and allow takeover.
@@ -998,7 +1292,7 @@ impl Processor {
       }
     };
 
-    if let Err(e) = write_local_or_cloud_file(&full_path, json).await {
+    if let Err(e) = self.scenario_storage.write(&full_path, json).await {
       return vec![ResponseMsg::Error(format!("Failed to write scenario: {e}"))];
     }
 
@@ -1068,3 +1362,241 @@ async fn send_response(stream: &mut WebSocketStream<SubStream>, message: &Respon
     error!("(processor) Failed to send {context}: {e:?}");
   });
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::authentication::{mint_api_token, ApiTokenScope, MockAuthenticator, UserRecord, UserStatus};
+  use crate::entity::{JumpDestination, Vec3};
+  use crate::payloads::{ApiTokenLoginMsg, JoinScenarioMsg, Role};
+  use crate::ship::{config_test_ship_templates, get_ship_template};
+  use crate::storage::MemoryStorage;
+  use cgmath::Zero;
+  use serde_json::json;
+
+  const ROSTER_FILE: &str = "roster.json";
+
+  fn test_processor(scenario_storage: MemoryStorage, roster_storage: Arc<MemoryStorage>) -> Processor {
+    let (_, connection_receiver) = futures::channel::mpsc::channel(1);
+    let (_, reload_receiver) = futures::channel::mpsc::unbounded();
+    Processor::new(
+      connection_receiver,
+      reload_receiver,
+      Box::new(MockAuthenticator::new("http://test.com")),
+      Arc::new(Mutex::new(HashMap::new())),
+      "scenarios",
+      Arc::new(scenario_storage),
+      ROSTER_FILE,
+      roster_storage,
+      true,
+      Arc::new(RwLock::new(Arc::new(UserDirectory::default()))),
+    )
+  }
+
+  /// A processor running scenario "campaign" with roster ships Sparrow and Hawk, both undamaged Gazelles, and a
+  /// player seated as its GM.
+  async fn campaign_processor() -> (Processor, Arc<Server>, Arc<MemoryStorage>, PlayerManager) {
+    config_test_ship_templates().await;
+    let scenario = json!({
+      "metadata": {"name": "Campaign"},
+      "roster": [{"id": "sparrow", "position": [0, 0, 0], "velocity": [0, 0, 0]},
+        {"id": "hawk", "position": [1e6, 0, 0], "velocity": [0, 0, 0]}]
+    });
+    let scenario_storage =
+      MemoryStorage::new().with_object("scenarios/campaign.json", scenario.to_string().as_bytes(), 0);
+    let roster_storage = Arc::new(MemoryStorage::new());
+    let design = get_ship_template("Gazelle").unwrap();
+    for (id, name) in [("sparrow", "Sparrow"), ("hawk", "Hawk")] {
+      let ship = Ship::new(name.to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+      update_roster(roster_storage.as_ref(), ROSTER_FILE, &mut |roster| {
+        roster.enlist(id, "owner", &ship, false)
+      })
+      .await
+      .unwrap();
+    }
+
+    let mut processor = test_processor(scenario_storage, roster_storage.clone());
+    let server = Arc::new(processor.start_server("campaign", "campaign.json").await);
+    processor.servers.insert("campaign".to_string(), server.clone());
+    processor.members.register("campaign", "campaign.json");
+    processor.members.open("campaign");
+
+    let mut authenticator = MockAuthenticator::new("http://test.com");
+    authenticator.set_email(Some(&"owner@example.com".to_string()));
+    authenticator.set_session_key("owner-session");
+    let player = PlayerManager::new(Some(server.clone()), Box::new(authenticator), true);
+    (processor, server, roster_storage, player)
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_roster_written_back_on_expiry() {
+    let (mut processor, server, roster_storage, mut player) = campaign_processor().await;
+    let design = get_ship_template("Gazelle").unwrap();
+    {
+      let entities = server.get_unlocked_entities().unwrap();
+      entities.ships["Sparrow"].write().unwrap().current_hull -= 5;
+      entities.ships["Hawk"].write().unwrap().current_hull = 0;
+    }
+
+    // Hawk is destroyed mid-game and is written back as it went, before it vanishes from the scenario.
+    processor.handle_request(RequestMsg::Update, &mut player).await;
+    assert!(server.roster_ships().iter().all(|ship| ship.get_name() != "Hawk"));
+    let (roster, _) = load_roster(roster_storage.as_ref(), ROSTER_FILE).await.unwrap();
+    assert_eq!(roster.get("hawk").unwrap().current_hull, 0);
+    assert_eq!(roster.get("sparrow").unwrap().current_hull, design.hull);
+
+    // Left empty, the scenario expires and its remaining roster ships are written back as it is torn down.
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+    assert!(!processor.expire_scenarios(now).await);
+    assert!(processor.expire_scenarios(now + 3600).await);
+    assert!(processor.servers.is_empty());
+    assert!(processor.members.current_scenario_list().is_empty());
+    let (roster, _) = load_roster(roster_storage.as_ref(), ROSTER_FILE).await.unwrap();
+    assert_eq!(roster.get("sparrow").unwrap().current_hull, design.hull - 5);
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_enlist_ship_needs_seat_or_gm() {
+    let (processor, server, _, mut player) = campaign_processor().await;
+    let design = get_ship_template("Gazelle").unwrap();
+    server
+      .get_unlocked_entities()
+      .unwrap()
+      .add_ship("Drone".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+    let enlist = |name: &str, id: &str| EnlistShipMsg {
+      name: name.to_string(),
+      id: id.to_string(),
+    };
+
+    // A pilot can't enlist someone else's ship, nor enlist their own over its entry to dodge a refit.
+    player.set_role_ship(Role::Pilot, Some("Hawk".to_string()));
+    let response = processor.handle_enlist_ship(&player, enlist("Drone", "drone")).await;
+    assert!(matches!(&response[..], [ResponseMsg::Error(_)]));
+    let response = processor.handle_enlist_ship(&player, enlist("Hawk", "hawk")).await;
+    assert!(matches!(&response[..], [ResponseMsg::Error(_)]));
+
+    // The GM can.
+    player.set_role_ship(Role::General, None);
+    let response = processor.handle_enlist_ship(&player, enlist("Drone", "drone")).await;
+    assert!(matches!(&response[..], [ResponseMsg::Roster(roster), _] if roster.get("drone").is_some()));
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_unattended_arrivals_count_origin_turns() {
+    config_test_ship_templates().await;
+    let mut processor = test_processor(MemoryStorage::new(), Arc::new(MemoryStorage::new()));
+    for id in ["origin", "other", "dest"] {
+      let server = Arc::new(processor.start_server(id, "").await);
+      processor.servers.insert(id.to_string(), server);
+      processor.members.register(id, "");
+    }
+    let design = get_ship_template("Gazelle").unwrap();
+    let ship = Ship::new("Sparrow".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+    let destination = JumpDestination {
+      name: "Dest".to_string(),
+      server: "dest".to_string(),
+      scenario: String::new(),
+      emergence_point: Vec3::zero(),
+      transit_turns: 2,
+    };
+    let dest = processor.servers["dest"].clone();
+    dest.queue_arrival(ship, "origin", &destination).unwrap();
+
+    // Turns in some unrelated scenario don't bring the ship out of jump space, however many there are.
+    for _ in 0..3 {
+      processor.advance_unattended_arrivals(&processor.servers["other"]);
+    }
+    assert_eq!(dest.ships_in_transit(), vec!["Sparrow".to_string()]);
+
+    // Turns in the scenario it left do.
+    processor.advance_unattended_arrivals(&processor.servers["origin"]);
+    assert_eq!(dest.ships_in_transit(), vec!["Sparrow".to_string()]);
+    processor.advance_unattended_arrivals(&processor.servers["origin"]);
+    assert!(dest.ships_in_transit().is_empty());
+    assert!(dest.get_unlocked_entities().unwrap().ships.contains_key("Sparrow"));
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_reset_keeps_roster_damage() {
+    let (mut processor, server, roster_storage, mut player) = campaign_processor().await;
+    let design = get_ship_template("Gazelle").unwrap();
+    processor
+      .handle_request(
+        RequestMsg::JoinScenario(JoinScenarioMsg {
+          scenario_name: "campaign".to_string(),
+        }),
+        &mut player,
+      )
+      .await;
+    {
+      let entities = server.get_unlocked_entities().unwrap();
+      entities.ships["Sparrow"].write().unwrap().current_hull -= 5;
+      entities.ships["Hawk"].write().unwrap().current_hull = 0;
+    }
+    processor.handle_request(RequestMsg::Update, &mut player).await;
+
+    // The next game starts from the damage the last one did: Sparrow stays hurt and Hawk stays lost.
+    processor.handle_request(RequestMsg::Reset, &mut player).await;
+    {
+      let entities = server.get_unlocked_entities().unwrap();
+      assert_eq!(entities.ships["Sparrow"].read().unwrap().current_hull, design.hull - 5);
+      assert!(!entities.ships.contains_key("Hawk"));
+    }
+
+    // So writing the roster back again on exit doesn't undo that damage.
+    processor.handle_request(RequestMsg::Exit, &mut player).await;
+    let (roster, _) = load_roster(roster_storage.as_ref(), ROSTER_FILE).await.unwrap();
+    assert_eq!(roster.get("sparrow").unwrap().current_hull, design.hull - 5);
+    assert_eq!(roster.get("hawk").unwrap().current_hull, 0);
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_exit_keeps_token_role() {
+    config_test_ship_templates().await;
+    let mut records = vec![UserRecord {
+      email: "bot@example.com".to_string(),
+      status: UserStatus::Active,
+      registered_at: 0,
+      blacklisted_at: None,
+      admin: false,
+      api_tokens: vec![],
+    }];
+    let scope = ApiTokenScope {
+      roles: vec![Role::Pilot],
+      scenarios: vec![],
+    };
+    let (_, token) = mint_api_token(&mut records, "bot@example.com", "bot", scope, 0).unwrap();
+    let directory = UserDirectory {
+      active: HashSet::from(["bot@example.com".to_string()]),
+      raw: records,
+      ..UserDirectory::default()
+    };
+
+    let scenario = json!({"metadata": {"name": "Arena"}});
+    let scenario_storage = MemoryStorage::new().with_object("scenarios/arena.json", scenario.to_string().as_bytes(), 0);
+    let mut processor = test_processor(scenario_storage, Arc::new(MemoryStorage::new()));
+    let server = Arc::new(processor.start_server("arena", "arena.json").await);
+    processor.servers.insert("arena".to_string(), server);
+    processor.members.register("arena", "arena.json");
+
+    let mut authenticator =
+      MockAuthenticator::new("http://test.com").with_directory(Arc::new(RwLock::new(Arc::new(directory))));
+    authenticator.set_session_key("bot-session");
+    let mut player = PlayerManager::new(None, Box::new(authenticator), true);
+    player.login_with_api_token(ApiTokenLoginMsg { token }).await.unwrap();
+    assert_eq!(player.get_role().0, Role::Pilot);
+
+    processor
+      .handle_request(
+        RequestMsg::JoinScenario(JoinScenarioMsg {
+          scenario_name: "arena".to_string(),
+        }),
+        &mut player,
+      )
+      .await;
+    processor.handle_request(RequestMsg::Exit, &mut player).await;
+
+    // Leaving the scenario must not promote a token scoped to Pilot to General.
+    assert_eq!(player.get_role(), (Role::Pilot, None));
+  }
+}
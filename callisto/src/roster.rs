@@ -0,0 +1,367 @@
+//! The campaign roster: named ships that outlive any one scenario.
+//!
+//! Each [`RosterShip`] keeps the state a ship carries from fight to fight (damage, crits, fuel and crew) and belongs
+//! to the user who enlisted it.  A scenario pulls ships in by roster id (its `roster` list) and the ships' state is
+//! written back when the scenario ends.  Between sessions owners pay for repairs and fuel out of their credits.
+//!
+//! The roster lives in one JSON file on the same storage as scenarios (local, `gs://` or `s3://`).  Writes are
+//! guarded by the file's generation so two servers finishing scenarios at once don't lose each other's updates.
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::crew::Crew;
+use crate::entity::{Entity, Vec3};
+use crate::payloads::Vec3asVec;
+use crate::ship::{get_ship_template, Sensors, Ship, ShipDesignTemplate};
+use crate::storage::{Generation, GenerationWriteError, StorageBackend};
+#[allow(unused_imports)]
+use crate::{debug, error, info, warn};
+
+pub const DEFAULT_ROSTER_FILE: &str = "./config/roster.json";
+
+// Refit prices in credits.
+pub const HULL_POINT_REPAIR_COST: u64 = 100_000;
+pub const ARMOR_POINT_REPAIR_COST: u64 = 250_000;
+pub const CRIT_LEVEL_REPAIR_COST: u64 = 500_000;
+pub const FUEL_TON_COST: u64 = 500;
+
+const ROSTER_WRITE_MAX_ATTEMPTS: u32 = 5;
+
+/// A campaign ship and the state it carries between scenarios.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RosterShip {
+  pub id: String,
+  pub name: String,
+  // Email of the user who enlisted the ship.  Only they can refit it.
+  pub owner: String,
+  // Name of the ship's design template.
+  pub design: String,
+  pub current_hull: u32,
+  pub current_armor: u32,
+  pub current_power: u32,
+  pub current_maneuver: u8,
+  pub current_jump: u8,
+  pub current_fuel: u32,
+  pub current_crew: u32,
+  pub current_sensors: Sensors,
+  pub current_computer: u32,
+  #[serde(default)]
+  pub crit_level: [u8; 11],
+  #[serde(default)]
+  pub crew: Crew,
+}
+
+/// Where a scenario puts one of the roster's ships.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RosterPlacement {
+  pub id: String,
+  #[serde_as(as = "Vec3asVec")]
+  pub position: Vec3,
+  #[serde_as(as = "Vec3asVec")]
+  pub velocity: Vec3,
+}
+
+/// All the campaign's ships, and the credits each user has for refits.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Roster {
+  #[serde(default)]
+  pub credits: BTreeMap<String, u64>,
+  #[serde(default)]
+  pub ships: Vec<RosterShip>,
+}
+
+impl RosterShip {
+  /// Capture `ship`, in its current state, as roster entry `id` owned by `owner`.
+  #[must_use]
+  pub fn from_ship(id: &str, owner: &str, ship: &Ship) -> Self {
+    RosterShip {
+      id: id.to_string(),
+      name: ship.get_name().to_string(),
+      owner: owner.to_string(),
+      design: ship.design.name.clone(),
+      current_hull: ship.current_hull,
+      current_armor: ship.current_armor,
+      current_power: ship.current_power,
+      current_maneuver: ship.current_maneuver,
+      current_jump: ship.current_jump,
+      current_fuel: ship.current_fuel,
+      current_crew: ship.current_crew,
+      current_sensors: ship.current_sensors,
+      current_computer: ship.current_computer,
+      crit_level: ship.crit_level,
+      crew: ship.crew.clone(),
+    }
+  }
+
+  /// Build the ship this entry describes, damage and all, at `position` moving at `velocity`.
+  #[must_use]
+  pub fn to_ship(&self, position: Vec3, velocity: Vec3, design: &Arc<ShipDesignTemplate>) -> Ship {
+    let mut ship = Ship::new(self.name.clone(), position, velocity, design, Some(self.crew.clone()));
+    ship.current_hull = self.current_hull;
+    ship.current_armor = self.current_armor;
+    ship.current_power = self.current_power;
+    ship.current_maneuver = self.current_maneuver;
+    ship.current_jump = self.current_jump;
+    ship.current_fuel = self.current_fuel;
+    ship.current_crew = self.current_crew;
+    ship.current_sensors = self.current_sensors;
+    ship.current_computer = self.current_computer;
+    ship.crit_level = self.crit_level;
+    ship.set_roster_id(Some(self.id.clone()));
+    ship
+  }
+
+  /// Record the state `ship` finished a scenario in.  The owner and id stay as they are.
+  pub fn record(&mut self, ship: &Ship) {
+    *self = RosterShip {
+      id: std::mem::take(&mut self.id),
+      owner: std::mem::take(&mut self.owner),
+      ..RosterShip::from_ship("", "", ship)
+    };
+  }
+
+  /// The price of repairing this ship back to its `design` (`repair`) and filling its tanks (`refuel`).
+  #[must_use]
+  pub fn refit_cost(&self, design: &ShipDesignTemplate, repair: bool, refuel: bool) -> u64 {
+    let mut cost = 0;
+    if repair {
+      cost += u64::from(design.hull.saturating_sub(self.current_hull)) * HULL_POINT_REPAIR_COST;
+      cost += u64::from(design.armor.saturating_sub(self.current_armor)) * ARMOR_POINT_REPAIR_COST;
+      cost += self.crit_level.iter().map(|level| u64::from(*level)).sum::<u64>() * CRIT_LEVEL_REPAIR_COST;
+    }
+    if refuel {
+      cost += u64::from(design.fuel.saturating_sub(self.current_fuel)) * FUEL_TON_COST;
+    }
+    cost
+  }
+
+  fn refit(&mut self, design: &ShipDesignTemplate, repair: bool, refuel: bool) {
+    if repair {
+      self.current_hull = design.hull;
+      self.current_armor = design.armor;
+      self.current_power = design.power;
+      self.current_maneuver = design.maneuver;
+      self.current_jump = design.jump;
+      self.current_crew = design.crew;
+      self.current_sensors = design.sensors;
+      self.current_computer = design.computer;
+      self.crit_level = [0; 11];
+    }
+    if refuel {
+      self.current_fuel = design.fuel;
+    }
+  }
+}
+
+impl Roster {
+  #[must_use]
+  pub fn get(&self, id: &str) -> Option<&RosterShip> {
+    self.ships.iter().find(|entry| entry.id == id)
+  }
+
+  /// Add `ship` to the roster as `id`, owned by `owner`.  Only a GM (`gm`) can enlist a ship under an id already in
+  /// use, replacing that entry's ship but keeping its owner; anyone else would be dodging the cost of a refit.
+  ///
+  /// # Errors
+  /// Returns an error if `id` is already on the roster and this isn't the GM.
+  pub fn enlist(&mut self, id: &str, owner: &str, ship: &Ship, gm: bool) -> Result<(), String> {
+    match self.ships.iter_mut().find(|entry| entry.id == id) {
+      Some(_) if !gm => Err(format!("Roster ship {id} is already enlisted.")),
+      Some(existing) => {
+        existing.record(ship);
+        Ok(())
+      }
+      None => {
+        self.ships.push(RosterShip::from_ship(id, owner, ship));
+        Ok(())
+      }
+    }
+  }
+
+  /// Write back the state of `ship` to its roster entry.
+  ///
+  /// # Errors
+  /// Returns an error if `ship` isn't a roster ship or its entry is gone.
+  pub fn record(&mut self, ship: &Ship) -> Result<(), String> {
+    let id = ship
+      .get_roster_id()
+      .ok_or_else(|| format!("Ship {} is not on the roster.", ship.get_name()))?;
+    self
+      .ships
+      .iter_mut()
+      .find(|entry| entry.id == id)
+      .ok_or_else(|| format!("Roster ship {id} no longer exists."))?
+      .record(ship);
+    Ok(())
+  }
+
+  /// Repair and/or refuel roster ship `id` for `owner`, paying from their credits.
+  ///
+  /// # Returns
+  /// The credits spent.
+  ///
+  /// # Errors
+  /// Returns an error if there is no such ship, it isn't `owner`'s, its design is unknown, or `owner` can't afford it.
+  pub fn refit(&mut self, id: &str, owner: &str, repair: bool, refuel: bool) -> Result<u64, String> {
+    let entry = self
+      .ships
+      .iter_mut()
+      .find(|entry| entry.id == id)
+      .ok_or_else(|| format!("No roster ship {id}."))?;
+    if entry.owner != owner {
+      return Err(format!("Roster ship {id} belongs to another player."));
+    }
+    let design = get_ship_template(&entry.design).ok_or_else(|| format!("Unknown ship design {}.", entry.design))?;
+    let cost = entry.refit_cost(&design, repair, refuel);
+    let credits = self.credits.get(owner).copied().unwrap_or(0);
+    if cost > credits {
+      return Err(format!("Refit of {id} costs {cost} credits but only {credits} are available."));
+    }
+    entry.refit(&design, repair, refuel);
+    self.credits.insert(owner.to_string(), credits - cost);
+    Ok(cost)
+  }
+}
+
+/// Read the roster from `file_name`.  A missing file is an empty roster.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed.
+pub async fn load_roster(
+  storage: &dyn StorageBackend, file_name: &str,
+) -> Result<(Roster, Option<Generation>), String> {
+  let (body, generation) = storage
+    .read_versioned(file_name)
+    .await
+    .map_err(|e| format!("Unable to read roster file {file_name}: {e}"))?;
+  if body.is_empty() {
+    return Ok((Roster::default(), generation));
+  }
+  let roster =
+    serde_json::from_slice::<Roster>(&body).map_err(|e| format!("Unable to parse roster file {file_name}: {e}"))?;
+  Ok((roster, generation))
+}
+
+/// Apply `update` to the roster in `file_name` and write it back, re-reading and re-applying if someone else wrote
+/// the file in the meantime.
+///
+/// # Returns
+/// The roster as written.
+///
+/// # Errors
+/// Returns an error if `update` does, or the roster cannot be read or written.
+pub async fn update_roster(
+  storage: &dyn StorageBackend, file_name: &str, update: &mut (dyn FnMut(&mut Roster) -> Result<(), String> + Send),
+) -> Result<Roster, String> {
+  for attempt in 1..=ROSTER_WRITE_MAX_ATTEMPTS {
+    let (mut roster, generation) = load_roster(storage, file_name).await?;
+    update(&mut roster)?;
+    let bytes = serde_json::to_vec_pretty(&roster).map_err(|e| format!("Unable to serialize roster: {e:?}"))?;
+    match storage.write_if_generation_match(file_name, bytes, generation.as_deref()).await {
+      Ok(_) => return Ok(roster),
+      Err(GenerationWriteError::PreconditionFailed) => {
+        warn!(
+          "(update_roster) Roster changed underneath us on attempt {attempt}/{ROSTER_WRITE_MAX_ATTEMPTS}; re-reading."
+        );
+        tokio::time::sleep(Duration::from_millis(50 * u64::from(attempt))).await;
+      }
+      Err(GenerationWriteError::Other(e)) => return Err(format!("Write failed for {file_name}: {e}")),
+    }
+  }
+  Err(format!(
+    "Exhausted {ROSTER_WRITE_MAX_ATTEMPTS} retries trying to update {file_name}."
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::crew::Skills;
+  use crate::ship::{config_test_ship_templates, ShipSystem};
+  use crate::storage::MemoryStorage;
+  use cgmath::Zero;
+
+  const ROSTER_FILE: &str = "roster.json";
+
+  fn damaged_ship() -> Ship {
+    let design = get_ship_template("Buccaneer").unwrap();
+    let mut crew = Crew::new();
+    crew.set_skill(Skills::Pilot, 2);
+    let mut ship = Ship::new("Sparrow".to_string(), Vec3::zero(), Vec3::zero(), &design, Some(crew));
+    ship.current_hull -= 3;
+    ship.current_fuel -= 10;
+    ship.crit_level[ShipSystem::Jump as usize] = 2;
+    ship
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_roster_round_trip() {
+    config_test_ship_templates().await;
+    let storage = MemoryStorage::new();
+    let ship = damaged_ship();
+
+    let roster = update_roster(&storage, ROSTER_FILE, &mut |roster| {
+      roster.enlist("sparrow", "owner", &ship, false)
+    })
+    .await
+    .unwrap();
+    // Nobody but the GM enlists over an existing entry, not even its owner with a ship in better repair.
+    for owner in ["thief", "owner"] {
+      assert!(update_roster(&storage, ROSTER_FILE, &mut |roster| roster
+        .enlist("sparrow", owner, &ship, false))
+      .await
+      .is_err());
+    }
+    let mut replaced = roster.clone();
+    replaced.enlist("sparrow", "gm", &ship, true).unwrap();
+    assert_eq!(replaced.get("sparrow").unwrap().owner, "owner");
+
+    // The ship comes back into a scenario as it left, and is recorded as it finishes.
+    let entry = roster.get("sparrow").unwrap();
+    let mut restored = entry.to_ship(Vec3::new(1.0, 0.0, 0.0), Vec3::zero(), &ship.design);
+    assert_eq!(restored.get_roster_id(), Some("sparrow"));
+    assert_eq!(restored.current_hull, ship.current_hull);
+    assert_eq!(restored.crit_level, ship.crit_level);
+    assert_eq!(restored.crew.get_pilot(), 2);
+
+    restored.current_armor -= 1;
+    let (roster, _) = load_roster(&storage, ROSTER_FILE).await.unwrap();
+    let mut roster = roster;
+    roster.record(&restored).unwrap();
+    let entry = roster.get("sparrow").unwrap();
+    assert_eq!(entry.owner, "owner");
+    assert_eq!(entry.current_armor, ship.design.armor - 1);
+  }
+
+  #[test_log::test(tokio::test)]
+  async fn test_refit() {
+    config_test_ship_templates().await;
+    let mut roster = Roster::default();
+    let ship = damaged_ship();
+    roster.enlist("sparrow", "owner", &ship, false).unwrap();
+
+    let repair = 3 * HULL_POINT_REPAIR_COST + 2 * CRIT_LEVEL_REPAIR_COST;
+    assert_eq!(roster.get("sparrow").unwrap().refit_cost(&ship.design, true, false), repair);
+    assert_eq!(
+      roster.get("sparrow").unwrap().refit_cost(&ship.design, true, true),
+      repair + 10 * FUEL_TON_COST
+    );
+
+    // Not without the money, and not someone else's ship.
+    assert!(roster.refit("sparrow", "owner", true, false).is_err());
+    roster.credits.insert("owner".to_string(), repair + 1);
+    assert!(roster.refit("sparrow", "thief", true, false).is_err());
+
+    assert_eq!(roster.refit("sparrow", "owner", true, false), Ok(repair));
+    assert_eq!(roster.credits["owner"], 1);
+    let entry = roster.get("sparrow").unwrap();
+    assert_eq!(entry.current_hull, ship.design.hull);
+    assert_eq!(entry.crit_level, [0; 11]);
+    assert_eq!(entry.current_fuel, ship.current_fuel);
+  }
+}
@@ -1,4 +1,13 @@
-use crate::ship::{CounterMeasures, Stealth};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::ruleset::{Edition, Ruleset};
+use crate::ship::{CounterMeasures, Range, Stealth, WeaponType};
+use crate::storage::StorageBackend;
+#[allow(unused_imports)]
+use crate::{debug, error, info, warn};
+
+pub const DEFAULT_RULES_FILE: &str = "./config/rules.json";
 
 // Index by Weapon: Beam, Pulse, Missile, Sand, Particle
 pub const HIT_WEAPON_MOD: [i32; 5] = [4, 2, 0, 0, 0];
@@ -15,20 +24,169 @@ pub const RANGE_MOD: [i32; 5] = [1, 0, -2, -4, -6];
 // DM to sensor checks based on sensor quality
 pub const SENSOR_QUALITY_MOD: [i16; 5] = [-4, -2, 0, 1, 2];
 
-// DM to sensor checks based on stealth
-pub fn stealth_mod(stealth: Option<Stealth>) -> i16 {
-  match stealth {
-    None => 0,
-    Some(stealth) => STEALTH_MOD[stealth as usize],
+// DM to sensor checks based on stealth: Basic, Improved, Enhanced, Advanced
+pub const STEALTH_MOD: [i16; 4] = [-2, -2, -4, -6];
+
+// DM to sensor checks based on countermeasures: Standard, Military
+pub const COUNTERMEASURES_MOD: [i16; 2] = [2, 4];
+
+// The rules every scenario without its own starts from: the rules file if one was loaded, otherwise the tables above.
+static DEFAULT_RULES: OnceCell<RulesTables> = OnceCell::new();
+
+/// The edition and tables combat and sensor checks are resolved with.  A scenario can override any of them (and only
+/// those it names) with a `rules` object in its JSON; the rest come from the server's rules file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RulesTables {
+  pub edition: Edition,
+  pub hit_weapon_mod: [i32; 5],
+  pub damage_weapon_dice: [u8; 5],
+  pub range_bands: [u32; 4],
+  pub range_mod: [i32; 5],
+  pub sensor_quality_mod: [i16; 5],
+  pub stealth_mod: [i16; 4],
+  pub countermeasures_mod: [i16; 2],
+}
+
+impl RulesTables {
+  /// The tables as hard-coded in the server, used when there is no rules file.
+  #[must_use]
+  pub const fn builtin() -> Self {
+    RulesTables {
+      edition: Edition::Core,
+      hit_weapon_mod: HIT_WEAPON_MOD,
+      damage_weapon_dice: DAMAGE_WEAPON_DICE,
+      range_bands: RANGE_BANDS,
+      range_mod: RANGE_MOD,
+      sensor_quality_mod: SENSOR_QUALITY_MOD,
+      stealth_mod: STEALTH_MOD,
+      countermeasures_mod: COUNTERMEASURES_MOD,
+    }
+  }
+
+  /// The ruleset of this edition.
+  #[must_use]
+  pub fn ruleset(&self) -> &'static dyn Ruleset {
+    self.edition.ruleset()
+  }
+
+  #[must_use]
+  pub fn hit_weapon_mod(&self, kind: WeaponType) -> i32 {
+    self.hit_weapon_mod[kind as usize]
+  }
+
+  #[must_use]
+  pub fn damage_weapon_dice(&self, kind: WeaponType) -> u8 {
+    self.damage_weapon_dice[kind as usize]
+  }
+
+  #[must_use]
+  pub fn range_mod(&self, range: Range) -> i32 {
+    self.range_mod[range as usize]
+  }
+
+  /// The range band a distance (in meters) falls in.
+  #[must_use]
+  pub fn range_band(&self, distance: u32) -> Range {
+    self
+      .range_bands
+      .iter()
+      .position(|&band| distance <= band)
+      .and_then(Range::from_repr)
+      .unwrap_or(Range::Distant)
+  }
+
+  // DM to sensor checks based on sensor quality
+  #[must_use]
+  pub fn sensor_quality_mod(&self, quality: usize) -> i16 {
+    self.sensor_quality_mod[quality]
+  }
+
+  // DM to sensor checks based on stealth
+  #[must_use]
+  pub fn stealth_mod(&self, stealth: Option<Stealth>) -> i16 {
+    match stealth {
+      None => 0,
+      Some(stealth) => self.stealth_mod[stealth as usize],
+    }
+  }
+
+  #[must_use]
+  pub fn countermeasures_mod(&self, countermeasures: Option<CounterMeasures>) -> i16 {
+    match countermeasures {
+      None => 0,
+      Some(countermeasures) => self.countermeasures_mod[countermeasures as usize],
+    }
+  }
+}
+
+/// Any table a scenario doesn't set comes from the default rules.
+impl Default for RulesTables {
+  fn default() -> Self {
+    default_rules().clone()
+  }
+}
+
+/// The rules used by scenarios that don't override them.
+#[must_use]
+pub fn default_rules() -> &'static RulesTables {
+  DEFAULT_RULES.get_or_init(RulesTables::builtin)
+}
+
+/// Load the default rules from `file_name`.  Tables missing from the file keep their built-in values.  This has to
+/// happen before any scenario is loaded; once the defaults have been used they cannot be replaced.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed, or if the default rules are already in use.
+pub async fn load_default_rules(storage: &dyn StorageBackend, file_name: &str) -> Result<(), String> {
+  let contents = storage
+    .read(file_name)
+    .await
+    .map_err(|e| format!("Unable to read rules file {file_name}: {e}"))?;
+  // Parse into the built-in tables rather than through `Default`, which would fix the defaults before they are set.
+  let mut rules = serde_json::from_slice::<serde_json::Value>(&contents)
+    .map_err(|e| format!("Unable to parse rules file {file_name}: {e}"))?;
+  let mut builtin = serde_json::to_value(RulesTables::builtin()).map_err(|e| e.to_string())?;
+  if let (Some(builtin), Some(rules)) = (builtin.as_object_mut(), rules.as_object_mut()) {
+    builtin.append(rules);
   }
+  let rules = serde_json::from_value::<RulesTables>(builtin)
+    .map_err(|e| format!("Unable to parse rules file {file_name}: {e}"))?;
+  debug!("(load_default_rules) Loaded rules from {file_name}: {rules:?}");
+  DEFAULT_RULES
+    .set(rules)
+    .map_err(|_| "Default rules are already in use and cannot be replaced.".to_string())
 }
-// Use this locally only.
-const STEALTH_MOD: [i16; 4] = [-2, -2, -4, -6];
-
-pub fn countermeasures_mod(countermeasures: Option<CounterMeasures>) -> i16 {
-  match countermeasures {
-    None => 0,
-    Some(CounterMeasures::Standard) => 2,
-    Some(CounterMeasures::Military) => 4,
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test_log::test]
+  fn test_range_band() {
+    let rules = RulesTables::builtin();
+    assert_eq!(rules.range_band(0), Range::Short);
+    assert_eq!(rules.range_band(1_250_000), Range::Short);
+    assert_eq!(rules.range_band(1_250_001), Range::Medium);
+    assert_eq!(rules.range_band(50_000_000), Range::VeryLong);
+    assert_eq!(rules.range_band(50_000_001), Range::Distant);
+  }
+
+  #[test_log::test]
+  fn test_partial_override() {
+    // A scenario only names the tables it changes.
+    let rules: RulesTables =
+      serde_json::from_str(r#"{"range_bands": [1000, 2000, 3000, 4000], "damage_weapon_dice": [2, 3, 4, 0, 5]}"#)
+        .unwrap();
+    assert_eq!(rules.range_band(1500), Range::Medium);
+    assert_eq!(rules.damage_weapon_dice(WeaponType::Beam), 2);
+    assert_eq!(rules.hit_weapon_mod(WeaponType::Beam), 4);
+    assert_eq!(rules.stealth_mod(Some(Stealth::Advanced)), -6);
+    assert_eq!(rules.countermeasures_mod(Some(CounterMeasures::Military)), 4);
+    assert_eq!(rules.countermeasures_mod(None), 0);
+
+    // The checked in rules file is exactly the built-in tables.
+    let file: RulesTables = serde_json::from_str(&std::fs::read_to_string(DEFAULT_RULES_FILE).unwrap()).unwrap();
+    assert_eq!(file, RulesTables::builtin());
   }
 }
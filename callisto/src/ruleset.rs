@@ -0,0 +1,297 @@
+use std::f64::consts::TAU;
+use std::fmt::Debug;
+
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::action::{BoostMap, ShipAction};
+use crate::combat::{attack, roll_dice};
+use crate::entity::Vec3;
+use crate::payloads::{EffectMsg, JumpMishap, RollPurpose, RollRecord};
+use crate::rules_tables::RulesTables;
+use crate::ship::{Ship, ShipSystem, Weapon};
+
+// Effect (over the standard 8+) of the attack roll needed for a critical hit.
+const CRITICAL_EFFECT: i32 = 6;
+
+// Distance (in m) per point of the roll for how far off course a displaced jump emerges: 100,000 km.
+const JUMP_DISPLACEMENT_UNIT: f64 = 100_000_000.0;
+
+/// The edition of the rules a scenario plays by.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Edition {
+  /// The Core Rulebook rules this server has always used.
+  #[default]
+  Core,
+  /// High Guard: critical hit severity follows the damage done rather than the attack's effect.
+  HighGuard,
+}
+
+impl Edition {
+  #[must_use]
+  pub fn ruleset(self) -> &'static dyn Ruleset {
+    match self {
+      Edition::Core => &CoreRules,
+      Edition::HighGuard => &HighGuardRules,
+    }
+  }
+}
+
+/// How an engineering check turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineeringCheck {
+  pub target: u8,
+  pub success: bool,
+  pub critical_failure: bool,
+}
+
+/// How a sensor check turned out.  `effect` is non-negative on success.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorCheck {
+  pub threshold: i16,
+  pub effect: i16,
+}
+
+/// The parts of combat, sensor and engineering resolution that differ between editions of the rules.  Every
+/// method defaults to the Core rules, so an edition only overrides what it changes.
+#[allow(clippy::too_many_arguments)]
+pub trait Ruleset: Debug + Send + Sync {
+  /// Resolve one attack of `attacker` on `defender`, returning its effects.
+  fn attack(
+    &self, hit_mod: i32, damage_mod: i32, attacker: &Ship, defender: &mut Ship, weapon: &Weapon,
+    called_shot_system: Option<&ShipSystem>, boost_map: &BoostMap, rules: &RulesTables, rng: &mut dyn RngCore,
+  ) -> Vec<EffectMsg> {
+    attack(
+      hit_mod,
+      damage_mod,
+      attacker,
+      defender,
+      weapon,
+      called_shot_system,
+      boost_map,
+      rules,
+      rng,
+    )
+  }
+
+  /// The level of the primary crit (if any) of a hit with `effect` on the attack roll doing `damage` (after armor).
+  /// It lands on the called shot system when there is one.
+  ///
+  /// Core: a hit with effect 6+ causes a crit of level `effect - 5`.
+  fn primary_crit(&self, effect: i32, _damage: u32) -> Option<u8> {
+    (effect >= CRITICAL_EFFECT).then(|| u8::try_from(effect - CRITICAL_EFFECT + 1).unwrap_or(u8::MAX))
+  }
+
+  /// How many level 1 crits at random locations `damage` (after armor) causes to `defender`, which has not yet
+  /// taken it.
+  ///
+  /// Core: one for each new 10% of the defender's hull points the damage takes it past.
+  fn sustained_damage_crits(&self, damage: u32, defender: &Ship) -> u64 {
+    // Given get_max_hull_points() is u32, we divide it by 10 then the conversion to u64 is safe.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    let crit_threshold = (f64::from(defender.get_max_hull_points()) / 10.0).ceil().max(1.0) as u64;
+    let lost = u64::from(defender.get_max_hull_points() - defender.get_current_hull_points());
+    (lost + u64::from(damage)) / crit_threshold - lost / crit_threshold
+  }
+
+  /// Resolve a sensor check for `action` with the given `roll`, total `modifier` and, for opposed checks, the
+  /// target's roll.
+  ///
+  /// Core: sensor locks must beat 8, jamming missiles needs 10+ and opposed checks must meet the target's roll.
+  fn sensor_check(&self, action: &ShipAction, roll: u8, modifier: i16, opposed_roll: Option<u8>) -> SensorCheck {
+    let threshold = match action {
+      ShipAction::SensorLock { .. } => 9,
+      ShipAction::JamMissiles => 10,
+      _ => 0,
+    };
+    SensorCheck {
+      threshold,
+      effect: i16::from(roll) + modifier - opposed_roll.map_or(0, i16::from) - threshold,
+    }
+  }
+
+  /// Resolve an engineering check for `action` whose roll plus modifiers came to `total`.
+  ///
+  /// Core: jumping needs 6+ and failing is a misjump; overloading needs 10+ and a total of 4 or less damages the
+  /// system; skimming fuel needs 8+ and a total of 4 or less is a brush with turbulence; repairs need 8+.
+  fn engineering_check(&self, action: &ShipAction, total: u8) -> EngineeringCheck {
+    let (target, critical_failure) = match action {
+      ShipAction::Jump => (6, total < 6),
+      ShipAction::OverloadDrive | ShipAction::OverloadPlant => (10, total <= 4),
+      ShipAction::SkimFuel => (8, total <= 4),
+      _ => (8, false),
+    };
+    EngineeringCheck {
+      target,
+      success: total >= target,
+      critical_failure,
+    }
+  }
+
+  /// Roll on the mishap table for a jump by `ship_name` that failed critically, missing its check by `effect`
+  /// (negative).  Every roll made is noted in `rolls`.
+  ///
+  /// Core: 2D plus the effect.  On 8+ the ship jumps but emerges 1D x 100,000 km off course a turn late; on 6-7 the
+  /// drive is damaged; on 3-5 the hull takes 3D; on 2 or less the ship misjumps and is lost.
+  fn jump_mishap(&self, ship_name: &str, effect: i16, rng: &mut dyn RngCore, rolls: &mut Vec<EffectMsg>) -> JumpMishap {
+    let roll = roll_dice(2, rng);
+    rolls.push(
+      RollRecord::new(RollPurpose::JumpMishap, ship_name, 2, roll)
+        .with_modifier("jump check effect", effect)
+        .into(),
+    );
+    match i16::from(roll) + effect {
+      8.. => {
+        let distance = roll_dice(1, rng);
+        rolls.push(RollRecord::new(RollPurpose::JumpMishap, ship_name, 1, distance).into());
+        JumpMishap::Displaced {
+          offset: random_direction(rng) * f64::from(distance) * JUMP_DISPLACEMENT_UNIT,
+          extra_turns: 1,
+        }
+      }
+      6..=7 => JumpMishap::DriveDamage,
+      3..=5 => {
+        let damage = roll_dice(3, rng);
+        rolls.push(RollRecord::new(RollPurpose::JumpMishap, ship_name, 3, damage).into());
+        JumpMishap::HullStress {
+          damage: u32::from(damage),
+        }
+      }
+      _ => JumpMishap::Misjump,
+    }
+  }
+}
+
+// A direction picked uniformly at random.
+fn random_direction(rng: &mut dyn RngCore) -> Vec3 {
+  let z: f64 = rng.gen_range(-1.0..=1.0);
+  let theta: f64 = rng.gen_range(0.0..TAU);
+  let r = (1.0 - z * z).sqrt();
+  Vec3::new(r * theta.cos(), r * theta.sin(), z)
+}
+
+/// The Core Rulebook rules: every default of [`Ruleset`].
+#[derive(Debug)]
+pub struct CoreRules;
+
+impl Ruleset for CoreRules {}
+
+/// The High Guard rules.
+#[derive(Debug)]
+pub struct HighGuardRules;
+
+impl Ruleset for HighGuardRules {
+  /// High Guard: a hit with effect 6+ causes a crit whose level is the damage done divided by 10 (rounding up), so
+  /// big guns cripple ships where Core only rewards accuracy.  Sustained damage crits are as Core.
+  fn primary_crit(&self, effect: i32, damage: u32) -> Option<u8> {
+    (effect >= CRITICAL_EFFECT).then(|| u8::try_from(damage.div_ceil(10).max(1)).unwrap_or(u8::MAX))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ship::ShipDesignTemplate;
+  use cgmath::{InnerSpace, Zero};
+  use rand::rngs::SmallRng;
+  use rand::SeedableRng;
+  use std::sync::Arc;
+
+  fn ship(hull: u32) -> Ship {
+    let design = Arc::new(ShipDesignTemplate {
+      hull,
+      ..ShipDesignTemplate::default()
+    });
+    let mut ship = Ship::new("target".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+    ship.fixup_current_values();
+    ship
+  }
+
+  #[test_log::test]
+  fn test_critical_hits() {
+    let target = ship(100);
+
+    // Below effect 6 there is no primary crit, but damage passing 10% of the hull still causes crits.
+    assert_eq!(CoreRules.primary_crit(5, 25), None);
+    assert_eq!(CoreRules.sustained_damage_crits(9, &target), 0);
+    assert_eq!(CoreRules.sustained_damage_crits(25, &target), 2);
+
+    // Core grades the crit by the effect; High Guard by the damage.
+    assert_eq!(CoreRules.primary_crit(7, 5), Some(2));
+    assert_eq!(HighGuardRules.primary_crit(7, 5), Some(1));
+    assert_eq!(HighGuardRules.primary_crit(6, 31), Some(4));
+    assert_eq!(HighGuardRules.sustained_damage_crits(31, &target), 3);
+  }
+
+  #[test_log::test]
+  fn test_checks() {
+    let lock = ShipAction::SensorLock {
+      target: "target".to_string(),
+    };
+    assert_eq!(
+      CoreRules.sensor_check(&lock, 8, 0, None),
+      SensorCheck {
+        threshold: 9,
+        effect: -1
+      }
+    );
+    assert_eq!(
+      CoreRules.sensor_check(&ShipAction::JamMissiles, 8, 3, None),
+      SensorCheck {
+        threshold: 10,
+        effect: 1
+      }
+    );
+    let jam = ShipAction::JamComms {
+      target: "target".to_string(),
+    };
+    assert_eq!(
+      CoreRules.sensor_check(&jam, 7, 1, Some(8)),
+      SensorCheck {
+        threshold: 0,
+        effect: 0
+      }
+    );
+
+    let check = |action, total| CoreRules.engineering_check(&action, total);
+    assert!(check(ShipAction::Jump, 5).critical_failure);
+    assert!(check(ShipAction::Jump, 6).success);
+    assert!(!check(ShipAction::OverloadDrive, 5).critical_failure);
+    assert!(check(ShipAction::OverloadPlant, 4).critical_failure);
+    assert_eq!(
+      check(
+        ShipAction::Repair {
+          system: ShipSystem::Jump
+        },
+        7
+      ),
+      EngineeringCheck {
+        target: 8,
+        success: false,
+        critical_failure: false
+      }
+    );
+  }
+
+  #[test_log::test]
+  fn test_jump_mishap() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut rolls = vec![];
+
+    // Even the best 2D can't save a jump that badly botched, and even the worst leaves a near miss displaced.
+    assert_eq!(CoreRules.jump_mishap("ship", -12, &mut rng, &mut rolls), JumpMishap::Misjump);
+    let JumpMishap::Displaced { offset, extra_turns } = CoreRules.jump_mishap("ship", 6, &mut rng, &mut rolls) else {
+      panic!("A jump missed by a little should only be displaced.");
+    };
+    assert_eq!(extra_turns, 1);
+    assert!(offset.magnitude() >= JUMP_DISPLACEMENT_UNIT - 1.0);
+    assert!(offset.magnitude() <= 6.0 * JUMP_DISPLACEMENT_UNIT + 1.0);
+
+    // The table roll and the displacement roll are both on the record.
+    assert_eq!(rolls.len(), 3);
+    assert!(rolls
+      .iter()
+      .all(|roll| matches!(roll, EffectMsg::DiceRoll { record } if record.purpose == RollPurpose::JumpMishap)));
+  }
+}
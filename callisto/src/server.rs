@@ -3,14 +3,20 @@
 //! (for reverting).
 //! `ServerMembersTable` holds membership indexed by the same unique id as used in `Server`, and stores
 //! the details for each current player in that server.
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 use std::time::SystemTime;
 
-use crate::entity::Entities;
-use crate::payloads::{email_to_display_name, Role, UserData};
-use crate::ship::{get_ship_templates_snapshot, ShipDesignTemplate};
-use crate::{error, warn, LOG_SCENARIO_ACTIVITY};
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::computer::FlightSeed;
+use crate::entity::{Entities, Entity, JumpDestination, Vec3};
+use crate::payloads::{email_to_display_name, EffectMsg, GameRollsMsg, Role, RollLogMsg, RollRecord, UserData};
+use crate::roster::Roster;
+use crate::ship::{get_ship_templates_snapshot, Ship, ShipDesignTemplate};
+use crate::storage::StorageBackend;
+use crate::{debug, error, warn, LOG_SCENARIO_ACTIVITY};
 use tracing::{event, Level};
 
 // Time in seconds for an unused scenario to exist before it is removed.
@@ -24,8 +30,45 @@ pub struct Server {
   // Unique random ID for this server
   pub id: String,
   pub entities: Mutex<Entities>,
-  pub initial_scenario: Entities,
+  pub initial_scenario: Mutex<Entities>,
   ship_templates: Arc<HashMap<String, Arc<ShipDesignTemplate>>>,
+  // Last flight solution per ship, used to warm start the solver when a pilot adjusts their course.
+  flight_seeds: Mutex<HashMap<String, FlightSeed>>,
+  dice: Mutex<Dice>,
+  // Ships in jump space bound for this server.
+  arrivals: Mutex<Vec<Arrival>>,
+}
+
+/// A ship in jump space, with everything about it as it was when it jumped.
+struct Arrival {
+  ship: Ship,
+  // Id of the server the ship jumped from.
+  origin: String,
+  emergence_point: Vec3,
+  turns_remaining: u32,
+}
+
+/// The server's random number generator and the audit trail of every roll made with it.
+struct Dice {
+  // Kept secret until the game ends.
+  seed: u64,
+  rng: SmallRng,
+  // Rolls of the current game, one entry per turn; the last is the turn in progress.
+  turns: Vec<Vec<RollRecord>>,
+  // Completed games (ended by a reset) with their seeds revealed.
+  finished: Vec<GameRollsMsg>,
+}
+
+impl Dice {
+  fn new() -> Self {
+    let seed = rand::random();
+    Dice {
+      seed,
+      rng: SmallRng::seed_from_u64(seed),
+      turns: vec![vec![]],
+      finished: vec![],
+    }
+  }
 }
 
 /// Maps a server ID to a server table that contains
@@ -64,17 +107,18 @@ impl PartialEq for Server {
 /// as well as the state of the server - the entities - as the
 /// initial state of the server - a static version of entities on creation.
 impl Server {
-  /// Create a new server with a id (usually random but created at the client) and a scenario name.
+  /// Create a new server with a id (usually random but created at the client) and a scenario name,
+  /// read from `storage`.
   ///
   /// # Panics
   /// Panics if the scenario file cannot be loaded (doesn't exist, etc.).
   #[must_use]
-  pub async fn new(id: &str, scenario_name: &str) -> Self {
+  pub async fn new(id: &str, scenario_name: &str, storage: &dyn StorageBackend) -> Self {
     let ship_templates = get_ship_templates_snapshot();
     let initial_scenario = if scenario_name.is_empty() {
       Entities::new()
     } else {
-      Entities::load_from_file_with_ship_templates(scenario_name, ship_templates.clone())
+      Entities::load_from_file_with_ship_templates(storage, scenario_name, ship_templates.clone())
         .await
         .unwrap_or_else(|e| {
           warn!("Issue loading scenario file {scenario_name}: {e}");
@@ -85,11 +129,80 @@ impl Server {
     Server {
       id: id.to_string(),
       entities: Mutex::new(initial_scenario.deep_copy()),
-      initial_scenario,
+      initial_scenario: Mutex::new(initial_scenario),
       ship_templates,
+      flight_seeds: Mutex::new(HashMap::new()),
+      dice: Mutex::new(Dice::new()),
+      arrivals: Mutex::new(vec![]),
     }
   }
 
+  /// Bring the campaign ships the scenario names in its `roster` in from `roster`, each as it finished its last
+  /// scenario.  Entries that are missing, destroyed or whose design is unknown are left out.
+  ///
+  /// # Panics
+  /// Panics if the lock on entities cannot be obtained.
+  #[must_use]
+  pub fn with_roster(self, roster: &Roster) -> Self {
+    self.place_roster_ships(roster);
+    self
+  }
+
+  /// Replace the campaign ships in the scenario's initial state with those in `roster`, as `with_roster` does, and
+  /// start the game over from there.  Used once a game's roster ships have been written back so the next game picks
+  /// up their damage rather than their state when the scenario was loaded.
+  ///
+  /// # Panics
+  /// Panics if the lock on entities cannot be obtained.
+  pub fn place_roster_ships(&self, roster: &Roster) {
+    let mut initial_scenario = self.initial_scenario.lock().unwrap();
+    initial_scenario
+      .ships
+      .retain(|_, ship| ship.read().unwrap().get_roster_id().is_none());
+    for placement in initial_scenario.roster.clone() {
+      let Some(entry) = roster.get(&placement.id) else {
+        warn!(
+          "(Server.place_roster_ships) Scenario {} wants roster ship {} which isn't on the roster.",
+          self.id, placement.id
+        );
+        continue;
+      };
+      if entry.current_hull == 0 {
+        debug!("(Server.place_roster_ships) Roster ship {} was destroyed.", entry.id);
+        continue;
+      }
+      let Some(design) = self.ship_templates.get(&entry.design) else {
+        warn!(
+          "(Server.place_roster_ships) Roster ship {} has unknown design {}.",
+          entry.id, entry.design
+        );
+        continue;
+      };
+      let ship = entry.to_ship(placement.position, placement.velocity, design);
+      initial_scenario
+        .ships
+        .insert(ship.get_name().to_string(), Arc::new(RwLock::new(ship)));
+    }
+    initial_scenario.deep_copy_into(&mut self.entities.lock().unwrap());
+  }
+
+  /// The campaign roster ships in the scenario as they stand.
+  ///
+  /// # Panics
+  /// Panics if the lock on entities cannot be obtained.
+  #[must_use]
+  pub fn roster_ships(&self) -> Vec<Ship> {
+    self
+      .entities
+      .lock()
+      .unwrap()
+      .ships
+      .values()
+      .map(|ship| ship.read().unwrap().clone())
+      .filter(|ship| ship.get_roster_id().is_some())
+      .collect()
+  }
+
   /// Get the ID of the server.
   #[must_use]
   pub fn get_id(&self) -> &str {
@@ -101,7 +214,156 @@ impl Server {
   /// # Panics
   /// Panics if the lock on entities cannot be obtained.
   pub fn reset(&self) {
-    *self.entities.lock().unwrap() = self.initial_scenario.clone();
+    *self.entities.lock().unwrap() = self.initial_scenario.lock().unwrap().clone();
+    self.end_game();
+  }
+
+  /// A random number generator for the next batch of rolls.  Each is seeded from the server's own generator so the
+  /// whole game can be replayed from the server seed.
+  ///
+  /// # Panics
+  /// Panics if the lock on the dice cannot be obtained.
+  #[must_use]
+  pub fn next_rng(&self) -> SmallRng {
+    SmallRng::seed_from_u64(self.dice.lock().unwrap().rng.next_u64())
+  }
+
+  /// Add `rolls` to the audit trail for the turn in progress.
+  ///
+  /// # Panics
+  /// Panics if the lock on the dice cannot be obtained.
+  pub fn log_rolls(&self, rolls: impl IntoIterator<Item = RollRecord>) {
+    let mut dice = self.dice.lock().unwrap();
+    if let Some(turn) = dice.turns.last_mut() {
+      turn.extend(rolls);
+    }
+  }
+
+  /// Close the audit trail for the turn just resolved and start the next.
+  ///
+  /// # Panics
+  /// Panics if the lock on the dice cannot be obtained.
+  pub fn end_turn(&self) {
+    self.dice.lock().unwrap().turns.push(vec![]);
+  }
+
+  /// End the current game: archive its rolls with the seed revealed and start afresh with a new seed.
+  ///
+  /// # Panics
+  /// Panics if the lock on the dice cannot be obtained.
+  pub fn end_game(&self) {
+    let mut dice = self.dice.lock().unwrap();
+    let fresh = Dice::new();
+    let mut turns = std::mem::take(&mut dice.turns);
+    if turns.last().is_some_and(Vec::is_empty) {
+      turns.pop();
+    }
+    let game = GameRollsMsg {
+      seed: Some(dice.seed),
+      turns,
+    };
+    dice.finished.push(game);
+    dice.seed = fresh.seed;
+    dice.rng = fresh.rng;
+    dice.turns = fresh.turns;
+  }
+
+  /// Every roll made on this server.  Only finished games include their seed.
+  ///
+  /// # Panics
+  /// Panics if the lock on the dice cannot be obtained.
+  #[must_use]
+  pub fn roll_log(&self) -> RollLogMsg {
+    let dice = self.dice.lock().unwrap();
+    let mut games = dice.finished.clone();
+    games.push(GameRollsMsg {
+      seed: None,
+      turns: dice.turns.clone(),
+    });
+    RollLogMsg { games }
+  }
+
+  /// Queue a ship that jumped from server `origin` for `destination` (which names this server).  Its design is
+  /// looked up again in this server's templates so it matches the rest of the scenario.
+  ///
+  /// # Errors
+  /// Returns an error if this server doesn't know the ship's design.
+  ///
+  /// # Panics
+  /// Panics if the lock on the arrivals cannot be obtained.
+  pub fn queue_arrival(&self, mut ship: Ship, origin: &str, destination: &JumpDestination) -> Result<(), String> {
+    let design = self
+      .get_ship_template(&ship.design.name)
+      .ok_or_else(|| format!("Unknown design {} for ship {}.", ship.design.name, ship.get_name()))?;
+    ship.design = design;
+    self.arrivals.lock().unwrap().push(Arrival {
+      ship,
+      origin: origin.to_string(),
+      emergence_point: destination.emergence_point,
+      turns_remaining: destination.transit_turns,
+    });
+    Ok(())
+  }
+
+  /// Count down one turn for every ship in jump space bound here and bring out those whose transit is over.  A ship
+  /// whose name is already taken in `entities` waits in jump space until it is free.
+  ///
+  /// # Panics
+  /// Panics if the lock on the arrivals cannot be obtained.
+  pub fn emerge_arrivals(&self, entities: &mut Entities) -> Vec<EffectMsg> {
+    self.advance_arrivals(None, entities)
+  }
+
+  /// As `emerge_arrivals`, but only for the ships that jumped from server `origin`.  While no one plays this server,
+  /// the ships bound here count their transit in the turns of the scenario they left.
+  ///
+  /// # Panics
+  /// Panics if the lock on the arrivals cannot be obtained.
+  pub fn emerge_arrivals_from(&self, origin: &str, entities: &mut Entities) -> Vec<EffectMsg> {
+    self.advance_arrivals(Some(origin), entities)
+  }
+
+  fn advance_arrivals(&self, origin: Option<&str>, entities: &mut Entities) -> Vec<EffectMsg> {
+    let mut arrivals = self.arrivals.lock().unwrap();
+    let mut effects = vec![];
+    arrivals.retain_mut(|arrival| {
+      if origin.is_some_and(|origin| origin != arrival.origin) {
+        return true;
+      }
+      arrival.turns_remaining = arrival.turns_remaining.saturating_sub(1);
+      let name = arrival.ship.get_name().to_string();
+      if arrival.turns_remaining > 0 {
+        return true;
+      }
+      if entities.ships.contains_key(&name) {
+        warn!(
+          "(Server.emerge_arrivals) {name} cannot emerge in {} as that name is taken.",
+          self.id
+        );
+        return true;
+      }
+      let mut ship = arrival.ship.clone();
+      ship.emerge(arrival.emergence_point);
+      entities.ships.insert(name.clone(), Arc::new(RwLock::new(ship)));
+      effects.push(EffectMsg::ShipEmerged { ship: name });
+      false
+    });
+    effects
+  }
+
+  /// Names of the ships in jump space bound for this server.
+  ///
+  /// # Panics
+  /// Panics if the lock on the arrivals cannot be obtained.
+  #[must_use]
+  pub fn ships_in_transit(&self) -> Vec<String> {
+    self
+      .arrivals
+      .lock()
+      .unwrap()
+      .iter()
+      .map(|arrival| arrival.ship.get_name().to_string())
+      .collect()
   }
 
   /// Get the entities of the server, unlocked.  This is a convenience routine that
@@ -115,6 +377,23 @@ impl Server {
     self.entities.lock()
   }
 
+  /// The last flight solution computed for `ship_name`, if any.
+  ///
+  /// # Panics
+  /// Panics if the lock on the flight solution cache cannot be obtained.
+  #[must_use]
+  pub fn flight_seed(&self, ship_name: &str) -> Option<FlightSeed> {
+    self.flight_seeds.lock().unwrap().get(ship_name).copied()
+  }
+
+  /// Remember `seed` as the latest flight solution for `ship_name`.
+  ///
+  /// # Panics
+  /// Panics if the lock on the flight solution cache cannot be obtained.
+  pub fn remember_flight_seed(&self, ship_name: &str, seed: FlightSeed) {
+    self.flight_seeds.lock().unwrap().insert(ship_name.to_string(), seed);
+  }
+
   #[must_use]
   pub fn get_ship_template(&self, design_name: &str) -> Option<Arc<ShipDesignTemplate>> {
     self.ship_templates.get(design_name).cloned()
@@ -141,6 +420,29 @@ impl ServerMembersTable {
       .insert(scenario_name.to_string(), template_name.to_string());
   }
 
+  /// Open an empty membership table for a server nobody has joined yet (e.g. one started for a ship's jump) so it is
+  /// listed with the current scenarios.  It expires like any other empty scenario once no ships are bound for it.
+  ///
+  /// # Returns
+  /// Returns true if the server wasn't already listed.
+  ///
+  /// # Panics
+  /// Panics if the current system clock is before the unix epoch.
+  pub fn open(&mut self, server_id: &str) -> bool {
+    if self.server_members.contains_key(server_id) {
+      return false;
+    }
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+    self.server_members.insert(
+      server_id.to_string(),
+      MembershipTable {
+        table: HashMap::new(),
+        last_exit: now,
+      },
+    );
+    true
+  }
+
   pub fn update(&mut self, server_id: &str, session_key: &str, email: &str, role: Role, ship: Option<String>) {
     if !self.scenario_definition.contains_key(server_id) {
       error!("Server {server_id} is not registered with a scenario description.");
@@ -211,6 +513,16 @@ impl ServerMembersTable {
       .collect()
   }
 
+  /// Number of members on each live server.
+  #[must_use]
+  pub fn member_counts(&self) -> Vec<(String, usize)> {
+    self
+      .server_members
+      .iter()
+      .map(|(server_id, members_table)| (server_id.clone(), members_table.table.len()))
+      .collect()
+  }
+
   #[must_use]
   pub fn current_scenario_list(&self) -> Vec<(String, String)> {
     self
@@ -225,32 +537,32 @@ impl ServerMembersTable {
       .collect()
   }
 
-  /// Find and remove any scenarios that have been empty for more than 5 minutes.
+  /// Find and remove any scenarios that have been empty for more than 5 minutes as of the unix time `now`.
+  /// Scenarios in `awaited` have ships in jump space bound for them and are kept however long they have been empty.
   ///
   /// # Returns
-  /// Returns true if any scenarios were removed.
-  ///
-  /// # Panics
-  /// Panics if the current system clock is before the unix epoch.
-  pub fn clean_expired_scenarios(&mut self) -> bool {
-    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
-    let initial_size = self.server_members.len();
+  /// The ids of the scenarios removed.
+  pub fn clean_expired_scenarios(&mut self, now: u64, awaited: &HashSet<String>) -> Vec<String> {
+    let mut expired = vec![];
     self.server_members.retain(|scenario_name, server_table| {
       // Need to log the event when deleting the scenario, thus the use of a somewhat empty if statement.
-      if server_table.table.is_empty() && now - server_table.last_exit > SCENARIO_EXPIRATION_TIME {
+      if server_table.table.is_empty()
+        && now.saturating_sub(server_table.last_exit) > SCENARIO_EXPIRATION_TIME
+        && !awaited.contains(scenario_name)
+      {
         event!(
           target: LOG_SCENARIO_ACTIVITY,
           Level::INFO,
           scenario = scenario_name,
           action = "expire"
         );
+        expired.push(scenario_name.clone());
         false
       } else {
         true
       }
     });
-
-    initial_size != self.server_members.len()
+    expired
   }
 }
 
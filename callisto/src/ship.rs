@@ -20,8 +20,8 @@ use crate::computer::MAX_ACCEL_WIGGLE_ROOM;
 use crate::crew::Crew;
 use crate::entity::{Entity, UpdateAction, Vec3, DEFAULT_ACCEL_DURATION, DELTA_TIME, DELTA_TIME_F64, G};
 use crate::payloads::Vec3asVec;
+use crate::storage::{join_path, LocalStorage, StorageBackend};
 use crate::{debug, error, warn};
-use crate::{list_local_or_cloud_dir, read_local_or_cloud_file};
 
 /// Directory holding one ship-design JSON file per design. Mirrors the
 /// scenarios layout: each file is a self-contained `ShipDesignTemplate`
@@ -31,6 +31,15 @@ use crate::{list_local_or_cloud_dir, read_local_or_cloud_file};
 /// scenario that references the design keeps working.
 pub const DEFAULT_SHIP_TEMPLATES_DIR: &str = "./ship_templates/";
 
+// Crew it takes to field each damage control team beyond the first.
+const DAMAGE_CONTROL_TEAM_CREW: u32 = 10;
+
+// Tons of ship each damage control team has room to work in beyond the first.
+const DAMAGE_CONTROL_TEAM_TONS: u32 = 500;
+
+// How far off the ship's axis (in degrees) a spinal mount can still be brought to bear.
+const SPINAL_ARC_DEGREES: u8 = 5;
+
 pub type ShipTemplateTable = HashMap<String, Arc<ShipDesignTemplate>>;
 type SharedShipTemplateTable = Arc<ShipTemplateTable>;
 
@@ -195,6 +204,34 @@ pub struct Ship {
   #[serde(default)]
   can_jump: bool,
 
+  // Name of the scenario's jump destination this ship is plotted for.  Without one a jump leaves the campaign.
+  #[derivative(PartialEq = "ignore")]
+  #[serde(default)]
+  jump_destination: Option<String>,
+
+  // Id of the campaign roster entry this ship was brought in from.  Its state is written back there when the
+  // scenario ends.
+  #[derivative(PartialEq = "ignore")]
+  #[serde(default)]
+  roster_id: Option<String>,
+
+  // How the engineer last shared out the power plant's output.  Without one every system asks for full power.
+  #[derivative(PartialEq = "ignore")]
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  power_allocation: Option<PowerAllocation>,
+
+  // The systems last reported offline for want of power, so a shortfall is only reported when it changes.
+  #[derivative(PartialEq = "ignore")]
+  #[serde(skip)]
+  reported_offline: Vec<String>,
+
+  // The way the pilot has turned the ship while it isn't thrusting (a unit vector).  Under thrust the ship points
+  // along its thrust instead.
+  #[derivative(PartialEq = "ignore")]
+  #[serde_as(as = "Option<Vec3asVec>")]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  facing: Option<Vec3>,
+
   // Engineer action fields
   #[derivative(PartialEq = "ignore")]
   #[serde(skip_deserializing, default, skip_serializing_if = "is_zero_u8")]
@@ -208,12 +245,10 @@ pub struct Ship {
   )]
   temporary_power_multiplier: f32,
 
+  // Systems the damage control teams are working on, oldest first.
   #[derivative(PartialEq = "ignore")]
-  last_repair_component: Option<ShipSystem>,
-
-  #[derivative(PartialEq = "ignore")]
-  #[serde(default, skip_serializing_if = "is_zero_u8")]
-  repair_bonus: u8,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  repair_tasks: Vec<RepairTask>,
 
   // Tracks whether engineer has taken an action this turn
   #[derivative(PartialEq = "ignore")]
@@ -247,6 +282,12 @@ pub struct Ship {
   #[serde(skip_deserializing, default, skip_serializing_if = "is_false")]
   leadership_rolled: bool,
 
+  // Whether the ship spent its engineer action skimming fuel from a gas giant.  While it is, it can't dodge and is
+  // easier to hit.  Reset by `reset_temporary_bonuses`.
+  #[derivative(PartialEq = "ignore")]
+  #[serde(skip_deserializing, default, skip_serializing_if = "is_false")]
+  skimming: bool,
+
   // Index by turning ShipSystem enum into usize.
   // Skip deserializing as we don't expect them when loading from a file
   // and don't intend to receive them from the client.
@@ -317,6 +358,10 @@ pub struct ShipDesignTemplate {
 pub struct Weapon {
   pub kind: WeaponType,
   pub mount: WeaponMount,
+  /// Where the weapon can fire.  Without one it bears all round, except a spinal mount which only fires along the
+  /// ship's axis whatever it declares.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub arc: Option<FiringArc>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -324,6 +369,61 @@ pub enum WeaponMount {
   Turret(u8),
   Barbette,
   Bay(BaySize),
+  /// A weapon built along the keel of the ship, so it is aimed by turning the whole ship.
+  Spinal,
+}
+
+/// The directions a weapon can fire in: those between `from` and `to` degrees off the ship's axis, where 0 is dead
+/// ahead and 180 dead astern.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FiringArc {
+  pub from: u8,
+  pub to: u8,
+}
+
+impl FiringArc {
+  pub const ALL_ROUND: FiringArc = FiringArc { from: 0, to: 180 };
+  pub const SPINAL: FiringArc = FiringArc {
+    from: 0,
+    to: SPINAL_ARC_DEGREES,
+  };
+
+  /// Whether a weapon with this arc on a ship pointing along `axis` can fire along `bearing`.  Without an axis
+  /// (a ship neither thrusting nor turned to a facing) only a weapon that bears all round can fire.
+  #[must_use]
+  pub fn bears(&self, axis: Option<Vec3>, bearing: Vec3) -> bool {
+    if *self == FiringArc::ALL_ROUND {
+      return true;
+    }
+    let Some(axis) = axis else {
+      return false;
+    };
+    if bearing.is_zero() {
+      return true;
+    }
+    let off_axis = axis.angle(bearing).0.to_degrees();
+    (f64::from(self.from)..=f64::from(self.to)).contains(&off_axis)
+  }
+}
+
+/// A damage control team's repair of one system.  It carries on from turn to turn until the system is fixed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RepairTask {
+  pub system: ShipSystem,
+  /// +1 for each failed attempt since the last success, as the team gets the measure of the damage.
+  #[serde(default, skip_serializing_if = "is_zero_u8")]
+  pub bonus: u8,
+}
+
+/// How the engineer shares the power plant's output among the ship's systems.  Basic systems always draw first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct PowerAllocation {
+  /// Thrust (in G) the maneuver drive is powered for.
+  pub maneuver: u8,
+  pub sensors: bool,
+  /// Whether each of the ship's weapons (by index into its design) is powered.
+  #[serde(default)]
+  pub weapons: Vec<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -352,7 +452,7 @@ pub enum Sensors {
   Advanced,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, FromRepr)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, PartialOrd, FromRepr)]
 pub enum Range {
   Short = 0,
   Medium,
@@ -425,14 +525,19 @@ impl Ship {
       dodge_thrust: 0,
       assist_gunners: false,
       can_jump: false,
+      jump_destination: None,
+      roster_id: None,
+      power_allocation: None,
+      reported_offline: vec![],
+      facing: None,
       temporary_maneuver: 0,
       temporary_power_multiplier: 1.0,
-      last_repair_component: None,
-      repair_bonus: 0,
+      repair_tasks: vec![],
       engineer_action_taken: false,
       evade_boost_used: false,
       leadership_points: 0,
       leadership_rolled: false,
+      skimming: false,
       point_defense_list: vec![],
     }
   }
@@ -468,22 +573,51 @@ impl Ship {
       self.name,
       max_accel,
       new_plan,
-      new_plan.0 .0.magnitude()
+      new_plan.first().0.magnitude()
     );
-    if new_plan.0.in_limits(max_accel) {
-      if let Some(second) = &new_plan.1 {
-        if second.in_limits(max_accel) {
-          self.plan = new_plan.clone();
-          Ok(())
-        } else {
-          Err("Flight plan has second acceleration that exceeds max acceleration".to_string())
-        }
-      } else {
+    match new_plan.iter().position(|segment| !segment.in_limits(max_accel)) {
+      None => {
         self.plan = new_plan.clone();
         Ok(())
       }
+      Some(0) => Err("Flight plan has first acceleration that exceeds max acceleration".to_string()),
+      Some(1) => Err("Flight plan has second acceleration that exceeds max acceleration".to_string()),
+      Some(index) => Err(format!(
+        "Flight plan segment {} has an acceleration that exceeds max acceleration",
+        index + 1
+      )),
+    }
+  }
+
+  /// The direction the ship points: along its thrust while it has any, otherwise the way it was last turned.
+  #[must_use]
+  pub fn axis(&self) -> Option<Vec3> {
+    let thrust = self.get_acceleration();
+    if thrust.is_zero() {
+      self.facing
     } else {
-      Err("Flight plan has first acceleration that exceeds max acceleration".to_string())
+      Some(thrust.normalize())
+    }
+  }
+
+  #[must_use]
+  pub fn get_facing(&self) -> Option<Vec3> {
+    self.facing
+  }
+
+  /// Turn the ship to point along `facing` whenever it isn't thrusting, or clear the facing with `None`.
+  ///
+  /// # Errors
+  /// Returns an error if `facing` has no direction.
+  pub fn set_facing(&mut self, facing: Option<Vec3>) -> Result<(), String> {
+    match facing {
+      Some(facing) if facing.is_zero() || !facing.magnitude().is_finite() => {
+        Err(format!("Facing {facing:?} for {} has no direction", self.name))
+      }
+      _ => {
+        self.facing = facing.map(InnerSpace::normalize);
+        Ok(())
+      }
     }
   }
 
@@ -491,12 +625,12 @@ impl Ship {
   /// outside this impl.
   #[must_use]
   pub fn get_acceleration(&self) -> Vec3 {
-    self.plan.0 .0
+    self.plan.first().0
   }
 
   #[must_use]
   pub fn max_acceleration(&self) -> u8 {
-    let power_limit = self.design.best_thrust(self.current_power);
+    let power_limit = self.powered_systems().maneuver;
     let maneuver_limit = self.current_maneuver;
 
     // TODO: Remove this once using a match doesn't trigger the warning about attributes on expressions being experimental.
@@ -566,6 +700,49 @@ impl Ship {
     self.can_jump
   }
 
+  #[must_use]
+  pub fn get_jump_destination(&self) -> Option<&str> {
+    self.jump_destination.as_deref()
+  }
+
+  pub fn set_jump_destination(&mut self, destination: Option<String>) {
+    self.jump_destination = destination;
+  }
+
+  #[must_use]
+  pub fn get_roster_id(&self) -> Option<&str> {
+    self.roster_id.as_deref()
+  }
+
+  pub fn set_roster_id(&mut self, roster_id: Option<String>) {
+    self.roster_id = roster_id;
+  }
+
+  #[must_use]
+  pub fn is_skimming(&self) -> bool {
+    self.skimming
+  }
+
+  pub fn set_skimming(&mut self, skimming: bool) {
+    self.skimming = skimming;
+  }
+
+  /// A ship dodges while it has dodge thrust left, unless it is held in a skimming orbit.
+  #[must_use]
+  pub fn is_dodging(&self) -> bool {
+    self.dodge_thrust > 0 && !self.skimming
+  }
+
+  /// Bring a ship out of jump space at `position`: at rest, with no plan, and with its jump plot used up.
+  pub fn emerge(&mut self, position: Vec3) {
+    self.position = position;
+    self.velocity = Vec3::zero();
+    self.plan = FlightPlan::default();
+    self.jump_destination = None;
+    self.can_jump = false;
+    self.sensor_locks.clear();
+  }
+
   /// Set possible pilot actions for the next round. These include allocating thrust to dodging as
   /// well as allocating a single point of thrust to assist gunners.
   ///
@@ -666,21 +843,41 @@ impl Ship {
   }
 
   #[must_use]
-  pub fn get_last_repair_component(&self) -> Option<ShipSystem> {
-    self.last_repair_component
+  pub fn get_repair_tasks(&self) -> &[RepairTask] {
+    &self.repair_tasks
   }
 
-  pub fn set_last_repair_component(&mut self, value: Option<ShipSystem>) {
-    self.last_repair_component = value;
+  pub fn get_repair_tasks_mut(&mut self) -> &mut Vec<RepairTask> {
+    &mut self.repair_tasks
   }
 
+  /// How many repairs the ship can have under way at once: one team for the engineer, plus one for each further
+  /// ten crew, but no more than the ship has room for (one per 500 tons beyond the first).  A ship with no crew left
+  /// has none.
   #[must_use]
-  pub fn get_repair_bonus(&self) -> u8 {
-    self.repair_bonus
+  pub fn damage_control_teams(&self) -> usize {
+    if self.current_crew == 0 {
+      return 0;
+    }
+    let by_crew = 1 + self.current_crew / DAMAGE_CONTROL_TEAM_CREW;
+    let by_size = 1 + self.design.displacement / DAMAGE_CONTROL_TEAM_TONS;
+    usize::try_from(by_crew.min(by_size)).unwrap_or(usize::MAX)
   }
 
-  pub fn set_repair_bonus(&mut self, value: u8) {
-    self.repair_bonus = value;
+  /// Put a damage control team to work on `system`.
+  ///
+  /// # Errors
+  /// Returns an error if a team is already on it or every team is busy.
+  pub fn start_repair(&mut self, system: ShipSystem) -> Result<(), String> {
+    if self.repair_tasks.iter().any(|task| task.system == system) {
+      return Err(format!("a team is already repairing {system:?}"));
+    }
+    let teams = self.damage_control_teams();
+    if self.repair_tasks.len() >= teams {
+      return Err(format!("all {teams} damage control teams are busy"));
+    }
+    self.repair_tasks.push(RepairTask { system, bonus: 0 });
+    Ok(())
   }
 
   /// Resets temporary bonuses from engineer overload actions and action tracking.
@@ -691,6 +888,7 @@ impl Ship {
     self.evade_boost_used = false;
     self.leadership_points = 0;
     self.leadership_rolled = false;
+    self.skimming = false;
   }
 
   #[must_use]
@@ -708,10 +906,13 @@ impl Ship {
     self.leadership_rolled
   }
 
-  /// Resets repair bonus tracking when engineer switches to a different component.
-  pub fn reset_repair_bonus(&mut self) {
-    self.repair_bonus = 0;
-    self.last_repair_component = None;
+  /// Fresh damage to `system` undoes the progress of any team repairing it.
+  pub fn repair_setback(&mut self, system: ShipSystem) {
+    for task in &mut self.repair_tasks {
+      if task.system == system {
+        task.bonus = 0;
+      }
+    }
   }
 
   #[must_use]
@@ -742,6 +943,124 @@ impl Ship {
   pub fn get_effective_power(&self) -> u32 {
     (self.current_power as f32 * self.temporary_power_multiplier) as u32
   }
+
+  #[must_use]
+  pub fn get_power_allocation(&self) -> Option<&PowerAllocation> {
+    self.power_allocation.as_ref()
+  }
+
+  pub fn set_power_allocation(&mut self, allocation: Option<PowerAllocation>) {
+    self.power_allocation = allocation;
+  }
+
+  /// Power drawn by the ship's basic systems plus everything in `allocation`.
+  #[must_use]
+  pub fn power_draw(&self, allocation: &PowerAllocation) -> u32 {
+    self.design.basic_power()
+      + if allocation.sensors {
+        self.design.sensors.power()
+      } else {
+        0
+      }
+      + self.design.thrust_power(allocation.maneuver)
+      + self
+        .design
+        .weapons
+        .iter()
+        .zip(&allocation.weapons)
+        .filter(|(_, powered)| **powered)
+        .map(|(weapon, _)| weapon.power())
+        .sum::<u32>()
+  }
+
+  /// What the power plant actually keeps running this turn.  The engineer's allocation (or full power to every
+  /// working system if there isn't one) is cut back until it fits within the effective power: first the powered
+  /// weapons, last first, then thrust a G at a time, and finally the sensors.
+  #[must_use]
+  pub fn powered_systems(&self) -> PowerAllocation {
+    let mut powered = self
+      .power_allocation
+      .clone()
+      .unwrap_or_else(|| self.design.full_power_allocation());
+    powered.maneuver = powered.maneuver.min(self.current_maneuver);
+    powered.weapons.resize(self.design.weapons.len(), false);
+    for (powered, active) in powered.weapons.iter_mut().zip(&self.active_weapons) {
+      *powered &= *active;
+    }
+
+    let available = self.get_effective_power();
+    for index in (0..powered.weapons.len()).rev() {
+      if self.power_draw(&powered) <= available {
+        return powered;
+      }
+      // Weapons that draw no power (missiles, sand) stay in action whatever happens.
+      if self.design.weapons[index].power() > 0 {
+        powered.weapons[index] = false;
+      }
+    }
+    while powered.maneuver > 0 && self.power_draw(&powered) > available {
+      powered.maneuver -= 1;
+    }
+    if self.power_draw(&powered) > available {
+      powered.sensors = false;
+    }
+    powered
+  }
+
+  /// The systems the engineer's allocation (or full power) asks for that the power plant can't carry.
+  #[must_use]
+  pub fn offline_systems(&self) -> Vec<String> {
+    let requested = self
+      .power_allocation
+      .clone()
+      .unwrap_or_else(|| self.design.full_power_allocation());
+    let powered = self.powered_systems();
+    let mut offline = self
+      .design
+      .weapons
+      .iter()
+      .enumerate()
+      .filter(|(index, _)| {
+        requested.weapons.get(*index).copied().unwrap_or(false)
+          && self.active_weapons.get(*index).copied().unwrap_or(false)
+          && !powered.weapons[*index]
+      })
+      .map(|(_, weapon)| String::from(weapon))
+      .collect::<Vec<_>>();
+    if powered.maneuver < requested.maneuver.min(self.current_maneuver) {
+      offline.push(format!(
+        "{}G of thrust",
+        requested.maneuver.min(self.current_maneuver) - powered.maneuver
+      ));
+    }
+    if requested.sensors && !powered.sensors {
+      offline.push("sensors".to_string());
+    }
+    offline
+  }
+
+  /// The systems offline for want of power (see [`Ship::offline_systems`]) if they aren't the ones last reported, which
+  /// they then become.
+  pub fn report_offline_systems(&mut self) -> Option<Vec<String>> {
+    let offline = self.offline_systems();
+    if offline == self.reported_offline {
+      return None;
+    }
+    self.reported_offline.clone_from(&offline);
+    Some(offline)
+  }
+
+  /// Whether weapon `weapon_id` is both working and powered.
+  #[must_use]
+  pub fn weapon_powered(&self, weapon_id: usize) -> bool {
+    self.powered_systems().weapons.get(weapon_id).copied().unwrap_or(false)
+  }
+
+  /// Charging the jump drive takes everything the power plant makes beyond the basic systems.
+  #[must_use]
+  pub fn can_power_jump(&self) -> bool {
+    self.get_effective_power() >= self.design.basic_power() + self.design.jump_power()
+  }
 }
 
 impl PartialOrd for Ship {
@@ -899,15 +1218,14 @@ enum ShipTemplateFileOutcome {
 /// Returns `Err` if listing the directory fails OR if any per-file read
 /// fails. Per-file parse errors are not propagated.
 pub async fn load_ship_templates_from_dir(
-  dir: &str,
+  storage: &dyn StorageBackend, dir: &str,
 ) -> Result<HashMap<String, Arc<ShipDesignTemplate>>, Box<dyn std::error::Error>> {
-  let entries = list_local_or_cloud_dir(dir).await?;
-  let dir_normalized = dir.trim_end_matches('/').to_string();
+  let entries = storage.list(dir).await.map_err(|e| e as Box<dyn std::error::Error>)?;
 
   let results = join_all(entries.into_iter().map(|entry| {
-    let path = format!("{dir_normalized}/{entry}");
+    let path = join_path(dir, &entry);
     async move {
-      match read_local_or_cloud_file(&path).await {
+      match storage.read(&path).await {
         Ok(body) => match serde_json::from_slice::<ShipDesignTemplate>(&body) {
           Ok(template) => ShipTemplateFileOutcome::Loaded(template),
           Err(e) => ShipTemplateFileOutcome::ParseError(path, format!("parse error: {e}")),
@@ -955,39 +1273,39 @@ pub async fn load_ship_templates_from_dir(
 ///
 /// If the directory cannot be listed.
 pub async fn config_test_ship_templates() {
-  let templates = load_ship_templates_from_dir(DEFAULT_SHIP_TEMPLATES_DIR)
+  let templates = load_ship_templates_from_dir(&LocalStorage, DEFAULT_SHIP_TEMPLATES_DIR)
     .await
     .expect("Unable to load ship templates directory.");
   replace_ship_templates(templates);
 }
 
 impl ShipDesignTemplate {
-  // Making this overly simplistic for now.  Assume for power usage that
-  // basic systems and sensors are prioritized, and we ignore weapons.
+  /// Power for life support, gravity and the rest of the basic ship systems: 20% of displacement.
   #[must_use]
-  pub fn best_thrust(&self, current_power: u32) -> u8 {
-    // First take out basic ship systems.
-    let power = current_power.saturating_sub(self.displacement / 5);
-    // Now adjust for sensors.  If we subtract all the power then we have no power left.
-    let power = power.saturating_sub(match self.sensors {
-      Sensors::Basic => 0,
-      Sensors::Civilian => 1,
-      Sensors::Military => 2,
-      Sensors::Improved => 4,
-      Sensors::Advanced => 6,
-    });
+  pub fn basic_power(&self) -> u32 {
+    self.displacement / 5
+  }
 
-    if power == 0 {
-      return 0;
-    }
+  /// Power to run the maneuver drive at `thrust` G: 10% of displacement per G.
+  #[must_use]
+  pub fn thrust_power(&self, thrust: u8) -> u32 {
+    self.displacement * u32::from(thrust) / 10
+  }
+
+  /// Power to charge the jump drive: 10% of displacement per jump number.
+  #[must_use]
+  pub fn jump_power(&self) -> u32 {
+    self.displacement * u32::from(self.jump) / 10
+  }
 
-    // Power left for thrust is one thrust per 10% of ship displacement in power units.
-    // Displacement cannot be over 1M tons ever.
-    // If somehow power is enough we are above u8::MAX then just use that as really thrust cannot be that high.
-    (power * 10 / self.displacement)
-      .try_into()
-      .unwrap_or(u8::MAX)
-      .min(self.maneuver)
+  /// Full power to the drive, the sensors and every weapon.
+  #[must_use]
+  pub fn full_power_allocation(&self) -> PowerAllocation {
+    PowerAllocation {
+      maneuver: self.maneuver,
+      sensors: true,
+      weapons: vec![true; self.weapons.len()],
+    }
   }
 }
 
@@ -1003,6 +1321,9 @@ impl Ord for Weapon {
     // but seems more readable when expanded.
     #[allow(clippy::match_same_arms)]
     match (&self.mount, &other.mount) {
+      (WeaponMount::Spinal, WeaponMount::Spinal) => self.kind.cmp(&other.kind),
+      (WeaponMount::Spinal, _) => std::cmp::Ordering::Less,
+      (_, WeaponMount::Spinal) => std::cmp::Ordering::Greater,
       (WeaponMount::Bay(BaySize::Large), WeaponMount::Bay(BaySize::Large)) => self.kind.cmp(&other.kind),
       (WeaponMount::Bay(BaySize::Large), _) => std::cmp::Ordering::Less,
       (WeaponMount::Bay(BaySize::Medium), WeaponMount::Bay(BaySize::Large)) => std::cmp::Ordering::Greater,
@@ -1029,6 +1350,18 @@ impl Sensors {
       rhs
     }
   }
+
+  /// Power drawn by sensors of this grade.
+  #[must_use]
+  pub fn power(self) -> u32 {
+    match self {
+      Sensors::Basic => 0,
+      Sensors::Civilian => 1,
+      Sensors::Military => 2,
+      Sensors::Improved => 4,
+      Sensors::Advanced => 6,
+    }
+  }
 }
 impl From<Sensors> for i32 {
   fn from(s: Sensors) -> Self {
@@ -1125,6 +1458,38 @@ impl From<&Weapon> for String {
         format!("{} medium bay", String::from(kind))
       }
       (kind, WeaponMount::Bay(BaySize::Large)) => format!("{} large bay", String::from(kind)),
+      (kind, WeaponMount::Spinal) => format!("{} spinal mount", String::from(kind)),
+    }
+  }
+}
+
+impl Weapon {
+  /// Power drawn by this weapon: the draw of one of its kind times the number of them the mount holds (a barbette
+  /// or bay counting as several).  Missiles and sand need no power.
+  #[must_use]
+  pub fn power(&self) -> u32 {
+    let each = match self.kind {
+      WeaponType::Beam | WeaponType::Pulse => 4,
+      WeaponType::Particle => 5,
+      WeaponType::Missile | WeaponType::Sand => 0,
+    };
+    let count = match self.mount {
+      WeaponMount::Turret(n) => u32::from(n),
+      WeaponMount::Barbette => 3,
+      WeaponMount::Bay(BaySize::Small) => 4,
+      WeaponMount::Bay(BaySize::Medium) => 10,
+      WeaponMount::Bay(BaySize::Large) => 20,
+      WeaponMount::Spinal => 50,
+    };
+    each * count
+  }
+
+  /// Where this weapon can fire: its declared arc, all round if it has none, or only along the axis if spinal.
+  #[must_use]
+  pub fn firing_arc(&self) -> FiringArc {
+    match self.mount {
+      WeaponMount::Spinal => FiringArc::SPINAL,
+      _ => self.arc.unwrap_or(FiringArc::ALL_ROUND),
     }
   }
 }
@@ -1206,31 +1571,46 @@ impl From<AccelPair> for (Vec3, u64) {
 }
 
 impl AccelPair {
+  /// A segment with no thrust: the ship drifts at its current velocity for `time` seconds.
+  #[must_use]
+  pub fn coast(time: u64) -> Self {
+    AccelPair(Vec3::zero(), time)
+  }
+
+  #[must_use]
+  pub fn is_coast(&self) -> bool {
+    self.0 == Vec3::zero()
+  }
+
   #[must_use]
   pub fn in_limits(&self, limit: f64) -> bool {
     self.0.magnitude() <= limit + MAX_ACCEL_WIGGLE_ROOM
       || approx::relative_eq!(&self.0.magnitude(), &limit, max_relative = 1e-3)
   }
 }
+
+/// A flight plan is a sequence of thrust and coast segments flown in order. The first segment is the one
+/// being flown now. A plan always holds at least one segment; an exhausted plan is a single zero-length coast.
+///
+/// On the wire this is an array of `[acceleration, duration]` pairs, so one- and two-segment plans look as
+/// they always have.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct FlightPlan(
-  pub AccelPair,
-  #[serde(
-    default,
-    skip_serializing_if = "Option::is_none",
-    with = "::serde_with::rust::unwrap_or_skip"
-  )]
-  pub Option<AccelPair>,
-);
+#[serde(from = "Vec<AccelPair>")]
+pub struct FlightPlan(Vec<AccelPair>);
+
+impl From<Vec<AccelPair>> for FlightPlan {
+  fn from(segments: Vec<AccelPair>) -> Self {
+    if segments.is_empty() {
+      FlightPlan::default()
+    } else {
+      FlightPlan(segments)
+    }
+  }
+}
 
 impl From<Vec<(Vec3, u64)>> for FlightPlan {
   fn from(vec: Vec<(Vec3, u64)>) -> Self {
-    match vec.len() {
-      0 => FlightPlan::default(),
-      1 => FlightPlan(vec[0].into(), None),
-      2 => FlightPlan(vec[0].into(), Some(vec[1].into())),
-      _ => panic!("(From<Vec<(Vec3, u64)>> for FlightPlan) illegal length of vector"),
-    }
+    vec.into_iter().map(AccelPair::from).collect::<Vec<_>>().into()
   }
 }
 
@@ -1240,57 +1620,77 @@ fn renormalize(orig: Vec3, limit: f64) -> Vec3 {
 }
 impl Default for FlightPlan {
   fn default() -> Self {
-    FlightPlan(AccelPair(Vec3::zero(), DEFAULT_ACCEL_DURATION), None)
+    FlightPlan(vec![AccelPair(Vec3::zero(), DEFAULT_ACCEL_DURATION)])
   }
 }
 
 impl FlightPlan {
   #[must_use]
   pub fn new(first: AccelPair, second: Option<AccelPair>) -> Self {
-    FlightPlan(first, second)
+    FlightPlan(std::iter::once(first).chain(second).collect())
   }
 
   // Constructor that creates a flight plan that just has a single acceleration.
   // We use i64::MAX to represent infinite time.
   #[must_use]
   pub fn acceleration(accel: Vec3) -> Self {
-    FlightPlan((accel, DEFAULT_ACCEL_DURATION).into(), None)
+    FlightPlan(vec![(accel, DEFAULT_ACCEL_DURATION).into()])
+  }
+
+  /// The segments of this plan, in the order they will be flown.
+  #[must_use]
+  pub fn segments(&self) -> &[AccelPair] {
+    &self.0
+  }
+
+  /// The segment being flown now.
+  #[must_use]
+  pub fn first(&self) -> &AccelPair {
+    &self.0[0]
+  }
+
+  #[must_use]
+  pub fn second(&self) -> Option<&AccelPair> {
+    self.0.get(1)
   }
 
-  // When the first element is set we clear the second element.
+  // When the first element is set we clear the rest of the plan.
   pub fn set_first(&mut self, accel: Vec3, time: u64) {
-    self.0 = (accel, time).into();
-    self.1 = None;
+    self.0 = vec![(accel, time).into()];
   }
 
+  // Replaces everything after the first segment.
   pub fn set_second(&mut self, accel: Vec3, time: u64) {
-    self.1 = Some((accel, time).into());
+    self.0.truncate(1);
+    self.0.push((accel, time).into());
+  }
+
+  /// Append a segment to the end of the plan.
+  pub fn push(&mut self, segment: AccelPair) {
+    self.0.push(segment);
   }
 
   #[must_use]
   pub fn has_second(&self) -> bool {
-    self.1.is_some()
+    self.0.len() > 1
   }
 
   #[must_use]
   pub fn duration(&self) -> u64 {
-    self.0 .1 + self.1.as_ref().map_or(0, |a| a.1)
+    self.0.iter().map(|segment| segment.1).sum()
   }
 
+  /// True when no thrust remains anywhere in the plan, so the ship simply drifts.
   #[must_use]
   pub fn empty(&self) -> bool {
-    self.0 .1 == 0 || self.0 .0 == Vec3::zero()
+    self.0.iter().all(|segment| segment.1 == 0 || segment.is_coast())
   }
 
   // Ensure the thrust limit on a flight plan. Limit is in m/s^2 (not G's)
   pub fn ensure_thrust_limit(&mut self, limit: f64) {
-    if self.0 .0.magnitude() > limit + MAX_ACCEL_WIGGLE_ROOM {
-      self.0 .0 = renormalize(self.0 .0, limit);
-    }
-
-    if let Some(second) = &self.1 {
-      if second.0.magnitude() > limit + MAX_ACCEL_WIGGLE_ROOM {
-        self.set_second(renormalize(second.0, limit), second.1);
+    for segment in &mut self.0 {
+      if segment.0.magnitude() > limit + MAX_ACCEL_WIGGLE_ROOM {
+        segment.0 = renormalize(segment.0, limit);
       }
     }
   }
@@ -1304,54 +1704,32 @@ impl FlightPlan {
   /// * `time` - The time to advance the plan.
   #[must_use]
   pub fn advance_time(&mut self, time: u64) -> Self {
-    if time < self.0 .1 {
-      // If time is less than the first duration:
-      // This plan: first acceleration reduced by the time
-      // Return: the first acceleration for time
-      self.0 .1 -= time;
-      FlightPlan::new((self.0 .0, time).into(), None)
-    } else {
-      match &self.1.clone() {
-        Some(second) if time < self.0 .1 + second.1 => {
-          // If time is between the first duration plus the second duration:
-          // This plan: The second acceleration for the remaining time (duration of the entire plan less the time)
-          // Return: The first acceleration for its full time, and the portion of the second acceleration up to time.
-          let new_first = self.0.clone();
-          let first_time = self.0 .1;
-          self.0 = (second.0, second.1 - (time - self.0 .1)).into();
-          self.1 = None;
-          debug!(
-            "(FlightPlan.advance_time) self: {:?} new_first: {:?} second: {:?} time: {} first_time: {}",
-            self, new_first, second, time, first_time
-          );
-          FlightPlan::new(
-            new_first,
-            if time <= first_time {
-              None
-            } else {
-              Some((second.0, time - first_time).into())
-            },
-          )
-        }
-        _ => {
-          // If time is more than first and second durations:
-          // This plan: becomes a zero acceleration plan.
-          // Return: the entire plan.
-          let result = self.clone();
-          self.0 = (Vec3::zero(), 0).into();
-          self.1 = None;
-          result
-        }
+    let mut advanced = Vec::new();
+    let mut remaining = time;
+    while remaining > 0 && !self.0.is_empty() {
+      let segment = &mut self.0[0];
+      if remaining < segment.1 {
+        // Partway through this segment: fly it for what's left of `time` and keep the rest.
+        segment.1 -= remaining;
+        advanced.push(AccelPair(segment.0, remaining));
+        remaining = 0;
+      } else {
+        // This segment completes within `time`.
+        remaining -= segment.1;
+        advanced.push(self.0.remove(0));
       }
     }
+
+    if self.0.is_empty() {
+      // The whole plan has been flown; it becomes a zero acceleration plan.
+      self.0.push(AccelPair::coast(0));
+    }
+    debug!("(FlightPlan.advance_time) Advanced {time}: flown {advanced:?}, remaining {self:?}");
+    advanced.into()
   }
 
   pub fn iter(&self) -> impl Iterator<Item = AccelPair> + '_ {
-    if let Some(second) = &self.1 {
-      vec![self.0.clone(), second.clone()].into_iter()
-    } else {
-      vec![self.0.clone()].into_iter()
-    }
+    self.0.iter().cloned()
   }
 }
 
@@ -1375,18 +1753,22 @@ impl Default for ShipDesignTemplate {
         Weapon {
           kind: WeaponType::Pulse,
           mount: WeaponMount::Turret(2),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Pulse,
           mount: WeaponMount::Turret(2),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Sand,
           mount: WeaponMount::Turret(2),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Sand,
           mount: WeaponMount::Turret(2),
+          arc: None,
         },
       ],
       tl: 15,
@@ -1668,27 +2050,27 @@ mod tests {
     let time1 = 5000;
     flight_plan.set_first(accel1, time1);
 
-    assert_eq!(flight_plan.0 .0, accel1);
-    assert_eq!(flight_plan.0 .1, time1);
-    assert_eq!(flight_plan.1, None);
+    assert_eq!(flight_plan.first().0, accel1);
+    assert_eq!(flight_plan.first().1, time1);
+    assert_eq!(flight_plan.second(), None);
 
     // Test set_second
     let accel2 = Vec3::new(-2.0, -1.0, 0.0);
     let time2 = 3000;
     flight_plan.set_second(accel2, time2);
 
-    assert_eq!(flight_plan.0 .0, accel1);
-    assert_eq!(flight_plan.0 .1, time1);
-    assert_eq!(flight_plan.1, Some(AccelPair(accel2, time2)));
+    assert_eq!(flight_plan.first().0, accel1);
+    assert_eq!(flight_plan.first().1, time1);
+    assert_eq!(flight_plan.second(), Some(&AccelPair(accel2, time2)));
 
     // Test overwriting first acceleration
     let new_accel1 = Vec3::new(4.0, 5.0, 6.0) * G;
     let new_time1 = 2000;
     flight_plan.set_first(new_accel1, new_time1);
 
-    assert_eq!(flight_plan.0 .0, new_accel1);
-    assert_eq!(flight_plan.0 .1, new_time1);
-    assert_eq!(flight_plan.1, None);
+    assert_eq!(flight_plan.first().0, new_accel1);
+    assert_eq!(flight_plan.first().1, new_time1);
+    assert_eq!(flight_plan.second(), None);
 
     // Test overwriting second acceleration
     flight_plan.set_second(accel2, time2);
@@ -1696,9 +2078,9 @@ mod tests {
     let new_time2 = 4000;
     flight_plan.set_second(new_accel2, new_time2);
 
-    assert_eq!(flight_plan.0 .0, new_accel1);
-    assert_eq!(flight_plan.0 .1, new_time1);
-    assert_eq!(flight_plan.1, Some(AccelPair(new_accel2, new_time2)));
+    assert_eq!(flight_plan.first().0, new_accel1);
+    assert_eq!(flight_plan.first().1, new_time1);
+    assert_eq!(flight_plan.second(), Some(&AccelPair(new_accel2, new_time2)));
   }
 
   #[test_log::test]
@@ -1713,10 +2095,10 @@ mod tests {
 
     flight_plan.ensure_thrust_limit(6.0 * G);
 
-    assert_ulps_eq!(flight_plan.0 .0, accel1);
-    assert_eq!(flight_plan.0 .1, time1);
-    assert_ulps_eq!(flight_plan.1.as_ref().unwrap().0, Vec3::new(1.0, 2.0, 2.0) * G);
-    assert_eq!(flight_plan.1.as_ref().unwrap().1, 3000);
+    assert_ulps_eq!(flight_plan.first().0, accel1);
+    assert_eq!(flight_plan.first().1, time1);
+    assert_ulps_eq!(flight_plan.second().unwrap().0, Vec3::new(1.0, 2.0, 2.0) * G);
+    assert_eq!(flight_plan.second().unwrap().1, 3000);
 
     // Test case 2: First acceleration exceeds limit
     let accel2 = Vec3::new(6.0, 8.0, 0.0) * G; // magnitude 10
@@ -1726,21 +2108,21 @@ mod tests {
     flight_plan.ensure_thrust_limit(6.0 * G);
 
     let expected_accel2 = accel2.normalize() * 6.0 * G;
-    assert_ulps_eq!(flight_plan.0 .0, expected_accel2);
-    assert_eq!(flight_plan.0 .1, time1);
-    assert_ulps_eq!(flight_plan.1.as_ref().unwrap().0, Vec3::new(1.0, 2.0, 2.0) * G);
-    assert_eq!(flight_plan.1.as_ref().unwrap().1, 3000);
+    assert_ulps_eq!(flight_plan.first().0, expected_accel2);
+    assert_eq!(flight_plan.first().1, time1);
+    assert_ulps_eq!(flight_plan.second().unwrap().0, Vec3::new(1.0, 2.0, 2.0) * G);
+    assert_eq!(flight_plan.second().unwrap().1, 3000);
 
     // Test case 3: Second acceleration exceeds limit
     flight_plan.set_second(Vec3::new(4.0, 4.0, 4.0) * G, 2000); // magnitude ~6.93G
 
     flight_plan.ensure_thrust_limit(6.0 * G);
 
-    assert_ulps_eq!(flight_plan.0 .0, expected_accel2);
-    assert_eq!(flight_plan.0 .1, time1);
+    assert_ulps_eq!(flight_plan.first().0, expected_accel2);
+    assert_eq!(flight_plan.first().1, time1);
     let expected_accel3 = Vec3::new(4.0, 4.0, 4.0).normalize() * 6.0 * G;
-    assert_ulps_eq!(flight_plan.1.as_ref().unwrap().0, expected_accel3);
-    assert_eq!(flight_plan.1.as_ref().unwrap().1, 2000);
+    assert_ulps_eq!(flight_plan.second().unwrap().0, expected_accel3);
+    assert_eq!(flight_plan.second().unwrap().1, 2000);
 
     // Test case 4: Both accelerations exceed limit
     flight_plan.set_first(Vec3::new(10.0, 0.0, 0.0) * G, 1000);
@@ -1748,10 +2130,10 @@ mod tests {
 
     flight_plan.ensure_thrust_limit(4.0 * G);
 
-    assert_ulps_eq!(flight_plan.0 .0, Vec3::new(4.0, 0.0, 0.0) * G);
-    assert_eq!(flight_plan.0 .1, 1000);
-    assert_ulps_eq!(flight_plan.1.as_ref().unwrap().0, Vec3::new(0.0, 3.2, 2.4) * G);
-    assert_eq!(flight_plan.1.as_ref().unwrap().1, 1500);
+    assert_ulps_eq!(flight_plan.first().0, Vec3::new(4.0, 0.0, 0.0) * G);
+    assert_eq!(flight_plan.first().1, 1000);
+    assert_ulps_eq!(flight_plan.second().unwrap().0, Vec3::new(0.0, 3.2, 2.4) * G);
+    assert_eq!(flight_plan.second().unwrap().1, 1500);
   }
 
   #[test_log::test]
@@ -1766,21 +2148,21 @@ mod tests {
 
     // Test case 1: Advance time less than first duration
     let result = flight_plan.advance_time(2000);
-    assert_eq!(result.0 .0, accel1);
-    assert_eq!(result.0 .1, 2000);
-    assert_eq!(result.1, None);
-    assert_eq!(flight_plan.0 .0, accel1);
-    assert_eq!(flight_plan.0 .1, 3000);
-    assert_eq!(flight_plan.1, Some(AccelPair(accel2, time2)));
+    assert_eq!(result.first().0, accel1);
+    assert_eq!(result.first().1, 2000);
+    assert_eq!(result.second(), None);
+    assert_eq!(flight_plan.first().0, accel1);
+    assert_eq!(flight_plan.first().1, 3000);
+    assert_eq!(flight_plan.second(), Some(&AccelPair(accel2, time2)));
 
     // Test case 2: Advance time equal to remaining first duration
     let result = flight_plan.advance_time(3000);
-    assert_eq!(result.0 .0, accel1);
-    assert_eq!(result.0 .1, 3000);
-    assert_eq!(result.1, None);
-    assert_eq!(flight_plan.0 .0, accel2);
-    assert_eq!(flight_plan.0 .1, time2);
-    assert_eq!(flight_plan.1, None);
+    assert_eq!(result.first().0, accel1);
+    assert_eq!(result.first().1, 3000);
+    assert_eq!(result.second(), None);
+    assert_eq!(flight_plan.first().0, accel2);
+    assert_eq!(flight_plan.first().1, time2);
+    assert_eq!(flight_plan.second(), None);
 
     // Reset flight plan for next test
     flight_plan.set_first(accel1, time1);
@@ -1788,21 +2170,71 @@ mod tests {
 
     // Test case 3: Advance time more than first duration but less than total duration
     let result = flight_plan.advance_time(6000);
-    assert_eq!(result.0 .0, accel1);
-    assert_eq!(result.0 .1, time1);
-    assert_eq!(result.1, Some(AccelPair(accel2, 1000)));
-    assert_eq!(flight_plan.0 .0, accel2);
-    assert_eq!(flight_plan.0 .1, 2000);
-    assert_eq!(flight_plan.1, None);
+    assert_eq!(result.first().0, accel1);
+    assert_eq!(result.first().1, time1);
+    assert_eq!(result.second(), Some(&AccelPair(accel2, 1000)));
+    assert_eq!(flight_plan.first().0, accel2);
+    assert_eq!(flight_plan.first().1, 2000);
+    assert_eq!(flight_plan.second(), None);
 
     // Test case 4: Advance time more than total duration
     let result = flight_plan.advance_time(3000);
-    assert_eq!(result.0 .0, accel2);
-    assert_eq!(result.0 .1, 2000);
-    assert_eq!(result.1, None);
-    assert_eq!(flight_plan.0 .0, Vec3::zero());
-    assert_eq!(flight_plan.0 .1, 0);
-    assert_eq!(flight_plan.1, None);
+    assert_eq!(result.first().0, accel2);
+    assert_eq!(result.first().1, 2000);
+    assert_eq!(result.second(), None);
+    assert_eq!(flight_plan.first().0, Vec3::zero());
+    assert_eq!(flight_plan.first().1, 0);
+    assert_eq!(flight_plan.second(), None);
+  }
+
+  #[test_log::test]
+  fn test_flight_plan_multi_segment() {
+    let burn = Vec3::new(1.0, 0.0, 0.0) * G;
+    let brake = Vec3::new(-1.0, 0.0, 0.0) * G;
+    let mut flight_plan = FlightPlan::new(AccelPair::coast(1000), Some(AccelPair(burn, 2000)));
+    flight_plan.push(AccelPair(brake, 2000));
+    assert_eq!(flight_plan.segments().len(), 3);
+    assert_eq!(flight_plan.duration(), 5000);
+    assert!(!flight_plan.empty());
+
+    // Crossing two segment boundaries in one step.
+    let flown = flight_plan.advance_time(3500);
+    assert_eq!(
+      flown.segments(),
+      &[AccelPair::coast(1000), AccelPair(burn, 2000), AccelPair(brake, 500)]
+    );
+    assert_eq!(flight_plan.segments(), &[AccelPair(brake, 1500)]);
+
+    // Thrust limits apply to every segment, not just the first two.
+    let mut flight_plan = FlightPlan::new(AccelPair(burn, 1000), Some(AccelPair::coast(1000)));
+    flight_plan.push(AccelPair(brake * 8.0, 1000));
+    flight_plan.ensure_thrust_limit(4.0 * G);
+    assert_ulps_eq!(flight_plan.segments()[2].0.magnitude(), 4.0 * G);
+    assert_eq!(flight_plan.segments()[0].0, burn);
+
+    // A ship rejects a plan whose later segment is beyond its drive.
+    let mut ship = Ship::new(
+      "TestShip".to_string(),
+      Vec3::zero(),
+      Vec3::zero(),
+      &Arc::new(ShipDesignTemplate::default()),
+      None,
+    );
+    let max_accel = f64::from(ship.max_acceleration()) * G;
+    let mut too_fast = FlightPlan::new(AccelPair::coast(1000), Some(AccelPair::coast(1000)));
+    too_fast.push(AccelPair(Vec3::new(max_accel * 2.0, 0.0, 0.0), 1000));
+    assert_eq!(
+      ship.set_flight_plan(&too_fast),
+      Err("Flight plan segment 3 has an acceleration that exceeds max acceleration".to_string())
+    );
+
+    // Longer plans round trip, and legacy one and two element plans still parse.
+    let json = serde_json::to_string(&flight_plan).unwrap();
+    assert_eq!(serde_json::from_str::<FlightPlan>(&json).unwrap(), flight_plan);
+    let legacy = serde_json::from_str::<FlightPlan>("[[[1.0, 0.0, 0.0], 100], [[0.0, 0.0, 0.0], 50]]").unwrap();
+    assert_eq!(legacy.second(), Some(&AccelPair::coast(50)));
+    let single = serde_json::from_str::<FlightPlan>("[[[1.0, 0.0, 0.0], 100]]").unwrap();
+    assert!(!single.has_second());
   }
 
   #[test_log::test]
@@ -2032,42 +2464,51 @@ mod tests {
     let large_bay_beam = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Bay(BaySize::Large),
+      arc: None,
     };
     let large_bay_pulse = Weapon {
       kind: WeaponType::Pulse,
       mount: WeaponMount::Bay(BaySize::Large),
+      arc: None,
     };
     let medium_bay = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Bay(BaySize::Medium),
+      arc: None,
     };
 
     let medium_bay_missile = Weapon {
       kind: WeaponType::Missile,
       mount: WeaponMount::Bay(BaySize::Medium),
+      arc: None,
     };
 
     let small_bay = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Bay(BaySize::Small),
+      arc: None,
     };
 
     let small_bay_pulse = Weapon {
       kind: WeaponType::Pulse,
       mount: WeaponMount::Bay(BaySize::Small),
+      arc: None,
     };
 
     let barbette = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Barbette,
+      arc: None,
     };
     let turret = Weapon {
       kind: WeaponType::Beam,
       mount: WeaponMount::Turret(2),
+      arc: None,
     };
     let turret_pulse = Weapon {
       kind: WeaponType::Pulse,
       mount: WeaponMount::Turret(2),
+      arc: None,
     };
 
     // Test ordering between same mount types
@@ -2140,10 +2581,12 @@ mod tests {
         Weapon {
           kind: WeaponType::Beam,
           mount: WeaponMount::Turret(2),
+          arc: None,
         },
         Weapon {
           kind: WeaponType::Pulse,
           mount: WeaponMount::Bay(BaySize::Small),
+          arc: None,
         },
       ],
       tl: 12,
@@ -2262,40 +2705,76 @@ mod tests {
     }
   }
 
-  #[test]
-  fn test_best_thrust() {
-    let design = ShipDesignTemplate {
-      name: "Test Ship".to_string(),
+  #[test_log::test]
+  fn test_weapon_arcs() {
+    let turret: Weapon = serde_json::from_str(r#"{"kind":"Beam","mount":{"Turret":1}}"#).unwrap();
+    assert_eq!(turret.firing_arc(), FiringArc::ALL_ROUND);
+    assert_eq!(
+      serde_json::to_string(&turret).unwrap(),
+      r#"{"kind":"Beam","mount":{"Turret":1}}"#
+    );
+
+    // A spinal mount fires along the axis whatever arc it claims.
+    let spinal: Weapon =
+      serde_json::from_str(r#"{"kind":"Particle","mount":"Spinal","arc":{"from":0,"to":180}}"#).unwrap();
+    assert_eq!(spinal.firing_arc(), FiringArc::SPINAL);
+    assert_eq!(String::from(&spinal), "particle beam spinal mount");
+
+    let aft = FiringArc { from: 135, to: 180 };
+    let axis = Some(Vec3::new(0.0, 0.0, 1.0));
+    assert!(aft.bears(axis, Vec3::new(0.0, 0.0, -10.0)));
+    assert!(!aft.bears(axis, Vec3::new(10.0, 0.0, 0.0)));
+    assert!(!aft.bears(None, Vec3::new(0.0, 0.0, -10.0)));
+    assert!(FiringArc::ALL_ROUND.bears(None, Vec3::new(10.0, 0.0, 0.0)));
+  }
+
+  #[test_log::test]
+  fn test_power_budget() {
+    let weapon = |kind, mount| Weapon { kind, mount, arc: None };
+    let design = Arc::new(ShipDesignTemplate {
       displacement: 400,
-      hull: 100,
-      armor: 50,
       maneuver: 4,
       jump: 2,
       power: 250,
-      fuel: 1000,
-      crew: 20,
       sensors: Sensors::Military,
-      stealth: None,
-      countermeasures: None,
-      computer: 10,
-      weapons: vec![],
-      tl: 12,
-    };
-
-    // Test normal case
-    assert_eq!(design.best_thrust(250), 4);
-
-    // Test with reduced power
-    assert_eq!(design.best_thrust(180), 2);
-
-    // Test case where power calculation results in <= 0
-    // With displacement 400, basic systems use 80 power (400/5)
-    // Military sensors use 2 more power
-    // Thrust 1 requires 40 more power
-    // So providing 121 or less power should result in 0 thrust
-    assert_eq!(design.best_thrust(122), 1);
-    assert_eq!(design.best_thrust(121), 0);
-    assert_eq!(design.best_thrust(0), 0);
+      weapons: vec![
+        weapon(WeaponType::Pulse, WeaponMount::Turret(2)),
+        weapon(WeaponType::Missile, WeaponMount::Turret(2)),
+        weapon(WeaponType::Beam, WeaponMount::Turret(3)),
+      ],
+      ..ShipDesignTemplate::default()
+    });
+    let mut ship = Ship::new("ship".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+
+    // Full power to everything needs 80 basic + 2 sensors + 160 thrust + 20 for the lasers: the last laser goes.
+    assert_eq!(ship.power_draw(&design.full_power_allocation()), 262);
+    assert_eq!(ship.offline_systems(), vec!["beam laser triple turret".to_string()]);
+    assert!(ship.weapon_powered(0) && ship.weapon_powered(1) && !ship.weapon_powered(2));
+    assert_eq!(ship.max_acceleration(), 4);
+    assert!(ship.can_power_jump());
+
+    // A damaged plant drops every laser before it gives up thrust; missiles need no power and keep firing.
+    ship.current_power = 150;
+    let powered = ship.powered_systems();
+    assert_eq!(powered.weapons, vec![false, true, false]);
+    assert_eq!(powered.maneuver, 1);
+    assert!(powered.sensors);
+    assert_eq!(ship.offline_systems().last().unwrap(), "3G of thrust");
+    assert!(!ship.can_power_jump());
+
+    // The engineer can trade thrust for guns.
+    ship.set_power_allocation(Some(PowerAllocation {
+      maneuver: 0,
+      sensors: true,
+      weapons: vec![true, true, true],
+    }));
+    assert!(ship.offline_systems().is_empty());
+    assert_eq!(ship.max_acceleration(), 0);
+    assert!(ship.weapon_powered(2));
+
+    // With nothing beyond the basic systems, even the sensors go dark.
+    ship.current_power = 80;
+    assert!(!ship.powered_systems().sensors);
   }
 
   #[test]
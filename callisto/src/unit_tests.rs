@@ -9,7 +9,9 @@
 use pretty_env_logger;
 
 use cgmath::{assert_relative_eq, assert_ulps_eq, Zero};
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::SystemTime;
 use test_log::test;
 
 use assert_json_diff::assert_json_eq;
@@ -17,13 +19,18 @@ use serde_json::json;
 
 use crate::authentication::Authenticator;
 use crate::authentication::MockAuthenticator;
+use crate::computer::MAX_ARRIVAL_TURNS;
 use crate::entity::G;
-use crate::entity::{Entities, Entity, Vec3, DEFAULT_ACCEL_DURATION, DELTA_TIME_F64};
-use crate::list_local_or_cloud_dir;
-use crate::payloads::{AddPlanetMsg, AddShipMsg, EffectMsg, SetPilotActions, EMPTY_FIRE_ACTIONS_MSG};
+use crate::entity::{Entities, Entity, Vec3, DEFAULT_ACCEL_DURATION, DELTA_TIME, DELTA_TIME_F64, MAX_PROJECTION_TURNS};
+use crate::payloads::{
+  AddPlanetMsg, AddShipMsg, ClosestApproachMsg, CritEffect, EffectMsg, ProjectTrajectoriesMsg, RollPurpose,
+  SetJumpDestinationMsg, SetPilotActions, EMPTY_FIRE_ACTIONS_MSG,
+};
 use crate::player::PlayerManager;
-use crate::server::Server;
+use crate::roster::Roster;
+use crate::server::{Server, ServerMembersTable};
 use crate::ship::{ShipDesignTemplate, ShipSystem};
+use crate::storage::{GcsStorage, LocalStorage, MemoryStorage, StorageBackend};
 
 fn setup_authenticator() -> Box<dyn Authenticator> {
   Box::new(MockAuthenticator::new("http://test.com"))
@@ -33,7 +40,7 @@ async fn setup_test_with_server(authenticator: Box<dyn Authenticator>) -> Player
   let _ = pretty_env_logger::try_init();
   crate::ship::config_test_ship_templates().await;
 
-  let basic_server = Server::new("test", "").await;
+  let basic_server = Server::new("test", "", &LocalStorage).await;
   PlayerManager::new(Some(Arc::new(basic_server)), authenticator, true)
 }
 
@@ -351,13 +358,7 @@ async fn test_update_missile() {
       {"kind": "ShipImpact","target": "ship2","position": [5000.0, 0.0, 5000.0]}
   ]);
 
-  assert_json_eq!(
-    response
-      .iter()
-      .filter(|e| !matches!(e, EffectMsg::Message { .. }))
-      .collect::<Vec<_>>(),
-    compare
-  );
+  assert_json_eq!(response.iter().filter(|e| e.render().is_none()).collect::<Vec<_>>(), compare);
 
   let entities = server.get_entities_json();
   let compare = json!(
@@ -449,8 +450,8 @@ async fn test_set_acceleration() {
 
   let ship = entities.ships.get("ship1").unwrap().read().unwrap();
   let flight_plan = &ship.plan;
-  assert_eq!(flight_plan.0 .0, [0.0, 0.0, 0.0].into());
-  assert_eq!(flight_plan.0 .1, DEFAULT_ACCEL_DURATION);
+  assert_eq!(flight_plan.first().0, [0.0, 0.0, 0.0].into());
+  assert_eq!(flight_plan.first().1, DEFAULT_ACCEL_DURATION);
   assert!(!flight_plan.has_second());
 
   let response = server.set_plan(&serde_json::from_str(r#"{"name":"ship1","plan":[[[1,2,2],50000]]}"#).unwrap());
@@ -460,8 +461,8 @@ async fn test_set_acceleration() {
   let entities = serde_json::from_str::<Entities>(response.as_str()).unwrap();
   let ship = entities.ships.get("ship1").unwrap().read().unwrap();
   let flight_plan = &ship.plan;
-  assert_eq!(flight_plan.0 .0, [1.0, 2.0, 2.0].into());
-  assert_eq!(flight_plan.0 .1, DEFAULT_ACCEL_DURATION);
+  assert_eq!(flight_plan.first().0, [1.0, 2.0, 2.0].into());
+  assert_eq!(flight_plan.first().1, DEFAULT_ACCEL_DURATION);
   assert!(!flight_plan.has_second());
 }
 
@@ -501,11 +502,11 @@ async fn test_compute_path_basic() {
     epsilon = 1e-4
   );
   assert_ulps_eq!(plan.end_velocity, Vec3::zero(), epsilon = 1e-7);
-  let (a, t) = plan.plan.0.into();
+  let (a, t) = plan.plan.first().clone().into();
   assert_ulps_eq!(a, Vec3 { x: 3.0, y: 0.0, z: 0.0 } * G, epsilon = 1e-7);
   assert_eq!(t, 1000);
 
-  if let Some(accel) = plan.plan.1 {
+  if let Some(accel) = plan.plan.second().cloned() {
     let (a, _t) = accel.into();
     assert_ulps_eq!(
       a,
@@ -522,6 +523,107 @@ async fn test_compute_path_basic() {
   assert_eq!(t, 1000);
 }
 
+#[test(tokio::test)]
+async fn test_compute_path_goals() {
+  let authenticator = setup_authenticator();
+  let server = setup_test_with_server(authenticator).await;
+
+  let ship = r#"{"name":"ship1","position":[0,0,0],"velocity":[0,0,0], "acceleration":[0,0,0], "design":"Buccaneer"}"#;
+  let response = server.add_ship(serde_json::from_str(ship).unwrap()).unwrap();
+  assert_eq!(response, "Add ship action executed");
+
+  // A thrust cap keeps every burn under the cap.
+  let path_request = r#"{"entity_name":"ship1","end_pos":[29430000,0,0],"end_vel":[0,0,0],"standoff_distance" : 0,
+    "goal": {"kind": "ThrustCap", "thrust": 1.0}}"#;
+  let plan = server.compute_path(&serde_json::from_str(path_request).unwrap()).unwrap();
+  assert!(plan.plan.iter().all(|accel| accel.in_limits(G)));
+  assert!(plan.plan.duration() > 2000);
+
+  let path_request = r#"{"entity_name":"ship1","end_pos":[29430000,0,0],"end_vel":[0,0,0],"standoff_distance" : 0,
+    "goal": {"kind": "ThrustCap", "thrust": 0.0}}"#;
+  assert!(server.compute_path(&serde_json::from_str(path_request).unwrap()).is_err());
+
+  // A fixed arrival lands exactly on the requested turn, or says when it could.
+  let path_request = r#"{"entity_name":"ship1","end_pos":[29430000,0,0],"end_vel":[0,0,0],"standoff_distance" : 0,
+    "goal": {"kind": "MinDeltaV", "arrival_turns": 12}}"#;
+  let plan = server.compute_path(&serde_json::from_str(path_request).unwrap()).unwrap();
+  assert_eq!(plan.plan.duration(), 12 * DELTA_TIME);
+
+  let path_request = r#"{"entity_name":"ship1","end_pos":[29430000,0,0],"end_vel":[0,0,0],"standoff_distance" : 0,
+    "goal": {"kind": "MinDeltaV", "arrival_turns": 2}}"#;
+  let err = server.compute_path(&serde_json::from_str(path_request).unwrap()).unwrap_err();
+  assert_eq!(err, "Cannot arrive in 2 turns; the earliest feasible arrival is in 6 turns");
+
+  for arrival_turns in [0, MAX_ARRIVAL_TURNS + 1, u64::MAX] {
+    let path_request = format!(
+      r#"{{"entity_name":"ship1","end_pos":[29430000,0,0],"end_vel":[0,0,0],"standoff_distance" : 0,
+      "goal": {{"kind": "MinDeltaV", "arrival_turns": {arrival_turns}}}}}"#
+    );
+    let err = server.compute_path(&serde_json::from_str(&path_request).unwrap()).unwrap_err();
+    assert_eq!(
+      err,
+      format!("Arrival can be planned 1 to {MAX_ARRIVAL_TURNS} turns ahead, not {arrival_turns}")
+    );
+  }
+
+  let path_request = r#"{"entity_name":"ship1","end_pos":[29430000,0,0],"end_vel":[0,0,0],"standoff_distance" : 0,
+    "goal": {"kind": "MinDeltaV", "arrival_turns": 12}, "waypoints": [{"position": [1000, 0, 0]}]}"#;
+  assert!(server.compute_path(&serde_json::from_str(path_request).unwrap()).is_err());
+}
+
+#[test(tokio::test)]
+async fn test_project_trajectories() {
+  let authenticator = setup_authenticator();
+  let server = setup_test_with_server(authenticator).await;
+
+  let ship =
+    r#"{"name":"ship1","position":[0,0,0],"velocity":[100,0,0], "acceleration":[0,0,0], "design":"Buccaneer"}"#;
+  server.add_ship(serde_json::from_str(ship).unwrap()).unwrap();
+
+  let projection = server.project_trajectories(&ProjectTrajectoriesMsg { turns: 3 }).unwrap();
+  assert_eq!(
+    projection.ships["ship1"].last(),
+    Some(&Vec3 {
+      x: 100.0 * 3.0 * DELTA_TIME_F64,
+      y: 0.0,
+      z: 0.0
+    })
+  );
+  // Projecting doesn't move anything.
+  assert_eq!(
+    server.get_entities().ships["ship1"].read().unwrap().get_position(),
+    Vec3::zero()
+  );
+
+  let ship =
+    r#"{"name":"ship2","position":[1000000,0,0],"velocity":[0,0,0], "acceleration":[0,0,0], "design":"Buccaneer"}"#;
+  server.add_ship(serde_json::from_str(ship).unwrap()).unwrap();
+  let request = ClosestApproachMsg {
+    first: "ship1".to_string(),
+    second: "ship2".to_string(),
+    turns: 30,
+  };
+  let approach = server.closest_approach(&request).unwrap();
+  assert_relative_eq!(approach.time, 1e6 / 100.0, epsilon = 1e-6);
+  assert_relative_eq!(approach.distance, 0.0, epsilon = 1e-6);
+  assert!(server.closest_approach(&ClosestApproachMsg { turns: 0, ..request }).is_err());
+
+  let odds = server
+    .hit_probability(&serde_json::from_str(r#"{"attacker":"ship1","weapon_id":0,"target":"ship2"}"#).unwrap())
+    .unwrap();
+  assert_relative_eq!(odds.hit_probability, 30.0 / 36.0, epsilon = 1e-9);
+  assert!(server
+    .hit_probability(&serde_json::from_str(r#"{"attacker":"ship1","weapon_id":99,"target":"ship2"}"#).unwrap())
+    .is_err());
+
+  assert!(server.project_trajectories(&ProjectTrajectoriesMsg { turns: 0 }).is_err());
+  assert!(server
+    .project_trajectories(&ProjectTrajectoriesMsg {
+      turns: MAX_PROJECTION_TURNS + 1
+    })
+    .is_err());
+}
+
 #[test(tokio::test)]
 async fn test_compute_path_with_standoff() {
   let authenticator = setup_authenticator();
@@ -561,11 +663,11 @@ async fn test_compute_path_with_standoff() {
     epsilon = 1e-4
   );
   assert_ulps_eq!(plan.end_velocity, Vec3::zero(), epsilon = 1e-5);
-  let (a, t) = plan.plan.0.into();
+  let (a, t) = plan.plan.first().clone().into();
   assert_ulps_eq!(a, Vec3 { x: 3.0, y: 0.0, z: 0.0 } * G, epsilon = 1e-5);
   assert_eq!(t, 1413);
 
-  if let Some(accel) = plan.plan.1 {
+  if let Some(accel) = plan.plan.second().cloned() {
     let (a, _t) = accel.into();
     assert_ulps_eq!(
       a,
@@ -702,30 +804,220 @@ async fn test_called_shot() {
 
   // First ensure there is at least one critical hit that matches.
   assert!(
-    effects.iter().any(
-      |e| matches!(e, EffectMsg::Message { content } if content.contains("maneuver") && content.contains("critical"))
-    ),
+    effects.iter().any(|e| matches!(
+      e,
+      EffectMsg::CriticalHit {
+        system: ShipSystem::Maneuver,
+        ..
+      }
+    )),
     "No critical hits to called shot area: maneuver"
   );
 
   // Second ensure 6 critical hits to maneuver and the rest to hull.
-  // This means we find all messages with the word "critical" but not the word "caused" (the latter are damage effects)
+  // This means we find all critical hits other than those beyond level 6 (the latter are damage effects)
   let crits = effects
     .iter()
-    .filter(
-      |e| matches!(e, EffectMsg::Message { content } if content.contains("critical") && !content.contains("caused")),
-    )
+    .filter(|e| matches!(e, EffectMsg::CriticalHit { effect, .. } if !matches!(effect, CritEffect::HullDamage { .. })))
     .collect::<Vec<_>>();
   assert_eq!(
     crits
       .iter()
-      .filter(|e| matches!(e, EffectMsg::Message { content } if content.contains("maneuver")))
+      .filter(|e| {
+        matches!(
+          e,
+          EffectMsg::CriticalHit {
+            system: ShipSystem::Maneuver,
+            ..
+          }
+        )
+      })
       .count(),
     4,
     "Expected 4 critical hits to maneuver: {crits:#?}"
   );
 }
 
+#[test(tokio::test)]
+async fn test_roll_log() {
+  let authenticator = setup_authenticator();
+  let server = setup_test_with_server(authenticator).await;
+
+  let ship = r#"{"name":"ship1","position":[0,0,0],"velocity":[0,0,0], "acceleration":[0,0,0], "design":"Gazelle"}"#;
+  server.add_ship(serde_json::from_str(ship).unwrap()).unwrap();
+  let ship2 =
+    r#"{"name":"ship2","position":[5e4,0,5e4],"velocity":[0,0,0], "acceleration":[0,0,0], "design":"Gazelle"}"#;
+  server.add_ship(serde_json::from_str(ship2).unwrap()).unwrap();
+
+  let fire_actions = json!([["ship1", [{"FireAction" : {"weapon_id": 0, "target": "ship2"}}]]]).to_string();
+  server.merge_actions(serde_json::from_str(&fire_actions).unwrap());
+  let effects = server.update();
+
+  // Every roll in the turn's effects is in the log for that turn, and the seed is still secret.
+  let rolls = effects
+    .iter()
+    .filter_map(|e| match e {
+      EffectMsg::DiceRoll { record } => Some(record.clone()),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  assert!(rolls
+    .iter()
+    .any(|r| r.purpose == RollPurpose::Attack && r.actor == "ship1" && r.threshold == Some(8)));
+  let log = server.roll_log().unwrap();
+  assert_eq!(log.games.len(), 1);
+  assert_eq!(log.games[0].seed, None);
+  assert_eq!(log.games[0].turns, vec![rolls.clone(), vec![]]);
+
+  // Once the game is over the seed is revealed and a new game begins.
+  server.reset().unwrap();
+  let log = server.roll_log().unwrap();
+  assert_eq!(log.games.len(), 2);
+  assert!(log.games[0].seed.is_some());
+  assert_eq!(log.games[0].turns, vec![rolls]);
+  assert_eq!(log.games[1].seed, None);
+  assert_eq!(log.games[1].turns, vec![Vec::new()]);
+}
+
+#[test_log::test(tokio::test)]
+async fn test_jump_to_destination() {
+  let source = setup_test_with_server(setup_authenticator()).await;
+  let destination = PlayerManager::new(
+    Some(Arc::new(Server::new("regina", "", &LocalStorage).await)),
+    setup_authenticator(),
+    true,
+  );
+
+  let ship = r#"{"name":"ship1","position":[0,0,0],"velocity":[1000,0,0], "design":"Gazelle",
+    "crew": {"engineering_jump": 6}}"#;
+  source.add_ship(serde_json::from_str(ship).unwrap()).unwrap();
+  {
+    let mut entities = source.server.as_ref().unwrap().get_unlocked_entities().unwrap();
+    entities.jump_destinations = serde_json::from_value(json!([
+      {"name": "Regina", "server": "regina", "emergence_point": [1e7, 0, 0], "transit_turns": 2}
+    ]))
+    .unwrap();
+    let mut ship = entities.ships["ship1"].write().unwrap();
+    ship.current_hull -= 10;
+    ship.crit_level[ShipSystem::Sensors as usize] = 1;
+  }
+
+  // Only the scenario's own destinations can be plotted.
+  let plot = |destination: &str| SetJumpDestinationMsg {
+    name: "ship1".to_string(),
+    destination: Some(destination.to_string()),
+  };
+  assert!(source.set_jump_destination(&plot("Nowhere")).is_err());
+  source.set_jump_destination(&plot("Regina")).unwrap();
+
+  source.merge_actions(serde_json::from_value(json!([["ship1", ["Jump"]]])).unwrap());
+  let effects = source.update();
+  assert!(effects.contains(&EffectMsg::ShipJumped {
+    ship: "ship1".to_string(),
+    destination: "Regina".to_string(),
+    transit_turns: 2,
+    off_course: false,
+  }));
+
+  // The ship leaves the source with all its state and is handed on to its destination.
+  let mut entities = source.server.as_ref().unwrap().get_unlocked_entities().unwrap();
+  assert!(!entities.ships.contains_key("ship1"));
+  let departures = entities.take_departures();
+  assert!(entities.take_departures().is_empty());
+  drop(entities);
+  assert_eq!(departures.len(), 1);
+  let departure = departures.into_iter().next().unwrap();
+  let regina = destination.server.as_ref().unwrap();
+  regina
+    .queue_arrival(departure.ship, source.server.as_ref().unwrap().get_id(), &departure.destination)
+    .unwrap();
+  assert_eq!(regina.ships_in_transit(), vec!["ship1".to_string()]);
+
+  // It spends the transit in jump space and then emerges at rest at the emergence point, damage and all.
+  let effects = destination.update();
+  assert!(effects.is_empty());
+  assert!(!regina.get_unlocked_entities().unwrap().ships.contains_key("ship1"));
+  let effects = destination.update();
+  assert_eq!(
+    effects,
+    vec![EffectMsg::ShipEmerged {
+      ship: "ship1".to_string()
+    }]
+  );
+  assert!(regina.ships_in_transit().is_empty());
+  let entities = regina.get_unlocked_entities().unwrap();
+  let ship = entities.ships["ship1"].read().unwrap();
+  assert_eq!(ship.get_position(), Vec3::new(1e7, 0.0, 0.0));
+  assert_eq!(ship.get_velocity(), Vec3::zero());
+  assert_eq!(ship.current_hull, ship.design.hull - 10);
+  assert_eq!(ship.crit_level[ShipSystem::Sensors as usize], 1);
+  assert_eq!(ship.get_jump_destination(), None);
+}
+
+#[test]
+fn test_awaited_scenario_does_not_expire() {
+  let mut members = ServerMembersTable::new();
+  members.register("regina", "");
+  assert!(members.open("regina"));
+  assert!(!members.open("regina"));
+  let later = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs() + 3600;
+
+  // Left empty for an hour, but a ship is still on its way.
+  let awaited = HashSet::from(["regina".to_string()]);
+  assert!(members.clean_expired_scenarios(later, &awaited).is_empty());
+  assert_eq!(members.current_scenario_list(), vec![("regina".to_string(), String::new())]);
+
+  // Once it has arrived the scenario expires like any other.
+  assert_eq!(
+    members.clean_expired_scenarios(later, &HashSet::new()),
+    vec!["regina".to_string()]
+  );
+  assert!(members.current_scenario_list().is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn test_roster_ships_in_scenario() {
+  crate::ship::config_test_ship_templates().await;
+  let scenario = json!({
+    "metadata": {"name": "Campaign"},
+    "roster": [{"id": "sparrow", "position": [1000, 0, 0], "velocity": [0, 10, 0]}, {"id": "missing",
+      "position": [0, 0, 0], "velocity": [0, 0, 0]}]
+  });
+  let storage = MemoryStorage::new().with_object("campaign.json", scenario.to_string().as_bytes(), 0);
+
+  // A roster ship that finished its last scenario damaged.
+  let mut roster = Roster::default();
+  let design = crate::ship::get_ship_template("Gazelle").unwrap();
+  let mut ship = crate::ship::Ship::new("Sparrow".to_string(), Vec3::zero(), Vec3::zero(), &design, None);
+  ship.current_hull -= 4;
+  ship.crit_level[ShipSystem::Maneuver as usize] = 1;
+  roster.enlist("sparrow", "owner", &ship, false).unwrap();
+
+  let server = Server::new("campaign", "campaign.json", &storage).await.with_roster(&roster);
+  let ships = server.roster_ships();
+  assert_eq!(ships.len(), 1);
+  let sparrow = &ships[0];
+  assert_eq!(sparrow.get_name(), "Sparrow");
+  assert_eq!(sparrow.get_roster_id(), Some("sparrow"));
+  assert_eq!(sparrow.get_velocity(), Vec3::new(0.0, 10.0, 0.0));
+  assert_eq!(sparrow.current_hull, design.hull - 4);
+  assert_eq!(sparrow.crit_level[ShipSystem::Maneuver as usize], 1);
+
+  // Resetting brings it back as it came in, not fully repaired.
+  server.get_unlocked_entities().unwrap().ships["Sparrow"]
+    .write()
+    .unwrap()
+    .current_hull = 1;
+  server.reset();
+  assert_eq!(server.roster_ships()[0].current_hull, design.hull - 4);
+
+  // Saved scenarios keep it as a placement rather than a copy of the ship.
+  let saved: serde_json::Value =
+    serde_json::from_slice(&server.get_unlocked_entities().unwrap().to_scenario_file_json().unwrap()).unwrap();
+  assert!(saved.get("ships").is_none());
+  assert_eq!(saved["roster"][0]["id"], "sparrow");
+}
+
 #[test(tokio::test)]
 async fn test_big_fight() {
   let authenticator = setup_authenticator();
@@ -767,24 +1059,12 @@ async fn test_big_fight() {
   ]);
   effects = effects
     .iter()
-    .filter_map(|e| {
-      if matches!(e, EffectMsg::Message { .. }) {
-        None
-      } else {
-        Some(e.clone())
-      }
-    })
+    .filter_map(|e| if e.render().is_some() { None } else { Some(e.clone()) })
     .collect::<Vec<EffectMsg>>();
 
   effects.sort_by_key(|a| serde_json::to_string(a).unwrap());
 
-  assert_json_eq!(
-    effects
-      .iter()
-      .filter(|e| !matches!(e, EffectMsg::Message { .. }))
-      .collect::<Vec<_>>(),
-    compare
-  );
+  assert_json_eq!(effects.iter().filter(|e| e.render().is_none()).collect::<Vec<_>>(), compare);
 
   let entities = server.get_entities_json();
   let compare = json!({"metadata":{"name":"","description":"","owner":""},"filename":"","ships":[
@@ -884,24 +1164,12 @@ async fn test_fight_with_crew() {
 
   effects = effects
     .iter()
-    .filter_map(|e| {
-      if matches!(e, EffectMsg::Message { .. }) {
-        None
-      } else {
-        Some(e.clone())
-      }
-    })
+    .filter_map(|e| if e.render().is_some() { None } else { Some(e.clone()) })
     .collect::<Vec<EffectMsg>>();
 
   effects.sort_by_key(|a| serde_json::to_string(a).unwrap());
 
-  assert_json_eq!(
-    effects
-      .iter()
-      .filter(|e| !matches!(e, EffectMsg::Message { .. }))
-      .collect::<Vec<_>>(),
-    compare
-  );
+  assert_json_eq!(effects.iter().filter(|e| e.render().is_none()).collect::<Vec<_>>(), compare);
 
   let entities = server.get_entities_json();
   let compare = json!({"metadata":{"name":"","description":"","owner":""},"filename":"","ships":[
@@ -1286,7 +1554,7 @@ async fn test_set_pilot_actions_aid_gunner() {
 async fn test_list_local_dir() {
   let directory_path = "./scenarios";
   // Call the function
-  let result = list_local_or_cloud_dir(directory_path).await.unwrap();
+  let result = LocalStorage.list(directory_path).await.unwrap();
 
   // Verify results
   assert!(result.len() > 2);
@@ -1642,7 +1910,7 @@ async fn test_list_gcs_dir() {
   let test_dir = format!("gs://{test_bucket}");
 
   // Call the function
-  let result = list_local_or_cloud_dir(&test_dir).await;
+  let result = GcsStorage.list(&test_dir).await;
 
   // Just verify that the function runs without error
   // We can't predict the actual contents
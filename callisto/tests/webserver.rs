@@ -13,42 +13,30 @@ use std::path::PathBuf;
 use std::sync::atomic::AtomicU16;
 
 use assert_json_diff::assert_json_eq;
-use futures_util::{SinkExt, StreamExt};
+use futures_util::StreamExt;
 
-use tokio::net::TcpStream;
 use tokio::process::{Child, Command};
 use tokio::time::{sleep, timeout, Duration};
-use tokio_tungstenite::{
-  connect_async,
-  tungstenite::{Error, Result},
-  MaybeTlsStream, WebSocketStream,
-};
-
-#[cfg(not(feature = "no_tls_upgrade"))]
-use rustls::pki_types::{pem::PemObject, CertificateDer};
-#[cfg(not(feature = "no_tls_upgrade"))]
-use std::sync::Arc;
-#[cfg(not(feature = "no_tls_upgrade"))]
-use tokio_tungstenite::connect_async_tls_with_config;
-#[cfg(not(feature = "no_tls_upgrade"))]
-use tokio_tungstenite::Connector;
+use tokio_tungstenite::connect_async;
 
 use serde_json::json;
 
 use callisto::{debug, error};
 
 use callisto::action::ShipAction;
+use callisto::authentication::ApiTokenScope;
+use callisto::client::{CallistoClient, ClientError};
 use callisto::entity::{Entity, Vec3, DEFAULT_ACCEL_DURATION, DELTA_TIME_F64, G};
 use callisto::payloads::{
-  AddPlanetMsg, AddShipMsg, ComputePathMsg, CreateScenarioMsg, EffectMsg, JoinScenarioMsg, LoginMsg, RequestMsg,
-  ResponseMsg, SetPilotActions, SetPlanMsg, EMPTY_FIRE_ACTIONS_MSG,
+  AddPlanetMsg, AddShipMsg, ChangeRole, ComputePathMsg, EffectMsg, FlightGoalMsg, LoginMsg, MintApiTokenMsg,
+  RequestMsg, ResponseMsg, Role, SetPilotActions, SetPlanMsg, EMPTY_FIRE_ACTIONS_MSG,
 };
 
 use callisto::crew::{Crew, Skills};
 
 use cgmath::{assert_ulps_eq, Zero};
 
-type MyWebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type MyWebSocket = CallistoClient;
 
 const SERVER_ADDRESS: &str = "127.0.0.1";
 const SERVER_PATH: &str = "target/debug/callisto";
@@ -83,12 +71,12 @@ fn get_next_port() -> u16 {
 async fn spawn_server(
   port: u16, test_mode: bool, scenario_dir: Option<String>, design_dir: Option<String>, auto_kill: bool,
 ) -> Result<Child, io::Error> {
-  spawn_server_full(port, test_mode, scenario_dir, design_dir, None, auto_kill).await
+  spawn_server_full(port, test_mode, scenario_dir, design_dir, None, &[], auto_kill).await
 }
 
 async fn spawn_server_full(
   port: u16, test_mode: bool, scenario_dir: Option<String>, design_dir: Option<String>, users_file: Option<String>,
-  auto_kill: bool,
+  extra_args: &[String], auto_kill: bool,
 ) -> Result<Child, io::Error> {
   let mut handle = Command::new(SERVER_PATH);
   let mut handle = handle
@@ -118,6 +106,7 @@ async fn spawn_server_full(
   if let Some(users_file) = users_file {
     handle = handle.arg("-u").arg(users_file);
   }
+  handle = handle.args(extra_args);
 
   let handle = handle.spawn()?;
   let _ = pretty_env_logger::try_init();
@@ -135,64 +124,43 @@ async fn spawn_test_server(port: u16) -> Result<Child, io::Error> {
   spawn_server(port, true, None, None, false).await
 }
 
-async fn open_socket(port: u16) -> Result<MyWebSocket, Error> {
+async fn open_socket(port: u16) -> Result<MyWebSocket, ClientError> {
   #[cfg(feature = "no_tls_upgrade")]
   {
     let socket_url = format!("ws://{SERVER_ADDRESS}:{port}/ws");
     debug!("(webservers.open_socket) Attempt to connect to WebSocket URL: {socket_url}");
-    let (ws_stream, _) = connect_async(socket_url).await?;
+    let stream = CallistoClient::connect(&socket_url).await?;
     debug!("(webservers.open_socket) WebSocket stream established.");
-    Ok(ws_stream)
+    Ok(stream)
   }
   #[cfg(not(feature = "no_tls_upgrade"))]
   {
-    let rust_cert = CertificateDer::pem_file_iter("keys/rootCA.crt")
-      .expect("Cannot open CA file")
-      .map(|result| result.unwrap());
-    let mut root_store = rustls::RootCertStore::empty();
-    root_store.add_parsable_certificates(rust_cert);
-
-    let config = rustls::client::ClientConfig::builder()
-      .with_root_certificates(root_store)
-      .with_no_client_auth();
-
-    let connector = Connector::Rustls(Arc::new(config));
-
     let socket_url = format!("wss://{SERVER_ADDRESS}:{port}/");
     debug!("(webservers.open_socket) Attempt to connect to WebSocket URL: {socket_url}");
 
-    let (ws_stream, _) = connect_async_tls_with_config(socket_url, None, false, Some(connector))
+    let stream = CallistoClient::connect_with_root_ca(&socket_url, "keys/rootCA.crt")
       .await
       .unwrap_or_else(|e| panic!("Client_async_tls failed with {e:?}"));
 
     debug!("(webservers.open_socket) WebSocket stream established.");
-    Ok(ws_stream)
+    Ok(stream)
   }
 }
 
-#[allow(clippy::result_large_err)]
 async fn rpc(stream: &mut MyWebSocket, request: RequestMsg) -> ResponseMsg {
   debug!("(webservers.rpc) Sending request: {request:?}");
-  stream.send(serde_json::to_string(&request).unwrap().into()).await.unwrap();
-
-  let reply = stream
-    .next()
+  let description = format!("{request:?}");
+  stream
+    .rpc(request)
     .await
-    .unwrap_or_else(|| panic!("No response from server for request: {request:?}."))
-    .unwrap_or_else(|err| panic!("Receiving error from server {err:?} in response to request: {request:?}."));
-  debug!("(webservers.rpc) Received response: {reply:?}");
-  let body = serde_json::from_str::<ResponseMsg>(reply.to_text().unwrap()).unwrap();
-  body
+    .unwrap_or_else(|err| panic!("Receiving error from server {err:?} in response to request: {description}."))
 }
 
-#[allow(clippy::result_large_err)]
 async fn next_response_with_timeout(stream: &mut MyWebSocket, duration: Duration) -> ResponseMsg {
-  let reply = timeout(duration, stream.next())
+  stream
+    .next_response_with_timeout(duration)
     .await
-    .unwrap_or_else(|_| panic!("Timed out waiting for WebSocket response after {duration:?}."))
-    .unwrap_or_else(|| panic!("WebSocket closed while waiting for response."))
-    .unwrap_or_else(|err| panic!("Receiving error from server {err:?} while waiting for response."));
-  serde_json::from_str::<ResponseMsg>(reply.to_text().unwrap()).unwrap()
+    .unwrap_or_else(|err| panic!("Failed waiting {duration:?} for WebSocket response: {err:?}."))
 }
 
 fn setup_reloadable_test_files(port: u16) -> (PathBuf, String, String, String) {
@@ -255,11 +223,10 @@ fn add_test_scenario(scenario_dir: &str, scenario_name: &str) {
  * will drain away an extra entity response.
  */
 async fn drain_entity_response(stream: &mut MyWebSocket) -> ResponseMsg {
-  let Ok(reply) = stream.next().await.unwrap() else {
-    panic!("Expected entity response.  Got error.");
-  };
-  let body = serde_json::from_str::<ResponseMsg>(reply.to_text().expect("Expected text response"))
-    .expect("Failed to parse entity response");
+  let body = stream
+    .next_response()
+    .await
+    .unwrap_or_else(|err| panic!("Expected entity response.  Got error {err:?}."));
   assert!(
     matches!(body, ResponseMsg::EntityResponse(_)),
     "Expected entity response: {body:?}"
@@ -269,134 +236,63 @@ async fn drain_entity_response(stream: &mut MyWebSocket) -> ResponseMsg {
 }
 
 /**
- * Drain all the messages the server sends on connect.  This right now is
- * 3 messages: templates, entities, and users.  See [`callisto:build_successful_auth_msgs`].
+ * Drain all the messages the server sends on login.  This right now is
+ * 2 messages: scenarios and templates.  See [`callisto:build_successful_auth_msgs`].
  */
 async fn drain_initialization_messages(stream: &mut MyWebSocket) {
-  let Ok(scenario_msg) = stream.next().await.unwrap() else {
-    panic!("Expected scenario response.  Got error.");
-  };
-
+  let scenario_msg = stream.next_response().await;
   assert!(
-    matches!(
-      serde_json::from_str::<ResponseMsg>(scenario_msg.to_text().unwrap()),
-      Ok(ResponseMsg::Scenarios(_))
-    ),
+    matches!(scenario_msg, Ok(ResponseMsg::Scenarios(_))),
     "Expected scenario response, got {scenario_msg:?}."
   );
   debug!("(webservers.drain_initialization_messages) Drained scenario message.");
 
-  let Ok(template_msg) = stream.next().await.unwrap() else {
-    panic!("Expected template response.  Got error.");
-  };
+  let template_msg = stream.next_response().await;
   assert!(
-    matches!(
-      serde_json::from_str::<ResponseMsg>(template_msg.to_text().unwrap()),
-      Ok(ResponseMsg::DesignTemplateResponse(_))
-    ),
+    matches!(template_msg, Ok(ResponseMsg::DesignTemplateResponse(_))),
     "Expected template response, got {template_msg:?}."
   );
   debug!("Drained template initialization message.");
 }
 
-/**
- * Drain all messages the server send post-creation/joining of a scenario.
- */
-async fn drain_post_scenario_messages(stream: &mut MyWebSocket) {
-  let Ok(entities_msg) = stream.next().await.unwrap() else {
-    panic!("Expected entities response.  Got error.");
-  };
-  assert!(
-    matches!(
-      serde_json::from_str::<ResponseMsg>(entities_msg.to_text().unwrap()),
-      Ok(ResponseMsg::EntityResponse(_))
-    ),
-    "Expected entities response, got {entities_msg:?}."
-  );
-  debug!("Drained entities initialization message.");
-
-  let Ok(users_msg) = stream.next().await.unwrap() else {
-    panic!("Expected users response.  Got error.");
-  };
-  assert!(
-    matches!(
-      serde_json::from_str::<ResponseMsg>(users_msg.to_text().unwrap()),
-      Ok(ResponseMsg::Users(_))
-    ),
-    "Expected users response, got {users_msg:?}."
-  );
-  debug!("Drained users initialization message.");
-}
-
 /**
  * Send a quit message to cleanly end a test.
  */
 async fn send_quit(stream: &mut MyWebSocket) {
-  stream
-    .send(serde_json::to_string(&RequestMsg::Quit).unwrap().into())
-    .await
-    .unwrap();
-
-  stream.close(None).await.unwrap();
+  stream.send(RequestMsg::Quit).await.unwrap();
+  stream.close().await.unwrap();
 }
 
 /**
  * Do authentication with the test server
- * Return the user name and the key from `SetCookie`
- * Also drain the initialization messages.
+ * Return the user name.
+ * The client also drains the initialization messages.
  */
 async fn test_authenticate(stream: &mut MyWebSocket) -> Result<String, String> {
-  let msg = RequestMsg::Login(LoginMsg {
-    code: "test_code".to_string(),
-  });
-
-  let body = rpc(stream, msg).await;
-  if let ResponseMsg::AuthResponse(auth_response) = body {
-    drain_initialization_messages(stream).await;
-    Ok(auth_response.email)
-  } else {
-    Err(format!("Expected auth response to login. Got {body:?}"))
-  }
+  stream
+    .login("test_code")
+    .await
+    .map(|session| session.auth.email)
+    .map_err(|err| format!("Expected auth response to login. Got {err:?}"))
 }
 
 async fn test_create_scenario(stream: &mut MyWebSocket) -> Result<(), String> {
-  let msg = RequestMsg::CreateScenario(CreateScenarioMsg {
-    name: "test_scenario".to_string(),
-    scenario: String::new(),
-  });
-  let body = rpc(stream, msg).await;
+  let joined = stream
+    .create_scenario("test_scenario", "")
+    .await
+    .map_err(|err| format!("Expected joined scenario.  Got {err:?}"))?;
 
   // Only when creating scenarios, we get back a Scenarios message
-  let Ok(scenario_msg) = stream.next().await.unwrap() else {
-    panic!("Expected scenario response.  Got error.");
-  };
-  assert!(
-    matches!(
-      serde_json::from_str::<ResponseMsg>(scenario_msg.to_text().unwrap()),
-      Ok(ResponseMsg::Scenarios(_))
-    ),
-    "Expected scenario response, got {scenario_msg:?}."
-  );
-
-  if let ResponseMsg::JoinedScenario(_) = body {
-    drain_post_scenario_messages(stream).await;
-    Ok(())
-  } else {
-    Err(format!("Expected simple message.  Got {body:?}"))
-  }
+  assert!(joined.scenarios.is_some(), "Expected scenario response on create.");
+  Ok(())
 }
 
 async fn test_join_scenario(stream: &mut MyWebSocket) -> Result<(), String> {
-  let msg = RequestMsg::JoinScenario(JoinScenarioMsg {
-    scenario_name: "test_scenario".to_string(),
-  });
-  let body = rpc(stream, msg).await;
-  if let ResponseMsg::JoinedScenario(_) = body {
-    drain_post_scenario_messages(stream).await;
-    Ok(())
-  } else {
-    Err(format!("Expected simple message.  Got {body:?}"))
-  }
+  stream
+    .join_scenario("test_scenario")
+    .await
+    .map(|_| ())
+    .map_err(|err| format!("Expected joined scenario.  Got {err:?}"))
 }
 
 /**
@@ -933,6 +829,7 @@ async fn integration_update_missile() {
       weapon_id: 1,
       target: "ship2".to_string(),
       called_shot_system: None,
+      multiple_attacks: false,
     }],
   )];
 
@@ -949,7 +846,7 @@ async fn integration_update_missile() {
 
   let effects = rpc(&mut stream, RequestMsg::Update).await;
   if let ResponseMsg::Effects(effects) = effects {
-    let filtered_effects: Vec<_> = effects.iter().filter(|e| !matches!(e, EffectMsg::Message { .. })).collect();
+    let filtered_effects: Vec<_> = effects.iter().filter(|e| e.render().is_none()).collect();
 
     let compare = vec![EffectMsg::ShipImpact {
       target: "ship2".to_string(),
@@ -1086,8 +983,8 @@ async fn integration_set_acceleration() {
   if let ResponseMsg::EntityResponse(entities) = entities {
     let ship = entities.ships.get("ship1").unwrap().read().unwrap();
     let flight_plan = &ship.plan;
-    assert_eq!(flight_plan.0 .0, [0.0, 0.0, 0.0].into());
-    assert_eq!(flight_plan.0 .1, DEFAULT_ACCEL_DURATION);
+    assert_eq!(flight_plan.first().0, [0.0, 0.0, 0.0].into());
+    assert_eq!(flight_plan.first().1, DEFAULT_ACCEL_DURATION);
     assert!(!flight_plan.has_second());
   }
 
@@ -1105,8 +1002,8 @@ async fn integration_set_acceleration() {
   if let ResponseMsg::EntityResponse(entities) = entities {
     let ship = entities.ships.get("ship1").unwrap().read().unwrap();
     let flight_plan = &ship.plan;
-    assert_eq!(flight_plan.0 .0, [1.0, 2.0, 2.0].into());
-    assert_eq!(flight_plan.0 .1, DEFAULT_ACCEL_DURATION);
+    assert_eq!(flight_plan.first().0, [1.0, 2.0, 2.0].into());
+    assert_eq!(flight_plan.first().1, DEFAULT_ACCEL_DURATION);
     assert!(!flight_plan.has_second());
   }
 
@@ -1148,6 +1045,8 @@ async fn integration_compute_path_basic() {
       end_pos: [58_842_000.0, 0.0, 0.0].into(),
       end_vel: [0.0, 0.0, 0.0].into(),
       standoff_distance: 0.0,
+      waypoints: Vec::new(),
+      goal: FlightGoalMsg::MinTime,
       target_velocity: None,
       target_acceleration: None,
     }),
@@ -1176,11 +1075,11 @@ async fn integration_compute_path_basic() {
       epsilon = 1e-5
     );
     assert_ulps_eq!(plan.end_velocity, Vec3::zero(), epsilon = 1e-5);
-    let (a, t) = plan.plan.0.into();
+    let (a, t) = plan.plan.first().clone().into();
     assert_ulps_eq!(a, Vec3 { x: 3.0, y: 0.0, z: 0.0 } * G, epsilon = 1e-5);
     assert_eq!(t, 1414);
 
-    if let Some(accel) = plan.plan.1 {
+    if let Some(accel) = plan.plan.second().cloned() {
       let (a, _t) = accel.into();
       assert_ulps_eq!(
         a,
@@ -1239,6 +1138,8 @@ async fn integration_compute_path_with_standoff() {
       end_pos: [58_842_000.0, 0.0, 0.0].into(),
       end_vel: [0.0, 0.0, 0.0].into(),
       standoff_distance: 60000.0,
+      waypoints: Vec::new(),
+      goal: FlightGoalMsg::MinTime,
       target_velocity: None,
       target_acceleration: None,
     }),
@@ -1268,11 +1169,11 @@ async fn integration_compute_path_with_standoff() {
     );
     assert_ulps_eq!(plan.end_velocity, Vec3::zero(), epsilon = 1e-7);
 
-    let (a, t) = plan.plan.0.into();
+    let (a, t) = plan.plan.first().clone().into();
     assert_ulps_eq!(a, Vec3 { x: 3.0, y: 0.0, z: 0.0 } * G, epsilon = 1e-5);
     assert_eq!(t, 1413);
 
-    if let Some(accel) = plan.plan.1 {
+    if let Some(accel) = plan.plan.second().cloned() {
       let (a, _t) = accel.into();
       assert_ulps_eq!(
         a,
@@ -1344,6 +1245,8 @@ async fn integration_malformed_requests() {
       end_pos: [0.0, 0.0, 0.0].into(),
       end_vel: [0.0, 0.0, 0.0].into(),
       standoff_distance: 0.0,
+      waypoints: Vec::new(),
+      goal: FlightGoalMsg::MinTime,
       target_velocity: None,
       target_acceleration: None,
     }),
@@ -1377,6 +1280,7 @@ async fn integration_malformed_requests() {
         weapon_id: 0,
         target: "nonexistent_target".to_string(),
         called_shot_system: None,
+        multiple_attacks: false,
       }],
     )]),
   )
@@ -1442,6 +1346,7 @@ async fn integration_bad_requests() {
       weapon_id: usize::MAX,
       target: "ship2".to_string(),
       called_shot_system: None,
+      multiple_attacks: false,
     }],
   )]);
   let _response = rpc(&mut stream, msg).await;
@@ -1698,7 +1603,7 @@ fn seed_users_file(port: u16, active: &[&str], blacklisted: &[&str]) -> (PathBuf
 }
 
 async fn spawn_with_users_file(port: u16, users_file: String) -> Child {
-  spawn_server_full(port, true, None, None, Some(users_file), false)
+  spawn_server_full(port, true, None, None, Some(users_file), &[], false)
     .await
     .unwrap()
 }
@@ -1810,7 +1715,7 @@ async fn integration_users_message_uses_display_name() {
   // EntitiesRequest to settle the queue, then ask for users via SetRole
   // (which echoes a Users message). Easier: just take the existing one off
   // the wire from create_scenario.
-  // The drain_post_scenario_messages already consumed it. Trigger a new
+  // The client's create_scenario already consumed it. Trigger a new
   // Users response by issuing SetRole.
   let _ = rpc(
     &mut stream,
@@ -1882,17 +1787,10 @@ async fn integration_blacklist_kick_on_reload() {
   let mut closed = false;
   while tokio::time::Instant::now() < deadline {
     let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
-    match timeout(remaining.min(Duration::from_secs(8)), stream.next()).await {
-      Ok(Some(Err(_))) => {
-        closed = true;
-        break;
-      }
-      Ok(Some(Ok(msg))) if msg.is_close() => {
-        closed = true;
-        break;
-      }
-      // Anything else (broadcast, timeout, None): keep polling.
-      _ => {}
+    // The client ends its stream on a Close frame. Anything else (broadcast, timeout): keep polling.
+    if let Ok(Some(Err(_)) | None) = timeout(remaining.min(Duration::from_secs(8)), stream.next()).await {
+      closed = true;
+      break;
     }
   }
   assert!(closed, "Expected the blacklisted user's socket to be closed within ~12s");
@@ -1903,3 +1801,118 @@ async fn integration_blacklist_kick_on_reload() {
   let mut shutdown_stream = open_socket(port).await.unwrap();
   send_quit(&mut shutdown_stream).await;
 }
+
+#[test_log::test(tokio::test)]
+async fn integration_api_token_lifecycle() {
+  let port = get_next_port();
+  let (_root, users_path) = seed_users_file(port, &["bob@example.com"], &[]);
+  let body = json!({
+    "version": 1,
+    "users": [
+      {"email":"alice@example.com","status":"active","registered_at":1,"admin":true},
+      {"email":"bob@example.com","status":"active","registered_at":1}
+    ]
+  });
+  fs::write(&users_path, serde_json::to_string_pretty(&body).unwrap()).unwrap();
+  let _server = spawn_with_users_file(port, users_path).await;
+
+  let mint = RequestMsg::MintApiToken(MintApiTokenMsg {
+    email: "bob@example.com".to_string(),
+    label: "bot".to_string(),
+    scope: ApiTokenScope {
+      roles: vec![Role::Pilot, Role::Observer],
+      scenarios: vec!["test_scenario".to_string()],
+    },
+  });
+
+  // Non-admins cannot mint.
+  let mut bob = open_socket(port).await.unwrap();
+  bob.login("test_code_bob").await.unwrap();
+  let body = rpc(&mut bob, mint).await;
+  assert!(matches!(body, ResponseMsg::Error(ref e) if e == "NOT_AUTHORIZED"), "{body:?}");
+
+  let mut admin = open_socket(port).await.unwrap();
+  admin.login("test_code_alice").await.unwrap();
+  let mint = RequestMsg::MintApiToken(MintApiTokenMsg {
+    email: "bob@example.com".to_string(),
+    label: "bot".to_string(),
+    scope: ApiTokenScope {
+      roles: vec![Role::Pilot, Role::Observer],
+      scenarios: vec!["test_scenario".to_string()],
+    },
+  });
+  let ResponseMsg::ApiTokenMinted(minted) = rpc(&mut admin, mint).await else {
+    panic!("Expected minted token");
+  };
+  assert_eq!(minted.email, "bob@example.com");
+
+  let mut bot = open_socket(port).await.unwrap();
+  let session = bot.login_with_api_token(&minted.token).await.unwrap();
+  assert_eq!(session.auth.email, "bob@example.com");
+  assert_eq!(session.auth.role, Some(Role::Pilot), "Scoped token should not start as General");
+
+  // Scope restricts scenarios and roles.
+  let err = bot.create_scenario("elsewhere", "").await.unwrap_err();
+  assert!(matches!(err, ClientError::Server(_)), "{err:?}");
+  bot.create_scenario("test_scenario", "").await.unwrap();
+  let body = rpc(
+    &mut bot,
+    RequestMsg::SetRole(ChangeRole {
+      role: Role::General,
+      ship: None,
+    }),
+  )
+  .await;
+  assert!(matches!(body, ResponseMsg::Error(_)), "{body:?}");
+
+  // Scenario activity broadcasts refreshed scenario lists to the admin; drain them.
+  while let Ok(ResponseMsg::Scenarios(_)) = admin.next_response_with_timeout(Duration::from_millis(500)).await {}
+
+  let body = rpc(&mut admin, RequestMsg::RevokeApiToken(minted.id.clone())).await;
+  assert!(
+    matches!(body, ResponseMsg::ApiTokenRevoked(ref id) if *id == minted.id),
+    "{body:?}"
+  );
+
+  let mut retry = open_socket(port).await.unwrap();
+  let err = retry.login_with_api_token(&minted.token).await.unwrap_err();
+  assert!(matches!(err, ClientError::Server(_)), "{err:?}");
+
+  send_quit(&mut retry).await;
+}
+
+#[test_log::test(tokio::test)]
+async fn integration_metrics_endpoint() {
+  let port = get_next_port();
+  let metrics_port = get_next_port();
+  let _server = spawn_server_full(
+    port,
+    true,
+    None,
+    None,
+    None,
+    &["--metrics-port".to_string(), metrics_port.to_string()],
+    false,
+  )
+  .await
+  .unwrap();
+
+  let mut stream = open_socket(port).await.unwrap();
+  test_authenticate(&mut stream).await.unwrap();
+  test_create_scenario(&mut stream).await.unwrap();
+
+  let metrics_url = format!("http://{SERVER_ADDRESS}:{metrics_port}/metrics");
+  let response = reqwest::get(&metrics_url).await.unwrap();
+  assert!(response.status().is_success());
+  let body = response.text().await.unwrap();
+  assert!(body.contains("callisto_requests_total{request=\"Login\"} 1"), "{body}");
+  assert!(body.contains("callisto_requests_total{request=\"CreateScenario\"} 1"), "{body}");
+  assert!(body.contains("callisto_connections 1"), "{body}");
+  assert!(body.contains("callisto_servers 1"), "{body}");
+  assert!(body.contains("callisto_server_members{server=\"test_scenario\"} 1"), "{body}");
+
+  let not_found = reqwest::get(format!("http://{SERVER_ADDRESS}:{metrics_port}/")).await.unwrap();
+  assert_eq!(not_found.status(), reqwest::StatusCode::NOT_FOUND);
+
+  send_quit(&mut stream).await;
+}
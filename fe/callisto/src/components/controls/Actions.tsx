@@ -1,3 +1,5 @@
+import type { PowerAllocation } from "lib/entities";
+
 export type ActionType = {
   [actor: string]: {
     sensor: SensorState;
@@ -11,6 +13,8 @@ export type ActionType = {
     // Action" button (cached on `ship.leadership_points`); end-of-turn Phase 0
     // truncates this list to the rolled N.
     leadershipCheck: { boosts: BoostTarget[] } | null;
+    // Name of the dying crew member the medic will try to stabilize, if any.
+    medical: string | null;
     // Transient anti-action flags. Set true when the user explicitly clears a
     // queued sensor/engineer/leadership action; emitted as
     // `ClearSensorAction` / `ClearEngineerAction` / `ClearLeadershipCheck`
@@ -38,6 +42,9 @@ export type FireAction = {
   target: string;
   weapon_id: number;
   called_shot_system: string | null;
+  // Fire each weapon of a multi-weapon turret as its own attack rather than
+  // together for extra damage.
+  multiple_attacks?: boolean;
 };
 
 export type FireState = FireAction[];
@@ -78,12 +85,16 @@ export function newSensorState(action: SensorAction, target: string) {
 
 // Engineer action queued for end-of-turn evaluation. Mirrors the Rust
 // ShipAction variants (`OverloadDrive` / `OverloadPlant` / `Repair { system }`
-// / `Jump`). `null` means "no engineer action queued for this ship."
+// / `Jump` / `SkimFuel` / `TransferFuel { target }` / `AllocatePower`).
+// `null` means "no engineer action queued for this ship."
 export type EngineerState =
   | { kind: "OverloadDrive" }
   | { kind: "OverloadPlant" }
   | { kind: "Repair"; system: string }
   | { kind: "Jump" }
+  | { kind: "SkimFuel" }
+  | { kind: "TransferFuel"; target: string }
+  | { kind: "AllocatePower"; allocation: PowerAllocation }
   | null;
 
 // Marshalling/d-marshalling utilities
@@ -110,6 +121,10 @@ export function actionPayload(actions: ActionType) {
       }
     }
 
+    if (value.medical) {
+      fire_actions.push({Stabilize: {member: value.medical}});
+    }
+
     // Captain leadership check (one per ship; mutually exclusive with
     // `clearLeadership`). Emit whenever there are boosts queued.
     if (value.leadershipCheck && value.leadershipCheck.boosts.length > 0) {
@@ -211,6 +226,12 @@ function engineerActionPayload(engineer: EngineerState) {
       return {Repair: {system: engineer.system}};
     case "Jump":
       return "Jump";
+    case "SkimFuel":
+      return "SkimFuel";
+    case "TransferFuel":
+      return {TransferFuel: {target: engineer.target}};
+    case "AllocatePower":
+      return {AllocatePower: {allocation: engineer.allocation}};
   }
 }
 
@@ -235,6 +256,7 @@ function fireActionPayload(fireAction: FireAction) {
       weapon_id: fireAction.weapon_id,
       target: fireAction.target,
       called_shot_system: fireAction.called_shot_system,
+      multiple_attacks: fireAction.multiple_attacks ?? false,
     },
   };
 }
@@ -373,6 +395,20 @@ export function payloadToAction(payload: object[]): ActionType {
         engineer = {kind: "Jump"};
         break;
       }
+      if (action === "SkimFuel") {
+        engineer = {kind: "SkimFuel"};
+        break;
+      }
+      if (typeof action === "object" && Object.hasOwn(action, "TransferFuel")) {
+        const transfer = (action as {TransferFuel: {target: string}}).TransferFuel;
+        engineer = {kind: "TransferFuel", target: transfer.target};
+        break;
+      }
+      if (typeof action === "object" && Object.hasOwn(action, "AllocatePower")) {
+        const allocate = (action as {AllocatePower: {allocation: PowerAllocation}}).AllocatePower;
+        engineer = {kind: "AllocatePower", allocation: allocate.allocation};
+        break;
+      }
       if (typeof action === "object" && Object.hasOwn(action, "Repair")) {
         const repair = (action as {Repair: {system: string}}).Repair;
         engineer = {kind: "Repair", system: repair.system};
@@ -395,6 +431,15 @@ export function payloadToAction(payload: object[]): ActionType {
     }
     result[shipName] = {...result[shipName], leadershipCheck};
 
+    let medical: string | null = null;
+    for (const action of actions) {
+      if (typeof action === "object" && Object.hasOwn(action, "Stabilize")) {
+        medical = (action as {Stabilize: {member: string}}).Stabilize.member;
+        break;
+      }
+    }
+    result[shipName] = {...result[shipName], medical};
+
     // Anti-action flags are transient client-only state; server never echoes them.
     result[shipName] = {
       ...result[shipName],
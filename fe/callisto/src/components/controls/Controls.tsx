@@ -9,7 +9,7 @@ import { Accordion } from "lib/Accordion";
 import { AddShip } from "./AddShip";
 import { AddPlanet } from "./AddPlanet";
 import { POSITION_SCALE, SCALE } from "lib/universal";
-import { Ship, Entity, Planet, findShip } from "lib/entities";
+import { Ship, Entity, Planet, findShip, nextAcceleration } from "lib/entities";
 import { ViewMode } from "lib/view";
 import { nextRound } from "lib/serverManager";
 import { EntitySelector, EntitySelectorType } from "lib/EntitySelector";
@@ -19,6 +19,7 @@ import { Actions, FireControl } from "./WeaponUse";
 import { DEFAULT_SENSOR_STATE, SensorAction } from "components/controls/Actions";
 import { ShipComputer } from "./ShipComputer";
 import { CaptainTasks } from "./CaptainTasks";
+import { CrewRoster } from "./CrewRoster";
 import { computeFlightPath } from "lib/serverManager";
 import { useAppSelector, useAppDispatch } from "state/hooks";
 import { entitiesSelector } from "state/serverSlice";
@@ -417,6 +418,7 @@ export function Controls() {
                   </pre>
                 </div>
               )}
+            <CrewRoster ship={computerShip} />
             <hr />
             {[ViewMode.Pilot, ViewMode.Sensors, ViewMode.Engineer].includes(
               role,
@@ -571,7 +573,7 @@ export function EntityInfoWindow(args: { entity: Entity }) {
   } else if ("plan" in args.entity) {
     // If its a Ship
     isShip = true;
-    ship_next_accel = (args.entity as Ship).plan[0][0];
+    ship_next_accel = nextAcceleration((args.entity as Ship).plan);
     design = "(" + (args.entity as Ship).design + " class)";
   }
 
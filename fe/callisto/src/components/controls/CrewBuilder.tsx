@@ -14,8 +14,40 @@ export interface Crew {
   sensors: number;
   gunnery: number[];
   leadership: number;
+  medic?: number;
+  // Named crew members, when the ship has them. Mirrors Rust `CrewMember`.
+  members?: CrewMember[];
 }
 
+export type Station = "Captain" | "Pilot" | "Engineer" | "Sensors" | "Gunner" | "Medic" | "Crew";
+
+// Mirrors Rust `Health`; omitted on the wire when fit.
+export type Health =
+  | "Fit"
+  | "Wounded"
+  | { Dying: { turns_left: number } }
+  | "Stable"
+  | "Dead";
+
+export interface CrewMember {
+  name: string;
+  station: Station;
+  skills: { [skill: string]: number };
+  health?: Health;
+  // Ids of the weapons a gunner controls.
+  weapons?: number[];
+}
+
+export const healthToString = (health: Health | undefined) => {
+  if (health === undefined) {
+    return "Fit";
+  }
+  if (typeof health === "string") {
+    return health;
+  }
+  return `Dying (${health.Dying.turns_left} turns)`;
+};
+
 export const createCrew = (num_gunners: number) => {
   let new_crew = {
     pilot: 0,
@@ -0,0 +1,92 @@
+import * as React from "react";
+import { Ship } from "lib/entities";
+import { useAppSelector, useAppDispatch } from "state/hooks";
+import { setMedicalAction } from "state/actionsSlice";
+import { templatesSelector } from "state/serverSlice";
+import { healthToString } from "components/controls/CrewBuilder";
+import { weaponToString } from "lib/weapon";
+import { assignGunner } from "lib/serverManager";
+
+interface CrewRosterProps {
+  ship: Ship;
+}
+
+// Named crew with their stations and health. The dying can be queued for the
+// medic to stabilize at end of turn (one patient per turn), and any crew member
+// able to serve can be put on a gun.
+export const CrewRoster: React.FC<CrewRosterProps> = ({ ship }) => {
+  const dispatch = useAppDispatch();
+  const queued = useAppSelector((state) => state.actions[ship.name]?.medical ?? null);
+  const design = useAppSelector((state) => templatesSelector(state)[ship.design]);
+  const members = ship.crew.members ?? [];
+  const able = members.filter(
+    (member) => member.health === undefined || member.health === "Fit" || member.health === "Wounded",
+  );
+
+  if (members.length === 0) {
+    return null;
+  }
+
+  return (
+    <div id="crew-roster">
+      <h2 className="control-form">Crew</h2>
+      {members.map((member) => {
+        const dying = typeof member.health === "object";
+        return (
+          <div key={member.name} style={{ display: "flex", justifyContent: "space-between" }}>
+            <pre className="plan-accel-text">
+              {`${member.name} (${member.station}): ${healthToString(member.health)}`}
+            </pre>
+            {dying && (
+              <button
+                type="button"
+                className="control-input control-button blue-button"
+                disabled={queued === member.name}
+                onClick={() => dispatch(setMedicalAction({ shipName: ship.name, member: member.name }))}
+                title="Have the medic try to stabilize this crew member"
+              >
+                {queued === member.name ? "Stabilizing" : "Stabilize"}
+              </button>
+            )}
+          </div>
+        );
+      })}
+      {design && design.weapons.length > 0 && (
+        <>
+          <h2 className="control-form">Gunners</h2>
+          {design.weapons.map((weapon, weapon_id) => {
+            const gunner = able.find(
+              (member) =>
+                member.station === "Gunner" && (member.weapons ?? []).includes(weapon_id),
+            );
+            return (
+              <div key={weapon_id} style={{ display: "flex", justifyContent: "space-between" }}>
+                <pre className="plan-accel-text">{weaponToString(weapon)}</pre>
+                <select
+                  className="control-input"
+                  value={gunner?.name ?? ""}
+                  onChange={(e) => {
+                    const member = able.find((m) => m.name === e.target.value);
+                    if (member) {
+                      const weapons = member.station === "Gunner" ? member.weapons ?? [] : [];
+                      assignGunner(ship.name, member.name, [...weapons, weapon_id]);
+                    }
+                  }}
+                >
+                  <option value="" disabled>
+                    No gunner
+                  </option>
+                  {able.map((member) => (
+                    <option key={member.name} value={member.name}>
+                      {member.name}
+                    </option>
+                  ))}
+                </select>
+              </div>
+            );
+          })}
+        </>
+      )}
+    </div>
+  );
+};
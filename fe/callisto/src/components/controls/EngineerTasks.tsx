@@ -1,13 +1,17 @@
 import * as React from "react";
 import { useMemo } from "react";
 import {
+  PowerAllocation,
   Ship,
   ShipSystem,
   shipSystemToString,
   stringToShipSystem,
 } from "lib/entities";
+import { weaponToString } from "lib/weapon";
 import { useAppDispatch, useAppSelector } from "state/hooks";
 import { setEngineerAction } from "state/actionsSlice";
+import { entitiesSelector, templatesSelector } from "state/serverSlice";
+import { setJumpDestination } from "lib/serverManager";
 
 // Map ShipSystem enum to display names (must match backend order)
 export const SYSTEM_NAMES: Record<ShipSystem, string> = {
@@ -36,6 +40,18 @@ export const EngineerTasks: React.FC<EngineerTasksProps> = ({ ship }) => {
   const queuedEngineer = useAppSelector(
     (state) => state.actions[ship.name]?.engineer ?? null,
   );
+  const jumpDestinations = useAppSelector(
+    (state) => entitiesSelector(state).jump_destinations ?? [],
+  );
+  const ships = useAppSelector((state) => entitiesSelector(state).ships);
+  const design = useAppSelector(
+    (state) => templatesSelector(state)[ship.design],
+  );
+  const fuelCapacity = design?.fuel ?? 0;
+  const otherShips = useMemo(
+    () => ships.filter((other) => other.name !== ship.name),
+    [ships, ship.name],
+  );
 
   // Get list of damaged systems (excluding Hull, Armor, and Crew which cannot be repaired)
   const damagedSystems = useMemo(() => {
@@ -51,23 +67,54 @@ export const EngineerTasks: React.FC<EngineerTasksProps> = ({ ship }) => {
       );
   }, [ship.crit_level]);
 
+  // Damage control teams: one for the engineer plus one per further ten crew,
+  // capped at one per 500 tons beyond the first (mirrors
+  // Ship::damage_control_teams on the server).
+  const repairTasks = ship.repair_tasks ?? [];
+  const teams =
+    ship.current_crew === 0
+      ? 0
+      : Math.min(
+          1 + Math.floor(ship.current_crew / 10),
+          1 + Math.floor((design?.displacement ?? 0) / 500),
+        );
+  const repairTask = (system: ShipSystem) =>
+    repairTasks.find((task) => stringToShipSystem(task.system) === system);
+
   // Calculate repair bonus for display
-  const getRepairBonus = (system: ShipSystem): number => {
-    if (
-      ship.last_repair_component &&
-      stringToShipSystem(ship.last_repair_component) === system &&
-      ship.repair_bonus
-    ) {
-      return ship.repair_bonus;
-    }
-    return 0;
-  };
+  const getRepairBonus = (system: ShipSystem): number =>
+    repairTask(system)?.bonus ?? 0;
 
   // Check if overload bonus is currently in effect on the ship (carries over
   // from a successful overload last turn).
   const hasOverloadDrive = (ship.temporary_maneuver ?? 0) > 0;
   const hasOverloadPlant = (ship.temporary_power_multiplier ?? 1.0) > 1.0;
 
+  // The allocation shown is the one queued this turn, else the one in force,
+  // else full power to everything (what the server assumes without one).
+  const allocation: PowerAllocation | null =
+    queuedEngineer?.kind === "AllocatePower"
+      ? queuedEngineer.allocation
+      : (ship.power_allocation ??
+        (design
+          ? {
+              maneuver: design.maneuver,
+              sensors: true,
+              weapons: design.weapons.map(() => true),
+            }
+          : null));
+
+  // Any change to the allocation queues it as this turn's engineer action.
+  const allocatePower = (change: Partial<PowerAllocation>) => {
+    if (allocation === null) return;
+    dispatch(
+      setEngineerAction({
+        shipName: ship.name,
+        action: { kind: "AllocatePower", allocation: { ...allocation, ...change } },
+      }),
+    );
+  };
+
   // Derive controlled-select value from the queued engineer action.
   let selectedValue = "none";
   if (queuedEngineer?.kind === "OverloadDrive") {
@@ -76,6 +123,12 @@ export const EngineerTasks: React.FC<EngineerTasksProps> = ({ ship }) => {
     selectedValue = "overload-plant";
   } else if (queuedEngineer?.kind === "Jump") {
     selectedValue = "jump";
+  } else if (queuedEngineer?.kind === "SkimFuel") {
+    selectedValue = "skim-fuel";
+  } else if (queuedEngineer?.kind === "TransferFuel") {
+    selectedValue = `transfer-${queuedEngineer.target}`;
+  } else if (queuedEngineer?.kind === "AllocatePower") {
+    selectedValue = "allocate-power";
   } else if (queuedEngineer?.kind === "Repair") {
     const sys = stringToShipSystem(queuedEngineer.system);
     if (sys != null) selectedValue = `repair-${sys}`;
@@ -108,6 +161,23 @@ export const EngineerTasks: React.FC<EngineerTasksProps> = ({ ship }) => {
           action: { kind: "Jump" },
         }),
       );
+    } else if (value === "skim-fuel") {
+      dispatch(
+        setEngineerAction({
+          shipName: ship.name,
+          action: { kind: "SkimFuel" },
+        }),
+      );
+    } else if (value.startsWith("transfer-")) {
+      dispatch(
+        setEngineerAction({
+          shipName: ship.name,
+          action: {
+            kind: "TransferFuel",
+            target: value.substring("transfer-".length),
+          },
+        }),
+      );
     } else if (value.startsWith("repair-")) {
       const n = parseInt(value.substring("repair-".length), 10);
       if (!Number.isNaN(n)) {
@@ -134,6 +204,11 @@ export const EngineerTasks: React.FC<EngineerTasksProps> = ({ ship }) => {
         style={{ width: "100%", boxSizing: "border-box" }}
       >
         <option value="none"></option>
+        {selectedValue === "allocate-power" && (
+          <option value="allocate-power" disabled>
+            Allocate Power
+          </option>
+        )}
         {/* Overload bonuses last only one turn — always allow re-queueing
             the action even when a bonus is currently active. The queued
             action evaluates at end-of-turn AFTER reset_temporary_bonuses
@@ -141,9 +216,25 @@ export const EngineerTasks: React.FC<EngineerTasksProps> = ({ ship }) => {
             extends overload into the next turn. */}
         <option value="overload-drive">Overload Drive</option>
         <option value="overload-plant">Overload Plant</option>
-        <option value="jump" disabled={!ship.can_jump}>
-          Jump
+        {/* Jumping inside a gravity well or with a damaged drive is allowed
+            but risks a mishap; can_jump means clear of every gravity well. */}
+        <option value="jump" disabled={ship.current_jump === 0}>
+          {ship.can_jump ? "Jump" : "Jump (inside gravity well)"}
+        </option>
+        {/* The server checks the ship is in a skimming orbit of a gas giant,
+            or alongside the other ship with matched velocity. */}
+        <option
+          value="skim-fuel"
+          disabled={ship.current_fuel >= fuelCapacity}
+        >
+          Skim Fuel
         </option>
+        {ship.current_fuel > 0 &&
+          otherShips.map((other) => (
+            <option key={other.name} value={`transfer-${other.name}`}>
+              Transfer Fuel to {other.name}
+            </option>
+          ))}
         {damagedSystems.length === 0 ? (
           <option disabled value="no-damage">
             No damaged systems to repair
@@ -152,16 +243,107 @@ export const EngineerTasks: React.FC<EngineerTasksProps> = ({ ship }) => {
           damagedSystems.map(({ system, level }) => {
             const bonus = getRepairBonus(system);
             const bonusText = bonus > 0 ? ` (+${bonus} bonus)` : "";
+            // A new repair needs a free team; one under way can always be
+            // worked alongside for the engineer's boost.
+            const working = repairTask(system) !== undefined;
             return (
-              <option key={system} value={`repair-${system}`}>
-                Repair {SYSTEM_NAMES[system]} (Crit Level {level}){bonusText}
+              <option
+                key={system}
+                value={`repair-${system}`}
+                disabled={!working && repairTasks.length >= teams}
+              >
+                {working ? "Assist" : "Repair"} {SYSTEM_NAMES[system]} (Crit
+                Level {level}){bonusText}
               </option>
             );
           })
         )}
       </select>
+      {damagedSystems.length > 0 && (
+        <div className="damage-control">
+          <p className="plan-accel-text">
+            Damage control teams: {repairTasks.length}/{teams}
+          </p>
+          {repairTasks.map((task) => {
+            const system = stringToShipSystem(task.system);
+            return (
+              <p className="plan-accel-text" key={task.system}>
+                {system != null ? SYSTEM_NAMES[system] : task.system}
+                {task.bonus ? ` (+${task.bonus} bonus)` : ""}
+              </p>
+            );
+          })}
+        </div>
+      )}
+      {jumpDestinations.length > 0 && (
+        <label className="control-label">
+          Jump to
+          <select
+            className="control-input"
+            value={ship.jump_destination ?? ""}
+            onChange={(e) =>
+              setJumpDestination(ship.name, e.target.value || null)
+            }
+          >
+            <option value="">Leave the campaign</option>
+            {jumpDestinations.map((destination) => (
+              <option key={destination.name} value={destination.name}>
+                {destination.name} ({destination.transit_turns} turns)
+              </option>
+            ))}
+          </select>
+        </label>
+      )}
+      {allocation !== null && design && (
+        <div className="power-allocation">
+          <label className="control-label">
+            Power to drive (G)
+            <input
+              className="control-input"
+              type="number"
+              min={0}
+              max={design.maneuver}
+              value={allocation.maneuver}
+              onChange={(e) =>
+                allocatePower({
+                  maneuver: Math.max(
+                    0,
+                    Math.min(design.maneuver, parseInt(e.target.value, 10) || 0),
+                  ),
+                })
+              }
+            />
+          </label>
+          <label className="control-label">
+            Sensors
+            <input
+              type="checkbox"
+              checked={allocation.sensors}
+              onChange={() => allocatePower({ sensors: !allocation.sensors })}
+            />
+          </label>
+          {design.weapons.map((weapon, index) => (
+            <label className="control-label" key={index}>
+              {weaponToString(weapon)}
+              <input
+                type="checkbox"
+                checked={allocation.weapons[index] ?? false}
+                onChange={() =>
+                  allocatePower({
+                    weapons: design.weapons.map((_, i) => {
+                      const powered = allocation.weapons[i] ?? false;
+                      return i === index ? !powered : powered;
+                    }),
+                  })
+                }
+              />
+            </label>
+          ))}
+        </div>
+      )}
       {hasOverloadDrive && <p className="plan-accel-text">Drive Overloaded</p>}
       {hasOverloadPlant && <p className="plan-accel-text">Plant Overloaded</p>}
+      {ship.skimming && <p className="plan-accel-text">Skimming Fuel</p>}
     </div>
   );
 };
@@ -1,10 +1,10 @@
 import * as React from "react";
 import {useState, useEffect, useMemo} from "react";
 import {DEFAULT_ACCEL_DURATION, POSITION_SCALE} from "lib/universal";
-import {Ship, Acceleration} from "lib/entities";
+import {Ship, Acceleration, nextAcceleration} from "lib/entities";
 import {ViewMode} from "lib/view";
 
-import {setPlan, setCrewActions} from "lib/serverManager";
+import {setPlan, setCrewActions, setFacing} from "lib/serverManager";
 import {SensorState, SensorAction, newSensorState} from "components/controls/Actions";
 import {EntitySelectorType, EntitySelector} from "lib/EntitySelector";
 import {findShip} from "lib/entities";
@@ -107,9 +107,9 @@ export const ShipComputer: React.FC<ShipComputerProps> = ({ship}) => {
       v_x: target.velocity[0],
       v_y: target.velocity[1],
       v_z: target.velocity[2],
-      a_x: plan ? plan[0][0][0] : 0.0,
-      a_y: plan ? plan[0][0][1] : 0.0,
-      a_z: plan ? plan[0][0][2] : 0.0,
+      a_x: plan ? nextAcceleration(plan)[0] : 0.0,
+      a_y: plan ? nextAcceleration(plan)[1] : 0.0,
+      a_z: plan ? nextAcceleration(plan)[2] : 0.0,
       standoff,
     });
 
@@ -119,7 +119,7 @@ export const ShipComputer: React.FC<ShipComputerProps> = ({ship}) => {
       target.position,
       target.velocity,
       target.velocity,
-      plan ? plan[0][0] : null,
+      plan ? nextAcceleration(plan) : null,
       standoff
     );
   }, [currentNavTarget, target, initNavigationTargetState, ship.name]);
@@ -129,7 +129,7 @@ export const ShipComputer: React.FC<ShipComputerProps> = ({ship}) => {
   const assistGunners = useMemo(() => ship.assist_gunners, [ship]);
   const agility = useMemo(() => ship.dodge_thrust, [ship]);
 
-  const startAccel = [ship?.plan[0][0][0], ship?.plan[0][0][1], ship?.plan[0][0][2]];
+  const startAccel = nextAcceleration(ship.plan);
 
   // This is where we convert from string back into number, and thus
   // we only do this precision-losing conversion when a human enters a new value.
@@ -186,12 +186,10 @@ export const ShipComputer: React.FC<ShipComputerProps> = ({ship}) => {
     if (proposedPlan == null) {
       console.error(`(Controls.handleAssignPlan) No current plan`);
     } else {
-      (document.getElementById("set-accel-input-x") as HTMLInputElement).value =
-        proposedPlan.plan[0][0][0].toString();
-      (document.getElementById("set-accel-input-y") as HTMLInputElement).value =
-        proposedPlan.plan[0][0][1].toString();
-      (document.getElementById("set-accel-input-z") as HTMLInputElement).value =
-        proposedPlan.plan[0][0][2].toString();
+      const [x, y, z] = nextAcceleration(proposedPlan.plan);
+      (document.getElementById("set-accel-input-x") as HTMLInputElement).value = x.toString();
+      (document.getElementById("set-accel-input-y") as HTMLInputElement).value = y.toString();
+      (document.getElementById("set-accel-input-z") as HTMLInputElement).value = z.toString();
 
       setPlan(ship.name, proposedPlan.plan);
     }
@@ -218,7 +216,7 @@ export const ShipComputer: React.FC<ShipComputerProps> = ({ship}) => {
       const y = checkNumericInput("set-accel-input-y");
       const z = checkNumericInput("set-accel-input-z");
 
-      setPlan(ship.name, [[[x, y, z], DEFAULT_ACCEL_DURATION], null]);
+      setPlan(ship.name, [[[x, y, z], DEFAULT_ACCEL_DURATION]]);
     }
 
     return (
@@ -259,6 +257,26 @@ export const ShipComputer: React.FC<ShipComputerProps> = ({ship}) => {
     function handleCrewActionChange(dodge: number, assist: boolean) {
       setCrewActions(ship.name, dodge, assist);
     }
+
+    // While coasting the pilot can turn the ship, e.g. to bring a spinal mount
+    // to bear. Under thrust the ship points along its thrust whatever its facing.
+    const bearingTo = (other: Ship): [number, number, number] | null => {
+      const offset = other.position.map((x, i) => x - ship.position[i]) as [number, number, number];
+      const length = Math.hypot(...offset);
+      return length > 0 ? (offset.map((x) => x / length) as [number, number, number]) : null;
+    };
+    const others = entities.ships.filter((other) => other.name !== ship.name);
+    const facing = ship.facing;
+    const facingTarget = facing
+      ? (others.find((other) => {
+          const bearing = bearingTo(other);
+          return bearing !== null && bearing.reduce((dot, x, i) => dot + x * facing[i], 0) > 0.9999;
+        })?.name ?? "held")
+      : "";
+    function handleFacingChange(value: string) {
+      const other = others.find((s) => s.name === value);
+      setFacing(ship.name, other ? bearingTo(other) : null);
+    }
     return (
       <>
         <div className="section-tag">Pilot</div>
@@ -279,6 +297,26 @@ export const ShipComputer: React.FC<ShipComputerProps> = ({ship}) => {
             onChange={() => handleCrewActionChange(agility, !assistGunners)}
           />
         </div>
+        <div className="pilot-actions-row">
+          <label className="control-label">Face</label>
+          <select
+            className="control-input"
+            value={facingTarget}
+            onChange={(event) => handleFacingChange(event.target.value)}
+          >
+            <option value="">Along thrust</option>
+            {facingTarget === "held" && (
+              <option value="held" disabled>
+                Held facing
+              </option>
+            )}
+            {others.map((other) => (
+              <option key={other.name} value={other.name}>
+                Toward {other.name}
+              </option>
+            ))}
+          </select>
+        </div>
       </>
     );
   }
@@ -582,7 +620,7 @@ const SensorActionChooser: React.FC<SensorActionChooserProps> = ({ship, sensorLo
   );
 };
 
-export function NavigationPlan(args: {plan: [Acceleration, Acceleration | null]}) {
+export function NavigationPlan(args: {plan: Acceleration[]}) {
   function prettyPrintAccel(accel: Acceleration) {
     // explicitly round down acceleration so if user is copy/pasting they
     // don't get an "acceleration too high" error.
@@ -594,19 +632,13 @@ export function NavigationPlan(args: {plan: [Acceleration, Acceleration | null]}
     return s;
   }
 
-  const accel0 = args.plan[0];
-  const accel1 = args.plan[1];
-
   return (
     <>
-      <div key={"accel-0"}>
-        <pre className="plan-accel-text">{prettyPrintAccel(accel0)}</pre>
-      </div>
-      {accel1 && (
-        <div key={"accel-1"}>
-          <pre className="plan-accel-text">{prettyPrintAccel(accel1)}</pre>
+      {args.plan.map((accel, index) => (
+        <div key={"accel-" + index}>
+          <pre className="plan-accel-text">{prettyPrintAccel(accel)}</pre>
         </div>
-      )}
+      ))}
     </>
   );
 }
@@ -49,6 +49,7 @@ import {
   fireWeapon,
   unfireWeapon,
   updateFireCalledShot,
+  updateFireMultipleAttacks,
   setSensorAction,
   setEngineerAction,
   toggleBoost,
@@ -75,6 +76,9 @@ const ENGINEER_ICON_COLORS: { [kind: string]: string } = {
   OverloadPlant: "yellow",
   Repair: "blue",
   Jump: "magenta",
+  SkimFuel: "orange",
+  TransferFuel: "cyan",
+  AllocatePower: "white",
 };
 
 const PILOT_ICON_COLORS = {
@@ -93,10 +97,10 @@ export const WeaponButton = (props: {
     return (
       <>
         <button
-          id={props.weapon + "-barbette-button"}
+          id={props.weapon + "-" + props.mount.toLowerCase() + "-button"}
           className="weapon-button"
           data-tooltip-id={props.weapon + props.mount}
-          data-tooltip-content={`${props.weapon} Barbette`}
+          data-tooltip-content={`${props.weapon} ${props.mount === "Spinal" ? "Spinal Mount" : "Barbette"}`}
           data-tooltip-delay-show={700}
           onClick={props.onClick}
           disabled={props.disabled}
@@ -679,6 +683,15 @@ export function Actions(args: {
       case "Jump":
         engineerLabel = "Jump";
         break;
+      case "SkimFuel":
+        engineerLabel = "Skim Fuel";
+        break;
+      case "TransferFuel":
+        engineerLabel = "Transfer Fuel to " + args.engineerAction.target;
+        break;
+      case "AllocatePower":
+        engineerLabel = "Allocate Power";
+        break;
     }
   }
 
@@ -708,6 +721,20 @@ export function Actions(args: {
           weapon_id: action.weapon_id,
         };
 
+        // With named crew, show who is on the gun (or that no one is).
+        const members = computerShip?.crew.members ?? [];
+        const gunner = members.find(
+          (member) =>
+            member.station === "Gunner" &&
+            (member.weapons ?? []).includes(action.weapon_id),
+        );
+        const gunnerLabel =
+          members.length === 0 ? "" : gunner ? ` (${gunner.name})` : " (no gunner)";
+
+        const mount = args.design.weapons[action.weapon_id].mount;
+        const multiWeaponTurret =
+          typeof mount === "object" && "Turret" in mount && mount.Turret > 1;
+
         return ["Beam", "Pulse", "Particle"].includes(kind) ? (
           <div className="fire-actions-div" key={index + "_fire_img"}>
             <div onClick={() => onClick(action.weapon_id)}>
@@ -719,8 +746,27 @@ export function Actions(args: {
                   }}
                 />{" "}
                 to {action.target}
+                {gunnerLabel}
               </p>
             </div>
+            {multiWeaponTurret && (
+              <label title="Fire each weapon in the turret as its own attack instead of together for extra damage">
+                <input
+                  type="checkbox"
+                  checked={action.multiple_attacks ?? false}
+                  onChange={(e) =>
+                    dispatch(
+                      updateFireMultipleAttacks({
+                        shipName: computerShipName!,
+                        index: index,
+                        multiple_attacks: e.target.checked,
+                      }),
+                    )
+                  }
+                />
+                Separate
+              </label>
+            )}
             <CalledShotMenu
               attacker={computerShip!}
               target={findShip(entities, action.target)!}
@@ -751,6 +797,7 @@ export function Actions(args: {
                   }}
                 />{" "}
                 to {action.target}
+                {gunnerLabel}
               </p>
             </div>
             {renderBoostCheckbox(fireBoostTarget)}
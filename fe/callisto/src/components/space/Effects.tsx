@@ -7,7 +7,7 @@ import { GrowLine } from "lib/Util";
 import { findShip } from "lib/entities";
 
 import { useAppSelector, useAppDispatch } from "state/hooks";
-import { setShowResults, setEvents } from "state/uiSlice";
+import { setShowResults, setShowDiceRolls, setEvents } from "state/uiSlice";
 import {entitiesSelector} from "state/serverSlice";
 
 
@@ -16,6 +16,7 @@ const EXHAUSTED_MISSILE = "ExhaustedMissile";
 const SHIP_DESTROYED = "ShipDestroyed";
 const BEAM_HIT = "BeamHit";
 const MESSAGE_EVENT = "Message";
+const DICE_ROLL_EVENT = "DiceRoll";
 
 const MISSILE_HIT_COLOR: [number, number, number] = [1.0, 0, 0];
 const MISSILE_EXHAUSTED_COLOR: [number, number, number] = [1.0, 1.0, 1.0];
@@ -171,6 +172,7 @@ export function Explosions() {
               />
             );
           case MESSAGE_EVENT:
+          case DICE_ROLL_EVENT:
             // DamageEffects don't show up as explosions so skip.
             return null;
           default:
@@ -188,23 +190,35 @@ export function Explosions() {
 
 export function ResultsWindow() {
   const events = useAppSelector(state => state.ui.events);
+  const showDiceRolls = useAppSelector(state => state.ui.showDiceRolls);
   const dispatch = useAppDispatch();
 
   const closeWindow = useCallback(() => {
     if (events !== null) {
-      dispatch(setEvents(events.filter((event) => event.kind !== MESSAGE_EVENT)));
+      dispatch(setEvents(events.filter((event) => event.kind !== MESSAGE_EVENT && event.kind !== DICE_ROLL_EVENT)));
     }
     dispatch(setShowResults(false));
   }, [events, dispatch]);
 
-  const messages = useMemo(() => events?.filter((event) => event.kind === MESSAGE_EVENT) ?? [], [events]);
+  const messages = useMemo(
+    () =>
+      events?.filter((event) => event.kind === MESSAGE_EVENT || (showDiceRolls && event.kind === DICE_ROLL_EVENT)) ??
+      [],
+    [events, showDiceRolls]
+  );
 
   return (
     <div id="results-window" className="computer-window">
       <h1>Results</h1>
       <br></br>
       {messages.length === 0 && <h2>No results</h2>}
-      {messages.length > 0 && messages.map((msg, index) => (<p key={"msg-" + index}>{msg.content}</p>))}
+      {messages.length > 0 && messages.map((msg, index) => (
+        <p key={"msg-" + index} className={msg.kind === DICE_ROLL_EVENT ? "dice-roll" : undefined}>{msg.content}</p>
+      ))}
+      <label>
+        <input type="checkbox" checked={showDiceRolls} onChange={(e) => dispatch(setShowDiceRolls(e.target.checked))} />
+        Show dice rolls
+      </label>
       <button className="control-input control-button blue-button button-next-round" onClick={closeWindow}>Okay!</button>
     </div>
   )
@@ -21,14 +21,15 @@ import {
   TURN_IN_SECONDS,
   RANGE_BANDS
 } from "lib/universal";
-import { Ship as ShipType, Missile as MissileType} from "lib/entities";
+import { Ship as ShipType, Missile as MissileType, shipAxis, nextAcceleration } from "lib/entities";
+import { FiringArc, firingArc, ALL_ROUND } from "lib/weapon";
 import { FlightPath } from "lib/flightPath";
 
 import { addVector, scaleVector, RangeSphere } from "lib/Util";
 
 import { useAppSelector, useAppDispatch } from "state/hooks";
 import { setEntityToShow, setComputerShipName } from "state/uiSlice";
-import {entitiesSelector} from "state/serverSlice";
+import {entitiesSelector, templatesSelector} from "state/serverSlice";
 
 extend({ TextGeometry });
 
@@ -51,14 +52,54 @@ new FontLoader().load(
   }
 );
 
+// Length (in screen units) of the lines drawn for a ship's axis and firing arcs.
+const ARC_LINE_LENGTH = 3;
+// Lines drawn round the axis to outline each edge of a firing arc.
+const ARC_EDGE_LINES = 8;
+
+// End points of lines outlining the edges of `arc` about `axis`: one cone of
+// lines for each edge that isn't dead ahead or dead astern.
+function arcEdges(axis: [number, number, number], arc: FiringArc): [number, number, number][] {
+  // Any two unit vectors perpendicular to the axis and each other.
+  const helper: [number, number, number] = Math.abs(axis[0]) < 0.9 ? [1, 0, 0] : [0, 1, 0];
+  const cross = (a: [number, number, number], b: [number, number, number]) =>
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]] as [number, number, number];
+  const u = cross(axis, helper);
+  const uLength = Math.hypot(...u);
+  const uNorm = u.map((x) => x / uLength) as [number, number, number];
+  const v = cross(axis, uNorm);
+
+  return [arc.from, arc.to]
+    .filter((degrees) => degrees > 0 && degrees < 180)
+    .flatMap((degrees) => {
+      const theta = (degrees * Math.PI) / 180;
+      return Array.from({ length: ARC_EDGE_LINES }, (_, i) => {
+        const phi = (2 * Math.PI * i) / ARC_EDGE_LINES;
+        return axis.map(
+          (a, k) =>
+            ARC_LINE_LENGTH *
+            (Math.cos(theta) * a + Math.sin(theta) * (Math.cos(phi) * uNorm[k] + Math.sin(phi) * v[k])),
+        ) as [number, number, number];
+      });
+    });
+}
+
 function Ship(args: {
   ship: ShipType;
   index: number;
 }) {
   const computerShipName = useAppSelector(state => state.ui.computerShipName);
   const showRange = useAppSelector(state => state.ui.showRange) === computerShipName;
+  const design = useAppSelector(state => templatesSelector(state)[args.ship.design]);
   const dispatch = useAppDispatch();
 
+  // The selected ship shows where it points and the arcs its weapons can fire in.
+  const axis = computerShipName === args.ship.name ? shipAxis(args.ship) : null;
+  const arcs = (design?.weapons ?? [])
+    .map(firingArc)
+    .filter((arc) => arc.from !== ALL_ROUND.from || arc.to !== ALL_ROUND.to)
+    .filter((arc, index, all) => all.findIndex((a) => a.from === arc.from && a.to === arc.to) === index);
+
   const { camera } = useThree();
   const textRef = useRef<Mesh>(null);
   const shipRef = useRef<Mesh>(null);
@@ -117,13 +158,23 @@ function Ship(args: {
           start={scaleVector(args.ship.velocity, SCALE * TURN_IN_SECONDS)}
           end={addVector(
             scaleVector(
-              args.ship.plan[0][0] as [number, number, number],
+              nextAcceleration(args.ship.plan),
               SCALE * TURN_IN_SECONDS * TURN_IN_SECONDS
             ),
             scaleVector(args.ship.velocity, SCALE * TURN_IN_SECONDS)
           )}
           color="green"
         />
+        {/* the ship's axis, which a spinal mount fires along, and the edges of any limited firing arcs */}
+        {axis && (
+          <Line start={[0, 0, 0]} end={scaleVector(axis, ARC_LINE_LENGTH)} color="yellow" />
+        )}
+        {axis &&
+          arcs.flatMap((arc) =>
+            arcEdges(axis, arc).map((end, index) => (
+              <Line key={`arc-${arc.from}-${arc.to}-${index}`} start={[0, 0, 0]} end={end} color="orange" />
+            )),
+          )}
         {labelFont != null && (
           <mesh position={[0.0, -1.5, 0.0]} ref={textRef}>
             <textGeometry
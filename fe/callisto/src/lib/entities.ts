@@ -49,7 +49,7 @@ export interface Entity {
 }
 
 export interface Ship extends Entity {
-  plan: [Acceleration, Acceleration | null];
+  plan: Acceleration[];
   design: string;
   current_hull: number;
   current_armor: number;
@@ -66,13 +66,55 @@ export interface Ship extends Entity {
   sensor_locks: string[];
   crew: Crew;
   crit_level?: number[]; // Array of 11 numbers indexed by ShipSystem
-  repair_bonus?: number;
-  last_repair_component?: string | null; // String representation of ShipSystem (e.g., "Sensors")
+  // Systems the damage control teams are working on.
+  repair_tasks?: RepairTask[];
   temporary_maneuver?: number;
   temporary_power_multiplier?: number;
   engineer_action_taken?: boolean;
   leadership_points?: number;
   leadership_rolled?: boolean;
+  // Name of the scenario's jump destination the ship is plotted for, if any.
+  jump_destination?: string | null;
+  // Campaign roster entry the ship was brought in from, if any.
+  roster_id?: string | null;
+  // Set while the ship is held in a skimming orbit: it can't dodge and is easier to hit.
+  skimming?: boolean;
+  // How the engineer last shared out the power plant's output, if they have.
+  power_allocation?: PowerAllocation | null;
+  // Unit vector the ship is turned to while it isn't thrusting, if any.
+  facing?: [number, number, number] | null;
+}
+
+// The thrust of the first segment of a flight plan, which is what the ship
+// burns this turn. Any number of segments may follow it.
+export function nextAcceleration(plan: Acceleration[]): [number, number, number] {
+  return plan.length > 0 ? plan[0][0] : [0, 0, 0];
+}
+
+// The direction a ship points: along its thrust while it has any, otherwise
+// its facing (mirrors Ship::axis on the server).
+export function shipAxis(ship: Ship): [number, number, number] | null {
+  const thrust = nextAcceleration(ship.plan);
+  const length = Math.hypot(...thrust);
+  if (length > 0) {
+    return thrust.map((x) => x / length) as [number, number, number];
+  }
+  return ship.facing ?? null;
+}
+
+// A damage control team's repair, carried on from turn to turn until the
+// system is fixed. The bonus grows with each failed attempt.
+export interface RepairTask {
+  system: string; // String representation of ShipSystem (e.g., "Sensors")
+  bonus?: number;
+}
+
+// Mirrors the Rust PowerAllocation: thrust (in G), sensors and each weapon
+// (by index into the design) the power plant should keep running.
+export interface PowerAllocation {
+  maneuver: number;
+  sensors: boolean;
+  weapons: boolean[];
 }
 
 export enum ShipSystem {
@@ -126,14 +168,18 @@ export function stringToShipSystem(name: string): ShipSystem | null {
 }
 
 // EngineerActionType is the queued engineer-slot's value type, mirroring the
-// Rust ShipAction variants for OverloadDrive / OverloadPlant / Repair.
+// Rust ShipAction variants for OverloadDrive / OverloadPlant / Repair /
+// SkimFuel / TransferFuel / AllocatePower.
 // Sent as part of ModifyActions; received as the `action` field on
 // EngineerActionResult inside an EngineerAction effect.
 // Note: ShipSystem serializes as a string (e.g., "Sensors", "Powerplant").
 export type EngineerActionType =
   | "OverloadDrive"
   | "OverloadPlant"
-  | { Repair: { system: string } };
+  | { Repair: { system: string } }
+  | "SkimFuel"
+  | { TransferFuel: { target: string; amount?: number } }
+  | { AllocatePower: { allocation: PowerAllocation } };
 
 // Inner shape of `EffectMsg::EngineerAction { result: ... }` from the backend.
 export interface EngineerActionResult {
@@ -144,13 +190,22 @@ export interface EngineerActionResult {
   target: number;
   message: string;
   critical_failure: boolean;
+  // Set when a failed jump rolled on the mishap table.
+  mishap?: JumpMishap;
 }
 
+// Inner shape of `JumpMishap` from the backend.
+export type JumpMishap =
+  | { Displaced: { offset: [number, number, number]; extra_turns: number } }
+  | "DriveDamage"
+  | { HullStress: { damage: number } }
+  | "Misjump";
+
 const createShip = (
   name: string,
   position: [number, number, number],
   velocity: [number, number, number],
-  plan: [Acceleration, Acceleration | null],
+  plan: Acceleration[],
   design: string,
   current_hull: number,
   current_armor: number,
@@ -194,7 +249,7 @@ export const defaultShip = () => {
     "New Ship",
     [0, 0, 0],
     [0, 0, 0],
-    [[[0, 0, 0], 0], null],
+    [[[0, 0, 0], 0]],
     "Buccaneer",
     0,
     0,
@@ -331,6 +386,15 @@ export const createMetaData = (name: string, description: string) => {
   };
 };
 
+// Another system ships can jump to from this scenario (see `JumpDestination` on the server).
+export interface JumpDestination {
+  name: string;
+  server: string;
+  scenario: string;
+  emergence_point: [number, number, number];
+  transit_turns: number;
+}
+
 export interface EntityList {
   ships: Ship[];
   planets: Planet[];
@@ -339,6 +403,8 @@ export interface EntityList {
   // Basename of the scenario file this list was loaded from (e.g.
   // "planetfun.json"). Empty for from-scratch builder sessions.
   filename: string;
+  // Absent when the scenario has nowhere to jump to.
+  jump_destinations?: JumpDestination[];
 }
 
 export const defaultEntityList = () => {
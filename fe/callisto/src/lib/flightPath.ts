@@ -3,13 +3,13 @@ export type Acceleration = [[number, number, number], number];
 export interface FlightPath {
   path: [number, number, number][];
   end_velocity: [number, number, number];
-  plan: [Acceleration, Acceleration | null];
+  plan: Acceleration[];
 }
 
 export const createFlightPath = (
   path: [number, number, number][],
   end_velocity: [number, number, number],
-  plan: [Acceleration, Acceleration | null]
+  plan: Acceleration[]
 ): FlightPath => {
   return {path, end_velocity, plan};
 };
\ No newline at end of file
@@ -35,6 +35,7 @@ import {
 } from "lib/entities";
 import { ViewMode, stringToViewMode } from "lib/view";
 import { Acceleration } from "lib/entities";
+import { Health } from "components/controls/CrewBuilder";
 import { ShipDesignTemplates } from "lib/shipDesignTemplates";
 import { FlightPath } from "lib/flightPath";
 import { resetState as resetServerState } from "state/store";
@@ -367,31 +368,35 @@ export function removeEntity(target: string) {
   socket.send(JSON.stringify(payload));
 }
 
-export async function setPlan(
-  target: string,
-  plan: [Acceleration, Acceleration | null],
-) {
-  let plan_arr = [];
-
-  // Since the Rust backend just expects null values in flight plans to be skipped
-  // we have to custom build the body.
+export async function setPlan(target: string, plan: Acceleration[]) {
   // Convert all accelerations to m/s^2 from G's
-  if (plan[1] == null) {
-    plan_arr[0] = [
-      [plan[0][0][0] * G, plan[0][0][1] * G, plan[0][0][2] * G],
-      plan[0][1],
-    ];
-  } else {
-    plan_arr = [
-      [[plan[0][0][0] * G, plan[0][0][1] * G, plan[0][0][2] * G], plan[0][1]],
-      [[plan[1][0][0] * G, plan[1][0][1] * G, plan[1][0][2] * G], plan[1][1]],
-    ];
-  }
+  const plan_arr = plan.map((accel) => scaleAcceleration(accel, G));
   const payload = { SetPlan: { name: target, plan: plan_arr } };
 
   socket.send(JSON.stringify(payload));
 }
 
+// Plot `target`'s jump for one of the scenario's jump destinations, or clear it with null.
+export function setJumpDestination(target: string, destination: string | null) {
+  const payload = { SetJumpDestination: { name: target, destination } };
+
+  socket.send(JSON.stringify(payload));
+}
+
+// Turn `target` to point along `facing` whenever it isn't thrusting, or clear its facing with null.
+export function setFacing(target: string, facing: [number, number, number] | null) {
+  const payload = { SetFacing: { name: target, facing } };
+
+  socket.send(JSON.stringify(payload));
+}
+
+// Put crew member `gunner` of ship `target` in control of `weapons`, taking them from any other gunner.
+export function assignGunner(target: string, gunner: string, weapons: number[]) {
+  const payload = { AssignGunner: { ship_name: target, gunner, weapons } };
+
+  socket.send(JSON.stringify(payload));
+}
+
 export function updateActions(actions: ActionType) {
   if (Object.entries(actions).length === 0) {
     return;
@@ -563,23 +568,17 @@ function handleTemplates(json: object) {
   store.dispatch(setTemplates(templates));
 }
 
+// Scale the thrust of one flight plan segment, keeping its duration.
+function scaleAcceleration(accel: Acceleration, factor: number): Acceleration {
+  return [[accel[0][0] * factor, accel[0][1] * factor, accel[0][2] * factor], accel[1]];
+}
+
 function handleEntities(json: object) {
   const entities = json as EntityList;
 
   // Convert all ship plans to G's from m/s^2
   entities.ships.forEach((ship) => {
-    ship.plan[0][0] = [
-      ship.plan[0][0][0] / G,
-      ship.plan[0][0][1] / G,
-      ship.plan[0][0][2] / G,
-    ];
-    if (ship.plan[1] != null) {
-      ship.plan[1][0] = [
-        ship.plan[1][0][0] / G,
-        ship.plan[1][0][1] / G,
-        ship.plan[1][0][2] / G,
-      ];
-    }
+    ship.plan = ship.plan.map((accel) => scaleAcceleration(accel, 1 / G));
   });
 
   console.groupCollapsed("Received Entities: ");
@@ -641,18 +640,7 @@ function handleFlightPath(json: object) {
   const path = json as FlightPath;
 
   // Convert all accelerations in FlightPath from m/s^2 to G's
-  path.plan[0][0] = [
-    path.plan[0][0][0] / G,
-    path.plan[0][0][1] / G,
-    path.plan[0][0][2] / G,
-  ];
-  if (path.plan[1] != null) {
-    path.plan[1][0] = [
-      path.plan[1][0][0] / G,
-      path.plan[1][0][1] / G,
-      path.plan[1][0][2] / G,
-    ];
-  }
+  path.plan = path.plan.map((accel) => scaleAcceleration(accel, 1 / G));
 
   store.dispatch(setProposedPlan(path));
 }
@@ -669,8 +657,24 @@ function handleEffect(json: object[]) {
   // event so it surfaces in the existing ResultsWindow alongside everything
   // else from the same turn.
   const events: Event[] = (
-    json as Array<Event | EngineerActionEffect | LeadershipActionEffect>
+    json as Array<
+      | Event
+      | EngineerActionEffect
+      | LeadershipActionEffect
+      | CombatEffect
+      | DiceRollEffect
+    >
   ).map((event) => {
+    // Dice rolls get their own kind so players can choose to show or hide them.
+    if ((event as DiceRollEffect).kind === "DiceRoll") {
+      return {
+        kind: "DiceRoll",
+        content: formatRoll((event as DiceRollEffect).record),
+        position: null,
+        target: null,
+        origin: null,
+      } as Event;
+    }
     if ((event as EngineerActionEffect).kind === "EngineerAction") {
       const result = (event as EngineerActionEffect).result;
       return {
@@ -691,6 +695,16 @@ function handleEffect(json: object[]) {
         origin: null,
       } as Event;
     }
+    const combat = formatCombatEffect(event as CombatEffect);
+    if (combat !== null) {
+      return {
+        kind: "Message",
+        content: combat,
+        position: null,
+        target: null,
+        origin: null,
+      } as Event;
+    }
     return event as Event;
   });
 
@@ -698,6 +712,34 @@ function handleEffect(json: object[]) {
   store.dispatch(setShowResults(true));
 }
 
+interface RollRecord {
+  purpose: string;
+  actor: string;
+  dice: number;
+  roll: number;
+  modifiers?: [string, number][];
+  threshold?: number;
+}
+
+interface DiceRollEffect {
+  kind: "DiceRoll";
+  record: RollRecord;
+}
+
+// e.g. "Buccaneer rolled 2D for Attack: 7 +2 skill -1 range = 8 vs 8"
+function formatRoll(record: RollRecord): string {
+  const modifiers = record.modifiers ?? [];
+  const named = modifiers
+    .map(([name, value]) => ` ${value >= 0 ? "+" : ""}${value} ${name}`)
+    .join("");
+  const total = modifiers.reduce((sum, [, value]) => sum + value, record.roll);
+  const versus =
+    record.threshold === undefined
+      ? ""
+      : ` = ${total} vs ${record.threshold}`;
+  return `${record.actor} rolled ${record.dice}D for ${record.purpose}: ${record.roll}${named}${versus}`;
+}
+
 interface EngineerActionEffect {
   kind: "EngineerAction";
   result: EngineerActionResult;
@@ -741,6 +783,208 @@ function describeBoost(b: object): string {
     : `${kind} ${v.ship}`;
 }
 
+// Typed combat and sensor results. Each carries its rolls and modifiers; the
+// text shown in the ResultsWindow is rendered here, mirroring
+// `EffectMsg::render` on the server.
+interface CombatEffect {
+  kind: string;
+  attacker?: string;
+  target?: string;
+  weapon?: string;
+  damage?: number;
+  ship?: string;
+  system?: string;
+  level?: number;
+  effect?: CritEffect;
+  success?: boolean;
+  reduction?: number;
+  missile?: string;
+  action?: string | Record<string, { target: string }>;
+  destination?: string;
+  transit_turns?: number;
+  off_course?: boolean;
+  offline?: string[];
+  member?: string;
+  health?: Health;
+  gunner?: string;
+  teams?: number;
+  planet?: string;
+  systems?: string[];
+  weapon_name?: string;
+}
+
+interface CritEffect {
+  outcome: string;
+  damage?: number | number[];
+  sensors?: string;
+  amount?: number;
+  percent?: number;
+  weapon?: { kind: string } | null;
+  dice?: number;
+  hours?: number;
+  rounds?: number;
+}
+
+const WEAPON_NAMES: Record<string, string> = {
+  Beam: "beam laser",
+  Pulse: "pulse laser",
+  Missile: "missile",
+  Sand: "sand",
+  Particle: "particle beam",
+};
+
+function weaponName(kind: string): string {
+  return WEAPON_NAMES[kind] ?? kind;
+}
+
+function describeCrit(effect: CritEffect): string {
+  const damage = ([] as number[]).concat(effect.damage ?? []);
+  switch (effect.outcome) {
+    case "HullDamage":
+      return `caused ${damage[0]} damage`;
+    case "AttackDmReduced":
+      return "attack DM reduced by 1";
+    case "Offline":
+      return "offline";
+    case "SensorsReduced":
+      return `reduced to ${effect.sensors}`;
+    case "ReducedBy":
+      return `reduced by ${effect.amount}`;
+    case "ReducedByPercent":
+      return `reduced by ${effect.percent}%`;
+    case "WeaponDisabled":
+      return effect.weapon
+        ? `${weaponName(effect.weapon.kind)} disabled`
+        : "all weapons already disabled";
+    case "CargoDestroyed":
+      return (effect.percent ?? 0) >= 100
+        ? "all cargo destroyed"
+        : `${effect.percent}% of cargo destroyed`;
+    case "OccupantsDamaged":
+      return damage.length === 1
+        ? `random occupant takes ${damage[0]} damage`
+        : `${damage.length} occupants take ${damage.join(", ")} points of damage`;
+    case "AllOccupantsDamaged":
+      return `all occupants take ${effect.dice}D damage (roll each separately)`;
+    case "LifeSupportFailsInHours":
+      return `life support fails within ${effect.hours} hours`;
+    case "LifeSupportFailsInRounds":
+      return `life support fails in ${effect.rounds} rounds`;
+    case "LifeSupportFailed":
+      return "life support fails";
+    case "BridgeSystemDisabled":
+      return "random bridge system disabled";
+    case "ComputerReboot":
+      return "computer reboots, all software unavailable this round and next";
+    case "BridgeStationDestroyed":
+      return `random bridge station destroyed: occupant takes ${damage[0]} damage`;
+    default:
+      return String(effect.outcome);
+  }
+}
+
+function describeSensorResult(e: CombatEffect): string {
+  const action = e.action ?? "";
+  const kind = typeof action === "string" ? action : Object.keys(action)[0];
+  const target = typeof action === "string" ? "" : action[kind].target;
+  switch (kind) {
+    case "SensorLock":
+      return e.success
+        ? `Sensor lock on ${target} established by ${e.ship}.`
+        : `Sensor lock on ${target} not established by ${e.ship}.`;
+    case "JamComms":
+      return e.success
+        ? `${e.ship} is jamming comms on ${target}.`
+        : `${e.ship} failed to jam comms on ${target}.`;
+    case "BreakSensorLock":
+      return e.success
+        ? `${e.ship} broke ${target}'s sensor lock!`
+        : `${e.ship} failed to break ${target}'s sensor lock.`;
+    default:
+      return e.success
+        ? `Missile jamming attempt by ${e.ship} succeeded.`
+        : `Missile jamming attempt by ${e.ship} failed.`;
+  }
+}
+
+// Returns null for effects that are not typed combat results.
+function describeCrewInjury(e: CombatEffect): string {
+  const who = `${e.member} of ${e.ship}`;
+  switch (e.health) {
+    case "Fit":
+      return `${who} is unhurt.`;
+    case "Wounded":
+      return `${who} is wounded.`;
+    case "Stable":
+      return `${who} is stable but out of action.`;
+    case "Dead":
+      return `${who} is dead.`;
+    default:
+      return `${who} is dying with ${e.health?.Dying.turns_left} turns left.`;
+  }
+}
+
+function formatCombatEffect(e: CombatEffect): string | null {
+  switch (e.kind) {
+    case "AttackMiss":
+      return `${e.attacker}'s ${weaponName(e.weapon ?? "")} attack misses ${e.target}.`;
+    case "ArmorAbsorbed":
+      return `${e.target} hit by ${e.attacker}'s ${weaponName(e.weapon ?? "")} but damage absorbed by armor.`;
+    case "AttackHit":
+      return e.weapon === "Missile"
+        ? `${e.target} hit by a missile for ${e.damage} damage.`
+        : `${e.target} hit by ${weaponName(e.weapon ?? "")} for ${e.damage} damage.`;
+    case "CriticalHit": {
+      const effect = e.effect ?? { outcome: "" };
+      return effect.outcome === "HullDamage"
+        ? `${e.ship}'s critical hit at level ${e.level} ${describeCrit(effect)}.`
+        : `${e.ship}'s ${String(e.system).toLowerCase()} critical hit (level ${e.level}) and ${describeCrit(effect)}.`;
+    }
+    case "SandDeployed":
+      return e.success
+        ? `${e.ship}'s sand successfully deployed against ${e.attacker} reducing damage by ${e.reduction}.`
+        : `${e.ship}'s sand failed to deploy against ${e.attacker}.`;
+    case "PointDefenseIntercept":
+      return `Missile ${e.missile} destroyed by ${e.ship}'s point defense.`;
+    case "MissileJammed":
+      return `Missile ${e.missile} destroyed by jamming.`;
+    case "SensorResult":
+      return describeSensorResult(e);
+    case "ShipJumped":
+      return e.off_course
+        ? `${e.ship} is in jump space bound for ${e.destination} but will emerge off course in ${e.transit_turns} turns.`
+        : `${e.ship} is in jump space bound for ${e.destination} and will emerge in ${e.transit_turns} turns.`;
+    case "ShipEmerged":
+      return `${e.ship} emerges from jump space.`;
+    case "CrewInjury":
+      return describeCrewInjury(e);
+    case "PowerShortfall":
+      return e.offline && e.offline.length > 0
+        ? `${e.ship} is drawing more power than its plant makes: ${e.offline.join(", ")} offline.`
+        : `${e.ship}'s power plant carries all its systems again.`;
+    case "SensorsUnpowered":
+      return `${e.ship}'s sensors have no power and can't be used.`;
+    case "WeaponUnpowered":
+      return `${e.ship}'s ${e.weapon_name} has no power and can't fire.`;
+    case "OutOfArc":
+      return `${e.target} is outside the firing arc of ${e.ship}'s ${e.weapon}.`;
+    case "LineOfSightBlocked":
+      return `${e.target} is hidden from ${e.ship} behind ${e.planet}.`;
+    case "SensorLockLost":
+      return `${e.ship} loses its sensor lock on ${e.target} behind ${e.planet}.`;
+    case "RepairsAbandoned":
+      return `${e.ship} can only field ${e.teams} damage control teams and abandons repairs to ${(e.systems ?? []).join(", ")}.`;
+    case "NoGunner":
+      return `${e.ship}'s ${e.weapon_name} has no gunner and can't fire.`;
+    case "GunnerBusy":
+      return `${e.gunner} is already firing this turn so ${e.ship}'s ${e.weapon_name} can't fire.`;
+    case "GunnerFires":
+      return `${e.gunner} fires ${e.ship}'s ${e.weapon_name} at ${e.target}.`;
+    default:
+      return null;
+  }
+}
+
 function handleUsers(json: [UserContext]) {
   const users: UserList = [];
   for (const user of json) {
@@ -3,15 +3,34 @@ export type WeaponMount = string | {Turret: number} | {BaySize: string};
 export interface Weapon {
   kind: string;
   mount: WeaponMount;
+  arc?: FiringArc | null;
 }
 
+// Mirrors the Rust FiringArc: the weapon fires between `from` and `to` degrees
+// off the ship's axis (0 dead ahead, 180 dead astern).
+export interface FiringArc {
+  from: number;
+  to: number;
+}
+
+export const ALL_ROUND: FiringArc = { from: 0, to: 180 };
+// Must match FiringArc::SPINAL on the server.
+export const SPINAL_ARC: FiringArc = { from: 0, to: 5 };
+
+// Where a weapon can fire: a spinal mount only along the axis, anything else
+// its declared arc or all round.
+export const firingArc = (weapon: Weapon): FiringArc =>
+  weapon.mount === "Spinal" ? SPINAL_ARC : (weapon.arc ?? ALL_ROUND);
+
 
 export const createWeapon = (kind: string, mount: WeaponMount): Weapon => {
   return {kind, mount};
 };
 
 export const weaponToString = (weapon: Weapon): string => {
-    if (typeof weapon.mount === "string") {
+    if (weapon.mount === "Spinal") {
+      return `${weapon.kind} Spinal Mount`;
+    } else if (typeof weapon.mount === "string") {
       return `${weapon.kind} Barbette`;
     } else if ("Turret" in weapon.mount) {
       if (weapon.mount.Turret === 1) {
@@ -26,6 +26,7 @@ const newShipAction = () => {
     pointDefense: [],
     engineer: null as EngineerState,
     leadershipCheck: null as { boosts: BoostTarget[] } | null,
+    medical: null as string | null,
     clearSensor: false,
     clearEngineer: false,
     clearLeadership: false,
@@ -112,6 +113,12 @@ export const actionsSlice = createSlice({
       state[item.payload.shipName].clearEngineer = item.payload.action === null;
       updateActions(state);
     },
+    // Queue the medic to stabilize a dying crew member.
+    setMedicalAction: (state, item: PayloadAction<{ shipName: string, member: string | null}>) => {
+      state[item.payload.shipName] ??= newShipAction();
+      state[item.payload.shipName].medical = item.payload.member;
+      updateActions(state);
+    },
     // Idempotently add or remove a boost target. When the list goes empty,
     // set `clearLeadership` so the server strips its queued LeadershipCheck;
     // otherwise the LeadershipCheck wire form rides along on the next
@@ -180,6 +187,14 @@ export const actionsSlice = createSlice({
         item.payload.system;
       updateActions(state);
     },
+    updateFireMultipleAttacks: (
+      state,
+      item: PayloadAction<{shipName: string; index: number; multiple_attacks: boolean}>
+    ) => {
+      state[item.payload.shipName].fire[item.payload.index].multiple_attacks =
+        item.payload.multiple_attacks;
+      updateActions(state);
+    },
     resetServer: () => initialState,
   },
 });
@@ -188,11 +203,13 @@ export const {
   setActions,
   setSensorAction,
   setEngineerAction,
+  setMedicalAction,
   toggleBoost,
   fireWeapon,
   pointDefenseWeapon,
   unfireWeapon,
   updateFireCalledShot,
+  updateFireMultipleAttacks,
   resetServer,
 } = actionsSlice.actions;
 
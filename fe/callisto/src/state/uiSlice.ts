@@ -9,6 +9,7 @@ export interface UISlice {
     entityToShow: Entity | null;
     proposedPlan: FlightPath | null;
     showResults: boolean;
+    showDiceRolls: boolean;
     events: Event[] | null;
     cameraPos: [number, number, number];
     cameraQuaternion: [number, number, number, number];
@@ -22,6 +23,7 @@ const initialState: UISlice  = {
     entityToShow: null,
     proposedPlan: null,
     showResults: false,
+    showDiceRolls: false,
     events: null,
     cameraPos: [-100, 0, 0],
     cameraQuaternion: INITIAL_QUATERNION,
@@ -45,6 +47,9 @@ export const uiSlice = createSlice({
     setShowResults: (state, action: PayloadAction<boolean>) => {
         state.showResults = action.payload;
     },
+    setShowDiceRolls: (state, action: PayloadAction<boolean>) => {
+        state.showDiceRolls = action.payload;
+    },
     setEvents: (state, action: PayloadAction<Event[] | null>) => {
         state.events = action.payload;
     },
@@ -66,6 +71,6 @@ export const uiSlice = createSlice({
   }
 });
 
-export const { setEntityToShow, setProposedPlan, setShowResults, setEvents, setCameraPos, setCameraQuaternion, setGravityWells, setJumpDistance, setShowRange, setComputerShipName, resetServer } = uiSlice.actions;
+export const { setEntityToShow, setProposedPlan, setShowResults, setShowDiceRolls, setEvents, setCameraPos, setCameraQuaternion, setGravityWells, setJumpDistance, setShowRange, setComputerShipName, resetServer } = uiSlice.actions;
 export type UIReducer = ReturnType<typeof uiSlice.reducer>;
 export default uiSlice.reducer;